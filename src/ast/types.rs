@@ -46,6 +46,8 @@ pub struct StatementNode {
     pub deferred_error: Option<DeferredError>,
     /// Original source text for verbose mode (set -v)
     pub source_text: Option<String>,
+    /// Line the statement starts on
+    pub line: Option<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -85,6 +87,7 @@ pub enum CommandNode {
     Simple(SimpleCommandNode),
     Compound(CompoundCommandNode),
     FunctionDef(FunctionDefNode),
+    Coproc(CoprocNode),
 }
 
 /// Simple command: name args... with optional redirections
@@ -100,6 +103,8 @@ pub struct SimpleCommandNode {
     pub redirections: Vec<RedirectionNode>,
     /// Source line number for $LINENO
     pub line: Option<usize>,
+    /// Original source text, for $BASH_COMMAND
+    pub source_text: Option<String>,
 }
 
 /// Compound commands: control structures
@@ -111,12 +116,48 @@ pub enum CompoundCommandNode {
     While(WhileNode),
     Until(UntilNode),
     Case(CaseNode),
+    Select(SelectNode),
     Subshell(SubshellNode),
     Group(GroupNode),
     ArithmeticCommand(ArithmeticCommandNode),
     ConditionalCommand(ConditionalCommandNode),
 }
 
+impl CompoundCommandNode {
+    /// Redirections attached after the closing keyword, e.g. `done < file`.
+    pub fn redirections(&self) -> &[RedirectionNode] {
+        match self {
+            CompoundCommandNode::If(node) => &node.redirections,
+            CompoundCommandNode::For(node) => &node.redirections,
+            CompoundCommandNode::CStyleFor(node) => &node.redirections,
+            CompoundCommandNode::While(node) => &node.redirections,
+            CompoundCommandNode::Until(node) => &node.redirections,
+            CompoundCommandNode::Case(node) => &node.redirections,
+            CompoundCommandNode::Select(node) => &node.redirections,
+            CompoundCommandNode::Subshell(node) => &node.redirections,
+            CompoundCommandNode::Group(node) => &node.redirections,
+            CompoundCommandNode::ArithmeticCommand(node) => &node.redirections,
+            CompoundCommandNode::ConditionalCommand(node) => &node.redirections,
+        }
+    }
+
+    /// The keyword or operator the command starts with.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            CompoundCommandNode::If(_) => "if",
+            CompoundCommandNode::For(_) | CompoundCommandNode::CStyleFor(_) => "for",
+            CompoundCommandNode::While(_) => "while",
+            CompoundCommandNode::Until(_) => "until",
+            CompoundCommandNode::Case(_) => "case",
+            CompoundCommandNode::Select(_) => "select",
+            CompoundCommandNode::Subshell(_) => "(",
+            CompoundCommandNode::Group(_) => "{",
+            CompoundCommandNode::ArithmeticCommand(_) => "((",
+            CompoundCommandNode::ConditionalCommand(_) => "[[",
+        }
+    }
+}
+
 // =============================================================================
 // CONTROL FLOW
 // =============================================================================
@@ -145,6 +186,16 @@ pub struct ForNode {
     pub redirections: Vec<RedirectionNode>,
 }
 
+/// select loop: select VAR in WORDS; do ...; done
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectNode {
+    pub variable: String,
+    /// Menu items (None = "$@")
+    pub words: Option<Vec<WordNode>>,
+    pub body: Vec<StatementNode>,
+    pub redirections: Vec<RedirectionNode>,
+}
+
 /// C-style for loop: for ((init; cond; step)); do ...; done
 #[derive(Debug, Clone, PartialEq)]
 pub struct CStyleForNode {
@@ -239,6 +290,14 @@ pub struct FunctionDefNode {
     pub source_file: Option<String>,
 }
 
+/// Coprocess: coproc [NAME] command
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoprocNode {
+    /// Name of the fd array, COPROC when not given
+    pub name: String,
+    pub body: Box<CommandNode>,
+}
+
 // =============================================================================
 // ASSIGNMENTS
 // =============================================================================
@@ -839,6 +898,28 @@ pub enum CondBinaryOperator {
     Ef,      // -ef
 }
 
+impl fmt::Display for CondBinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eq => write!(f, "="),
+            Self::EqEq => write!(f, "=="),
+            Self::Ne => write!(f, "!="),
+            Self::Match => write!(f, "=~"),
+            Self::Lt => write!(f, "<"),
+            Self::Gt => write!(f, ">"),
+            Self::NumEq => write!(f, "-eq"),
+            Self::NumNe => write!(f, "-ne"),
+            Self::NumLt => write!(f, "-lt"),
+            Self::NumLe => write!(f, "-le"),
+            Self::NumGt => write!(f, "-gt"),
+            Self::NumGe => write!(f, "-ge"),
+            Self::Nt => write!(f, "-nt"),
+            Self::Ot => write!(f, "-ot"),
+            Self::Ef => write!(f, "-ef"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CondBinaryNode {
     pub operator: CondBinaryOperator,
@@ -852,6 +933,40 @@ pub enum CondUnaryOperator {
     UpperG, L, N, UpperO, UpperS, Z, LowerN, LowerO, V, UpperR,
 }
 
+impl fmt::Display for CondUnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = match self {
+            Self::A => 'a',
+            Self::B => 'b',
+            Self::C => 'c',
+            Self::D => 'd',
+            Self::E => 'e',
+            Self::F => 'f',
+            Self::G => 'g',
+            Self::H => 'h',
+            Self::K => 'k',
+            Self::P => 'p',
+            Self::R => 'r',
+            Self::S => 's',
+            Self::T => 't',
+            Self::U => 'u',
+            Self::W => 'w',
+            Self::X => 'x',
+            Self::UpperG => 'G',
+            Self::L => 'L',
+            Self::N => 'N',
+            Self::UpperO => 'O',
+            Self::UpperS => 'S',
+            Self::Z => 'z',
+            Self::LowerN => 'n',
+            Self::LowerO => 'o',
+            Self::V => 'v',
+            Self::UpperR => 'R',
+        };
+        write!(f, "-{}", flag)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CondUnaryNode {
     pub operator: CondUnaryOperator,
@@ -910,6 +1025,7 @@ impl AST {
             background,
             deferred_error,
             source_text,
+            line: None,
         }
     }
 
@@ -941,6 +1057,7 @@ impl AST {
             assignments,
             redirections,
             line: None,
+            source_text: None,
         }
     }
 
@@ -978,6 +1095,10 @@ impl AST {
         WordPart::CommandSubstitution(CommandSubstitutionPart { body, legacy })
     }
 
+    pub fn process_substitution(body: ScriptNode, direction: ProcessDirection) -> WordPart {
+        WordPart::ProcessSubstitution(ProcessSubstitutionPart { body, direction })
+    }
+
     pub fn arithmetic_expansion(expression: ArithmeticExpressionNode) -> WordPart {
         WordPart::ArithmeticExpansion(ArithmeticExpansionPart { expression })
     }
@@ -1050,6 +1171,20 @@ impl AST {
         }
     }
 
+    pub fn select_node(
+        variable: impl Into<String>,
+        words: Option<Vec<WordNode>>,
+        body: Vec<StatementNode>,
+        redirections: Vec<RedirectionNode>,
+    ) -> SelectNode {
+        SelectNode {
+            variable: variable.into(),
+            words,
+            body,
+            redirections,
+        }
+    }
+
     pub fn while_node(
         condition: Vec<StatementNode>,
         body: Vec<StatementNode>,
@@ -1126,6 +1261,13 @@ impl AST {
         }
     }
 
+    pub fn coproc(name: impl Into<String>, body: CommandNode) -> CoprocNode {
+        CoprocNode {
+            name: name.into(),
+            body: Box::new(body),
+        }
+    }
+
     pub fn conditional_command(
         expression: ConditionalExpressionNode,
         redirections: Vec<RedirectionNode>,
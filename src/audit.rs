@@ -0,0 +1,350 @@
+//! Audit
+//!
+//! Structured records of what a script did: the commands it ran, the files
+//! it read, wrote, deleted and changed, and the network requests it made.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::clock::Clock;
+use crate::commands::types::{FetchFn, FetchResponse};
+use crate::fs::{CpOptions, DirentEntry, FileSystem, FsError, FsStat, MkdirOptions, RmOptions};
+
+/// Receives audit events as they happen.
+pub type AuditSink = Arc<dyn Fn(AuditEvent) + Send + Sync>;
+
+thread_local! {
+    /// Set while the shell continues a write that was already reported.
+    static UNAUDITED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` without reporting the file access it does on this thread. Used
+/// for the output of `> file`, which was reported when the file was opened.
+pub fn unaudited<R>(f: impl FnOnce() -> R) -> R {
+    let outer = UNAUDITED.with(|flag| flag.replace(true));
+    let result = f();
+    UNAUDITED.with(|flag| flag.set(outer));
+    result
+}
+
+/// One audit record.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuditEvent {
+    Command(CommandEvent),
+    File(FileEvent),
+    Network(NetworkEvent),
+}
+
+/// How a command name was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CommandKind {
+    Builtin,
+    Function,
+    /// A command from the registry (`ls`, `grep`, ...)
+    Registry,
+    /// An executable file in the virtual filesystem
+    Script,
+    NotFound,
+    /// Stopped by the `CommandPolicy`
+    Denied,
+}
+
+/// A simple command that ran.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandEvent {
+    pub name: String,
+    pub kind: CommandKind,
+    /// Where PATH lookup found a registry command or script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Expanded arguments, starting with the command name
+    pub argv: Vec<String>,
+    pub cwd: String,
+    pub exit_code: i32,
+    pub duration_ms: f64,
+    pub stdin_bytes: usize,
+    pub stdout_bytes: usize,
+    pub stderr_bytes: usize,
+}
+
+/// What a script did to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileOperation {
+    Read,
+    Write,
+    Delete,
+    Mkdir,
+    /// Permission bits changed
+    Chmod,
+    /// Modification time set
+    Utimes,
+}
+
+/// A file accessed or changed through the `FileSystem`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEvent {
+    pub operation: FileOperation,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A network request made through the `FetchFn`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkEvent {
+    pub method: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: f64,
+}
+
+impl AuditEvent {
+    /// The event as one line of JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Milliseconds between two readings of a clock.
+pub fn elapsed_ms(start: SystemTime, end: SystemTime) -> f64 {
+    end.duration_since(start).map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0)
+}
+
+/// A `FileSystem` that reports reads, writes, deletions and metadata
+/// changes to a sink.
+pub struct AuditFs {
+    inner: Arc<dyn FileSystem>,
+    sink: AuditSink,
+}
+
+impl AuditFs {
+    pub fn new(inner: Arc<dyn FileSystem>, sink: AuditSink) -> Self {
+        Self { inner, sink }
+    }
+
+    fn record<T>(&self, operation: FileOperation, path: &str, result: &Result<T, FsError>) {
+        if UNAUDITED.with(Cell::get) {
+            return;
+        }
+        (self.sink)(AuditEvent::File(FileEvent {
+            operation,
+            path: path.to_string(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        }));
+    }
+}
+
+#[async_trait]
+impl FileSystem for AuditFs {
+    async fn read_file(&self, path: &str) -> Result<String, FsError> {
+        let result = self.inner.read_file(path).await;
+        self.record(FileOperation::Read, path, &result);
+        result
+    }
+
+    async fn read_file_buffer(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        let result = self.inner.read_file_buffer(path).await;
+        self.record(FileOperation::Read, path, &result);
+        result
+    }
+
+    async fn write_file(&self, path: &str, content: &[u8]) -> Result<(), FsError> {
+        let result = self.inner.write_file(path, content).await;
+        self.record(FileOperation::Write, path, &result);
+        result
+    }
+
+    async fn append_file(&self, path: &str, content: &[u8]) -> Result<(), FsError> {
+        let result = self.inner.append_file(path, content).await;
+        self.record(FileOperation::Write, path, &result);
+        result
+    }
+
+    async fn exists(&self, path: &str) -> bool {
+        self.inner.exists(path).await
+    }
+
+    async fn stat(&self, path: &str) -> Result<FsStat, FsError> {
+        self.inner.stat(path).await
+    }
+
+    async fn lstat(&self, path: &str) -> Result<FsStat, FsError> {
+        self.inner.lstat(path).await
+    }
+
+    async fn mkdir(&self, path: &str, options: &MkdirOptions) -> Result<(), FsError> {
+        let result = self.inner.mkdir(path, options).await;
+        self.record(FileOperation::Mkdir, path, &result);
+        result
+    }
+
+    async fn readdir(&self, path: &str) -> Result<Vec<String>, FsError> {
+        self.inner.readdir(path).await
+    }
+
+    async fn readdir_with_file_types(&self, path: &str) -> Result<Vec<DirentEntry>, FsError> {
+        self.inner.readdir_with_file_types(path).await
+    }
+
+    async fn rm(&self, path: &str, options: &RmOptions) -> Result<(), FsError> {
+        let result = self.inner.rm(path, options).await;
+        self.record(FileOperation::Delete, path, &result);
+        result
+    }
+
+    async fn cp(&self, src: &str, dest: &str, options: &CpOptions) -> Result<(), FsError> {
+        let result = self.inner.cp(src, dest, options).await;
+        self.record(FileOperation::Read, src, &result);
+        self.record(FileOperation::Write, dest, &result);
+        result
+    }
+
+    async fn mv(&self, src: &str, dest: &str) -> Result<(), FsError> {
+        let result = self.inner.mv(src, dest).await;
+        self.record(FileOperation::Write, dest, &result);
+        self.record(FileOperation::Delete, src, &result);
+        result
+    }
+
+    async fn chmod(&self, path: &str, mode: u32) -> Result<(), FsError> {
+        let result = self.inner.chmod(path, mode).await;
+        self.record(FileOperation::Chmod, path, &result);
+        result
+    }
+
+    async fn symlink(&self, target: &str, link_path: &str) -> Result<(), FsError> {
+        let result = self.inner.symlink(target, link_path).await;
+        self.record(FileOperation::Write, link_path, &result);
+        result
+    }
+
+    async fn link(&self, existing_path: &str, new_path: &str) -> Result<(), FsError> {
+        let result = self.inner.link(existing_path, new_path).await;
+        self.record(FileOperation::Write, new_path, &result);
+        result
+    }
+
+    async fn readlink(&self, path: &str) -> Result<String, FsError> {
+        self.inner.readlink(path).await
+    }
+
+    async fn realpath(&self, path: &str) -> Result<String, FsError> {
+        self.inner.realpath(path).await
+    }
+
+    async fn utimes(&self, path: &str, mtime: SystemTime) -> Result<(), FsError> {
+        let result = self.inner.utimes(path, mtime).await;
+        self.record(FileOperation::Utimes, path, &result);
+        result
+    }
+
+    fn resolve_path(&self, base: &str, path: &str) -> String {
+        self.inner.resolve_path(base, path)
+    }
+
+    fn get_all_paths(&self) -> Vec<String> {
+        self.inner.get_all_paths()
+    }
+}
+
+/// Wrap `fetch` so every request it makes is reported to `sink`.
+pub fn audit_fetch(fetch: FetchFn, sink: AuditSink, clock: Clock) -> FetchFn {
+    Arc::new(move |url: String, method: String, headers: HashMap<String, String>, body: Option<String>| {
+        let fetch = fetch.clone();
+        let sink = sink.clone();
+        let clock = clock.clone();
+        Box::pin(async move {
+            let start = clock.peek();
+            let result: Result<FetchResponse, String> = fetch(url.clone(), method.clone(), headers, body).await;
+            sink(AuditEvent::Network(NetworkEvent {
+                method,
+                url,
+                status: result.as_ref().ok().map(|response| response.status),
+                error: result.as_ref().err().cloned(),
+                duration_ms: elapsed_ms(start, clock.peek()),
+            }));
+            result
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::InMemoryFs;
+    use std::sync::Mutex;
+
+    fn collecting_sink() -> (AuditSink, Arc<Mutex<Vec<AuditEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collected = events.clone();
+        let sink: AuditSink = Arc::new(move |event| collected.lock().unwrap().push(event));
+        (sink, events)
+    }
+
+    #[tokio::test]
+    async fn test_audit_fs_records_file_access() {
+        let (sink, events) = collecting_sink();
+        let fs = AuditFs::new(Arc::new(InMemoryFs::new()), sink);
+        fs.write_file("/a.txt", b"hi").await.unwrap();
+        fs.read_file("/a.txt").await.unwrap();
+        assert!(fs.read_file("/missing").await.is_err());
+        fs.chmod("/a.txt", 0o600).await.unwrap();
+        fs.utimes("/a.txt", SystemTime::UNIX_EPOCH).await.unwrap();
+        fs.rm("/a.txt", &RmOptions { recursive: false, force: false }).await.unwrap();
+        fs.mkdir("/d", &MkdirOptions { recursive: false }).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let ops: Vec<(FileOperation, &str, bool)> = events
+            .iter()
+            .map(|event| match event {
+                AuditEvent::File(file) => (file.operation, file.path.as_str(), file.error.is_none()),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(ops, vec![
+            (FileOperation::Write, "/a.txt", true),
+            (FileOperation::Read, "/a.txt", true),
+            (FileOperation::Read, "/missing", false),
+            (FileOperation::Chmod, "/a.txt", true),
+            (FileOperation::Utimes, "/a.txt", true),
+            (FileOperation::Delete, "/a.txt", true),
+            (FileOperation::Mkdir, "/d", true),
+        ]);
+        assert_eq!(events[0].to_json(), r#"{"type":"file","operation":"write","path":"/a.txt"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_audit_fetch_records_requests() {
+        let (sink, events) = collecting_sink();
+        let fetch: FetchFn = Arc::new(|url, _, _, _| {
+            Box::pin(async move {
+                Ok(FetchResponse { status: 200, headers: HashMap::new(), body: url, url: String::new() })
+            })
+        });
+        let fetch = audit_fetch(fetch, sink, Clock::fixed_at_unix(0));
+        fetch("https://example.com/".to_string(), "GET".to_string(), HashMap::new(), None).await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.as_slice(), &[AuditEvent::Network(NetworkEvent {
+            method: "GET".to_string(),
+            url: "https://example.com/".to_string(),
+            status: Some(200),
+            error: None,
+            duration_ms: 0.0,
+        })]);
+    }
+}
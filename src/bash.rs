@@ -4,10 +4,23 @@
 //! Ties together the parser, interpreter, and filesystem.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::fs::{FileSystem, FsError, InMemoryFs, MkdirOptions};
-use crate::interpreter::types::{ExecResult, ExecutionLimits, InterpreterState};
+use crate::audit::{audit_fetch, AuditFs, AuditSink};
+use crate::clock::Clock;
+use crate::policy::CommandPolicy;
+use crate::commands::registry::{create_batch_abcdefghijklmno_registry, CommandRegistry};
+use crate::commands::types::{CommandResult, ExecFn, FetchFn};
+use crate::fs::{FileSystem, FsError, FsLimits, InMemoryFs, MkdirOptions};
+use crate::random::Random;
+use crate::interpreter::errors::{ExecutionLimitError, InterpreterError, LimitType};
+use crate::interpreter::builtins::jobs_cmd::drain_jobs;
+use crate::interpreter::types::{
+    CancellationToken, ExecResult, ExecutionLimits, InterpreterState, ProcessIdentity, ShellNesting,
+};
+use crate::interpreter::{ExecutionEngine, SyncFsAdapter};
 use crate::interpreter::helpers::shellopts::{build_shellopts, build_bashopts};
 
 /// Options for creating a Bash environment.
@@ -17,13 +30,30 @@ pub struct BashOptions {
     pub env: Option<HashMap<String, String>>,
     /// Working directory
     pub cwd: Option<String>,
-    /// File system instance (defaults to InMemoryFs)
+    /// File system instance (defaults to InMemoryFs). The filesystem limits
+    /// in `ExecutionLimits` apply to the default one only; a custom
+    /// filesystem enforces its own, e.g. `InMemoryFs::with_clock_and_limits`.
     pub fs: Option<Arc<dyn FileSystem>>,
     /// Execution limits
     pub limits: Option<ExecutionLimits>,
+    /// HTTP handler for network commands like curl (network is disabled without it)
+    pub fetch: Option<FetchFn>,
+    /// Time source for sleep, date, $SECONDS and file timestamps
+    /// (defaults to the host clock)
+    pub clock: Option<Clock>,
+    /// Seed for $RANDOM, awk rand(), sort -R and mktemp. Setting it also
+    /// fixes $$, $PPID, $UID and $EUID so reruns produce identical output.
+    pub seed: Option<u64>,
+    /// Receives a record of every command, file access and network request
+    /// the scripts make
+    pub audit: Option<AuditSink>,
+    /// Consulted before every command runs; can deny it or rewrite its
+    /// arguments
+    pub policy: Option<Arc<dyn CommandPolicy>>,
 }
 
 /// Per-execution options.
+#[derive(Default)]
 pub struct ExecOptions {
     /// Temporary environment variables
     pub env: Option<HashMap<String, String>>,
@@ -31,6 +61,11 @@ pub struct ExecOptions {
     pub cwd: Option<String>,
     /// Skip script normalization
     pub raw_script: bool,
+    /// Wall-clock limit for this execution in milliseconds, overriding
+    /// `ExecutionLimits::max_duration_ms`
+    pub timeout_ms: Option<u64>,
+    /// Token the caller can cancel to stop the script
+    pub cancellation: Option<CancellationToken>,
 }
 
 /// The main Bash shell environment.
@@ -38,6 +73,10 @@ pub struct Bash {
     pub fs: Arc<dyn FileSystem>,
     limits: ExecutionLimits,
     state: InterpreterState,
+    registry: Arc<CommandRegistry>,
+    fetch: Option<FetchFn>,
+    audit: Option<AuditSink>,
+    policy: Option<Arc<dyn CommandPolicy>>,
 }
 
 impl Bash {
@@ -46,11 +85,16 @@ impl Bash {
         let use_default_layout = options.cwd.is_none();
         let cwd = options.cwd.unwrap_or_else(|| "/home/user".to_string());
 
-        let fs: Arc<dyn FileSystem> = options.fs.unwrap_or_else(|| {
-            Arc::new(InMemoryFs::new())
-        });
-
+        let clock = options.clock.unwrap_or_default();
         let limits = options.limits.unwrap_or_default();
+        // The default filesystem gets its limits once the shell has set it up
+        let (fs, default_fs): (Arc<dyn FileSystem>, _) = match options.fs {
+            Some(fs) => (fs, None),
+            None => {
+                let fs = Arc::new(InMemoryFs::with_clock(clock.clone()));
+                (fs.clone(), Some(fs))
+            }
+        };
 
         // Build default environment
         let mut env = HashMap::new();
@@ -90,6 +134,15 @@ impl Bash {
         state.previous_dir = if use_default_layout { "/home/user".to_string() } else { "/".to_string() };
         state.exported_vars = Some(exported);
         state.readonly_vars = Some(["SHELLOPTS".to_string(), "BASHOPTS".to_string()].into_iter().collect());
+        let host = match options.seed {
+            Some(seed) => Host {
+                clock,
+                random: Random::seeded(seed),
+                identity: ProcessIdentity::virtual_identity(),
+            },
+            None => Host { clock, random: Random::from_entropy(), identity: ProcessIdentity::host() },
+        };
+        host.apply(&mut state);
 
         // Set SHELLOPTS and BASHOPTS
         let shellopts = build_shellopts(&state.options);
@@ -97,13 +150,37 @@ impl Bash {
         state.env.insert("SHELLOPTS".to_string(), shellopts);
         state.env.insert("BASHOPTS".to_string(), bashopts);
 
+        let registry = Arc::new(create_batch_abcdefghijklmno_registry());
+
         // Initialize filesystem
         init_filesystem(&*fs, use_default_layout).await;
+        install_command_stubs(&*fs, &registry).await;
 
         // Ensure cwd exists
         let _ = fs.mkdir(&cwd, &MkdirOptions { recursive: true }).await;
 
-        Self { fs, limits, state }
+        if let Some(default_fs) = default_fs {
+            default_fs.apply_limits(FsLimits {
+                max_file_size: limits.max_file_size,
+                max_total_bytes: limits.max_fs_size,
+                max_inodes: limits.max_fs_inodes,
+            }).await;
+        }
+
+        let fetch = match (options.fetch, &options.audit) {
+            (Some(fetch), Some(sink)) => Some(audit_fetch(fetch, sink.clone(), state.clock.clone())),
+            (fetch, _) => fetch,
+        };
+
+        Self {
+            fs,
+            limits,
+            state,
+            registry,
+            fetch,
+            audit: options.audit,
+            policy: options.policy,
+        }
     }
 
     /// Execute a bash script.
@@ -136,32 +213,65 @@ impl Bash {
             normalize_script(script)
         };
 
+        // The deadline covers the whole execution, nested scripts included
+        let timeout_ms = options.as_ref().and_then(|o| o.timeout_ms).or(self.limits.max_duration_ms);
+        let interrupt = Interrupt {
+            deadline: timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms)),
+            cancellation: options.as_ref().and_then(|o| o.cancellation.clone()),
+            reported: Arc::default(),
+        };
+
         // Parse the script
         match crate::parser::parse(&normalized) {
             Ok(ast) => {
-                // Execute AST via interpreter
-                let fs = self.fs.clone();
+                // Execute AST via interpreter. Only what the script does
+                // is audited, not the host setting up the filesystem.
+                let fs: Arc<dyn FileSystem> = match &self.audit {
+                    Some(sink) => Arc::new(AuditFs::new(self.fs.clone(), sink.clone())),
+                    None => self.fs.clone(),
+                };
+                let audit = self.audit.clone();
+                let policy = self.policy.clone();
                 let limits = self.limits.clone();
+                let registry = self.registry.clone();
+                let fetch = self.fetch.clone();
+                let host = Host::from_state(&self.state);
                 let state = &mut self.state;
 
                 // Use block_in_place to bridge async context with sync execution engine
                 tokio::task::block_in_place(|| {
-                    let handle = tokio::runtime::Handle::current();
-                    let sync_fs = crate::interpreter::SyncFsAdapter::new(fs, handle);
-                    let engine = crate::interpreter::ExecutionEngine::new(&limits, &sync_fs);
-
-                    match engine.execute_script(state, &ast) {
-                        Ok(result) => result,
-                        Err(crate::interpreter::InterpreterError::Exit(e)) => {
-                            ExecResult::new(e.stdout, e.stderr, e.exit_code)
+                    run_on_engine_thread(|| {
+                        let handle = tokio::runtime::Handle::current();
+                        let sync_fs = SyncFsAdapter::new(fs.clone(), handle);
+                        let nesting = ShellNesting::new();
+                        let exec_fn = create_exec_fn(
+                            registry.clone(),
+                            limits.clone(),
+                            fetch.clone(),
+                            interrupt.clone(),
+                            host,
+                            audit.clone(),
+                            policy.clone(),
+                            nesting.clone(),
+                        );
+                        let mut engine = ExecutionEngine::new(&limits, &sync_fs)
+                            .with_registry(registry, fs)
+                            .with_exec_fn(exec_fn)
+                            .with_nesting(nesting);
+                        if let Some(fetch) = fetch {
+                            engine = engine.with_fetch_fn(fetch);
                         }
-                        Err(crate::interpreter::InterpreterError::ExecutionLimit(e)) => {
-                            ExecResult::new(e.stdout, e.stderr, 126)
+                        if let Some(audit) = audit {
+                            engine = engine.with_audit(audit);
                         }
-                        Err(e) => {
-                            ExecResult::new(String::new(), format!("{}\n", e), 1)
+                        if let Some(policy) = policy {
+                            engine = engine.with_policy(policy);
                         }
-                    }
+                        engine = interrupt.apply(engine);
+
+                        let result = engine.execute_script(state, &ast);
+                        finish_execution(&mut engine, state, result)
+                    })
                 })
             }
             Err(e) => {
@@ -194,6 +304,263 @@ impl Bash {
     }
 }
 
+/// Stack size of the thread running the execution engine. The engine recurses
+/// for every nested construct and function call, so the default stack would
+/// overflow long before `max_recursion_depth` is reached.
+const ENGINE_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Run `f` on a dedicated thread with a large stack, inside the current
+/// runtime context so the filesystem adapter can still block on it.
+fn run_on_engine_thread<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    let handle = tokio::runtime::Handle::current();
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .name("just-bash-engine".to_string())
+            .stack_size(ENGINE_STACK_SIZE)
+            .spawn_scoped(scope, move || {
+                let _guard = handle.enter();
+                f()
+            })
+            .expect("failed to spawn engine thread")
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// How long the TERM or INT trap of an interrupted script may run.
+const INTERRUPT_TRAP_GRACE: Duration = Duration::from_secs(1);
+
+/// Map the outcome of a top-level script run to its final result.
+///
+/// The EXIT trap runs here, whether the script ended normally or through
+/// `exit`, unless it hit an execution limit. A script that timed out or was
+/// cancelled runs its TERM or INT trap instead, with a short grace period.
+/// Background jobs nobody waited for are joined after that, following
+/// everything the script printed itself.
+fn finish_execution(
+    engine: &mut ExecutionEngine,
+    state: &mut InterpreterState,
+    result: Result<ExecResult, InterpreterError>,
+) -> ExecResult {
+    let limited = matches!(result, Err(InterpreterError::ExecutionLimit(_)));
+    let mut result = match result {
+        Ok(result) => engine.run_exit_trap(state, result),
+        Err(InterpreterError::Exit(e)) => engine.run_exit_trap(state, ExecResult::new(e.stdout, e.stderr, e.exit_code)),
+        // A script stopped by a safety limit gets no chance to run more code
+        Err(InterpreterError::ExecutionLimit(mut e)) => {
+            if e.limit_type == LimitType::Output {
+                e.truncate_stdout(engine.limits.max_output_size);
+            }
+            let exit_code = e.exit_code();
+            let mut result = ExecResult::new(e.stdout, e.stderr, exit_code);
+            let signal = match e.limit_type {
+                LimitType::Timeout => Some("TERM"),
+                LimitType::Cancelled => Some("INT"),
+                _ => None,
+            };
+            if let Some(signal) = signal {
+                engine.cancellation = None;
+                engine.deadline = Some(Instant::now() + INTERRUPT_TRAP_GRACE);
+                let trap = engine.run_trap(state, signal).unwrap_or_else(|mut error| {
+                    let output = error.as_control_flow_mut();
+                    ExecResult::new(std::mem::take(output.stdout_mut()), std::mem::take(output.stderr_mut()), 0)
+                });
+                result.stdout.push_str(&trap.stdout);
+                result.stderr.push_str(&trap.stderr);
+            }
+            result
+        }
+        Err(e) => engine.run_exit_trap(state, ExecResult::new(String::new(), format!("{}\n", e), 1)),
+    };
+    if !limited {
+        result.stderr.push_str(&engine.finish_coprocesses(state));
+    }
+    let (stdout, stderr) = drain_jobs(state, &mut |state, stmt| engine.execute_statement(state, stmt));
+    result.stdout.push_str(&stdout);
+    result.stderr.push_str(&stderr);
+    result
+}
+
+/// The deadline and cancellation token of one `Bash::exec`.
+#[derive(Clone, Default)]
+struct Interrupt {
+    deadline: Option<Instant>,
+    cancellation: Option<CancellationToken>,
+    /// Set once a shell has reported the timeout or cancellation
+    reported: Arc<AtomicBool>,
+}
+
+impl Interrupt {
+    fn apply<'a>(&self, mut engine: ExecutionEngine<'a>) -> ExecutionEngine<'a> {
+        if let Some(deadline) = self.deadline {
+            engine = engine.with_deadline(deadline);
+        }
+        engine = engine.with_interrupt_reported(self.reported.clone());
+        if let Some(ref token) = self.cancellation {
+            engine = engine.with_cancellation(token.clone());
+        }
+        engine
+    }
+}
+
+/// The clock, random source and process identity shared by a shell and the
+/// nested shells it starts.
+#[derive(Clone)]
+struct Host {
+    clock: Clock,
+    random: Random,
+    identity: ProcessIdentity,
+}
+
+impl Host {
+    fn from_state(state: &InterpreterState) -> Self {
+        Self {
+            clock: state.clock.clone(),
+            random: state.random.clone(),
+            identity: state.identity.clone(),
+        }
+    }
+
+    fn apply(&self, state: &mut InterpreterState) {
+        state.start_time = self.clock.peek_unix_secs();
+        state.clock = self.clock.clone();
+        state.random = self.random.clone();
+        state.identity = self.identity.clone();
+        state.bash_pid = self.identity.pid;
+    }
+}
+
+/// Build the callback registered commands use to run nested scripts
+/// (`bash -c`, `xargs`, `find -exec`).
+///
+/// Each call runs in a fresh shell state seeded from the given env and cwd,
+/// sharing the registry, limits, fetch handler, deadline, host, audit sink
+/// and policy of the parent. It continues the parent's command count one
+/// level deeper, as read from `nesting`, and adds the commands it ran.
+#[allow(clippy::too_many_arguments)]
+fn create_exec_fn(
+    registry: Arc<CommandRegistry>,
+    limits: ExecutionLimits,
+    fetch: Option<FetchFn>,
+    interrupt: Interrupt,
+    host: Host,
+    audit: Option<AuditSink>,
+    policy: Option<Arc<dyn CommandPolicy>>,
+    nesting: ShellNesting,
+) -> ExecFn {
+    Arc::new(move |script, stdin, cwd, env, fs| {
+        let registry = registry.clone();
+        let limits = limits.clone();
+        let fetch = fetch.clone();
+        let interrupt = interrupt.clone();
+        let host = host.clone();
+        let audit = audit.clone();
+        let policy = policy.clone();
+        let parent = nesting.clone();
+        Box::pin(async move {
+            tokio::task::block_in_place(|| {
+                let (command_count, depth) = parent.current();
+                if depth >= limits.max_recursion_depth {
+                    return CommandResult::with_exit_code(
+                        String::new(),
+                        format!("bash: maximum recursion depth ({}) exceeded\n", limits.max_recursion_depth),
+                        ExecutionLimitError::EXIT_CODE,
+                    );
+                }
+                let ast = match crate::parser::parse(&normalize_script(&script)) {
+                    Ok(ast) => ast,
+                    Err(e) => {
+                        return CommandResult::with_exit_code(
+                            String::new(),
+                            format!("bash: syntax error: {}\n", e),
+                            2,
+                        );
+                    }
+                };
+
+                let mut state = InterpreterState {
+                    exported_vars: Some(
+                        env.keys()
+                            .filter(|name| is_exportable_name(name))
+                            .cloned()
+                            .collect(),
+                    ),
+                    env,
+                    ..Default::default()
+                };
+                state.env.insert("PWD".to_string(), cwd.clone());
+                state.cwd = cwd;
+                state.command_count = command_count;
+                state.script_depth = depth + 1;
+                state.captured_fds = parent.captured_fds();
+                host.apply(&mut state);
+                if let Some(clock) = Clock::scoped() {
+                    state.clock = clock;
+                }
+                if !stdin.is_empty() {
+                    state.group_stdin = Some(stdin);
+                }
+
+                let handle = tokio::runtime::Handle::current();
+                let sync_fs = SyncFsAdapter::new(fs.clone(), handle);
+                let nesting = ShellNesting::new();
+                let exec_fn = create_exec_fn(
+                    registry.clone(),
+                    limits.clone(),
+                    fetch.clone(),
+                    interrupt.clone(),
+                    host,
+                    audit.clone(),
+                    policy.clone(),
+                    nesting.clone(),
+                );
+                let mut engine = ExecutionEngine::new(&limits, &sync_fs)
+                    .with_registry(registry, fs)
+                    .with_exec_fn(exec_fn)
+                    .with_nesting(nesting);
+                if let Some(fetch) = fetch {
+                    engine = engine.with_fetch_fn(fetch);
+                }
+                if let Some(audit) = audit {
+                    engine = engine.with_audit(audit);
+                }
+                if let Some(policy) = policy {
+                    engine = engine.with_policy(policy);
+                }
+                engine = interrupt.apply(engine);
+
+                let result = engine.execute_script(&mut state, &ast);
+                let result = finish_execution(&mut engine, &mut state, result);
+                parent.add_commands(state.command_count.saturating_sub(command_count));
+                CommandResult::with_exit_code(result.stdout, result.stderr, result.exit_code)
+            })
+        })
+    })
+}
+
+/// Whether an env key is a variable name (as opposed to `#`, `@`, `1`, ...).
+fn is_exportable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Write a stub file for every registered command into /bin and /usr/bin so
+/// PATH lookup, `ls /bin` and `command -v` see them like real executables.
+async fn install_command_stubs(fs: &dyn FileSystem, registry: &CommandRegistry) {
+    for name in registry.names() {
+        for dir in ["/bin", "/usr/bin"] {
+            let path = format!("{}/{}", dir, name);
+            if fs.exists(&path).await {
+                continue;
+            }
+            let stub = format!("#!/bin/bash\n# Built-in command: {}\n", name);
+            let _ = fs.write_file(&path, stub.as_bytes()).await;
+            let _ = fs.chmod(&path, 0o755).await;
+        }
+    }
+}
+
 /// Initialize the filesystem with standard directories and device files.
 async fn init_filesystem(fs: &dyn FileSystem, use_default_layout: bool) {
     let _ = fs.mkdir("/bin", &MkdirOptions { recursive: true }).await;
@@ -435,4 +802,426 @@ mod tests {
         let result = bash.exec("exit 42", None).await;
         assert_eq!(result.exit_code, 42);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_exec_exit_trap() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        let result = bash.exec("trap 'echo \"bye $?\"' EXIT; echo hi; exit 3", None).await;
+        assert_eq!(result.stdout, "hi\nbye 3\n");
+        assert_eq!(result.exit_code, 3);
+
+        // The trap fired with the script that set it
+        let result = bash.exec("echo again", None).await;
+        assert_eq!(result.stdout, "again\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_log() {
+        use crate::audit::{AuditEvent, CommandKind, FileOperation};
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collected = events.clone();
+        let mut bash = Bash::new(BashOptions {
+            audit: Some(Arc::new(move |event| collected.lock().unwrap().push(event))),
+            ..Default::default()
+        })
+        .await;
+        // Setting up the filesystem is not audited
+        assert!(events.lock().unwrap().is_empty());
+        let home = bash.get_cwd().to_string();
+
+        let script = "f() { cd /tmp; }; f; printf ab > out; cat out | wc -c; missing; echo x >> out 2>&1";
+        let result = bash.exec(script, None).await;
+        assert_eq!(result.stdout, "2\n");
+
+        let events = events.lock().unwrap();
+        let commands: Vec<(&str, CommandKind, i32)> = events
+            .iter()
+            .filter_map(|event| match event {
+                AuditEvent::Command(command) => Some((command.name.as_str(), command.kind, command.exit_code)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(commands, vec![
+            ("cd", CommandKind::Builtin, 0),
+            ("f", CommandKind::Function, 0),
+            ("printf", CommandKind::Registry, 0),
+            ("cat", CommandKind::Registry, 0),
+            ("wc", CommandKind::Registry, 0),
+            ("missing", CommandKind::NotFound, 127),
+            ("echo", CommandKind::Registry, 0),
+        ]);
+        let command = |name: &str| {
+            events.iter().find_map(|event| match event {
+                AuditEvent::Command(command) if command.name == name => Some(command),
+                _ => None,
+            })
+        };
+        // `cd` and the function calling it started in the old directory
+        assert_eq!(command("cd").unwrap().cwd, home);
+        assert_eq!(command("f").unwrap().cwd, home);
+        let wc = command("wc").unwrap();
+        assert_eq!(wc.argv, vec!["wc", "-c"]);
+        assert_eq!(wc.cwd, "/tmp");
+        assert_eq!(wc.path.as_deref(), Some("/usr/bin/wc"));
+        assert_eq!((wc.stdin_bytes, wc.stdout_bytes), (2, 2));
+        assert!(events.iter().any(|event| matches!(event,
+            AuditEvent::File(file) if file.operation == FileOperation::Read && file.path == "/tmp/out")));
+        // One write for each redirection, however many streams it carried
+        let writes = events.iter().filter(|event| matches!(event,
+            AuditEvent::File(file) if file.operation == FileOperation::Write && file.path == "/tmp/out"));
+        assert_eq!(writes.count(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_log_does_not_change_output() {
+        let script = "echo $SECONDS; date +%s; printf ab > out; cat out; sleep 2; echo $SECONDS; date +%s";
+        let mut outputs = Vec::new();
+        for audit in [None, Some(Arc::new(|_| {}) as AuditSink)] {
+            let clock = Clock::auto_advancing(std::time::UNIX_EPOCH, Duration::from_secs(1));
+            let mut bash = Bash::new(BashOptions { clock: Some(clock), audit, ..Default::default() }).await;
+            outputs.push(bash.exec(script, None).await.stdout);
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_command_policy() {
+        use crate::policy::{CommandPolicy, CommandRequest, PolicyDecision};
+
+        struct Workspace;
+        impl CommandPolicy for Workspace {
+            fn check(&self, request: &CommandRequest) -> PolicyDecision {
+                if request.name == "rm" && request.args.iter().any(|arg| arg == "/") {
+                    return PolicyDecision::deny("rm", "refusing to remove /");
+                }
+                if request.redirections.iter().any(|r| r.is_write() && !r.path.starts_with("/workspace/")) {
+                    return PolicyDecision::Deny { stderr: "writes outside /workspace\n".to_string(), exit_code: 1 };
+                }
+                if request.name == "echo" && request.args.first().is_some_and(|arg| arg == "secret") {
+                    return PolicyDecision::Rewrite { args: vec!["[redacted]".to_string()] };
+                }
+                if request.name == "exec" && request.redirections.iter().any(|r| r.path.ends_with("/exec.log")) {
+                    return PolicyDecision::Rewrite { args: vec!["echo".to_string(), "rewritten".to_string()] };
+                }
+                PolicyDecision::Allow
+            }
+        }
+
+        let mut bash = Bash::new(BashOptions {
+            cwd: Some("/workspace".to_string()),
+            policy: Some(Arc::new(Workspace)),
+            ..Default::default()
+        })
+        .await;
+
+        let result = bash.exec("rm -rf /; echo $?", None).await;
+        assert_eq!(result.stdout, "126\n");
+        assert_eq!(result.stderr, "bash: rm: refusing to remove /\n");
+
+        // Builtins that run other commands and nested shells are covered too
+        let result = bash.exec("command rm -rf /; bash -c 'rm -rf /'; echo / | xargs rm -rf", None).await;
+        assert_eq!(result.stderr, "bash: rm: refusing to remove /\n".repeat(3));
+
+        // A denied redirection never opens its file
+        let result = bash.exec("echo hi > /tmp/out; echo $?; echo ok > out; cat out", None).await;
+        assert_eq!(result.stdout, "1\nok\n");
+        assert_eq!(result.stderr, "writes outside /workspace\n");
+        assert!(!bash.fs.exists("/tmp/out").await);
+
+        let result = bash.exec("echo secret; echo secret > log; cat log", None).await;
+        assert_eq!(result.stdout, "[redacted]\n[redacted]\n");
+
+        // Redirections of compound commands, function definitions and bare
+        // redirections are checked as well
+        let scripts = [
+            "{ echo x; } > /tmp/f",
+            "while false; do :; done > /tmp/f",
+            "( echo x ) > /tmp/f",
+            "if true; then echo x; fi >> /tmp/f",
+            "f() { echo x; } > /tmp/f; f",
+            "> /tmp/f",
+            "exec > /tmp/f; echo x",
+        ];
+        for script in scripts {
+            let result = bash.exec(script, None).await;
+            assert_eq!(result.stderr, "writes outside /workspace\n", "{}", script);
+            assert!(!bash.fs.exists("/tmp/f").await, "{}", script);
+        }
+        let result = bash.exec("{ echo x; } > inside; cat inside", None).await;
+        assert_eq!(result.stdout, "x\n");
+
+        // A rewritten bare `exec` runs the command it was rewritten to
+        let result = bash.exec("exec > exec.log", None).await;
+        assert_eq!(result.stdout, "");
+        assert_eq!(bash.read_file("exec.log").await.unwrap(), "rewritten\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_nesting_limits() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        bash.write_file("self.sh", "source self.sh\n").await.unwrap();
+        let result = bash.exec("source self.sh; echo never", None).await;
+        assert_eq!(result.stdout, "");
+        assert_eq!(result.stderr, "bash: self.sh: maximum source nesting level exceeded (1000)\n");
+        assert_eq!(result.exit_code, 126);
+
+        let result = bash.exec("f() { eval f; }; eval f; echo never", None).await;
+        assert_eq!(result.stdout, "");
+        assert!(result.stderr.contains("maximum"), "{}", result.stderr);
+        assert_eq!(result.exit_code, 126);
+
+        let result = bash.exec("e='eval \"$e\"'; eval \"$e\"; echo never", None).await;
+        assert_eq!(result.stderr, "bash: eval: maximum eval nesting level exceeded (1000)\n");
+        assert_eq!(result.exit_code, 126);
+
+        bash.write_file("nest.sh", "bash nest.sh\n").await.unwrap();
+        let result = bash.exec("bash nest.sh; echo $?", None).await;
+        assert!(result.stderr.ends_with("bash: maximum recursion depth (1000) exceeded\n"), "{}", result.stderr);
+        assert_eq!(result.stdout, "126\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_nested_shells_share_command_limit() {
+        let mut bash = Bash::new(BashOptions {
+            limits: Some(ExecutionLimits { max_command_count: 50, ..Default::default() }),
+            ..Default::default()
+        })
+        .await;
+        // Each nested shell stays well under the limit on its own
+        let script = "for i in 1 2 3 4 5; do bash -c 'for j in $(seq 10); do :; done'; done; echo never";
+        let result = bash.exec(script, None).await;
+        assert_eq!(result.stdout, "");
+        assert!(result.stderr.contains("too many commands executed"), "{}", result.stderr);
+        assert_eq!(result.exit_code, 126);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_output_limit_counts_script_output() {
+        let mut bash = Bash::new(BashOptions {
+            limits: Some(ExecutionLimits { max_output_size: 1000, ..Default::default() }),
+            ..Default::default()
+        })
+        .await;
+        let script = "x=$(seq 500); echo \"$x\" | tail -1; seq 500 | tail -1; { seq 500; } >/dev/null; \
+                      seq 500 2>&1 >/dev/null; cat <(seq 500) | tail -1; bash -c 'seq 500' | tail -1";
+        let result = bash.exec(script, None).await;
+        assert_eq!(result.stdout, "500\n500\n500\n500\n");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+
+        let result = bash.exec("seq 500; echo never", None).await;
+        assert_eq!(result.exit_code, 126);
+        assert_eq!(result.stdout.len(), 1000);
+        assert!(result.stderr.contains("output size limit exceeded"), "{}", result.stderr);
+
+        // A pipe from a file larger than the default limit
+        let mut bash = Bash::new(BashOptions::default()).await;
+        bash.write_file("/tmp/big", &"x".repeat(11 * 1024 * 1024)).await.unwrap();
+        let result = bash.exec("cat /tmp/big | wc -c", None).await;
+        assert_eq!(result.stdout.trim(), "11534336");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_size_limits() {
+        let mut bash = Bash::new(BashOptions {
+            limits: Some(ExecutionLimits {
+                max_output_size: 100,
+                max_string_length: 64,
+                max_array_elements: 10,
+                max_file_size: 50,
+                max_fs_size: 100_000,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let result = bash.exec("echo start; while true; do echo xxxxxxxxx; done", None).await;
+        assert_eq!(result.exit_code, 126);
+        assert_eq!(result.stdout.len(), 100);
+        assert!(result.stdout.starts_with("start\nxxxxxxxxx\n"));
+        assert!(result.stderr.contains("output size limit exceeded"), "{}", result.stderr);
+
+        let result = bash.exec("x=a; while true; do x=$x$x; done; echo never", None).await;
+        assert_eq!(result.exit_code, 126);
+        assert!(result.stderr.contains("string length limit exceeded"), "{}", result.stderr);
+
+        let result = bash.exec("a=($(seq 1 20)); echo never", None).await;
+        assert_eq!(result.exit_code, 126);
+        assert!(result.stderr.contains("array size limit exceeded"), "{}", result.stderr);
+
+        let result = bash.exec("while true; do echo xxxxxxxxx >> /tmp/f || break; done; wc -c < /tmp/f", None).await;
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.stdout.trim(), "50");
+        assert!(result.stderr.contains("EFBIG: file too large"), "{}", result.stderr);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fs_limits_exclude_setup() {
+        let mut bash = Bash::new(BashOptions {
+            limits: Some(ExecutionLimits { max_fs_size: 10, max_fs_inodes: 2, ..Default::default() }),
+            ..Default::default()
+        })
+        .await;
+        let result = bash.exec("ls /bin | grep -c '^cat$'; echo 12345 > a; echo 1234 > b; echo x > c", None).await;
+        assert_eq!(result.stdout, "1\n");
+        assert!(result.stderr.contains("ENOSPC"), "{}", result.stderr);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_exec_timeout_and_cancellation() {
+        let mut bash = Bash::new(BashOptions {
+            limits: Some(ExecutionLimits {
+                max_command_count: u64::MAX,
+                max_iterations: u64::MAX,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await;
+        let options = ExecOptions { timeout_ms: Some(50), ..Default::default() };
+        let result = bash.exec("echo start; while true; do :; done; echo never", Some(options)).await;
+        assert_eq!(result.stdout, "start\n");
+        assert_eq!(result.stderr, "bash: execution timed out\n");
+        assert_eq!(result.exit_code, 124);
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            canceller.cancel();
+        });
+        let options = ExecOptions { cancellation: Some(token), ..Default::default() };
+        let result = bash.exec("echo start; bash -c 'while :; do :; done'; echo never", Some(options)).await;
+        assert_eq!(result.stdout, "start\n");
+        assert_eq!(result.stderr, "bash: execution cancelled\n");
+        assert_eq!(result.exit_code, 124);
+
+        // A nested shell that times out reports it once
+        let options = ExecOptions { timeout_ms: Some(50), ..Default::default() };
+        let result = bash.exec("bash -c 'while true; do :; done'; echo never", Some(options)).await;
+        assert_eq!(result.stdout, "");
+        assert_eq!(result.stderr, "bash: execution timed out\n");
+        assert_eq!(result.exit_code, 124);
+
+        // The next execution starts without a deadline
+        let result = bash.exec("echo again", None).await;
+        assert_eq!(result.stdout, "again\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_background_job_can_be_killed_before_it_runs() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        let start = std::time::Instant::now();
+        let result = bash.exec("sleep 5 & echo started; jobs; kill %1; wait %1; echo \"status $?\"", None).await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(result.stdout, "started\n[1]+  Running                 sleep 5 &\nstatus 143\n");
+
+        let result = bash.exec("sleep 0.05 && echo slept & echo first; wait; echo \"status $?\"", None).await;
+        assert_eq!(result.stdout, "first\nslept\nstatus 0\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_background_job_runs_before_shell_looks_for_its_effects() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        let script = "echo bg > /tmp/o & sleep 0.01; cat /tmp/o\n\
+                      (touch /tmp/ready; echo made) & until [ -f /tmp/ready ]; do sleep 0.01; done; echo ready\n\
+                      echo x > /tmp/o & read l < /tmp/o; echo \"$l\"";
+        let result = bash.exec(script, None).await;
+        assert_eq!(result.stdout, "bg\nready\nx\nmade\n");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unread_coprocess_runs_at_exit() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        let result = bash.exec("coproc { cat > /tmp/co.txt; echo bye >&2; }; echo hi >&${COPROC[1]}", None).await;
+        assert_eq!(result.stderr, "bye\n");
+        assert_eq!(bash.read_file("/tmp/co.txt").await.unwrap(), "hi\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_interrupted_sleep_runs_signal_trap() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        let started = Instant::now();
+        let options = ExecOptions { timeout_ms: Some(100), ..Default::default() };
+        let result = bash.exec("trap 'echo cleanup' TERM; sleep 3; echo never", Some(options)).await;
+        assert!(started.elapsed() < Duration::from_secs(2), "{:?}", started.elapsed());
+        assert_eq!(result.stdout, "cleanup\n");
+        assert_eq!(result.exit_code, 124);
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let started = Instant::now();
+        let options = ExecOptions { cancellation: Some(token), ..Default::default() };
+        let result = bash.exec("trap 'echo interrupted' INT; sleep 3", Some(options)).await;
+        assert!(started.elapsed() < Duration::from_secs(2), "{:?}", started.elapsed());
+        assert_eq!(result.stdout, "interrupted\n");
+        assert!(result.stderr.contains("bash: execution cancelled\n"), "{:?}", result.stderr);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_virtual_clock() {
+        // 2024-01-02T03:04:05Z
+        let clock = Clock::fixed_at_unix(1_704_164_645);
+        let mut bash = Bash::new(BashOptions { clock: Some(clock.clone()), ..Default::default() }).await;
+        let started = Instant::now();
+        let script = "sleep 3600; echo $SECONDS $EPOCHSECONDS $EPOCHREALTIME; date -u +%FT%T; \
+                      touch f; find f -printf '%T@\\n'; jq -n now; awk 'BEGIN { print systime() }'";
+        let result = bash.exec(script, None).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            result.stdout,
+            "3600 1704168245 1704168245.000000\n2024-01-02T04:04:05\n1704168245\n1704168245\n1704168245\n"
+        );
+
+        let result = bash.exec("timeout 1 sleep 5; echo $?; timeout 10 sleep 5; echo $?", None).await;
+        assert_eq!(result.stdout, "124\n0\n");
+        assert_eq!(clock.unix_secs_f64(), 1_704_168_255.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_timeout_stops_real_sleep() {
+        let mut bash = Bash::new(BashOptions::default()).await;
+        let started = Instant::now();
+        let result = bash.exec("timeout 0.2 sleep 100; echo $?; timeout 0.2 bash -c 'sleep 100'; echo $?", None).await;
+        assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+        assert_eq!(result.stdout, "124\n124\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_auto_advancing_clock_ignores_file_access() {
+        let clock = Clock::auto_advancing(std::time::UNIX_EPOCH, Duration::from_secs(1));
+        let mut bash = Bash::new(BashOptions { clock: Some(clock), ..Default::default() }).await;
+        let result = bash.exec("echo $SECONDS; echo x > f; cat f; ls /bin > /dev/null; echo $SECONDS", None).await;
+        assert_eq!(result.stdout, "0\nx\n1\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_seeded_execution_is_reproducible() {
+        let script = "echo $RANDOM $RANDOM; echo $$ $BASHPID $PPID $UID; (echo $$ $BASHPID); \
+                      awk 'BEGIN { print rand() }'; printf 'a\\nb\\nc\\nd\\ne\\n' | sort -R | tr '\\n' ' '; \
+                      mktemp; bash -c 'echo $$ $RANDOM'";
+        let mut outputs = Vec::new();
+        for _ in 0..2 {
+            let mut bash = Bash::new(BashOptions { seed: Some(7), ..Default::default() }).await;
+            let result = bash.exec(script, None).await;
+            assert_eq!(result.exit_code, 0, "{}", result.stderr);
+            outputs.push(result.stdout);
+        }
+        assert_eq!(outputs[0], outputs[1]);
+        let lines: Vec<&str> = outputs[0].lines().collect();
+        assert_eq!(lines[1], "100 100 1 1000");
+        assert_eq!(lines[2], "100 1000");
+
+        let mut bash = Bash::new(BashOptions { seed: Some(8), ..Default::default() }).await;
+        assert_ne!(bash.exec(script, None).await.stdout, outputs[0]);
+    }
 }
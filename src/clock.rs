@@ -0,0 +1,209 @@
+//! Clock
+//!
+//! Time source shared by the shell, its commands and the in-memory
+//! filesystem. The default clock reads the host time; a virtual clock makes
+//! `sleep`, `date`, `$SECONDS` and file timestamps deterministic.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often a real-clock `sleep` checks whether it was interrupted.
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+tokio::task_local! {
+    /// Clock handed down to nested shells started inside `Clock::scope`.
+    static SCOPED: Clock;
+}
+
+/// Pluggable time source.
+///
+/// Clones share the same virtual time, so a `sleep` in one command is seen
+/// by every later reader.
+#[derive(Clone, Default)]
+pub struct Clock {
+    virtual_time: Option<Arc<Mutex<VirtualTime>>>,
+    interrupted: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clock")
+            .field("virtual_time", &self.virtual_time)
+            .field("interruptible", &self.interrupted.is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct VirtualTime {
+    now: SystemTime,
+    tick: Duration,
+}
+
+impl Clock {
+    /// The host clock; `sleep` really blocks.
+    pub fn real() -> Self {
+        Self::default()
+    }
+
+    /// A virtual clock frozen at `at`. Only `sleep` moves it forward.
+    pub fn fixed(at: SystemTime) -> Self {
+        Self::auto_advancing(at, Duration::ZERO)
+    }
+
+    /// A virtual clock starting at `start` that moves forward by `tick`
+    /// after every read through `now`.
+    pub fn auto_advancing(start: SystemTime, tick: Duration) -> Self {
+        Self {
+            virtual_time: Some(Arc::new(Mutex::new(VirtualTime { now: start, tick }))),
+            interrupted: None,
+        }
+    }
+
+    /// The same clock, with a real-clock `sleep` that returns early once
+    /// `interrupted` returns true, or once an earlier interrupt fires.
+    pub fn with_interrupt(&self, interrupted: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        let interrupted: Arc<dyn Fn() -> bool + Send + Sync> = match self.interrupted.clone() {
+            Some(earlier) => Arc::new(move || earlier() || interrupted()),
+            None => Arc::new(interrupted),
+        };
+        Self { virtual_time: self.virtual_time.clone(), interrupted: Some(interrupted) }
+    }
+
+    /// Run `future` with this clock as the one nested shells started inside
+    /// it sleep on.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        SCOPED.scope(self, future).await
+    }
+
+    /// The clock set by the innermost enclosing `scope`, if any.
+    pub fn scoped() -> Option<Self> {
+        SCOPED.try_with(Clone::clone).ok()
+    }
+
+    /// A fixed virtual clock at `secs` seconds after the Unix epoch.
+    pub fn fixed_at_unix(secs: u64) -> Self {
+        Self::fixed(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        self.virtual_time.is_some()
+    }
+
+    /// Current time.
+    pub fn now(&self) -> SystemTime {
+        match &self.virtual_time {
+            None => SystemTime::now(),
+            Some(time) => {
+                let mut time = time.lock().unwrap();
+                let now = time.now;
+                let tick = time.tick;
+                time.now += tick;
+                now
+            }
+        }
+    }
+
+    /// Current time without moving an auto-advancing clock forward. Used for
+    /// readings the script can't observe directly, like file timestamps.
+    pub fn peek(&self) -> SystemTime {
+        match &self.virtual_time {
+            None => SystemTime::now(),
+            Some(time) => time.lock().unwrap().now,
+        }
+    }
+
+    /// Current time as seconds since the Unix epoch.
+    pub fn unix_secs_f64(&self) -> f64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0)
+    }
+
+    /// Whole seconds since the Unix epoch, read with `peek`.
+    pub fn peek_unix_secs(&self) -> u64 {
+        self.peek().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+
+    /// Wait for `duration`. A virtual clock advances instantly; the real
+    /// clock sleeps in short slices so an interrupt cuts the wait short.
+    pub fn sleep(&self, duration: Duration) {
+        if let Some(time) = &self.virtual_time {
+            time.lock().unwrap().now += duration;
+            return;
+        }
+        let Some(interrupted) = &self.interrupted else {
+            return std::thread::sleep(duration);
+        };
+        let end = std::time::Instant::now() + duration;
+        loop {
+            let now = std::time::Instant::now();
+            if now >= end || interrupted() {
+                return;
+            }
+            std::thread::sleep(SLEEP_SLICE.min(end - now));
+        }
+    }
+
+    /// Move a virtual clock forward to at least `at`. No-op for the real clock.
+    pub fn advance_to(&self, at: SystemTime) {
+        if let Some(time) = &self.virtual_time {
+            let mut time = time.lock().unwrap();
+            if at > time.now {
+                time.now = at;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = Clock::fixed_at_unix(1_000);
+        assert!(clock.is_virtual());
+        assert_eq!(clock.unix_secs_f64(), 1_000.0);
+        assert_eq!(clock.unix_secs_f64(), 1_000.0);
+        clock.sleep(Duration::from_secs(5));
+        assert_eq!(clock.unix_secs_f64(), 1_005.0);
+    }
+
+    #[test]
+    fn test_auto_advancing_clock() {
+        let clock = Clock::auto_advancing(UNIX_EPOCH, Duration::from_secs(1));
+        let shared = clock.clone();
+        assert_eq!(clock.unix_secs_f64(), 0.0);
+        assert_eq!(shared.unix_secs_f64(), 1.0);
+        assert_eq!(shared.peek(), UNIX_EPOCH + Duration::from_secs(2));
+        assert_eq!(shared.peek(), UNIX_EPOCH + Duration::from_secs(2));
+        clock.advance_to(UNIX_EPOCH + Duration::from_secs(10));
+        assert_eq!(shared.unix_secs_f64(), 10.0);
+    }
+
+    #[test]
+    fn test_interrupted_sleep() {
+        let clock = Clock::real().with_interrupt(|| true);
+        let started = std::time::Instant::now();
+        clock.sleep(Duration::from_secs(5));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_interrupts_combine() {
+        let clock = Clock::real().with_interrupt(|| true).with_interrupt(|| false);
+        let started = std::time::Instant::now();
+        clock.sleep(Duration::from_secs(5));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_real_clock() {
+        let clock = Clock::real();
+        assert!(!clock.is_virtual());
+        assert!(clock.unix_secs_f64() > 0.0);
+    }
+}
@@ -68,26 +68,13 @@ mod tests {
     use crate::fs::InMemoryFs;
 
     fn create_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     fn create_ctx_with_env(args: Vec<&str>, env: HashMap<String, String>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
             env,
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
@@ -8,6 +8,7 @@ use std::collections::HashMap;
 
 use crate::commands::awk::coercion::{to_number, to_string};
 use crate::commands::awk::context::AwkContext;
+use crate::random::Random;
 
 // ─── Result Type ─────────────────────────────────────────────────
 
@@ -783,32 +784,21 @@ pub fn builtin_exp(args: &[String]) -> String {
 }
 
 /// rand() - Random number in [0, 1).
-pub fn builtin_rand() -> String {
-    // Use a simple LCG for reproducibility when seeded
-    // In practice, the interpreter should maintain the RNG state
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let seed = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_nanos())
-        .unwrap_or(0);
-    let r = ((seed as u64).wrapping_mul(1103515245).wrapping_add(12345) % (1 << 31)) as f64
-        / (1u64 << 31) as f64;
-    to_string(r)
+pub fn builtin_rand(ctx: &AwkContext) -> String {
+    to_string(ctx.random.next_f64())
 }
 
-/// srand(seed?) - Seed the random number generator.
-/// Returns the previous seed.
-pub fn builtin_srand(args: &[String]) -> String {
+/// srand(seed?) - Seed the random number generator with `seed`, or the
+/// time of day. Returns the previous seed.
+pub fn builtin_srand(args: &[String], ctx: &mut AwkContext) -> String {
     let seed = if args.is_empty() {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0) as f64
+        ctx.clock.unix_secs_f64().floor()
     } else {
         to_number(&args[0])
     };
-    to_string(seed)
+    let previous = std::mem::replace(&mut ctx.rand_seed, seed);
+    ctx.random = Random::seeded(seed.to_bits());
+    to_string(previous)
 }
 
 // ─── Stub Functions ──────────────────────────────────────────────
@@ -828,6 +818,11 @@ pub fn builtin_fflush(_args: &[String]) -> String {
     "0".to_string()
 }
 
+/// systime() - Current time in seconds since the epoch.
+pub fn builtin_systime(ctx: &AwkContext) -> String {
+    (ctx.clock.unix_secs_f64() as u64).to_string()
+}
+
 // ─── Built-in Function Registry ──────────────────────────────────
 
 /// Check if a function name is a built-in function.
@@ -857,6 +852,7 @@ pub fn is_builtin(name: &str) -> bool {
             | "system"
             | "close"
             | "fflush"
+            | "systime"
     )
 }
 
@@ -882,8 +878,8 @@ pub fn call_builtin(
         "atan2" => Some(BuiltinResult::Value(builtin_atan2(args))),
         "log" => Some(BuiltinResult::Value(builtin_log(args))),
         "exp" => Some(BuiltinResult::Value(builtin_exp(args))),
-        "rand" => Some(BuiltinResult::Value(builtin_rand())),
-        "srand" => Some(BuiltinResult::Value(builtin_srand(args))),
+        "rand" => Some(BuiltinResult::Value(builtin_rand(ctx))),
+        "srand" => Some(BuiltinResult::Value(builtin_srand(args, ctx))),
         "match" => {
             if args.len() >= 2 {
                 Some(BuiltinResult::Value(builtin_match(&args[0], &args[1], ctx)))
@@ -894,6 +890,7 @@ pub fn call_builtin(
         "system" => Some(builtin_system(args)),
         "close" => Some(BuiltinResult::Value(builtin_close(args))),
         "fflush" => Some(BuiltinResult::Value(builtin_fflush(args))),
+        "systime" => Some(BuiltinResult::Value(builtin_systime(ctx))),
         // split, sub, gsub, gensub need special handling by the interpreter
         // because they modify arrays or variables
         _ => None,
@@ -6,6 +6,8 @@
 
 use std::collections::{HashMap, HashSet};
 use regex_lite::Regex;
+use crate::clock::Clock;
+use crate::random::Random;
 use crate::commands::awk::types::AwkFunctionDef;
 
 const DEFAULT_MAX_ITERATIONS: usize = 10000;
@@ -84,6 +86,13 @@ pub struct AwkContext {
     // I/O
     pub output: String,
     pub opened_files: HashSet<String>,
+
+    // Time source for systime()
+    pub clock: Clock,
+
+    // Random numbers for rand(); srand() replaces the source
+    pub random: Random,
+    pub rand_seed: f64,
 }
 
 impl AwkContext {
@@ -139,6 +148,11 @@ impl AwkContext {
 
             output: String::new(),
             opened_files: HashSet::new(),
+
+            clock: Clock::real(),
+
+            random: Random::from_entropy(),
+            rand_seed: 0.0,
         }
     }
 }
@@ -12,7 +12,7 @@ pub mod types;
 pub mod variables;
 
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use self::context::AwkContext;
 use self::interpreter::AwkInterpreter;
 use self::parser::parse;
@@ -54,6 +54,10 @@ impl Command for AwkCommand {
         "awk"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         // Handle --help
         if ctx.args.iter().any(|a| a == "--help") {
@@ -62,7 +66,8 @@ impl Command for AwkCommand {
                  Pattern scanning and text processing language.\n\n\
                  Options:\n  \
                  -F FS      use FS as field separator\n  \
-                 -v VAR=VAL assign VAL to variable VAR\n      \
+                 -v VAR=VAL assign VAL to variable VAR\n  \
+                 -f FILE    read the program from FILE\n      \
                  --help     display this help and exit\n"
                     .to_string(),
             );
@@ -71,6 +76,7 @@ impl Command for AwkCommand {
         let mut field_sep = " ".to_string();
         let mut preset_vars: Vec<(String, String)> = Vec::new();
         let mut program_idx: Option<usize> = None;
+        let mut program_file: Option<String> = None;
 
         // Parse options
         let args = &ctx.args;
@@ -93,6 +99,10 @@ impl Command for AwkCommand {
                     preset_vars.push((var_name, var_value));
                 }
                 i += 1;
+            } else if arg == "-f" && i + 1 < args.len() {
+                i += 1;
+                program_file = Some(args[i].clone());
+                i += 1;
             } else if arg.starts_with("--") {
                 return CommandResult::error(format!("awk: unknown option: {}\n", arg));
             } else if arg.starts_with('-') && arg.len() > 1 {
@@ -109,19 +119,32 @@ impl Command for AwkCommand {
             }
         }
 
-        // Check for missing program
-        let program_idx = match program_idx {
-            Some(idx) => idx,
-            None => {
-                return CommandResult::error("awk: missing program\n".to_string());
+        // With -f, the program comes from a file and every operand is input
+        let (program_text, files) = match program_file {
+            Some(file) => {
+                let path = ctx.fs.resolve_path(&ctx.cwd, &file);
+                let text = match ctx.fs.read_file(&path).await {
+                    Ok(text) => text,
+                    Err(_) => {
+                        return CommandResult::error(format!(
+                            "awk: can't open file {}: No such file or directory\n",
+                            file
+                        ));
+                    }
+                };
+                let files = program_idx.map(|idx| args[idx..].to_vec()).unwrap_or_default();
+                (text, files)
             }
+            None => match program_idx {
+                Some(idx) => (args[idx].clone(), args[idx + 1..].to_vec()),
+                None => {
+                    return CommandResult::error("awk: missing program\n".to_string());
+                }
+            },
         };
 
-        let program_text = &args[program_idx];
-        let files: Vec<String> = args[program_idx + 1..].to_vec();
-
         // Parse the AWK program
-        let ast = match parse(program_text) {
+        let ast = match parse(&program_text) {
             Ok(ast) => ast,
             Err(e) => {
                 return CommandResult::error(format!("awk: {}\n", e));
@@ -147,6 +170,8 @@ impl Command for AwkCommand {
         for (key, value) in &ctx.env {
             awk_ctx.environ.insert(key.clone(), value.clone());
         }
+        awk_ctx.clock = ctx.clock();
+        awk_ctx.random = ctx.random();
 
         // Create interpreter
         let mut interp = AwkInterpreter::new(awk_ctx, ast.clone());
@@ -291,37 +316,23 @@ mod tests {
 
     fn make_ctx(args: Vec<&str>, stdin: &str) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
     fn make_ctx_with_fs(args: Vec<&str>, stdin: &str, fs: Arc<InMemoryFs>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
     fn make_ctx_with_env(args: Vec<&str>, stdin: &str, env: HashMap<String, String>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
             env,
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
@@ -852,19 +863,22 @@ mod tests {
         fs.write_file("/file1.txt", b"a\nb\n").await.unwrap();
         fs.write_file("/file2.txt", b"c\nd\n").await.unwrap();
         let cmd = AwkCommand;
-        let ctx = CommandContext {
-            args: vec!["{ print }".to_string(), "/file1.txt".to_string(), "/file2.txt".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["{ print }".to_string(), "/file1.txt".to_string(), "/file2.txt".to_string()], fs);
         let result = cmd.execute(ctx).await;
         assert_eq!(result.stdout, "a\nb\nc\nd\n");
     }
 
+    #[tokio::test]
+    async fn test_program_file() {
+        let fs = Arc::new(InMemoryFs::new());
+        fs.write_file("/prog.awk", b"#!/usr/bin/awk -f\n{ print $2 }\n").await.unwrap();
+        fs.write_file("/data.txt", b"a b\nc d\n").await.unwrap();
+        let cmd = AwkCommand;
+        let ctx = make_ctx_with_fs(vec!["-f", "prog.awk", "data.txt"], "", fs);
+        let result = cmd.execute(ctx).await;
+        assert_eq!(result.stdout, "b\nd\n");
+    }
+
     #[tokio::test]
     async fn test_missing_program_error() {
         let cmd = AwkCommand;
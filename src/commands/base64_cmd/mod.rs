@@ -2,7 +2,7 @@
 use async_trait::async_trait;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct Base64Command;
 
@@ -40,6 +40,10 @@ impl Command for Base64Command {
         "base64"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let args = &ctx.args;
 
@@ -141,53 +145,26 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     fn make_ctx_with_stdin(args: Vec<&str>, stdin: &str) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
     fn make_ctx_with_fs(args: Vec<&str>, fs: Arc<InMemoryFs>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     fn make_ctx_with_stdin_and_fs(args: Vec<&str>, stdin: &str, fs: Arc<InMemoryFs>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
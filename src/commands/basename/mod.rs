@@ -84,18 +84,9 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     #[tokio::test]
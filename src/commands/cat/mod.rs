@@ -1,6 +1,6 @@
 // src/commands/cat/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct CatCommand;
 
@@ -10,6 +10,10 @@ impl Command for CatCommand {
         "cat"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let args = &ctx.args;
 
@@ -101,22 +105,13 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -168,13 +163,8 @@ mod tests {
     async fn test_cat_stdin() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-".to_string()],
             stdin: "from stdin\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-".to_string()], fs)
         };
         let cmd = CatCommand;
         let result = cmd.execute(ctx).await;
@@ -267,13 +257,8 @@ mod tests {
         fs.mkdir("/home/user", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/home/user/file.txt", b"content").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["file.txt".to_string()],
-            stdin: String::new(),
             cwd: "/home/user".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["file.txt".to_string()], fs)
         };
         let cmd = CatCommand;
         let result = cmd.execute(ctx).await;
@@ -287,13 +272,8 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/file.txt", b"from file\n").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["-".to_string(), "/file.txt".to_string()],
             stdin: "from stdin\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-".to_string(), "/file.txt".to_string()], fs)
         };
         let cmd = CatCommand;
         let result = cmd.execute(ctx).await;
@@ -307,13 +287,8 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/file.txt", b"from file\n").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["/file.txt".to_string(), "-".to_string()],
             stdin: "from stdin\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["/file.txt".to_string(), "-".to_string()], fs)
         };
         let cmd = CatCommand;
         let result = cmd.execute(ctx).await;
@@ -327,13 +302,8 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/file.txt", b"line1\n").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["-n".to_string(), "/file.txt".to_string(), "-".to_string()],
             stdin: "line2\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-n".to_string(), "/file.txt".to_string(), "-".to_string()], fs)
         };
         let cmd = CatCommand;
         let result = cmd.execute(ctx).await;
@@ -130,12 +130,11 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (p, c) in files { fs.write_file(p, c.as_bytes()).await.unwrap(); }
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: String::new(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
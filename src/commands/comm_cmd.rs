@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct CommCommand;
 
@@ -19,6 +19,10 @@ impl Command for CommCommand {
         "comm"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let mut suppress1 = false;
         let mut suppress2 = false;
@@ -124,20 +128,11 @@ impl Command for CommCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::{InMemoryFs, FileSystem};
 
     fn create_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     #[tokio::test]
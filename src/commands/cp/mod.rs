@@ -122,22 +122,13 @@ mod tests {
     use super::*;
     use crate::fs::{InMemoryFs, FileSystem};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -159,15 +150,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/src.txt", b"content").await.unwrap();
         fs.mkdir("/destdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/src.txt".to_string(), "/destdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/src.txt".to_string(), "/destdir".to_string()], fs.clone());
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -178,15 +161,7 @@ mod tests {
     async fn test_cp_directory_without_r() {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/srcdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/srcdir".to_string(), "/destdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/srcdir".to_string(), "/destdir".to_string()], fs);
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stderr.contains("omitting directory"));
@@ -241,15 +216,7 @@ mod tests {
         fs.write_file("/a.txt", b"aaa").await.unwrap();
         fs.write_file("/b.txt", b"bbb").await.unwrap();
         fs.mkdir("/dir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/a.txt".to_string(), "/b.txt".to_string(), "/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/a.txt".to_string(), "/b.txt".to_string(), "/dir".to_string()], fs.clone());
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -274,15 +241,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/srcdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/srcdir/file.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["-r".to_string(), "/srcdir".to_string(), "/dstdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-r".to_string(), "/srcdir".to_string(), "/dstdir".to_string()], fs.clone());
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -295,15 +254,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/srcdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/srcdir/file.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["-R".to_string(), "/srcdir".to_string(), "/dstdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-R".to_string(), "/srcdir".to_string(), "/dstdir".to_string()], fs.clone());
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -318,15 +269,7 @@ mod tests {
         fs.mkdir("/src/a/b", &crate::fs::MkdirOptions { recursive: true }).await.unwrap();
         fs.write_file("/src/a/b/c.txt", b"deep").await.unwrap();
         fs.write_file("/src/root.txt", b"root").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["-r".to_string(), "/src".to_string(), "/dst".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-r".to_string(), "/src".to_string(), "/dst".to_string()], fs.clone());
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -339,15 +282,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/srcdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/srcdir/file.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["--recursive".to_string(), "/srcdir".to_string(), "/dstdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["--recursive".to_string(), "/srcdir".to_string(), "/dstdir".to_string()], fs.clone());
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -382,13 +317,8 @@ mod tests {
         fs.mkdir("/home/user", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/home/user/src.txt", b"content").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["src.txt".to_string(), "dst.txt".to_string()],
-            stdin: String::new(),
             cwd: "/home/user".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["src.txt".to_string(), "dst.txt".to_string()], fs.clone())
         };
         let cmd = CpCommand;
         let result = cmd.execute(ctx).await;
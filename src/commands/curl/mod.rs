@@ -349,25 +349,15 @@ mod tests {
 
     fn make_ctx(args: Vec<&str>, fetch_fn: Option<FetchFn>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
             fetch_fn,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
     fn make_ctx_with_fs(args: Vec<&str>, fetch_fn: Option<FetchFn>, fs: Arc<InMemoryFs>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
             fetch_fn,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
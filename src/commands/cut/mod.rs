@@ -1,6 +1,6 @@
 // src/commands/cut/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct CutCommand;
 
@@ -115,6 +115,10 @@ impl Command for CutCommand {
         "cut"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -259,7 +263,6 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use crate::fs::types::FileSystem;
-    use std::collections::HashMap;
     use std::sync::Arc;
 
     async fn make_ctx(
@@ -272,13 +275,8 @@ mod tests {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
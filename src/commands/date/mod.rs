@@ -56,7 +56,7 @@ impl Command for DateCommand {
         }
 
         // Determine the date/time to use
-        let now_utc = Utc::now();
+        let now_utc: DateTime<Utc> = ctx.clock().now().into();
         let dt_utc: DateTime<Utc> = if let Some(ref ds) = date_str {
             match parse_date_string(ds, now_utc) {
                 Some(dt) => dt,
@@ -167,11 +167,10 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: String::new(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
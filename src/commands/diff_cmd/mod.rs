@@ -1,6 +1,6 @@
 // src/commands/diff_cmd/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use similar::{ChangeTag, TextDiff};
 
 pub struct DiffCommand;
@@ -11,6 +11,10 @@ impl Command for DiffCommand {
         "diff"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -157,7 +161,6 @@ fn format_unified_diff(file1: &str, file2: &str, content1: &str, content2: &str)
 mod tests {
     use super::*;
     use crate::fs::{InMemoryFs, FileSystem};
-    use std::collections::HashMap;
     use std::sync::Arc;
 
     async fn make_ctx_with_files(
@@ -170,13 +173,8 @@ mod tests {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
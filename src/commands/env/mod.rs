@@ -230,13 +230,8 @@ mod tests {
     fn make_ctx(args: Vec<&str>, env: HashMap<String, String>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
             env,
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct ExpandCommand;
 
@@ -116,6 +116,10 @@ impl Command for ExpandCommand {
         "expand"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let mut tab_stops = vec![8usize];
         let mut leading_only = false;
@@ -192,7 +196,6 @@ impl Command for ExpandCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
@@ -200,13 +203,8 @@ mod tests {
     async fn test_expand_default_tabs() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec![],
             stdin: "a\tb\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec![], fs)
         };
         let cmd = ExpandCommand;
         let result = cmd.execute(ctx).await;
@@ -218,13 +216,8 @@ mod tests {
     async fn test_expand_custom_tab_size() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-t4".to_string()],
             stdin: "a\tb\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-t4".to_string()], fs)
         };
         let cmd = ExpandCommand;
         let result = cmd.execute(ctx).await;
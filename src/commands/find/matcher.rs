@@ -45,8 +45,7 @@ pub fn evaluate(expr: &Expression, ctx: &EvalContext) -> EvalResult {
             EvalResult { matches: ctx.is_empty, pruned: false, printed: false, output: String::new() }
         }
         Expression::Mtime { days, comparison } => {
-            let now = SystemTime::now();
-            let duration = now.duration_since(ctx.mtime).unwrap_or_default();
+            let duration = ctx.now.duration_since(ctx.mtime).unwrap_or_default();
             let days_ago = (duration.as_secs() / 86400) as i64;
             let matches = match comparison {
                 Comparison::GreaterThan => days_ago > *days,
@@ -496,6 +495,7 @@ mod tests {
             depth: 0,
             is_empty: false,
             newer_ref_mtime: None,
+            now: SystemTime::now(),
             starting_point: ".".to_string(),
         }
     }
@@ -84,6 +84,8 @@ impl Command for FindCommand {
 
             // Resolve -newer reference file mtime
             let newer_ref_mtime = collect_newer_ref_mtime(&expression, &ctx, &base_path).await;
+            // -mtime ages are measured from when the search starts
+            let now = ctx.clock().now();
             // Traversal
             let mut matched_paths: Vec<String> = Vec::new();
             let mut output = String::new();
@@ -98,6 +100,7 @@ impl Command for FindCommand {
                     &options,
                     has_action,
                     newer_ref_mtime,
+                    now,
                     &mut matched_paths,
                     &mut output,
                     &mut all_stderr,
@@ -113,6 +116,7 @@ impl Command for FindCommand {
                     &options,
                     has_action,
                     newer_ref_mtime,
+                    now,
                     &mut matched_paths,
                     &mut output,
                     &mut all_stderr,
@@ -261,6 +265,7 @@ fn collect_newer_ref_path(expr: &Expression) -> Option<String> {
 }
 
 /// Build an EvalContext for a given entry.
+#[allow(clippy::too_many_arguments)]
 fn build_eval_context(
     name: &str,
     path: &str,
@@ -269,6 +274,7 @@ fn build_eval_context(
     depth: usize,
     is_empty: bool,
     newer_ref_mtime: Option<SystemTime>,
+    now: SystemTime,
     starting_point: &str,
 ) -> EvalContext {
     EvalContext {
@@ -284,6 +290,7 @@ fn build_eval_context(
         depth,
         is_empty,
         newer_ref_mtime,
+        now,
         starting_point: starting_point.to_string(),
     }
 }
@@ -364,6 +371,7 @@ async fn traverse_breadth_first(
     options: &FindOptions,
     has_action: bool,
     newer_ref_mtime: Option<SystemTime>,
+    now: SystemTime,
     matched_paths: &mut Vec<String>,
     output: &mut String,
     stderr: &mut String,
@@ -402,7 +410,7 @@ async fn traverse_breadth_first(
         // Build eval context
         let eval_ctx = build_eval_context(
             &name, &current_path, &relative_path, &stat, depth,
-            is_empty, newer_ref_mtime, search_path,
+            is_empty, newer_ref_mtime, now, search_path,
         );
 
         // Check mindepth
@@ -449,6 +457,7 @@ async fn traverse_depth_first(
     options: &FindOptions,
     has_action: bool,
     newer_ref_mtime: Option<SystemTime>,
+    now: SystemTime,
     matched_paths: &mut Vec<String>,
     output: &mut String,
     stderr: &mut String,
@@ -537,7 +546,7 @@ async fn traverse_depth_first(
 
         let eval_ctx = build_eval_context(
             &name, &node_info.path, &relative_path, &stat, node_info.depth,
-            is_empty, newer_ref_mtime, search_path,
+            is_empty, newer_ref_mtime, now, search_path,
         );
 
         let at_or_beyond_min = options.min_depth.map_or(true, |min| node_info.depth >= min);
@@ -550,20 +559,11 @@ async fn traverse_depth_first(
 mod tests {
     use super::*;
     use crate::fs::{InMemoryFs, FileSystem, MkdirOptions};
-    use std::collections::HashMap;
     use std::sync::Arc;
     use std::time::{Duration, SystemTime};
 
     fn make_ctx(fs: Arc<InMemoryFs>, args: &[&str]) -> CommandContext {
-        CommandContext {
-            args: args.iter().map(|s| s.to_string()).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.iter().map(|s| s.to_string()).collect(), fs)
     }
 
     fn sorted_lines(s: &str) -> Vec<&str> {
@@ -904,13 +904,8 @@ mod tests {
         fs.mkdir("/cwd", &MkdirOptions { recursive: true }).await.unwrap();
         fs.write_file("/cwd/test.txt", b"x").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["-name".to_string(), "*.txt".to_string()],
-            stdin: String::new(),
             cwd: "/cwd".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-name".to_string(), "*.txt".to_string()], fs)
         };
         let cmd = FindCommand;
         let result = cmd.execute(ctx).await;
@@ -935,13 +930,8 @@ mod tests {
         fs.mkdir("/cwd/sub", &MkdirOptions { recursive: true }).await.unwrap();
         fs.write_file("/cwd/sub/file.txt", b"x").await.unwrap();
         let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
             cwd: "/cwd".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec![], fs)
         };
         let cmd = FindCommand;
         let result = cmd.execute(ctx).await;
@@ -1231,16 +1221,14 @@ mod tests {
         fs.write_file("/project/src/utils.ts", b"").await.unwrap();
         fs.write_file("/project/lib/index.ts", b"").await.unwrap();
         let ctx = CommandContext {
-            args: vec![".", "-path", "./src/*", "-type", "f"]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-            stdin: String::new(),
             cwd: "/project".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(
+                vec![".", "-path", "./src/*", "-type", "f"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                fs,
+            )
         };
         let cmd = FindCommand;
         let result = cmd.execute(ctx).await;
@@ -1355,16 +1343,14 @@ mod tests {
         fs.mkdir("/project/src", &MkdirOptions { recursive: true }).await.unwrap();
         fs.write_file("/project/src/index.ts", b"content").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["/project/", "-name", "*.ts"]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-            stdin: String::new(),
             cwd: "/project".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(
+                vec!["/project/", "-name", "*.ts"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                fs,
+            )
         };
         let cmd = FindCommand;
         let result = cmd.execute(ctx).await;
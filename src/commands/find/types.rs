@@ -66,6 +66,8 @@ pub struct EvalContext {
     pub depth: usize,
     pub is_empty: bool,
     pub newer_ref_mtime: Option<SystemTime>,
+    /// Time the search started, for -mtime
+    pub now: SystemTime,
     pub starting_point: String,
 }
 
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct FoldCommand;
 
@@ -104,6 +104,10 @@ impl Command for FoldCommand {
         "fold"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let mut width = 80usize;
         let mut break_at_spaces = false;
@@ -229,7 +233,6 @@ impl Command for FoldCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
@@ -237,13 +240,8 @@ mod tests {
     async fn test_fold_default_width() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-w10".to_string()],
             stdin: "hello world foo bar\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-w10".to_string()], fs)
         };
         let cmd = FoldCommand;
         let result = cmd.execute(ctx).await;
@@ -255,13 +253,8 @@ mod tests {
     async fn test_fold_word_wrap() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-sw10".to_string()],
             stdin: "hello world\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-sw10".to_string()], fs)
         };
         let cmd = FoldCommand;
         let result = cmd.execute(ctx).await;
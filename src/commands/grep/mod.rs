@@ -1,7 +1,7 @@
 // src/commands/grep/mod.rs
 use async_trait::async_trait;
 use regex_lite::Regex;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct GrepCommand;
 
@@ -115,6 +115,10 @@ impl Command for GrepCommand {
         "grep"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -260,22 +264,13 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
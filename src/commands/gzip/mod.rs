@@ -4,7 +4,7 @@ use flate2::Compression;
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use std::io::{Read, Write};
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use crate::fs::types::RmOptions;
 
 pub struct GzipCommand;
@@ -14,6 +14,7 @@ pub struct ZcatCommand;
 #[async_trait]
 impl Command for GzipCommand {
     fn name(&self) -> &'static str { "gzip" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         execute_gzip(ctx, "gzip").await
     }
@@ -22,6 +23,7 @@ impl Command for GzipCommand {
 #[async_trait]
 impl Command for GunzipCommand {
     fn name(&self) -> &'static str { "gunzip" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         execute_gzip(ctx, "gunzip").await
     }
@@ -30,6 +32,7 @@ impl Command for GunzipCommand {
 #[async_trait]
 impl Command for ZcatCommand {
     fn name(&self) -> &'static str { "zcat" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         execute_gzip(ctx, "zcat").await
     }
@@ -698,7 +701,6 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use crate::fs::types::FileSystem;
-    use std::collections::HashMap;
     use std::sync::Arc;
 
     async fn make_ctx(
@@ -711,13 +713,8 @@ mod tests {
             fs.write_file(path, content).await.unwrap();
         }
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
@@ -979,15 +976,7 @@ mod tests {
         fs.write_file("/dir/a.txt", b"file a").await.unwrap();
         fs.write_file("/dir/b.txt", b"file b").await.unwrap();
 
-        let ctx = CommandContext {
-            args: vec!["-r".to_string(), "/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-r".to_string(), "/dir".to_string()], fs.clone());
         let result = GzipCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
         assert!(fs.exists("/dir/a.txt.gz").await);
@@ -1338,15 +1327,7 @@ mod tests {
         fs.mkdir("/dir", &MkdirOptions { recursive: true }).await.unwrap();
         fs.write_file("/dir/file.txt", b"content").await.unwrap();
 
-        let ctx = CommandContext {
-            args: vec!["/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/dir".to_string()], fs.clone());
         let result = GzipCommand.execute(ctx).await;
         assert_ne!(result.exit_code, 0);
         assert!(result.stderr.contains("is a directory"));
@@ -1,6 +1,6 @@
 // src/commands/head/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use crate::commands::utils::{parse_head_tail_args, process_head_tail_files, get_head, HeadTailParseResult};
 
 pub struct HeadCommand;
@@ -11,6 +11,10 @@ impl Command for HeadCommand {
         "head"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -44,22 +48,13 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -134,15 +129,7 @@ mod tests {
     #[tokio::test]
     async fn test_head_missing_file() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec!["/missing.txt".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/missing.txt".to_string()], fs);
         let cmd = HeadCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 1);
@@ -153,13 +140,8 @@ mod tests {
     async fn test_head_from_stdin() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-n".to_string(), "2".to_string()],
             stdin: "a\nb\nc\nd\ne\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-n".to_string(), "2".to_string()], fs)
         };
         let cmd = HeadCommand;
         let result = cmd.execute(ctx).await;
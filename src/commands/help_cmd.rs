@@ -4,7 +4,7 @@ use crate::commands::{Command, CommandContext, CommandResult};
 pub struct HelpCommand;
 
 const CATEGORIES: &[(&str, &[&str])] = &[
-    ("File operations", &["ls", "cat", "head", "tail", "wc", "touch", "mkdir", "rm", "cp", "mv", "ln", "chmod", "stat", "readlink"]),
+    ("File operations", &["ls", "cat", "head", "tail", "wc", "touch", "mkdir", "rm", "cp", "mv", "ln", "chmod", "stat", "readlink", "mktemp"]),
     ("Text processing", &["grep", "sed", "awk", "sort", "uniq", "cut", "tr", "tee", "diff"]),
     ("Search", &["find"]),
     ("Navigation & paths", &["pwd", "basename", "dirname", "tree", "du"]),
@@ -53,20 +53,11 @@ impl Command for HelpCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     fn create_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     #[tokio::test]
@@ -18,22 +18,13 @@ impl Command for HostnameCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     #[tokio::test]
     async fn test_hostname() {
         let cmd = HostnameCommand;
-        let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec![], Arc::new(InMemoryFs::new()));
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
         assert_eq!(result.stdout, "localhost\n");
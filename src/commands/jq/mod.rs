@@ -1,6 +1,6 @@
 // src/commands/jq/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use crate::commands::query_engine::{Value, parse, evaluate};
 use crate::commands::query_engine::context::{EvalContext, JqError};
 
@@ -271,6 +271,8 @@ struct JqOptions {
     sort_keys: bool,
     use_tab: bool,
     filter: String,
+    /// `-f FILE`: read the filter from FILE instead of the first argument
+    filter_file: Option<String>,
     files: Vec<String>,
 }
 
@@ -285,6 +287,7 @@ fn parse_jq_args(args: &[String]) -> Result<JqOptions, CommandResult> {
     let mut use_tab = false;
     let mut filter = ".".to_string();
     let mut filter_set = false;
+    let mut filter_file: Option<String> = None;
     let mut files: Vec<String> = Vec::new();
 
     let mut i = 0;
@@ -312,6 +315,18 @@ fn parse_jq_args(args: &[String]) -> Result<JqOptions, CommandResult> {
             // ignored
         } else if a == "--tab" {
             use_tab = true;
+        } else if a == "-f" || a == "--from-file" {
+            i += 1;
+            match args.get(i) {
+                Some(path) => filter_file = Some(path.clone()),
+                None => {
+                    return Err(CommandResult::with_exit_code(
+                        String::new(),
+                        format!("jq: error: {} takes a parameter\n", a),
+                        2,
+                    ));
+                }
+            }
         } else if a == "-" {
             files.push("-".to_string());
         } else if a.starts_with("--") {
@@ -352,6 +367,11 @@ fn parse_jq_args(args: &[String]) -> Result<JqOptions, CommandResult> {
         i += 1;
     }
 
+    // With a filter file every positional argument is an input file
+    if filter_file.is_some() && filter_set {
+        files.insert(0, std::mem::replace(&mut filter, ".".to_string()));
+    }
+
     Ok(JqOptions {
         raw,
         compact,
@@ -362,6 +382,7 @@ fn parse_jq_args(args: &[String]) -> Result<JqOptions, CommandResult> {
         sort_keys,
         use_tab,
         filter,
+        filter_file,
         files,
     })
 }
@@ -386,6 +407,7 @@ Options:
   -S, --sort-keys   sort object keys
   -C, --color       colorize output (ignored)
   -M, --monochrome  monochrome output (ignored)
+  -f, --from-file   read the filter from a file
       --tab         use tabs for indentation
       --help        display this help and exit
 ";
@@ -400,6 +422,10 @@ impl Command for JqCommand {
         "jq"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         // Check for --help
         if ctx.args.iter().any(|a| a == "--help") {
@@ -444,8 +470,25 @@ impl Command for JqCommand {
             }
         }
 
+        let filter = match &opts.filter_file {
+            Some(file) => {
+                let path = ctx.fs.resolve_path(&ctx.cwd, file);
+                match ctx.fs.read_file(&path).await {
+                    Ok(content) => content,
+                    Err(_) => {
+                        return CommandResult::with_exit_code(
+                            String::new(),
+                            format!("jq: error: Could not open {}: No such file or directory\n", file),
+                            2,
+                        );
+                    }
+                }
+            }
+            None => opts.filter.clone(),
+        };
+
         // Parse the filter
-        let ast = match parse(&opts.filter) {
+        let ast = match parse(&filter) {
             Ok(a) => a,
             Err(e) => {
                 return CommandResult::with_exit_code(
@@ -457,6 +500,7 @@ impl Command for JqCommand {
         };
 
         let mut eval_ctx = EvalContext::with_env(ctx.env.clone());
+        eval_ctx.clock = ctx.clock();
 
         // Evaluate
         let values: Vec<Value> = if opts.null_input {
@@ -581,17 +625,11 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: &[&str], stdin: &str) -> CommandContext {
         CommandContext {
-            args: args.iter().map(|s| s.to_string()).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.iter().map(|s| s.to_string()).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
@@ -605,13 +643,8 @@ mod tests {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
         CommandContext {
-            args: args.iter().map(|s| s.to_string()).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.iter().map(|s| s.to_string()).collect(), fs)
         }
     }
 
@@ -624,6 +657,20 @@ mod tests {
         assert!(result.stdout.contains("\"a\": 1"));
     }
 
+    #[tokio::test]
+    async fn test_jq_from_file() {
+        let ctx = make_ctx_with_files(
+            &["-f", "/filter.jq", "/data.json"],
+            "",
+            &[("/filter.jq", "#!/usr/bin/env jq -f\n.a\n"), ("/data.json", r#"{"a":1}"#)],
+        )
+        .await;
+        let cmd = JqCommand;
+        let result = cmd.execute(ctx).await;
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.stdout.trim(), "1");
+    }
+
     #[tokio::test]
     async fn test_jq_field_access() {
         let ctx = make_ctx(&[".a"], r#"{"a":1}"#);
@@ -43,7 +43,7 @@ fn format_size(size: u64, human_readable: bool) -> String {
     if g < 10.0 { format!("{:.1}G", g) } else { format!("{}G", g as u64) }
 }
 
-fn format_time(mtime: SystemTime) -> String {
+fn format_time(mtime: SystemTime, now: SystemTime) -> String {
     let duration = mtime.duration_since(UNIX_EPOCH).unwrap_or_default();
     let secs = duration.as_secs();
 
@@ -60,7 +60,7 @@ fn format_time(mtime: SystemTime) -> String {
     let hour = time_of_day / 3600;
     let minute = (time_of_day % 3600) / 60;
 
-    let now_secs = SystemTime::now()
+    let now_secs = now
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
@@ -149,7 +149,7 @@ impl Command for LsCommand {
                 if long_format {
                     let mode_str = format_mode(stat.mode, stat.is_directory, stat.is_symlink);
                     let size_str = format_size(stat.size, human_readable);
-                    let time_str = format_time(stat.mtime);
+                    let time_str = format_time(stat.mtime, ctx.clock().now());
                     stdout.push_str(&format!("{} 1 user user {:>5} {} {}\n",
                         mode_str, size_str, time_str, path));
                 } else {
@@ -193,7 +193,7 @@ impl Command for LsCommand {
                     if let Ok(stat) = ctx.fs.stat(&entry_path).await {
                         let mode_str = format_mode(stat.mode, entry.is_directory, entry.is_symlink);
                         let size_str = format_size(stat.size, human_readable);
-                        let time_str = format_time(stat.mtime);
+                        let time_str = format_time(stat.mtime, ctx.clock().now());
                         stdout.push_str(&format!("{} 1 user user {:>5} {} {}\n",
                             mode_str, size_str, time_str, entry.name));
                     }
@@ -212,7 +212,6 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs, MkdirOptions};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_structure(args: Vec<&str>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
@@ -220,15 +219,7 @@ mod tests {
         fs.write_file("/testdir/file1.txt", b"content1").await.unwrap();
         fs.write_file("/testdir/file2.txt", b"content2content2").await.unwrap();
         fs.write_file("/testdir/.hidden", b"hidden").await.unwrap();
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -260,15 +251,7 @@ mod tests {
     #[tokio::test]
     async fn test_ls_nonexistent() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec!["/nonexistent".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/nonexistent".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stderr.contains("No such file or directory"));
@@ -279,15 +262,7 @@ mod tests {
     async fn test_ls_current_directory() {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/file.txt", b"").await.unwrap();
-        let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec![], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stdout.contains("file.txt"));
@@ -329,15 +304,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/dir", &MkdirOptions { recursive: false }).await.unwrap();
         fs.mkdir("/dir/subdir", &MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["-l".to_string(), "/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-l".to_string(), "/dir".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stdout.contains("drwxr-xr-x"));
@@ -363,15 +330,7 @@ mod tests {
         fs.mkdir("/dir2", &MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/dir1/a.txt", b"").await.unwrap();
         fs.write_file("/dir2/b.txt", b"").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/dir1".to_string(), "/dir2".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/dir1".to_string(), "/dir2".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stdout.contains("/dir1:"));
@@ -385,15 +344,7 @@ mod tests {
     async fn test_ls_single_file() {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/file.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/file.txt".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/file.txt".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stdout.contains("/file.txt"));
@@ -408,15 +359,7 @@ mod tests {
         fs.write_file("/dir/zebra.txt", b"").await.unwrap();
         fs.write_file("/dir/apple.txt", b"").await.unwrap();
         fs.write_file("/dir/mango.txt", b"").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/dir".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         let lines: Vec<&str> = result.stdout.lines().collect();
@@ -430,15 +373,7 @@ mod tests {
     async fn test_ls_empty_directory() {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/empty", &MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/empty".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/empty".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.stdout, "");
@@ -453,15 +388,7 @@ mod tests {
         fs.write_file("/dir/aaa.txt", b"").await.unwrap();
         fs.write_file("/dir/bbb.txt", b"").await.unwrap();
         fs.write_file("/dir/ccc.txt", b"").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["-r".to_string(), "/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-r".to_string(), "/dir".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         let lines: Vec<&str> = result.stdout.lines().collect();
@@ -499,15 +426,7 @@ mod tests {
         fs.write_file("/dir/x.txt", b"").await.unwrap();
         fs.write_file("/dir/y.txt", b"").await.unwrap();
         fs.write_file("/dir/z.txt", b"").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["--reverse".to_string(), "/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["--reverse".to_string(), "/dir".to_string()], fs);
         let cmd = LsCommand;
         let result = cmd.execute(ctx).await;
         let lines: Vec<&str> = result.stdout.lines().collect();
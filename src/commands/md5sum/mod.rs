@@ -1,7 +1,7 @@
 // src/commands/md5sum/mod.rs
 // md5sum, sha1sum, sha256sum — checksum commands
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 /// Pure Rust MD5 implementation (matches TypeScript pure-JS version)
 fn md5(data: &[u8]) -> String {
@@ -293,6 +293,7 @@ pub struct Md5sumCommand;
 #[async_trait]
 impl Command for Md5sumCommand {
     fn name(&self) -> &'static str { "md5sum" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         checksum_execute("md5sum", HashAlgorithm::Md5, "compute MD5 message digest", ctx).await
     }
@@ -303,6 +304,7 @@ pub struct Sha1sumCommand;
 #[async_trait]
 impl Command for Sha1sumCommand {
     fn name(&self) -> &'static str { "sha1sum" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         checksum_execute("sha1sum", HashAlgorithm::Sha1, "compute SHA1 message digest", ctx).await
     }
@@ -313,6 +315,7 @@ pub struct Sha256sumCommand;
 #[async_trait]
 impl Command for Sha256sumCommand {
     fn name(&self) -> &'static str { "sha256sum" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         checksum_execute("sha256sum", HashAlgorithm::Sha256, "compute SHA256 message digest", ctx).await
     }
@@ -323,15 +326,14 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>, stdin: &str) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: stdin.into(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext { stdin: stdin.into(), ..CommandContext::new(args.into_iter().map(String::from).collect(), fs) }
     }
 
     fn make_ctx_with_fs(args: Vec<&str>, stdin: &str, fs: Arc<InMemoryFs>) -> CommandContext {
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: stdin.into(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext { stdin: stdin.into(), ..CommandContext::new(args.into_iter().map(String::from).collect(), fs) }
     }
 
     #[tokio::test]
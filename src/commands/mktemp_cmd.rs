@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use crate::commands::{Command, CommandContext, CommandResult};
+use crate::fs::MkdirOptions;
+
+pub struct MktempCommand;
+
+const USAGE: &str = "Usage: mktemp [OPTION]... [TEMPLATE]
+Create a temporary file or directory, safely, and print its name.
+TEMPLATE must contain at least 3 consecutive 'X's in last component.
+If TEMPLATE is not specified, use tmp.XXXXXXXXXX, and --tmpdir is implied.
+
+Options:
+  -d, --directory     create a directory, not a file
+  -u, --dry-run       do not create anything; merely print a name
+  -q, --quiet         suppress diagnostics about file/dir-creation failure
+  -p DIR, --tmpdir[=DIR]  interpret TEMPLATE relative to DIR; if DIR is not
+                      specified, use $TMPDIR if set, else /tmp
+  -t                  interpret TEMPLATE relative to the temporary directory
+      --help          display this help and exit";
+
+const NAME_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const MAX_ATTEMPTS: usize = 100;
+
+#[async_trait]
+impl Command for MktempCommand {
+    fn name(&self) -> &'static str {
+        "mktemp"
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> CommandResult {
+        let mut directory = false;
+        let mut dry_run = false;
+        let mut quiet = false;
+        let mut tmpdir: Option<String> = None;
+        let mut use_tmpdir = false;
+        let mut template: Option<String> = None;
+
+        let mut i = 0;
+        while i < ctx.args.len() {
+            let arg = &ctx.args[i];
+            match arg.as_str() {
+                "--help" => return CommandResult::success(format!("{}\n", USAGE)),
+                "-d" | "--directory" => directory = true,
+                "-u" | "--dry-run" => dry_run = true,
+                "-q" | "--quiet" => quiet = true,
+                "-t" | "--tmpdir" => use_tmpdir = true,
+                "-p" => {
+                    i += 1;
+                    match ctx.args.get(i) {
+                        Some(dir) => tmpdir = Some(dir.clone()),
+                        None => return CommandResult::error("mktemp: option requires an argument -- 'p'\n".to_string()),
+                    }
+                    use_tmpdir = true;
+                }
+                _ if arg.starts_with("--tmpdir=") => {
+                    tmpdir = Some(arg["--tmpdir=".len()..].to_string());
+                    use_tmpdir = true;
+                }
+                _ if arg.starts_with('-') && arg.len() > 1 => {
+                    for c in arg.chars().skip(1) {
+                        match c {
+                            'd' => directory = true,
+                            'u' => dry_run = true,
+                            'q' => quiet = true,
+                            't' => use_tmpdir = true,
+                            _ => return CommandResult::error(format!("mktemp: invalid option -- '{}'\n", c)),
+                        }
+                    }
+                }
+                _ if template.is_none() => template = Some(arg.clone()),
+                _ => return CommandResult::error(format!("mktemp: too many templates\n{}\n", USAGE)),
+            }
+            i += 1;
+        }
+
+        let template = match template {
+            Some(t) => t,
+            None => {
+                use_tmpdir = true;
+                "tmp.XXXXXXXXXX".to_string()
+            }
+        };
+
+        let x_count = template.chars().rev().take_while(|&c| c == 'X').count();
+        if x_count < 3 {
+            return CommandResult::error(format!("mktemp: too few X's in template '{}'\n", template));
+        }
+
+        let base = if use_tmpdir {
+            let dir = tmpdir
+                .filter(|d| !d.is_empty())
+                .or_else(|| ctx.env.get("TMPDIR").filter(|d| !d.is_empty()).cloned())
+                .unwrap_or_else(|| "/tmp".to_string());
+            format!("{}/", dir.trim_end_matches('/'))
+        } else {
+            String::new()
+        };
+        let prefix = &template[..template.len() - x_count];
+
+        let random = ctx.random();
+        for _ in 0..MAX_ATTEMPTS {
+            let suffix: String = (0..x_count)
+                .map(|_| NAME_CHARS[random.below(NAME_CHARS.len() as u64) as usize] as char)
+                .collect();
+            let name = format!("{}{}{}", base, prefix, suffix);
+            let path = ctx.fs.resolve_path(&ctx.cwd, &name);
+            if ctx.fs.exists(&path).await {
+                continue;
+            }
+            if !dry_run {
+                let created = if directory {
+                    ctx.fs.mkdir(&path, &MkdirOptions { recursive: false }).await
+                } else {
+                    ctx.fs.write_file(&path, b"").await
+                };
+                if created.is_err() {
+                    let kind = if directory { "directory" } else { "file" };
+                    let stderr = if quiet {
+                        String::new()
+                    } else {
+                        format!("mktemp: failed to create {} via template '{}{}': No such file or directory\n",
+                            kind, base, template)
+                    };
+                    return CommandResult::with_exit_code(String::new(), stderr, 1);
+                }
+            }
+            return CommandResult::success(format!("{}\n", name));
+        }
+
+        let stderr = if quiet {
+            String::new()
+        } else {
+            format!("mktemp: failed to create file via template '{}{}': File exists\n", base, template)
+        };
+        CommandResult::with_exit_code(String::new(), stderr, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{FileSystem, InMemoryFs};
+    use crate::random::Random;
+    use std::sync::Arc;
+
+    fn make_ctx(args: Vec<&str>, fs: Arc<InMemoryFs>, random: Random) -> CommandContext {
+        CommandContext {
+            cwd: "/home".to_string(),
+            random: Some(random),
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mktemp() {
+        let fs = Arc::new(InMemoryFs::new());
+        fs.mkdir("/tmp", &MkdirOptions { recursive: false }).await.unwrap();
+        fs.mkdir("/home", &MkdirOptions { recursive: false }).await.unwrap();
+
+        let dry_run = MktempCommand.execute(make_ctx(vec!["-u"], fs.clone(), Random::seeded(1))).await;
+        let path = dry_run.stdout.trim_end().to_string();
+        assert!(path.starts_with("/tmp/tmp.") && path.len() == "/tmp/tmp.".len() + 10, "{}", path);
+        assert!(!fs.exists(&path).await);
+
+        // The same seed picks the same name
+        let result = MktempCommand.execute(make_ctx(vec![], fs.clone(), Random::seeded(1))).await;
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.stdout, dry_run.stdout);
+        assert!(fs.stat(&path).await.unwrap().is_file);
+
+        let result = MktempCommand.execute(make_ctx(vec!["-d", "work.XXXX"], fs.clone(), Random::seeded(2))).await;
+        let path = result.stdout.trim_end();
+        assert!(path.starts_with("work.") && path.len() == 9, "{}", path);
+        assert!(fs.stat(&format!("/home/{}", path)).await.unwrap().is_directory);
+
+        let result = MktempCommand.execute(make_ctx(vec!["bad.XX"], fs, Random::seeded(3))).await;
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.stderr, "mktemp: too few X's in template 'bad.XX'\n");
+    }
+}
@@ -108,22 +108,13 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs, MkdirOptions};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -145,15 +136,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.write_file("/src.txt", b"content").await.unwrap();
         fs.mkdir("/destdir", &MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/src.txt".to_string(), "/destdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/src.txt".to_string(), "/destdir".to_string()], fs.clone());
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -204,15 +187,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/dir", &MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/dir/oldname.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/dir/oldname.txt".to_string(), "/dir/newname.txt".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/dir/oldname.txt".to_string(), "/dir/newname.txt".to_string()], fs.clone());
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -226,15 +201,7 @@ mod tests {
         fs.write_file("/a.txt", b"aaa").await.unwrap();
         fs.write_file("/b.txt", b"bbb").await.unwrap();
         fs.mkdir("/dir", &MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/a.txt".to_string(), "/b.txt".to_string(), "/dir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/a.txt".to_string(), "/b.txt".to_string(), "/dir".to_string()], fs.clone());
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -261,15 +228,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/srcdir", &MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/srcdir/file.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/srcdir".to_string(), "/dstdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/srcdir".to_string(), "/dstdir".to_string()], fs.clone());
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -286,15 +245,7 @@ mod tests {
         fs.mkdir("/src/a/b", &MkdirOptions { recursive: true }).await.unwrap();
         fs.write_file("/src/a/b/c.txt", b"deep").await.unwrap();
         fs.write_file("/src/root.txt", b"root").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/src".to_string(), "/dst".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/src".to_string(), "/dst".to_string()], fs.clone());
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -337,13 +288,8 @@ mod tests {
         fs.mkdir("/home/user", &MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/home/user/old.txt", b"content").await.unwrap();
         let ctx = CommandContext {
-            args: vec!["old.txt".to_string(), "new.txt".to_string()],
-            stdin: String::new(),
             cwd: "/home/user".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["old.txt".to_string(), "new.txt".to_string()], fs.clone())
         };
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
@@ -359,15 +305,7 @@ mod tests {
         fs.mkdir("/src", &MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/src/file.txt", b"content").await.unwrap();
         fs.mkdir("/dst", &MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/src".to_string(), "/dst/".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/src".to_string(), "/dst/".to_string()], fs.clone());
         let cmd = MvCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -1,6 +1,6 @@
 // src/commands/nl/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct NlCommand;
 
@@ -142,6 +142,10 @@ impl Command for NlCommand {
         "nl"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let (opts, files) = match parse_options(&ctx.args) {
             Ok(v) => v,
@@ -222,7 +226,6 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use crate::fs::types::FileSystem;
-    use std::collections::HashMap;
     use std::sync::Arc;
 
     async fn make_ctx(
@@ -235,13 +238,8 @@ mod tests {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
@@ -47,18 +47,12 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>, cwd: &str) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
             cwd: cwd.to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
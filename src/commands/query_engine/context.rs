@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use crate::clock::Clock;
 use super::value::Value;
 use super::ast::AstNode;
 
@@ -23,6 +24,8 @@ pub struct EvalContext {
     pub max_iterations: usize,
     pub max_depth: usize,
     pub iteration_count: usize,
+    /// Time source for `now`
+    pub clock: Clock,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +46,7 @@ impl EvalContext {
             max_iterations: DEFAULT_MAX_ITERATIONS,
             max_depth: DEFAULT_MAX_DEPTH,
             iteration_count: 0,
+            clock: Clock::real(),
         }
     }
 
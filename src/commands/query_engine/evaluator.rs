@@ -3415,13 +3415,7 @@ fn eval_builtin(
         },
 
         // ===== Date builtins =====
-        "now" => {
-            let secs = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs_f64())
-                .unwrap_or(0.0);
-            Ok(vec![Value::Number(secs)])
-        }
+        "now" => Ok(vec![Value::Number(ctx.clock.unix_secs_f64())]),
 
         "gmtime" => {
             if let Value::Number(ts) = value {
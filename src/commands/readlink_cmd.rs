@@ -96,22 +96,13 @@ impl Command for ReadlinkCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     #[tokio::test]
     async fn test_readlink_missing_operand() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec![], fs);
         let cmd = ReadlinkCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 1);
@@ -121,15 +112,7 @@ mod tests {
     #[tokio::test]
     async fn test_readlink_help() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec!["--help".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["--help".to_string()], fs);
         let cmd = ReadlinkCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -98,6 +98,7 @@ use super::clear_cmd::ClearCommand;
 use super::whoami_cmd::WhoamiCommand;
 use super::hostname_cmd::HostnameCommand;
 use super::rmdir_cmd::RmdirCommand;
+use super::mktemp_cmd::MktempCommand;
 use super::tac_cmd::TacCommand;
 use super::rev_cmd::RevCommand;
 
@@ -262,7 +263,7 @@ pub fn create_batch_abcdef_registry() -> CommandRegistry {
     registry
 }
 
-/// 注册批次 G 的所有命令 (true, false, clear, whoami, hostname, rmdir, tac, rev)
+/// 注册批次 G 的所有命令 (true, false, clear, whoami, hostname, rmdir, tac, rev, mktemp)
 pub fn register_batch_g(registry: &mut CommandRegistry) {
     registry.register(Box::new(TrueCommand));
     registry.register(Box::new(FalseCommand));
@@ -272,6 +273,7 @@ pub fn register_batch_g(registry: &mut CommandRegistry) {
     registry.register(Box::new(RmdirCommand));
     registry.register(Box::new(TacCommand));
     registry.register(Box::new(RevCommand));
+    registry.register(Box::new(MktempCommand));
 }
 
 /// 创建包含批次 A-G 命令的注册表
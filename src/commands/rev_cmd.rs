@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct RevCommand;
 
@@ -32,6 +32,10 @@ impl Command for RevCommand {
         "rev"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -87,7 +91,6 @@ impl Command for RevCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
@@ -95,13 +98,8 @@ mod tests {
     async fn test_rev_stdin() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec![],
             stdin: "hello\nworld\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec![], fs)
         };
         let cmd = RevCommand;
         let result = cmd.execute(ctx).await;
@@ -113,13 +111,8 @@ mod tests {
     async fn test_rev_no_trailing_newline() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec![],
             stdin: "hello".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec![], fs)
         };
         let cmd = RevCommand;
         let result = cmd.execute(ctx).await;
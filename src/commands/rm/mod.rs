@@ -105,22 +105,13 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -154,15 +145,7 @@ mod tests {
     async fn test_rm_directory_without_r() {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/testdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["/testdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/testdir".to_string()], fs);
         let cmd = RmCommand;
         let result = cmd.execute(ctx).await;
         assert!(result.stderr.contains("Is a directory"));
@@ -174,15 +157,7 @@ mod tests {
         let fs = Arc::new(InMemoryFs::new());
         fs.mkdir("/testdir", &crate::fs::MkdirOptions { recursive: false }).await.unwrap();
         fs.write_file("/testdir/file.txt", b"content").await.unwrap();
-        let ctx = CommandContext {
-            args: vec!["-r".to_string(), "/testdir".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["-r".to_string(), "/testdir".to_string()], fs.clone());
         let cmd = RmCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -173,21 +173,12 @@ fn get_parent_path(path: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     async fn create_ctx_with_fs() -> (CommandContext, Arc<InMemoryFs>) {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec![], fs.clone());
         (ctx, fs)
     }
 
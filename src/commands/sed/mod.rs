@@ -8,7 +8,7 @@ pub mod executor;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use crate::fs::FileSystem;
 use self::types::{SedCmd, RangeState, ExecuteContext};
 use self::parser::parse_scripts;
@@ -28,6 +28,10 @@ impl Command for SedCommand {
         "sed"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -407,31 +411,20 @@ async fn process_content(
 mod tests {
     use super::*;
     use std::sync::Arc;
-    use std::collections::HashMap;
     use crate::fs::InMemoryFs;
     use crate::commands::CommandContext;
 
     fn make_ctx(args: Vec<&str>, stdin: &str) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
     fn make_ctx_with_fs(args: Vec<&str>, stdin: &str, fs: Arc<InMemoryFs>) -> CommandContext {
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
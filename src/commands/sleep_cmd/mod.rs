@@ -39,7 +39,7 @@ impl Command for SleepCommand {
                 None => return CommandResult::with_exit_code("".into(), format!("sleep: invalid time interval '{}'\n", arg), 1),
             }
         }
-        std::thread::sleep(std::time::Duration::from_millis(total_ms as u64));
+        ctx.clock().sleep(std::time::Duration::from_millis(total_ms as u64));
         CommandResult::success("".into())
     }
 }
@@ -49,11 +49,10 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: String::new(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[test]
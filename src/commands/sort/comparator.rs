@@ -66,6 +66,8 @@ pub struct SortOptions {
     pub output_file: Option<String>,
     pub keys: Vec<KeySpec>,
     pub field_separator: Option<char>,
+    /// Set by -R: lines are ordered by a hash salted with this value
+    pub random_salt: Option<u64>,
 }
 
 impl Default for SortOptions {
@@ -85,6 +87,7 @@ impl Default for SortOptions {
             output_file: None,
             keys: Vec::new(),
             field_separator: None,
+            random_salt: None,
         }
     }
 }
@@ -429,6 +432,27 @@ pub fn compare_values(a: &str, b: &str, mode: CompareMode, ignore_case: bool, di
     }
 }
 
+/// Keyed hash used by -R; equal keys hash alike and stay together.
+///
+/// The FNV-1a hash of the key is combined with the salt and passed through
+/// the SplitMix64 finalizer, so every bit of the salt affects the order.
+fn random_hash(s: &str, salt: u64) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in s.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    let mut z = hash ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Compare two values in the random order chosen by `salt`
+fn compare_random(a: &str, b: &str, salt: u64) -> Ordering {
+    random_hash(a, salt).cmp(&random_hash(b, salt)).then_with(|| a.cmp(b))
+}
+
 /// Create a comparator function that compares two lines based on sort options
 pub fn create_comparator(opts: &SortOptions) -> Box<dyn Fn(&str, &str) -> Ordering + '_> {
     Box::new(move |a: &str, b: &str| {
@@ -442,7 +466,10 @@ pub fn create_comparator(opts: &SortOptions) -> Box<dyn Fn(&str, &str) -> Orderi
                 let ic = should_ignore_case(&key.options, opts);
                 let dict = should_dictionary_order(&key.options, opts);
 
-                let mut ord = compare_values(&key_a, &key_b, mode, ic, dict);
+                let mut ord = match opts.random_salt {
+                    Some(salt) if mode == CompareMode::String => compare_random(&key_a, &key_b, salt),
+                    _ => compare_values(&key_a, &key_b, mode, ic, dict),
+                };
 
                 if should_reverse(&key.options, opts) {
                     ord = ord.reverse();
@@ -474,13 +501,16 @@ pub fn create_comparator(opts: &SortOptions) -> Box<dyn Fn(&str, &str) -> Orderi
                 CompareMode::String
             };
 
-            let mut ord = compare_values(
-                a,
-                b,
-                mode,
-                opts.ignore_case,
-                opts.dictionary_order,
-            );
+            let mut ord = match opts.random_salt {
+                Some(salt) if mode == CompareMode::String => compare_random(a, b, salt),
+                _ => compare_values(
+                    a,
+                    b,
+                    mode,
+                    opts.ignore_case,
+                    opts.dictionary_order,
+                ),
+            };
 
             if opts.reverse {
                 ord = ord.reverse();
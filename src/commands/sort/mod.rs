@@ -2,7 +2,7 @@
 pub mod comparator;
 
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use comparator::{SortOptions, parse_key_spec, create_comparator};
 
 pub struct SortCommand;
@@ -13,6 +13,10 @@ impl Command for SortCommand {
         "sort"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -27,6 +31,7 @@ impl Command for SortCommand {
                    -V, --version-sort         natural sort of (version) numbers within text\n\
                    -d, --dictionary-order     consider only blanks and alphanumeric characters\n\
                    -M, --month-sort           compare (unknown) < 'JAN' < ... < 'DEC'\n\
+                   -R, --random-sort          shuffle, but group identical keys\n\
                    -b, --ignore-leading-blanks ignore leading blanks\n\
                    -s, --stable               stabilize sort by disabling last-resort comparison\n\
                    -c, --check                check for sorted input; do not sort\n\
@@ -66,6 +71,7 @@ impl Command for SortCommand {
                     "--version-sort" => opts.version_sort = true,
                     "--dictionary-order" => opts.dictionary_order = true,
                     "--month-sort" => opts.month_sort = true,
+                    "--random-sort" => opts.random_salt = Some(ctx.random().next_u64()),
                     "--ignore-leading-blanks" => opts.ignore_leading_blanks = true,
                     "--stable" => opts.stable = true,
                     "--check" => opts.check = true,
@@ -105,6 +111,7 @@ impl Command for SortCommand {
                         'V' => opts.version_sort = true,
                         'd' => opts.dictionary_order = true,
                         'M' => opts.month_sort = true,
+                        'R' => opts.random_salt = Some(ctx.random().next_u64()),
                         'b' => opts.ignore_leading_blanks = true,
                         's' => opts.stable = true,
                         'c' => opts.check = true,
@@ -264,13 +271,8 @@ mod tests {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
@@ -1031,4 +1033,19 @@ mod tests {
         assert_eq!(result.exit_code, 0);
         assert_eq!(result.stdout, "a:1\nb:2\nc:3\n");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_sort_random_depends_on_seed() {
+        let mut orders = std::collections::HashMap::new();
+        for seed in [1, 2, 3, 42, 1000] {
+            let mut ctx = make_ctx(vec!["-R"], "1\n2\n3\n4\n5\n", vec![]).await;
+            ctx.random = Some(crate::random::Random::seeded(seed));
+            let result = SortCommand.execute(ctx).await;
+            assert_eq!(result.exit_code, 0);
+            orders.insert(seed, result.stdout);
+        }
+        assert_ne!(orders[&42], "1\n2\n3\n4\n5\n");
+        let distinct: std::collections::HashSet<_> = orders.values().collect();
+        assert!(distinct.len() >= 4, "too few distinct orders: {:?}", orders);
+    }
+}
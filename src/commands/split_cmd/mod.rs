@@ -1,6 +1,6 @@
 // src/commands/split_cmd/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use regex_lite::Regex;
 
 pub struct SplitCommand;
@@ -50,6 +50,7 @@ enum SplitMode { Lines, Bytes, Chunks }
 #[async_trait]
 impl Command for SplitCommand {
     fn name(&self) -> &'static str { "split" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
 
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let args = &ctx.args;
@@ -211,10 +212,9 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>, stdin: &str, fs: Arc<InMemoryFs>) -> CommandContext {
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: stdin.into(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext { stdin: stdin.into(), ..CommandContext::new(args.into_iter().map(String::from).collect(), fs) }
     }
 
     #[tokio::test]
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct StringsCommand;
 
@@ -67,6 +67,10 @@ impl Command for StringsCommand {
         "strings"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let mut min_length = 4usize;
         let mut offset_format: Option<OffsetFormat> = None;
@@ -188,7 +192,6 @@ impl Command for StringsCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
@@ -196,13 +199,8 @@ mod tests {
     async fn test_strings_basic() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec![],
             stdin: "hello\x00world\x00test".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec![], fs)
         };
         let cmd = StringsCommand;
         let result = cmd.execute(ctx).await;
@@ -215,13 +213,8 @@ mod tests {
     async fn test_strings_min_length() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-n8".to_string()],
             stdin: "hello\x00worldtest".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-n8".to_string()], fs)
         };
         let cmd = StringsCommand;
         let result = cmd.execute(ctx).await;
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct TacCommand;
 
@@ -9,6 +9,10 @@ impl Command for TacCommand {
         "tac"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let content = if !ctx.args.is_empty() && ctx.args[0] != "-" {
             let file_path = if ctx.args[0].starts_with('/') {
@@ -49,7 +53,6 @@ impl Command for TacCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
@@ -57,13 +60,8 @@ mod tests {
     async fn test_tac_stdin() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec![],
             stdin: "line1\nline2\nline3\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec![], fs)
         };
         let cmd = TacCommand;
         let result = cmd.execute(ctx).await;
@@ -74,15 +72,7 @@ mod tests {
     #[tokio::test]
     async fn test_tac_empty() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec![], fs);
         let cmd = TacCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
@@ -92,15 +82,7 @@ mod tests {
     #[tokio::test]
     async fn test_tac_file_not_found() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec!["nonexistent.txt".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["nonexistent.txt".to_string()], fs);
         let cmd = TacCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 1);
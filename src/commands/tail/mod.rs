@@ -1,6 +1,6 @@
 // src/commands/tail/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use crate::commands::utils::{parse_head_tail_args, process_head_tail_files, get_tail, HeadTailParseResult};
 
 pub struct TailCommand;
@@ -11,6 +11,10 @@ impl Command for TailCommand {
         "tail"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -47,22 +51,13 @@ mod tests {
     use crate::fs::InMemoryFs;
     use crate::fs::types::FileSystem;
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     async fn make_ctx_with_files(args: Vec<&str>, files: Vec<(&str, &str)>) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         for (path, content) in files {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), fs)
     }
 
     #[tokio::test]
@@ -147,15 +142,7 @@ mod tests {
     #[tokio::test]
     async fn test_tail_missing_file() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec!["/missing.txt".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["/missing.txt".to_string()], fs);
         let cmd = TailCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 1);
@@ -166,13 +153,8 @@ mod tests {
     async fn test_tail_from_stdin() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-n".to_string(), "2".to_string()],
             stdin: "a\nb\nc\nd\ne\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-n".to_string(), "2".to_string()], fs)
         };
         let cmd = TailCommand;
         let result = cmd.execute(ctx).await;
@@ -251,13 +233,8 @@ mod tests {
     async fn test_tail_from_line_stdin() {
         let fs = Arc::new(InMemoryFs::new());
         let ctx = CommandContext {
-            args: vec!["-n".to_string(), "+3".to_string()],
             stdin: "a\nb\nc\nd\ne\n".to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-n".to_string(), "+3".to_string()], fs)
         };
         let cmd = TailCommand;
         let result = cmd.execute(ctx).await;
@@ -993,7 +993,6 @@ impl TarCommand {
 mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
-    use std::collections::HashMap;
 
     async fn make_ctx(
         args: Vec<&str>,
@@ -1014,13 +1013,8 @@ mod tests {
             fs.write_file(path, content).await.unwrap();
         }
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
@@ -1084,20 +1078,15 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/output".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         let content = fs.read_file("/output/hello.txt").await.unwrap();
@@ -1115,20 +1104,15 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/out".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert_eq!(fs.read_file("/out/a.txt").await.unwrap(), "aaa");
@@ -1161,20 +1145,15 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xzf".to_string(),
                 "archive.tar.gz".to_string(),
                 "-C".to_string(),
                 "/out".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert_eq!(
@@ -1194,15 +1173,7 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec!["-tf".to_string(), "archive.tar".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx2 = CommandContext::new(vec!["-tf".to_string(), "archive.tar".to_string()], fs.clone());
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert!(result.stdout.contains("a.txt"));
@@ -1220,18 +1191,13 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-tvf".to_string(),
                 "archive.tar".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert!(result.stdout.contains("hello.txt"));
@@ -1288,21 +1254,16 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "--strip-components=2".to_string(),
                 "-C".to_string(),
                 "/out".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert_eq!(
@@ -1322,18 +1283,13 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xOf".to_string(),
                 "archive.tar".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert_eq!(result.stdout, "Hello, World!");
@@ -1354,20 +1310,15 @@ mod tests {
             .await
             .unwrap();
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xkf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/out".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert_eq!(
@@ -1405,19 +1356,14 @@ mod tests {
         TarCommand.execute(ctx).await;
 
         fs.write_file("/b.txt", b"bbb").await.unwrap();
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-rf".to_string(),
                 "archive.tar".to_string(),
                 "b.txt".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
 
@@ -1443,19 +1389,14 @@ mod tests {
         // Add a new file that doesn't exist in the archive (always added by -u)
         fs.write_file("/b.txt", b"new_file").await.unwrap();
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-uf".to_string(),
                 "archive.tar".to_string(),
                 "b.txt".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
 
@@ -1521,19 +1462,14 @@ mod tests {
         fs.mkdir("/emptydir", &MkdirOptions { recursive: true })
             .await
             .unwrap();
-        let ctx = CommandContext {
-            args: vec![
+        let ctx = CommandContext::new(
+            vec![
                 "-cf".to_string(),
                 "archive.tar".to_string(),
                 "emptydir".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
         let data = fs.read_file_buffer("/archive.tar").await.unwrap();
@@ -1550,35 +1486,25 @@ mod tests {
             .unwrap();
         fs.chmod("/script.sh", 0o755).await.unwrap();
 
-        let ctx = CommandContext {
-            args: vec![
+        let ctx = CommandContext::new(
+            vec![
                 "-cf".to_string(),
                 "archive.tar".to_string(),
                 "script.sh".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xpf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/out".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         let stat = fs.stat("/out/script.sh").await.unwrap();
@@ -1690,20 +1616,15 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/dest".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         TarCommand.execute(ctx2).await;
 
         let data = fs.read_file_buffer("/dest/binary.bin").await.unwrap();
@@ -1722,20 +1643,15 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/dest".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         TarCommand.execute(ctx2).await;
 
         let data = fs.read_file_buffer("/dest/nulls.bin").await.unwrap();
@@ -1755,20 +1671,15 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/dest".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         TarCommand.execute(ctx2).await;
 
         let data = fs.read_file_buffer("/dest/allbytes.bin").await.unwrap();
@@ -1794,13 +1705,8 @@ mod tests {
             archive_data.iter().map(|&b| b as char).collect();
 
         let ctx2 = CommandContext {
-            args: vec!["-t".to_string()],
             stdin: stdin_str,
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(vec!["-t".to_string()], fs.clone())
         };
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
@@ -1823,17 +1729,15 @@ mod tests {
             archive_data.iter().map(|&b| b as char).collect();
 
         let ctx2 = CommandContext {
-            args: vec![
-                "-x".to_string(),
-                "-C".to_string(),
-                "/dest".to_string(),
-            ],
             stdin: stdin_str,
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(
+                vec![
+                    "-x".to_string(),
+                    "-C".to_string(),
+                    "/dest".to_string(),
+                ],
+                fs.clone(),
+            )
         };
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
@@ -1859,17 +1763,15 @@ mod tests {
             archive_data.iter().map(|&b| b as char).collect();
 
         let ctx2 = CommandContext {
-            args: vec![
-                "-xz".to_string(),
-                "-C".to_string(),
-                "/dest".to_string(),
-            ],
             stdin: stdin_str,
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(
+                vec![
+                    "-xz".to_string(),
+                    "-C".to_string(),
+                    "/dest".to_string(),
+                ],
+                fs.clone(),
+            )
         };
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
@@ -1890,20 +1792,15 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "-C".to_string(),
                 "/dest".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         TarCommand.execute(ctx2).await;
 
         let content = fs.read_file("/dest/unicode.txt").await.unwrap();
@@ -1922,15 +1819,7 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec!["-tf".to_string(), "archive.tar".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx2 = CommandContext::new(vec!["-tf".to_string(), "archive.tar".to_string()], fs.clone());
         let result = TarCommand.execute(ctx2).await;
         assert!(result.stdout.contains("file-name_123.txt"));
     }
@@ -1950,21 +1839,16 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "dir/file1.txt".to_string(),
                 "-C".to_string(),
                 "/dest".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
 
@@ -1988,21 +1872,16 @@ mod tests {
         let fs = ctx.fs.clone();
         TarCommand.execute(ctx).await;
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xf".to_string(),
                 "archive.tar".to_string(),
                 "project/src".to_string(),
                 "-C".to_string(),
                 "/dest".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
 
@@ -2034,20 +1913,15 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec![
+        let ctx2 = CommandContext::new(
+            vec![
                 "-xzvf".to_string(),
                 "backup.tar.gz".to_string(),
                 "-C".to_string(),
                 "/restore".to_string(),
             ],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+            fs.clone(),
+        );
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
 
@@ -2076,15 +1950,7 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec!["-tzf".to_string(), "test.tar.gz".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx2 = CommandContext::new(vec!["-tzf".to_string(), "test.tar.gz".to_string()], fs.clone());
         let result = TarCommand.execute(ctx2).await;
         assert_eq!(result.exit_code, 0);
         assert!(result.stdout.contains("data.txt"));
@@ -2094,17 +1960,15 @@ mod tests {
             archive_data.iter().map(|&b| b as char).collect();
 
         let ctx3 = CommandContext {
-            args: vec![
-                "-xz".to_string(),
-                "-C".to_string(),
-                "/dest".to_string(),
-            ],
             stdin: stdin_str,
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(
+                vec![
+                    "-xz".to_string(),
+                    "-C".to_string(),
+                    "/dest".to_string(),
+                ],
+                fs.clone(),
+            )
         };
         let result = TarCommand.execute(ctx3).await;
         assert_eq!(result.exit_code, 0);
@@ -2136,15 +2000,7 @@ mod tests {
         let result = TarCommand.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
 
-        let ctx2 = CommandContext {
-            args: vec!["-tf".to_string(), "archive.tar".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: fs.clone(),
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx2 = CommandContext::new(vec!["-tf".to_string(), "archive.tar".to_string()], fs.clone());
         let result = TarCommand.execute(ctx2).await;
         let line_count = result.stdout.lines().count();
         assert!(line_count >= 50);
@@ -1,6 +1,6 @@
 // src/commands/tee/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct TeeCommand;
 
@@ -16,6 +16,7 @@ fn resolve_path(cwd: &str, path: &str) -> String {
 #[async_trait]
 impl Command for TeeCommand {
     fn name(&self) -> &'static str { "tee" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Always }
 
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         let args = &ctx.args;
@@ -60,15 +61,14 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>, stdin: &str) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: stdin.into(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext { stdin: stdin.into(), ..CommandContext::new(args.into_iter().map(String::from).collect(), fs) }
     }
 
     fn make_ctx_with_fs(args: Vec<&str>, stdin: &str, fs: Arc<InMemoryFs>) -> CommandContext {
-        CommandContext { args: args.into_iter().map(String::from).collect(), stdin: stdin.into(), cwd: "/".into(), env: HashMap::new(), fs, exec_fn: None, fetch_fn: None }
+        CommandContext { stdin: stdin.into(), ..CommandContext::new(args.into_iter().map(String::from).collect(), fs) }
     }
 
     #[tokio::test]
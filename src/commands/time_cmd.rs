@@ -1,6 +1,5 @@
 use async_trait::async_trait;
 use crate::commands::{Command, CommandContext, CommandResult};
-use std::time::Instant;
 
 pub struct TimeCommand;
 
@@ -65,7 +64,8 @@ impl Command for TimeCommand {
             return CommandResult::success(String::new());
         }
 
-        let start_time = Instant::now();
+        let clock = ctx.clock();
+        let start_time = clock.now();
 
         let command_string = command_args.join(" ");
         let result = if let Some(ref exec_fn) = ctx.exec_fn {
@@ -80,7 +80,10 @@ impl Command for TimeCommand {
             return CommandResult::error("time: exec not available\n".to_string());
         };
 
-        let elapsed_seconds = start_time.elapsed().as_secs_f64();
+        let elapsed_seconds = clock.now()
+            .duration_since(start_time)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
 
         let timing_output = if posix_format {
             format!("real {:.2}\nuser 0.00\nsys 0.00\n", elapsed_seconds)
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
 use crate::commands::{Command, CommandContext, CommandResult};
 
@@ -17,13 +19,16 @@ Options:
   --preserve-status  exit with same status as COMMAND, even on timeout
   --help             display this help and exit";
 
-fn is_valid_duration(arg: &str) -> bool {
-    let s = if arg.ends_with('s') || arg.ends_with('m') || arg.ends_with('h') || arg.ends_with('d') {
-        &arg[..arg.len()-1]
-    } else {
-        arg
+fn parse_duration(arg: &str) -> Option<Duration> {
+    let (number, multiplier) = match arg.chars().last()? {
+        's' => (&arg[..arg.len() - 1], 1.0),
+        'm' => (&arg[..arg.len() - 1], 60.0),
+        'h' => (&arg[..arg.len() - 1], 3600.0),
+        'd' => (&arg[..arg.len() - 1], 86400.0),
+        _ => (arg, 1.0),
     };
-    s.parse::<f64>().is_ok()
+    let secs = number.parse::<f64>().ok()? * multiplier;
+    Duration::try_from_secs_f64(secs).ok()
 }
 
 #[async_trait]
@@ -82,20 +87,21 @@ impl Command for TimeoutCommand {
             return CommandResult::error("timeout: missing operand\n".to_string());
         }
 
-        if !is_valid_duration(&remaining[0]) {
-            return CommandResult::error(format!(
-                "timeout: invalid time interval '{}'\n",
-                remaining[0]
-            ));
-        }
+        let limit = match parse_duration(&remaining[0]) {
+            Some(limit) => limit,
+            None => {
+                return CommandResult::error(format!(
+                    "timeout: invalid time interval '{}'\n",
+                    remaining[0]
+                ));
+            }
+        };
 
         let command_args: Vec<String> = remaining[1..].to_vec();
         if command_args.is_empty() {
             return CommandResult::error("timeout: missing operand\n".to_string());
         }
 
-        let _ = preserve_status;
-
         let exec_fn = match &ctx.exec_fn {
             Some(f) => f.clone(),
             None => {
@@ -114,6 +120,8 @@ impl Command for TimeoutCommand {
             .collect::<Vec<_>>()
             .join(" ");
 
+        let clock = ctx.clock();
+        let started = clock.now();
         let exec_future = exec_fn(
             command_str,
             ctx.stdin.clone(),
@@ -122,27 +130,35 @@ impl Command for TimeoutCommand {
             ctx.fs.clone(),
         );
 
-        exec_future.await
+        // A real `sleep` in the command returns once the limit has passed. A
+        // zero duration disables the timeout.
+        let mut result = if limit.is_zero() {
+            exec_future.await
+        } else {
+            let deadline = Instant::now().checked_add(limit);
+            let child_clock = clock.with_interrupt(move || deadline.is_some_and(|d| Instant::now() >= d));
+            child_clock.scope(exec_future).await
+        };
+
+        // Other commands run to completion, so a command that outlived the
+        // limit is reported as timed out once it finishes.
+        let elapsed = clock.now().duration_since(started).unwrap_or_default();
+        if !limit.is_zero() && elapsed > limit {
+            // SIGTERM (143) with --preserve-status, like GNU timeout
+            result.exit_code = if preserve_status { 143 } else { 124 };
+        }
+        result
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     fn create_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     #[tokio::test]
@@ -175,11 +191,11 @@ mod tests {
     }
 
     #[test]
-    fn test_is_valid_duration() {
-        assert!(is_valid_duration("5"));
-        assert!(is_valid_duration("5s"));
-        assert!(is_valid_duration("5m"));
-        assert!(is_valid_duration("1.5h"));
-        assert!(!is_valid_duration("abc"));
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("5s"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert!(parse_duration("abc").is_none());
     }
 }
@@ -1,6 +1,5 @@
 // src/commands/touch/mod.rs
 use async_trait::async_trait;
-use std::time::SystemTime;
 use crate::commands::{Command, CommandContext, CommandResult};
 
 pub struct TouchCommand;
@@ -68,7 +67,7 @@ impl Command for TouchCommand {
             return CommandResult::error("touch: missing file operand\n".to_string());
         }
 
-        let target_time = SystemTime::now();
+        let target_time = ctx.clock().now();
 
         let mut stderr = String::new();
         let mut exit_code = 0;
@@ -103,18 +102,9 @@ mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
     use std::sync::Arc;
-    use std::collections::HashMap;
 
     fn make_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     #[tokio::test]
@@ -1,6 +1,6 @@
 // src/commands/tr/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use std::collections::HashSet;
 
 pub struct TrCommand;
@@ -53,6 +53,10 @@ impl Command for TrCommand {
         "tr"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Always
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -197,19 +201,13 @@ impl Command for TrCommand {
 mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
-    use std::collections::HashMap;
     use std::sync::Arc;
 
     fn make_ctx(args: Vec<&str>, stdin: &str) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
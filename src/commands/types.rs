@@ -4,7 +4,9 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use crate::clock::Clock;
 use crate::fs::FileSystem;
+use crate::random::Random;
 
 /// Callback for executing shell commands (used by xargs, find -exec)
 /// Parameters: command_string, stdin, cwd, env, fs
@@ -56,11 +58,60 @@ pub struct CommandContext {
     pub fs: Arc<dyn FileSystem>,
     pub exec_fn: Option<ExecFn>,
     pub fetch_fn: Option<FetchFn>,
+    /// Time source (the host clock when not set)
+    pub clock: Option<Clock>,
+    /// Randomness source (the operating system when not set)
+    pub random: Option<Random>,
+}
+
+impl CommandContext {
+    /// A context in `/` with no stdin, no environment and no callbacks.
+    pub fn new(args: Vec<String>, fs: Arc<dyn FileSystem>) -> Self {
+        Self {
+            args,
+            stdin: String::new(),
+            cwd: "/".to_string(),
+            env: HashMap::new(),
+            fs,
+            exec_fn: None,
+            fetch_fn: None,
+            clock: None,
+            random: None,
+        }
+    }
+
+    /// The clock commands should read and sleep on.
+    pub fn clock(&self) -> Clock {
+        self.clock.clone().unwrap_or_default()
+    }
+
+    /// The random source commands should draw from.
+    pub fn random(&self) -> Random {
+        self.random.clone().unwrap_or_default()
+    }
+}
+
+/// How a command uses its standard input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdinUse {
+    /// Never reads it (`ls`, `mkdir`)
+    Ignored,
+    /// Reads it unless an operand names a file; `-` always means stdin
+    Filter,
+    /// Always reads it (`tee`, `tr`)
+    Always,
 }
 
 /// 命令 trait
 #[async_trait]
 pub trait Command: Send + Sync {
     fn name(&self) -> &'static str;
+
+    /// Whether the command reads its standard input, so input it leaves
+    /// unread stays for the next command of a redirected group or loop.
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Ignored
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult;
 }
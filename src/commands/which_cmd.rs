@@ -82,22 +82,13 @@ impl Command for WhichCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     #[tokio::test]
     async fn test_which_no_args() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec![],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec![], fs);
         let cmd = WhichCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 1);
@@ -106,15 +97,7 @@ mod tests {
     #[tokio::test]
     async fn test_which_help() {
         let fs = Arc::new(InMemoryFs::new());
-        let ctx = CommandContext {
-            args: vec!["--help".to_string()],
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
-        };
+        let ctx = CommandContext::new(vec!["--help".to_string()], fs);
         let cmd = WhichCommand;
         let result = cmd.execute(ctx).await;
         assert_eq!(result.exit_code, 0);
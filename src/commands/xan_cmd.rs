@@ -1,11 +1,12 @@
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct XanCommand;
 
 #[async_trait]
 impl Command for XanCommand {
     fn name(&self) -> &'static str { "xan" }
+    fn stdin_use(&self) -> StdinUse { StdinUse::Filter }
 
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.is_empty() || ctx.args.iter().any(|a| a == "--help" || a == "-h") {
@@ -471,20 +472,11 @@ async fn cmd_from(ctx: &CommandContext, args: &[String]) -> CommandResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use crate::fs::InMemoryFs;
 
     fn create_ctx(args: Vec<&str>) -> CommandContext {
-        CommandContext {
-            args: args.into_iter().map(String::from).collect(),
-            stdin: String::new(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
-        }
+        CommandContext::new(args.into_iter().map(String::from).collect(), Arc::new(InMemoryFs::new()))
     }
 
     const CSV_DATA: &str = "name,age,city\nalice,30,nyc\nbob,25,la\ncharlie,35,sf\n";
@@ -1,6 +1,6 @@
 // src/commands/xargs/mod.rs
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 
 pub struct XargsCommand;
 
@@ -64,6 +64,10 @@ impl Command for XargsCommand {
         "xargs"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Always
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(
@@ -254,7 +258,6 @@ impl Command for XargsCommand {
 mod tests {
     use super::*;
     use crate::fs::InMemoryFs;
-    use std::collections::HashMap;
     use std::sync::Arc;
     use std::pin::Pin;
     use std::future::Future;
@@ -262,13 +265,8 @@ mod tests {
     fn make_ctx(args: Vec<&str>, stdin: &str) -> CommandContext {
         let fs = Arc::new(InMemoryFs::new());
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
@@ -280,13 +278,9 @@ mod tests {
             }) as Pin<Box<dyn Future<Output = CommandResult> + Send>>
         });
         CommandContext {
-            args: args.into_iter().map(String::from).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
             exec_fn: Some(exec_fn),
-            fetch_fn: None,
+            ..CommandContext::new(args.into_iter().map(String::from).collect(), fs)
         }
     }
 
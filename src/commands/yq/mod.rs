@@ -2,7 +2,7 @@
 pub mod formats;
 
 use async_trait::async_trait;
-use crate::commands::{Command, CommandContext, CommandResult};
+use crate::commands::{Command, CommandContext, CommandResult, StdinUse};
 use crate::commands::query_engine::{Value, parse, evaluate};
 use crate::commands::query_engine::context::{EvalContext, JqError};
 use formats::*;
@@ -285,6 +285,10 @@ impl Command for YqCommand {
         "yq"
     }
 
+    fn stdin_use(&self) -> StdinUse {
+        StdinUse::Filter
+    }
+
     async fn execute(&self, ctx: CommandContext) -> CommandResult {
         if ctx.args.iter().any(|a| a == "--help") {
             return CommandResult::success(YQ_HELP.to_string());
@@ -369,6 +373,7 @@ impl Command for YqCommand {
         };
 
         let mut eval_ctx = EvalContext::with_env(ctx.env.clone());
+        eval_ctx.clock = ctx.clock();
 
         // Evaluate
         let values: Vec<Value> = if opts.null_input {
@@ -567,18 +572,12 @@ mod tests {
     use super::*;
     use crate::fs::{FileSystem, InMemoryFs};
     use std::sync::Arc;
-    use std::collections::HashMap;
     use serde_json;
 
     fn make_ctx(args: &[&str], stdin: &str) -> CommandContext {
         CommandContext {
-            args: args.iter().map(|s| s.to_string()).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs: Arc::new(InMemoryFs::new()),
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.iter().map(|s| s.to_string()).collect(), Arc::new(InMemoryFs::new()))
         }
     }
 
@@ -592,13 +591,8 @@ mod tests {
             fs.write_file(path, content.as_bytes()).await.unwrap();
         }
         CommandContext {
-            args: args.iter().map(|s| s.to_string()).collect(),
             stdin: stdin.to_string(),
-            cwd: "/".to_string(),
-            env: HashMap::new(),
-            fs,
-            exec_fn: None,
-            fetch_fn: None,
+            ..CommandContext::new(args.iter().map(|s| s.to_string()).collect(), fs)
         }
     }
 
@@ -4,27 +4,154 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::ops::Deref;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
 use tokio::sync::RwLock;
 
 use super::types::*;
+use crate::clock::Clock;
 
 /// In-memory virtual file system.
 pub struct InMemoryFs {
-    data: RwLock<HashMap<String, FsEntry>>,
+    data: RwLock<Entries>,
+    clock: Clock,
+    limits: std::sync::RwLock<FsLimits>,
+}
+
+/// The entries of the filesystem by path, with a running total of the bytes
+/// held in files so capacity checks don't rescan every entry.
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, FsEntry>,
+    file_bytes: u64,
+    /// Usage that doesn't count against the limits
+    exempt_bytes: u64,
+    exempt_inodes: u64,
+}
+
+impl Entries {
+    fn insert(&mut self, path: String, entry: FsEntry) -> Option<FsEntry> {
+        self.file_bytes += file_size(&entry);
+        let old = self.map.insert(path, entry);
+        self.file_bytes -= old.as_ref().map_or(0, file_size);
+        old
+    }
+
+    fn remove(&mut self, path: &str) -> Option<FsEntry> {
+        let old = self.map.remove(path);
+        self.file_bytes -= old.as_ref().map_or(0, file_size);
+        old
+    }
+
+    /// Mutable access for changing an entry's mode or mtime. File content
+    /// must be replaced through `insert` to keep the byte count right.
+    fn get_mut(&mut self, path: &str) -> Option<&mut FsEntry> {
+        self.map.get_mut(path)
+    }
+
+    /// File bytes and entry count that count against the limits. The root
+    /// directory is not counted.
+    fn usage(&self) -> (u64, u64) {
+        let inodes = self.map.len().saturating_sub(1) as u64;
+        (self.file_bytes.saturating_sub(self.exempt_bytes), inodes.saturating_sub(self.exempt_inodes))
+    }
+}
+
+impl Deref for Entries {
+    type Target = HashMap<String, FsEntry>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+fn file_size(entry: &FsEntry) -> u64 {
+    match entry {
+        FsEntry::File { content, .. } => content.len() as u64,
+        _ => 0,
+    }
 }
 
 impl InMemoryFs {
     /// Create a new empty in-memory filesystem.
     pub fn new() -> Self {
-        let mut data = HashMap::new();
+        Self::with_clock(Clock::real())
+    }
+
+    /// Create a new empty filesystem whose timestamps come from `clock`.
+    pub fn with_clock(clock: Clock) -> Self {
+        Self::with_clock_and_limits(clock, FsLimits::UNLIMITED)
+    }
+
+    /// Create a new empty filesystem whose timestamps come from `clock` and
+    /// whose writes fail with ENOSPC/EFBIG once `limits` are exceeded.
+    pub fn with_clock_and_limits(clock: Clock, limits: FsLimits) -> Self {
+        let mut data = Entries::default();
         data.insert("/".to_string(), FsEntry::Directory {
             mode: 0o755,
-            mtime: SystemTime::now(),
+            mtime: clock.peek(),
         });
-        Self { data: RwLock::new(data) }
+        Self { data: RwLock::new(data), clock, limits: std::sync::RwLock::new(limits) }
+    }
+
+    pub fn limits(&self) -> FsLimits {
+        *self.limits.read().unwrap()
+    }
+
+    /// Enforce `limits` from now on. Whatever the filesystem already holds
+    /// doesn't count against them, so the directories and command stubs a
+    /// shell sets up leave the whole quota to its scripts.
+    pub async fn apply_limits(&self, limits: FsLimits) {
+        let mut data = self.data.write().await;
+        data.exempt_bytes = data.file_bytes;
+        data.exempt_inodes = data.map.len().saturating_sub(1) as u64;
+        *self.limits.write().unwrap() = limits;
+    }
+
+    /// Fail with EFBIG if a file of `size` bytes would exceed the limit.
+    fn check_file_size(&self, path: &str, size: u64, operation: &str) -> Result<(), FsError> {
+        if size > self.limits().max_file_size {
+            return Err(FsError::FileTooLarge {
+                path: path.to_string(),
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fail with ENOSPC if storing `added_bytes` in `added_inodes` new entries
+    /// at `target` would exceed the filesystem limits. An existing file at
+    /// `target` is treated as replaced.
+    fn check_capacity(
+        &self,
+        data: &Entries,
+        target: &str,
+        added_bytes: u64,
+        added_inodes: u64,
+        path: &str,
+        operation: &str,
+    ) -> Result<(), FsError> {
+        let limits = self.limits();
+        if limits.is_unlimited() {
+            return Ok(());
+        }
+        let (mut bytes, mut inodes) = data.usage();
+        match data.get(target) {
+            Some(FsEntry::File { content, .. }) => bytes = bytes.saturating_sub(content.len() as u64),
+            Some(_) => {}
+            None => inodes += added_inodes + missing_ancestors(data, target),
+        }
+        if bytes.saturating_add(added_bytes) > limits.max_total_bytes
+            || inodes > limits.max_inodes
+        {
+            return Err(FsError::NoSpace {
+                path: path.to_string(),
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
     }
 
     /// Create with initial files.
@@ -33,7 +160,7 @@ impl InMemoryFs {
         let mut data = fs.data.blocking_write();
         for (path, init) in files {
             let normalized = normalize_path(path);
-            ensure_parent_dirs(&mut data, &normalized);
+            ensure_parent_dirs(&mut data, &normalized, fs.clock.peek());
             let content = match &init.content {
                 FileContent::Text(s) => s.as_bytes().to_vec(),
                 FileContent::Binary(b) => b.clone(),
@@ -41,7 +168,7 @@ impl InMemoryFs {
             data.insert(normalized, FsEntry::File {
                 content,
                 mode: init.mode.unwrap_or(0o644),
-                mtime: init.mtime.unwrap_or_else(SystemTime::now),
+                mtime: init.mtime.unwrap_or_else(|| fs.clock.peek()),
             });
         }
         drop(data);
@@ -59,7 +186,7 @@ impl InMemoryFs {
             if !data.contains_key(&current) {
                 data.insert(current.clone(), FsEntry::Directory {
                     mode: 0o755,
-                    mtime: SystemTime::now(),
+                    mtime: self.clock.peek(),
                 });
             }
         }
@@ -69,11 +196,11 @@ impl InMemoryFs {
     pub fn write_file_sync(&self, path: &str, content: &[u8]) {
         let mut data = self.data.blocking_write();
         let normalized = normalize_path(path);
-        ensure_parent_dirs(&mut data, &normalized);
+        ensure_parent_dirs(&mut data, &normalized, self.clock.peek());
         data.insert(normalized, FsEntry::File {
             content: content.to_vec(),
             mode: 0o644,
-            mtime: SystemTime::now(),
+            mtime: self.clock.peek(),
         });
     }
 }
@@ -127,20 +254,31 @@ fn dirname(path: &str) -> String {
     }
 }
 
-fn ensure_parent_dirs(data: &mut HashMap<String, FsEntry>, path: &str) {
+fn ensure_parent_dirs(data: &mut Entries, path: &str, mtime: SystemTime) {
     let dir = dirname(path);
     if dir == "/" {
         return;
     }
     if !data.contains_key(&dir) {
-        ensure_parent_dirs(data, &dir);
+        ensure_parent_dirs(data, &dir, mtime);
         data.insert(dir, FsEntry::Directory {
             mode: 0o755,
-            mtime: SystemTime::now(),
+            mtime,
         });
     }
 }
 
+/// Number of parent directories `ensure_parent_dirs` would create.
+fn missing_ancestors(data: &HashMap<String, FsEntry>, path: &str) -> u64 {
+    let mut count = 0;
+    let mut dir = dirname(path);
+    while dir != "/" && !data.contains_key(&dir) {
+        count += 1;
+        dir = dirname(&dir);
+    }
+    count
+}
+
 fn resolve_symlink_target(symlink_path: &str, target: &str) -> String {
     if target.starts_with('/') {
         normalize_path(target)
@@ -263,11 +401,13 @@ impl FileSystem for InMemoryFs {
     async fn write_file(&self, path: &str, content: &[u8]) -> Result<(), FsError> {
         let mut data = self.data.write().await;
         let normalized = normalize_path(path);
-        ensure_parent_dirs(&mut data, &normalized);
+        self.check_file_size(path, content.len() as u64, "write")?;
+        self.check_capacity(&data, &normalized, content.len() as u64, 1, path, "write")?;
+        ensure_parent_dirs(&mut data, &normalized, self.clock.peek());
         data.insert(normalized, FsEntry::File {
             content: content.to_vec(),
             mode: 0o644,
-            mtime: SystemTime::now(),
+            mtime: self.clock.peek(),
         });
         Ok(())
     }
@@ -284,20 +424,25 @@ impl FileSystem for InMemoryFs {
         }
 
         if let Some(FsEntry::File { content: existing, mode, .. }) = data.get(&normalized) {
+            let size = (existing.len() + content.len()) as u64;
+            self.check_file_size(path, size, "write")?;
+            self.check_capacity(&data, &normalized, size, 1, path, "write")?;
             let mut combined = existing.clone();
             let mode = *mode;
             combined.extend_from_slice(content);
             data.insert(normalized, FsEntry::File {
                 content: combined,
                 mode,
-                mtime: SystemTime::now(),
+                mtime: self.clock.peek(),
             });
         } else {
-            ensure_parent_dirs(&mut data, &normalized);
+            self.check_file_size(path, content.len() as u64, "write")?;
+            self.check_capacity(&data, &normalized, content.len() as u64, 1, path, "write")?;
+            ensure_parent_dirs(&mut data, &normalized, self.clock.peek());
             data.insert(normalized, FsEntry::File {
                 content: content.to_vec(),
                 mode: 0o644,
-                mtime: SystemTime::now(),
+                mtime: self.clock.peek(),
             });
         }
         Ok(())
@@ -394,6 +539,7 @@ impl FileSystem for InMemoryFs {
         let parent = dirname(&normalized);
         if parent != "/" && !data.contains_key(&parent) {
             if options.recursive {
+                self.check_capacity(&data, &normalized, 0, 1, path, "mkdir")?;
                 // Recursively create parents
                 let parts: Vec<&str> = normalized.split('/').filter(|p| !p.is_empty()).collect();
                 let mut current = String::new();
@@ -402,7 +548,7 @@ impl FileSystem for InMemoryFs {
                     if !data.contains_key(&current) {
                         data.insert(current.clone(), FsEntry::Directory {
                             mode: 0o755,
-                            mtime: SystemTime::now(),
+                            mtime: self.clock.peek(),
                         });
                     }
                 }
@@ -415,9 +561,10 @@ impl FileSystem for InMemoryFs {
             }
         }
 
+        self.check_capacity(&data, &normalized, 0, 1, path, "mkdir")?;
         data.insert(normalized, FsEntry::Directory {
             mode: 0o755,
-            mtime: SystemTime::now(),
+            mtime: self.clock.peek(),
         });
         Ok(())
     }
@@ -540,7 +687,8 @@ impl FileSystem for InMemoryFs {
                 operation: "cp".to_string(),
             }),
             Some(FsEntry::File { content, mode, mtime }) => {
-                ensure_parent_dirs(&mut data, &dest_norm);
+                self.check_capacity(&data, &dest_norm, content.len() as u64, 1, dest, "cp")?;
+                ensure_parent_dirs(&mut data, &dest_norm, self.clock.peek());
                 data.insert(dest_norm, FsEntry::File { content, mode, mtime });
                 Ok(())
             }
@@ -561,6 +709,13 @@ impl FileSystem for InMemoryFs {
                     .filter(|(k, _)| k.starts_with(&prefix) || *k == &src_norm)
                     .map(|(k, v)| (k.clone(), v.clone()))
                     .collect();
+                let bytes = entries.iter()
+                    .map(|(_, v)| match v {
+                        FsEntry::File { content, .. } => content.len() as u64,
+                        _ => 0,
+                    })
+                    .sum();
+                self.check_capacity(&data, &dest_norm, bytes, entries.len() as u64, dest, "cp")?;
 
                 for (k, v) in entries {
                     let relative = if k == src_norm {
@@ -569,13 +724,14 @@ impl FileSystem for InMemoryFs {
                         k[src_norm.len()..].to_string()
                     };
                     let new_path = format!("{}{}", dest_norm, relative);
-                    ensure_parent_dirs(&mut data, &new_path);
+                    ensure_parent_dirs(&mut data, &new_path, self.clock.peek());
                     data.insert(new_path, v);
                 }
                 Ok(())
             }
             Some(FsEntry::Symlink { target, mode, mtime }) => {
-                ensure_parent_dirs(&mut data, &dest_norm);
+                self.check_capacity(&data, &dest_norm, 0, 1, dest, "cp")?;
+                ensure_parent_dirs(&mut data, &dest_norm, self.clock.peek());
                 data.insert(dest_norm, FsEntry::Symlink { target, mode, mtime });
                 Ok(())
             }
@@ -610,11 +766,12 @@ impl FileSystem for InMemoryFs {
                 operation: "symlink".to_string(),
             });
         }
-        ensure_parent_dirs(&mut data, &normalized);
+        self.check_capacity(&data, &normalized, 0, 1, link_path, "symlink")?;
+        ensure_parent_dirs(&mut data, &normalized, self.clock.peek());
         data.insert(normalized, FsEntry::Symlink {
             target: target.to_string(),
             mode: 0o777,
-            mtime: SystemTime::now(),
+            mtime: self.clock.peek(),
         });
         Ok(())
     }
@@ -637,7 +794,8 @@ impl FileSystem for InMemoryFs {
                         operation: "link".to_string(),
                     });
                 }
-                ensure_parent_dirs(&mut data, &new_norm);
+                self.check_capacity(&data, &new_norm, content.len() as u64, 1, new_path, "link")?;
+                ensure_parent_dirs(&mut data, &new_norm, self.clock.peek());
                 data.insert(new_norm, FsEntry::File { content, mode, mtime });
                 Ok(())
             }
@@ -1379,4 +1537,29 @@ mod tests {
         let entries = fs.readdir("/linkdir").await.unwrap();
         assert_eq!(entries, vec!["file.txt"]);
     }
+
+    #[tokio::test]
+    async fn test_capacity_limits() {
+        let limits = FsLimits { max_file_size: 8, max_total_bytes: 12, max_inodes: 4 };
+        let fs = InMemoryFs::with_clock_and_limits(Clock::real(), limits);
+
+        fs.write_file("/a", b"12345678").await.unwrap();
+        assert!(matches!(fs.write_file("/b", b"123456789").await, Err(FsError::FileTooLarge { .. })));
+        assert!(matches!(fs.append_file("/a", b"9").await, Err(FsError::FileTooLarge { .. })));
+        assert!(matches!(fs.write_file("/b", b"12345").await, Err(FsError::NoSpace { .. })));
+        // Overwriting a file frees its old content first
+        fs.write_file("/a", b"1").await.unwrap();
+        fs.write_file("/b", b"12345").await.unwrap();
+
+        fs.mkdir("/d", &MkdirOptions { recursive: false }).await.unwrap();
+        // The missing parent directory counts too
+        assert!(matches!(fs.write_file("/e/f", b"").await, Err(FsError::NoSpace { .. })));
+        fs.symlink("/a", "/l").await.unwrap();
+        assert!(matches!(fs.cp("/a", "/c", &CpOptions { recursive: false }).await, Err(FsError::NoSpace { .. })));
+        assert!(!fs.exists("/c").await);
+        // Removing entries gives their space back
+        fs.rm("/l", &RmOptions { recursive: false, force: false }).await.unwrap();
+        fs.rm("/b", &RmOptions { recursive: false, force: false }).await.unwrap();
+        fs.write_file("/c", b"12345678").await.unwrap();
+    }
 }
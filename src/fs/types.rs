@@ -34,6 +34,12 @@ pub enum FsError {
     #[error("EPERM: operation not permitted, {operation} '{path}'")]
     PermissionDenied { path: String, operation: String },
 
+    #[error("ENOSPC: no space left on device, {operation} '{path}'")]
+    NoSpace { path: String, operation: String },
+
+    #[error("EFBIG: file too large, {operation} '{path}'")]
+    FileTooLarge { path: String, operation: String },
+
     #[error("EROFS: read-only file system, {operation}")]
     ReadOnly { operation: String },
 
@@ -41,6 +47,36 @@ pub enum FsError {
     Other { message: String },
 }
 
+/// Capacity limits for an in-memory filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsLimits {
+    /// Largest size of a single file in bytes
+    pub max_file_size: u64,
+    /// Largest combined size of all files in bytes
+    pub max_total_bytes: u64,
+    /// Largest number of entries (files, directories and symlinks)
+    pub max_inodes: u64,
+}
+
+impl FsLimits {
+    /// No limits.
+    pub const UNLIMITED: Self = Self {
+        max_file_size: u64::MAX,
+        max_total_bytes: u64::MAX,
+        max_inodes: u64::MAX,
+    };
+
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::UNLIMITED
+    }
+}
+
+impl Default for FsLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
 /// Supported buffer encodings
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BufferEncoding {
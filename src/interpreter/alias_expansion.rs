@@ -7,19 +7,22 @@
 //! 2. Alias value is substituted for the command name
 //! 3. If alias value ends with a space, the next word is also checked for alias expansion
 //! 4. Recursive expansion is allowed but limited to prevent infinite loops
+//! 5. Expansion happens when a line is read, before any of it runs: an alias
+//!    defined on a line is not used on that line, and a function body keeps
+//!    the aliases in effect when it was defined
 
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
+use crate::interpreter::types::InterpreterState;
 use crate::{
-    WordNode, WordPart, LiteralPart, SimpleCommandNode, AssignmentNode,
-    RedirectionNode, ScriptNode,
+    CommandNode, CommandSubstitutionPart, CompoundCommandNode, GroupNode, ProcessSubstitutionPart,
+    RedirectionTarget, ScriptNode, SimpleCommandNode, StatementNode, WordNode, WordPart, LiteralPart, AST,
 };
 
-/// Alias prefix used in environment variables
-pub const ALIAS_PREFIX: &str = "BASH_ALIAS_";
-
 /// Context needed for alias expansion operations
 pub struct AliasExpansionContext<'a> {
-    pub env: &'a HashMap<String, String>,
+    /// Alias definitions (name -> replacement text)
+    pub aliases: &'a HashMap<String, String>,
 }
 
 /// Check if a word is a literal unquoted word (eligible for alias expansion).
@@ -45,36 +48,42 @@ pub fn get_literal_value(word: &WordNode) -> Option<&str> {
 
 /// Get the alias value for a name, if defined
 pub fn get_alias<'a>(ctx: &'a AliasExpansionContext<'a>, name: &str) -> Option<&'a str> {
-    let key = format!("{}{}", ALIAS_PREFIX, name);
-    ctx.env.get(&key).map(|s| s.as_str())
+    ctx.aliases.get(name).map(|s| s.as_str())
 }
 
 /// Check if an alias is defined for a name
 pub fn has_alias(ctx: &AliasExpansionContext, name: &str) -> bool {
-    let key = format!("{}{}", ALIAS_PREFIX, name);
-    ctx.env.contains_key(&key)
+    ctx.aliases.contains_key(name)
+}
+
+/// Define or redefine an alias
+pub fn set_alias(aliases: &mut HashMap<String, String>, name: &str, value: &str) {
+    aliases.insert(name.to_string(), value.to_string());
 }
 
-/// Set an alias in the environment
-pub fn set_alias(env: &mut HashMap<String, String>, name: &str, value: &str) {
-    let key = format!("{}{}", ALIAS_PREFIX, name);
-    env.insert(key, value.to_string());
+/// Remove an alias, returning whether it was defined
+pub fn unset_alias(aliases: &mut HashMap<String, String>, name: &str) -> bool {
+    aliases.remove(name).is_some()
 }
 
-/// Remove an alias from the environment
-pub fn unset_alias(env: &mut HashMap<String, String>, name: &str) -> bool {
-    let key = format!("{}{}", ALIAS_PREFIX, name);
-    env.remove(&key).is_some()
+/// Get all defined aliases as (name, value) pairs, sorted by name
+pub fn get_all_aliases(aliases: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut all: Vec<(String, String)> = aliases
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    all.sort();
+    all
 }
 
-/// Get all defined aliases as (name, value) pairs
-pub fn get_all_aliases(env: &HashMap<String, String>) -> Vec<(String, String)> {
-    env.iter()
-        .filter_map(|(k, v)| {
-            k.strip_prefix(ALIAS_PREFIX)
-                .map(|name| (name.to_string(), v.clone()))
+/// Check if a name can be used for an alias. Bash rejects names containing
+/// quoting, expansion or shell metacharacters.
+pub fn is_valid_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace()
+                || matches!(c, '/' | '$' | '`' | '=' | '\\' | '\'' | '"' | '|' | '&' | ';' | '(' | ')' | '<' | '>')
         })
-        .collect()
 }
 
 /// Convert a WordNode back to a string representation for re-parsing.
@@ -139,151 +148,308 @@ pub enum AliasExpansionResult {
     Expanded(SimpleCommandNode),
     /// Expansion resulted in a complex command (multiple statements/pipelines)
     /// that needs to be executed as a script
-    ComplexAlias(String),
+    ComplexAlias(ScriptNode),
     /// Parse error during expansion
     ParseError(String),
 }
 
 /// Expand alias in a SimpleCommandNode if applicable.
-/// Returns the expansion result.
+///
+/// The alias value is parsed on its own and the command's words, prefix
+/// assignments and redirections are carried over unchanged, so they are
+/// never re-parsed. Names already in `alias_expansion_stack` are not
+/// expanded again, which stops alias loops.
 pub fn expand_alias(
     ctx: &AliasExpansionContext,
     node: &SimpleCommandNode,
     alias_expansion_stack: &mut HashSet<String>,
 ) -> AliasExpansionResult {
-    // Need a command name to expand
-    let name = match &node.name {
-        Some(n) => n,
-        None => return AliasExpansionResult::NoExpansion,
+    // Need a literal, unquoted command name with an alias defined for it
+    let Some(cmd_name) = node.name.as_ref().and_then(get_literal_value) else {
+        return AliasExpansionResult::NoExpansion;
+    };
+    let Some(alias_value) = get_alias(ctx, cmd_name) else {
+        return AliasExpansionResult::NoExpansion;
     };
 
-    // Check if the command name is a literal unquoted word
-    if !is_literal_unquoted_word(name) {
+    // Prevent infinite recursion
+    if !alias_expansion_stack.insert(cmd_name.to_string()) {
         return AliasExpansionResult::NoExpansion;
     }
 
-    let cmd_name = match get_literal_value(name) {
-        Some(n) => n,
-        None => return AliasExpansionResult::NoExpansion,
+    let mut parser = crate::parser::Parser::new();
+    let mut expanded_ast = match parser.parse(alias_value) {
+        Ok(ast) => ast,
+        Err(e) => return AliasExpansionResult::ParseError(e.to_string()),
     };
 
-    // Check for alias
-    let alias_value = match get_alias(ctx, cmd_name) {
-        Some(v) => v.to_string(),
-        None => return AliasExpansionResult::NoExpansion,
+    // An alias ending in a blank makes the word after it eligible too
+    let expand_next = alias_value.ends_with([' ', '\t']);
+    // One ending in a separator leaves the words after it as a command of
+    // their own
+    let ends_command = alias_value.trim_end_matches([' ', '\t']).ends_with([';', '&', '\n']);
+
+    let simple = match expanded_ast.statements.as_slice() {
+        _ if ends_command => None,
+        [] => Some(SimpleCommandNode {
+            name: None,
+            args: vec![],
+            assignments: vec![],
+            redirections: vec![],
+            line: node.line,
+            source_text: node.source_text.clone(),
+        }),
+        [stmt] if !stmt.background => match stmt.pipelines.as_slice() {
+            [pipeline] if !pipeline.negated && !pipeline.timed => match pipeline.commands.as_slice() {
+                [CommandNode::Simple(simple)] => Some(simple.clone()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
     };
 
-    // Prevent infinite recursion
-    if alias_expansion_stack.contains(cmd_name) {
-        return AliasExpansionResult::NoExpansion;
+    let Some(simple) = simple else {
+        // Complex alias: the words after the alias name belong to the last
+        // command of the replacement, or follow it after a separator;
+        // prefix assignments belong to the first
+        if ends_command {
+            if !node.args.is_empty() || !node.redirections.is_empty() {
+                let mut words = node.args.clone();
+                let rest = SimpleCommandNode {
+                    name: (!words.is_empty()).then(|| words.remove(0)),
+                    args: words,
+                    assignments: vec![],
+                    redirections: node.redirections.clone(),
+                    line: node.line,
+                    source_text: node.source_text.clone(),
+                };
+                let pipeline = AST::pipeline(vec![CommandNode::Simple(rest)], false, false, false, None);
+                expanded_ast.statements.push(AST::statement(vec![pipeline], vec![], false, None, None));
+            }
+        } else if let Some(CommandNode::Simple(last)) = expanded_ast
+            .statements
+            .last_mut()
+            .and_then(|stmt| stmt.pipelines.last_mut())
+            .and_then(|pipeline| pipeline.commands.last_mut())
+        {
+            last.args.extend(node.args.iter().cloned());
+            last.redirections.extend(node.redirections.iter().cloned());
+        }
+        if let Some(CommandNode::Simple(first)) = expanded_ast
+            .statements
+            .first_mut()
+            .and_then(|stmt| stmt.pipelines.first_mut())
+            .and_then(|pipeline| pipeline.commands.first_mut())
+        {
+            first.assignments.splice(0..0, node.assignments.iter().cloned());
+        }
+        return AliasExpansionResult::ComplexAlias(expanded_ast);
+    };
+
+    let mut args = node.args.clone();
+    if expand_next && !args.is_empty() {
+        let next = SimpleCommandNode {
+            name: Some(args.remove(0)),
+            args: args.split_off(0),
+            assignments: vec![],
+            redirections: vec![],
+            line: node.line,
+            source_text: node.source_text.clone(),
+        };
+        match expand_alias(ctx, &next, alias_expansion_stack) {
+            AliasExpansionResult::Expanded(expanded) => {
+                args.extend(expanded.name);
+                args.extend(expanded.args);
+            }
+            _ => {
+                args.extend(next.name);
+                args.extend(next.args);
+            }
+        }
     }
 
-    alias_expansion_stack.insert(cmd_name.to_string());
+    // The replacement's own words come first; with an empty replacement the
+    // first remaining word becomes the command name
+    let mut words: Vec<WordNode> = simple.name.into_iter().chain(simple.args).collect();
+    words.extend(args);
+    let name = (!words.is_empty()).then(|| words.remove(0));
+
+    let mut assignments = node.assignments.clone();
+    assignments.extend(simple.assignments);
+    let mut redirections = simple.redirections;
+    redirections.extend(node.redirections.iter().cloned());
+
+    let expanded = SimpleCommandNode {
+        name,
+        args: words,
+        assignments,
+        redirections,
+        line: node.line,
+        source_text: node.source_text.clone(),
+    };
 
-    // Build the full command line: alias value + original args
-    let mut full_command = alias_value.clone();
+    // The first word of the replacement may itself be an alias
+    match expand_alias(ctx, &expanded, alias_expansion_stack) {
+        AliasExpansionResult::NoExpansion => AliasExpansionResult::Expanded(expanded),
+        result => result,
+    }
+}
 
-    // Check if alias value ends with a space (triggers expansion of next word)
-    let expand_next = alias_value.ends_with(' ');
+/// Reads statements the way bash reads its input. Every statement on a line
+/// is expanded with the aliases defined before that line started to run.
+#[derive(Debug, Default)]
+pub struct AliasReader {
+    /// Line of the statements read so far
+    line: Option<usize>,
+    /// Aliases in effect when that line was read, if expansion is on
+    aliases: Option<HashMap<String, String>>,
+}
 
-    // If not expanding next, append args directly
-    if !expand_next {
-        for arg in &node.args {
-            let arg_literal = word_node_to_string(arg);
-            full_command.push(' ');
-            full_command.push_str(&arg_literal);
+impl AliasReader {
+    /// Read `statement`, expanding the aliases in all of its commands.
+    /// Returns the message of the first alias whose value does not parse.
+    pub fn read<'s>(
+        &mut self,
+        state: &InterpreterState,
+        statement: &'s StatementNode,
+    ) -> Result<Cow<'s, StatementNode>, String> {
+        if statement.line.is_none() || statement.line != self.line {
+            self.line = statement.line;
+            self.aliases = state
+                .aliases
+                .clone()
+                .filter(|aliases| state.shopt_options.expand_aliases && !aliases.is_empty());
         }
+        let Some(aliases) = &self.aliases else {
+            return Ok(Cow::Borrowed(statement));
+        };
+        let mut statement = statement.clone();
+        expand_statement_aliases(&AliasExpansionContext { aliases }, &mut statement, &HashSet::new())?;
+        Ok(Cow::Owned(statement))
     }
+}
 
-    // Parse the expanded command
-    let mut parser = crate::parser::Parser::new();
-    let expanded_ast = match parser.parse(&full_command) {
-        Ok(ast) => ast,
-        Err(e) => {
-            alias_expansion_stack.remove(cmd_name);
-            return AliasExpansionResult::ParseError(e.to_string());
+fn expand_statement_aliases(
+    ctx: &AliasExpansionContext,
+    statement: &mut StatementNode,
+    stack: &HashSet<String>,
+) -> Result<(), String> {
+    for pipeline in &mut statement.pipelines {
+        for command in &mut pipeline.commands {
+            expand_command_aliases(ctx, command, stack)?;
         }
-    };
-
-    // Check if we got a simple command
-    if expanded_ast.statements.len() != 1
-        || expanded_ast.statements[0].pipelines.len() != 1
-        || expanded_ast.statements[0].pipelines[0].commands.len() != 1
-    {
-        // Complex alias - multiple commands, pipelines, etc.
-        alias_expansion_stack.remove(cmd_name);
-        return AliasExpansionResult::ComplexAlias(full_command);
     }
+    Ok(())
+}
 
-    let expanded_cmd = &expanded_ast.statements[0].pipelines[0].commands[0];
-    match expanded_cmd {
-        crate::CommandNode::Simple(simple_cmd) => {
-            // Merge the expanded command with original node's context
-            let mut new_node = SimpleCommandNode {
-                name: simple_cmd.name.clone(),
-                args: simple_cmd.args.clone(),
-                // Preserve original assignments (prefix assignments like FOO=bar alias_cmd)
-                assignments: {
-                    let mut assignments = node.assignments.clone();
-                    assignments.extend(simple_cmd.assignments.clone());
-                    assignments
-                },
-                // Preserve original redirections
-                redirections: {
-                    let mut redirections = simple_cmd.redirections.clone();
-                    redirections.extend(node.redirections.clone());
-                    redirections
-                },
-                // Preserve line number
-                line: node.line,
-            };
-
-            // If alias ends with space, expand next word too (recursive alias on first arg)
-            if expand_next && !node.args.is_empty() {
-                // Add the original args to the expanded command's args
-                new_node.args.extend(node.args.clone());
-
-                // Now recursively expand the first arg if it's an alias
-                if !new_node.args.is_empty() {
-                    let first_arg = &new_node.args[0];
-                    if is_literal_unquoted_word(first_arg) {
-                        if let Some(first_arg_name) = get_literal_value(first_arg) {
-                            if has_alias(ctx, first_arg_name) {
-                                // Create a temporary node with the first arg as command
-                                let temp_node = SimpleCommandNode {
-                                    name: Some(new_node.args[0].clone()),
-                                    args: new_node.args[1..].to_vec(),
-                                    assignments: vec![],
-                                    redirections: vec![],
-                                    line: None,
-                                };
-                                let expanded_first = expand_alias(ctx, &temp_node, alias_expansion_stack);
-                                match expanded_first {
-                                    AliasExpansionResult::Expanded(expanded) => {
-                                        // Merge back
-                                        new_node.name = expanded.name;
-                                        new_node.args = expanded.args;
-                                    }
-                                    _ => {
-                                        // Keep the original if expansion failed or was complex
-                                    }
-                                }
-                            }
-                        }
+fn expand_body_aliases(ctx: &AliasExpansionContext, body: &mut [StatementNode]) -> Result<(), String> {
+    body.iter_mut().try_for_each(|statement| expand_statement_aliases(ctx, statement, &HashSet::new()))
+}
+
+fn expand_command_aliases(
+    ctx: &AliasExpansionContext,
+    command: &mut CommandNode,
+    stack: &HashSet<String>,
+) -> Result<(), String> {
+    match command {
+        CommandNode::Simple(simple) => {
+            let mut stack = stack.clone();
+            match expand_alias(ctx, simple, &mut stack) {
+                AliasExpansionResult::NoExpansion => {}
+                AliasExpansionResult::Expanded(expanded) => *simple = expanded,
+                AliasExpansionResult::ComplexAlias(script) => {
+                    // The replacement runs as a group; aliases already being
+                    // expanded stay out of it
+                    let mut body = script.statements;
+                    for statement in &mut body {
+                        expand_statement_aliases(ctx, statement, &stack)?;
                     }
+                    *command = CommandNode::Compound(CompoundCommandNode::Group(GroupNode {
+                        body,
+                        redirections: vec![],
+                    }));
+                    return Ok(());
+                }
+                AliasExpansionResult::ParseError(message) => return Err(message),
+            }
+            for word in simple.name.iter_mut().chain(&mut simple.args) {
+                expand_word_aliases(ctx, word)?;
+            }
+            for assignment in &mut simple.assignments {
+                for word in assignment.value.iter_mut().chain(assignment.array.iter_mut().flatten()) {
+                    expand_word_aliases(ctx, word)?;
+                }
+            }
+            for redirection in &mut simple.redirections {
+                match &mut redirection.target {
+                    RedirectionTarget::Word(word) => expand_word_aliases(ctx, word)?,
+                    RedirectionTarget::HereDoc(heredoc) => expand_word_aliases(ctx, &mut heredoc.content)?,
                 }
             }
+            Ok(())
+        }
+        CommandNode::Compound(compound) => expand_compound_aliases(ctx, compound),
+        CommandNode::FunctionDef(function) => expand_compound_aliases(ctx, &mut function.body),
+        CommandNode::Coproc(coproc) => expand_command_aliases(ctx, &mut coproc.body, stack),
+    }
+}
 
-            AliasExpansionResult::Expanded(new_node)
+fn expand_compound_aliases(ctx: &AliasExpansionContext, compound: &mut CompoundCommandNode) -> Result<(), String> {
+    match compound {
+        CompoundCommandNode::If(node) => {
+            for clause in &mut node.clauses {
+                expand_body_aliases(ctx, &mut clause.condition)?;
+                expand_body_aliases(ctx, &mut clause.body)?;
+            }
+            node.else_body.iter_mut().try_for_each(|body| expand_body_aliases(ctx, body))
+        }
+        CompoundCommandNode::For(node) => {
+            node.words.iter_mut().flatten().try_for_each(|word| expand_word_aliases(ctx, word))?;
+            expand_body_aliases(ctx, &mut node.body)
+        }
+        CompoundCommandNode::Select(node) => {
+            node.words.iter_mut().flatten().try_for_each(|word| expand_word_aliases(ctx, word))?;
+            expand_body_aliases(ctx, &mut node.body)
+        }
+        CompoundCommandNode::CStyleFor(node) => expand_body_aliases(ctx, &mut node.body),
+        CompoundCommandNode::While(node) => {
+            expand_body_aliases(ctx, &mut node.condition)?;
+            expand_body_aliases(ctx, &mut node.body)
         }
-        _ => {
-            // Alias expanded to a compound command
-            alias_expansion_stack.remove(cmd_name);
-            AliasExpansionResult::ComplexAlias(full_command)
+        CompoundCommandNode::Until(node) => {
+            expand_body_aliases(ctx, &mut node.condition)?;
+            expand_body_aliases(ctx, &mut node.body)
         }
+        CompoundCommandNode::Case(node) => {
+            expand_word_aliases(ctx, &mut node.word)?;
+            node.items.iter_mut().try_for_each(|item| expand_body_aliases(ctx, &mut item.body))
+        }
+        CompoundCommandNode::Subshell(node) => expand_body_aliases(ctx, &mut node.body),
+        CompoundCommandNode::Group(node) => expand_body_aliases(ctx, &mut node.body),
+        CompoundCommandNode::ArithmeticCommand(_) | CompoundCommandNode::ConditionalCommand(_) => Ok(()),
     }
 }
 
+/// Expand aliases in the command and process substitutions of a word
+fn expand_word_aliases(ctx: &AliasExpansionContext, word: &mut WordNode) -> Result<(), String> {
+    expand_parts_aliases(ctx, &mut word.parts)
+}
+
+fn expand_parts_aliases(ctx: &AliasExpansionContext, parts: &mut [WordPart]) -> Result<(), String> {
+    for part in parts {
+        match part {
+            WordPart::DoubleQuoted(quoted) => expand_parts_aliases(ctx, &mut quoted.parts)?,
+            WordPart::CommandSubstitution(CommandSubstitutionPart { body, .. })
+            | WordPart::ProcessSubstitution(ProcessSubstitutionPart { body, .. }) => {
+                expand_body_aliases(ctx, &mut body.statements)?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,7 +493,7 @@ mod tests {
         let mut env = make_env();
         set_alias(&mut env, "ll", "ls -la");
 
-        let ctx = AliasExpansionContext { env: &env };
+        let ctx = AliasExpansionContext { aliases: &env };
         assert_eq!(get_alias(&ctx, "ll"), Some("ls -la"));
         assert_eq!(get_alias(&ctx, "nonexistent"), None);
     }
@@ -340,7 +506,7 @@ mod tests {
         assert!(unset_alias(&mut env, "ll"));
         assert!(!unset_alias(&mut env, "ll")); // Already removed
 
-        let ctx = AliasExpansionContext { env: &env };
+        let ctx = AliasExpansionContext { aliases: &env };
         assert_eq!(get_alias(&ctx, "ll"), None);
     }
 
@@ -369,7 +535,7 @@ mod tests {
     #[test]
     fn test_expand_alias_no_alias() {
         let env = make_env();
-        let ctx = AliasExpansionContext { env: &env };
+        let ctx = AliasExpansionContext { aliases: &env };
 
         let node = SimpleCommandNode {
             name: Some(make_literal_word("echo")),
@@ -377,6 +543,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         };
 
         let mut stack = HashSet::new();
@@ -388,7 +555,7 @@ mod tests {
     fn test_expand_alias_simple() {
         let mut env = make_env();
         set_alias(&mut env, "ll", "ls -la");
-        let ctx = AliasExpansionContext { env: &env };
+        let ctx = AliasExpansionContext { aliases: &env };
 
         let node = SimpleCommandNode {
             name: Some(make_literal_word("ll")),
@@ -396,6 +563,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         };
 
         let mut stack = HashSet::new();
@@ -415,7 +583,7 @@ mod tests {
         let mut env = make_env();
         // Create a self-referencing alias
         set_alias(&mut env, "foo", "foo bar");
-        let ctx = AliasExpansionContext { env: &env };
+        let ctx = AliasExpansionContext { aliases: &env };
 
         let node = SimpleCommandNode {
             name: Some(make_literal_word("foo")),
@@ -423,6 +591,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         };
 
         let mut stack = HashSet::new();
@@ -431,4 +600,69 @@ mod tests {
         let result = expand_alias(&ctx, &node, &mut stack);
         assert!(matches!(result, AliasExpansionResult::NoExpansion));
     }
+
+    #[test]
+    fn test_expand_alias_trailing_space_and_args() {
+        let mut env = make_env();
+        set_alias(&mut env, "run", "command ");
+        set_alias(&mut env, "ll", "ls -la");
+        let ctx = AliasExpansionContext { aliases: &env };
+
+        let node = SimpleCommandNode {
+            name: Some(make_literal_word("run")),
+            args: vec![make_literal_word("ll"), make_literal_word("/tmp")],
+            assignments: vec![],
+            redirections: vec![],
+            line: None,
+            source_text: None,
+        };
+
+        let mut stack = HashSet::new();
+        match expand_alias(&ctx, &node, &mut stack) {
+            AliasExpansionResult::Expanded(expanded) => {
+                let words: Vec<&str> = expanded
+                    .name
+                    .iter()
+                    .chain(expanded.args.iter())
+                    .filter_map(get_literal_value)
+                    .collect();
+                assert_eq!(words, vec!["command", "ls", "-la", "/tmp"]);
+            }
+            _ => panic!("Expected Expanded result"),
+        }
+    }
+
+    #[test]
+    fn test_expand_alias_ending_in_separator() {
+        let mut env = make_env();
+        set_alias(&mut env, "e", "echo one; ");
+        let ctx = AliasExpansionContext { aliases: &env };
+
+        let node = SimpleCommandNode {
+            name: Some(make_literal_word("e")),
+            args: vec![make_literal_word("two"), make_literal_word("three")],
+            assignments: vec![],
+            redirections: vec![],
+            line: None,
+            source_text: None,
+        };
+
+        let mut stack = HashSet::new();
+        match expand_alias(&ctx, &node, &mut stack) {
+            AliasExpansionResult::ComplexAlias(script) => {
+                let commands: Vec<Vec<&str>> = script
+                    .statements
+                    .iter()
+                    .map(|stmt| match &stmt.pipelines[0].commands[0] {
+                        CommandNode::Simple(simple) => {
+                            simple.name.iter().chain(simple.args.iter()).filter_map(get_literal_value).collect()
+                        }
+                        other => panic!("Expected simple command, got {:?}", other),
+                    })
+                    .collect();
+                assert_eq!(commands, vec![vec!["echo", "one"], vec!["two", "three"]]);
+            }
+            _ => panic!("Expected ComplexAlias result"),
+        }
+    }
 }
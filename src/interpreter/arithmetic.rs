@@ -133,6 +133,10 @@ fn apply_unary_op(operand: i64, operator: &ArithUnaryOperator) -> i64 {
 /// In bash, when an array variable is used without an index in arithmetic context,
 /// it decays to the value at index 0.
 fn get_arith_variable(ctx: &InterpreterContext, name: &str) -> String {
+    // LINENO is dynamic and never stored in env
+    if name == "LINENO" {
+        return ctx.state.current_line.to_string();
+    }
     // First try to get the direct variable value
     if let Some(direct_value) = ctx.state.env.get(name) {
         return direct_value.clone();
@@ -369,6 +373,13 @@ fn resolve_arith_variable(
 
     // If value is empty, return 0
     if value.is_empty() {
+        let state = &mut *ctx.state;
+        if state.options.nounset
+            && state.unbound_variable.is_none()
+            && !crate::interpreter::expansion::variable::is_variable_set(state, name)
+        {
+            state.unbound_variable = Some(name.to_string());
+        }
         return Ok(0);
     }
 
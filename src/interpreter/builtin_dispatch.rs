@@ -3,13 +3,34 @@
 //! Handles dispatch of built-in shell commands like export, unset, cd, etc.
 //! Separated from interpreter.rs for modularity.
 
-use std::collections::HashMap;
-use crate::interpreter::types::{ExecResult, InterpreterState};
-use crate::interpreter::helpers::result::{OK, failure, test_result};
+use crate::ast::types::{FunctionDefNode, ScriptNode};
+use crate::interpreter::builtins::{
+    handle_alias, handle_break, handle_cd, handle_compgen, handle_complete, handle_compopt,
+    handle_continue, handle_declare, handle_dirs, handle_exit, handle_export, handle_getopts,
+    handle_hash, handle_help, handle_jobs, handle_kill, handle_let, handle_local, handle_mapfile,
+    handle_popd, handle_pushd, handle_read, handle_readonly, handle_return, handle_set,
+    handle_shift, handle_shopt, handle_trap, handle_unalias, handle_unset, handle_wait,
+    BuiltinResult,
+};
+use crate::interpreter::builtins::eval_cmd::{eval_parse_error, handle_eval_parse};
+use crate::interpreter::builtins::jobs_cmd::RunStatementFn;
+use crate::interpreter::builtins::source_cmd::{
+    handle_source_parse, prepare_source_state, resolve_source_paths, restore_source_state,
+    source_file_not_found, source_parse_error,
+};
+use crate::interpreter::command_resolution::ResolveCommandResult;
+use crate::interpreter::conditionals::evaluate_test_args;
+use crate::interpreter::errors::{ExecutionLimitError, InterpreterError, LimitType};
+use crate::interpreter::helpers::file_tests::InterpreterFs;
+use crate::interpreter::helpers::result::{OK, failure};
 use crate::interpreter::helpers::shell_constants::SHELL_BUILTINS;
+use crate::interpreter::helpers::variable_tests::evaluate_variable_test;
+use crate::interpreter::interpreter::FileSystem;
+use crate::interpreter::types::{ExecResult, InterpreterState};
 
 /// Type for the function that runs a command recursively
 pub type RunCommandFn<'a> = &'a dyn Fn(
+    &mut InterpreterState,
     &str,           // command_name
     &[String],      // args
     &[bool],        // quoted_args
@@ -17,24 +38,48 @@ pub type RunCommandFn<'a> = &'a dyn Fn(
     bool,           // skip_functions
     bool,           // use_default_path
     i32,            // stdin_source_fd
-) -> ExecResult;
+) -> Result<ExecResult, InterpreterError>;
+
+/// Type for the function that invokes a user-defined shell function
+pub type CallFunctionFn<'a> = &'a dyn Fn(
+    &mut InterpreterState,
+    &FunctionDefNode,
+    &[String],      // args
+    &str,           // stdin
+) -> Result<ExecResult, InterpreterError>;
 
-/// Type for the function that builds exported environment
-pub type BuildExportedEnvFn<'a> = &'a dyn Fn() -> HashMap<String, String>;
+/// Type for the function that runs parsed code in the current shell, as
+/// `eval` and `source` do
+pub type RunScriptFn<'a> = &'a dyn Fn(
+    &mut InterpreterState,
+    &ScriptNode,
+    &str,           // stdin
+) -> Result<ExecResult, InterpreterError>;
 
-/// Type for the function that executes user scripts
-pub type ExecuteUserScriptFn<'a> = &'a dyn Fn(&str, &[String], Option<&str>) -> ExecResult;
+/// Type for the function that finds a command the way running it would
+pub type ResolveCommandFn<'a> = &'a dyn Fn(
+    &InterpreterState,
+    &str,           // command_name
+    bool,           // use_default_path
+) -> ResolveCommandResult;
 
 /// Dispatch context containing dependencies needed for builtin dispatch
 pub struct BuiltinDispatchContext<'a> {
     pub state: &'a mut InterpreterState,
+    pub fs: &'a dyn FileSystem,
+    /// How deeply `source` and `eval` may nest
+    pub max_nesting_depth: u32,
     pub run_command: RunCommandFn<'a>,
-    pub build_exported_env: BuildExportedEnvFn<'a>,
-    pub execute_user_script: ExecuteUserScriptFn<'a>,
+    pub call_function: CallFunctionFn<'a>,
+    pub run_script: RunScriptFn<'a>,
+    /// Runs a statement that has already been read, such as a job's command
+    pub execute_statement: RunStatementFn<'a>,
+    pub resolve_command: ResolveCommandFn<'a>,
 }
 
 /// Dispatch a command to the appropriate builtin handler or external command.
 /// Returns None if the command should be handled by external command resolution.
+#[allow(clippy::too_many_arguments)]
 pub fn dispatch_builtin(
     dispatch_ctx: &mut BuiltinDispatchContext,
     command_name: &str,
@@ -43,102 +88,112 @@ pub fn dispatch_builtin(
     stdin: &str,
     skip_functions: bool,
     _use_default_path: bool,
-    _stdin_source_fd: i32,
-) -> Option<ExecResult> {
+    stdin_source_fd: i32,
+) -> Option<Result<ExecResult, InterpreterError>> {
+    let state = &mut *dispatch_ctx.state;
+
     // Built-in commands (special builtins that cannot be overridden by functions)
     match command_name {
         "export" => {
-            return Some(handle_export_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_export(state, args))));
         }
         "exit" => {
-            return Some(handle_exit_stub(dispatch_ctx.state, args));
+            return Some(handle_exit(state, args).map(|never| match never {}));
         }
         "set" => {
-            return Some(handle_set_stub(dispatch_ctx.state, args));
+            return Some(handle_set(state, args).map(from_builtin));
         }
         "break" => {
-            return Some(handle_break_stub(dispatch_ctx.state, args));
+            return Some(handle_break(state, args).map(from_builtin));
         }
         "continue" => {
-            return Some(handle_continue_stub(dispatch_ctx.state, args));
+            return Some(handle_continue(state, args).map(from_builtin));
         }
         "return" => {
-            return Some(handle_return_stub(dispatch_ctx.state, args));
+            return Some(handle_return(state, args).map(from_builtin));
         }
         "shift" => {
-            return Some(handle_shift_stub(dispatch_ctx.state, args));
+            return Some(handle_shift(state, args).map(from_builtin));
         }
         "shopt" => {
-            return Some(handle_shopt_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_shopt(state, args))));
         }
         "help" => {
-            return Some(handle_help_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_help(args))));
         }
         "unset" => {
-            return Some(handle_unset_stub(dispatch_ctx.state, args));
+            return Some(Ok(handle_unset(state, args)));
         }
         "local" => {
-            return Some(handle_local_stub(dispatch_ctx.state, args));
+            return Some(Ok(handle_local(state, args)));
         }
         "getopts" => {
-            return Some(handle_getopts_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_getopts(state, args))));
         }
         "compgen" => {
-            return Some(handle_compgen_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_tuple(handle_compgen(state, args))));
         }
         "complete" => {
-            return Some(handle_complete_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_complete(state, args))));
         }
         "compopt" => {
-            return Some(handle_compopt_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_compopt(state, args))));
         }
         "pushd" => {
-            return Some(handle_pushd_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_tuple(handle_pushd(state, args))));
         }
         "popd" => {
-            return Some(handle_popd_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_tuple(handle_popd(state, args))));
         }
         "dirs" => {
-            return Some(handle_dirs_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_tuple(handle_dirs(state, args))));
         }
         "source" | "." => {
-            return Some(handle_source_stub(dispatch_ctx.state, args));
+            return Some(handle_source(dispatch_ctx, args, stdin));
         }
         "read" => {
-            return Some(handle_read_stub(dispatch_ctx.state, args, stdin));
+            return Some(Ok(from_builtin(handle_read(state, args, stdin, stdin_source_fd))));
         }
         "mapfile" | "readarray" => {
-            return Some(handle_mapfile_stub(dispatch_ctx.state, args, stdin));
+            return Some(Ok(from_builtin(handle_mapfile(state, args, stdin))));
         }
         "declare" | "typeset" => {
-            return Some(handle_declare_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_declare(state, args))));
         }
         "readonly" => {
-            return Some(handle_readonly_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_builtin(handle_readonly(state, args))));
+        }
+        "trap" => {
+            return Some(Ok(from_builtin(handle_trap(state, args))));
+        }
+        "alias" => {
+            return Some(Ok(from_builtin(handle_alias(state, args))));
+        }
+        "unalias" => {
+            return Some(Ok(from_builtin(handle_unalias(state, args))));
         }
         _ => {}
     }
 
     // In POSIX mode, eval is a special builtin that cannot be overridden by functions
-    if command_name == "eval" && dispatch_ctx.state.options.posix {
-        return Some(handle_eval_stub(dispatch_ctx.state, args, stdin));
+    if command_name == "eval" && state.options.posix {
+        return Some(handle_eval(dispatch_ctx, args, stdin));
     }
 
     // User-defined functions override most builtins (except special ones above)
     if !skip_functions {
-        if dispatch_ctx.state.functions.contains_key(command_name) {
-            // Would call function here
-            return Some(OK);
+        if let Some(func) = state.functions.get(command_name).cloned() {
+            return Some((dispatch_ctx.call_function)(state, &func, args, stdin));
         }
     }
 
     // Simple builtins (can be overridden by functions)
     match command_name {
         ":" | "true" => {
-            return Some(OK);
+            return Some(Ok(OK));
         }
         "false" => {
-            return Some(test_result(false));
+            return Some(Ok(ExecResult::new(String::new(), String::new(), 1)));
         }
         "command" => {
             return Some(handle_command_builtin(dispatch_ctx, args, stdin));
@@ -148,39 +203,54 @@ pub fn dispatch_builtin(
         }
         "exec" => {
             if args.is_empty() {
-                return Some(OK);
+                return Some(Ok(OK));
             }
             let cmd = &args[0];
             let rest: Vec<String> = args[1..].to_vec();
-            return Some((dispatch_ctx.run_command)(cmd, &rest, &[], stdin, false, false, -1));
+            return Some((dispatch_ctx.run_command)(
+                dispatch_ctx.state, cmd, &rest, &[], stdin, false, false, -1,
+            ));
         }
         "wait" => {
-            return Some(OK);
+            return Some(Ok(from_builtin(handle_wait(state, args, &mut *dispatch_ctx.execute_statement))));
+        }
+        "jobs" => {
+            return Some(Ok(from_builtin(handle_jobs(state, args))));
+        }
+        "kill" => {
+            return Some(Ok(from_builtin(handle_kill(state, args))));
         }
         "eval" => {
-            return Some(handle_eval_stub(dispatch_ctx.state, args, stdin));
+            return Some(handle_eval(dispatch_ctx, args, stdin));
         }
         "cd" => {
-            return Some(handle_cd_stub(dispatch_ctx.state, args));
+            return Some(Ok(handle_cd(state, args, dispatch_ctx.fs)));
         }
         "let" => {
-            return Some(handle_let_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_tuple(handle_let(state, args))));
         }
         "type" => {
-            return Some(handle_type_stub(dispatch_ctx.state, args));
+            return Some(Ok(handle_type_stub(state, args, dispatch_ctx.resolve_command)));
         }
         "hash" => {
-            return Some(handle_hash_stub(dispatch_ctx.state, args));
+            return Some(Ok(from_tuple(handle_hash(state, args))));
+        }
+        "pwd" => {
+            return Some(Ok(ExecResult::new(format!("{}\n", state.cwd), String::new(), 0)));
         }
         "[" | "test" => {
             let mut test_args = args.to_vec();
             if command_name == "[" {
                 if test_args.last().map(|s| s.as_str()) != Some("]") {
-                    return Some(failure("[: missing `]'\n"));
+                    return Some(Ok(ExecResult::new(
+                        String::new(),
+                        "bash: [: missing `]'\n".to_string(),
+                        2,
+                    )));
                 }
                 test_args.pop();
             }
-            return Some(handle_test_stub(dispatch_ctx.state, &test_args));
+            return Some(Ok(handle_test(state, &test_args, dispatch_ctx.fs)));
         }
         _ => {}
     }
@@ -189,14 +259,37 @@ pub fn dispatch_builtin(
     None
 }
 
+/// Convert a builtin handler result into an ExecResult.
+fn from_builtin(result: BuiltinResult) -> ExecResult {
+    ExecResult::new(result.stdout, result.stderr, result.exit_code)
+}
+
+/// Convert a tuple-style `(stdout, stderr, exit_code)` handler result.
+fn from_tuple((stdout, stderr, exit_code): (String, String, i32)) -> ExecResult {
+    ExecResult::new(stdout, stderr, exit_code)
+}
+
+/// Evaluate test/[ arguments against the virtual filesystem.
+fn handle_test(state: &InterpreterState, args: &[String], fs: &dyn FileSystem) -> ExecResult {
+    let result = evaluate_test_args(state, args, &InterpreterFs(fs), |state, name| {
+        evaluate_variable_test(state, &state.env, name, None).0
+    });
+    let stderr = if result.stderr.is_empty() {
+        String::new()
+    } else {
+        format!("bash: {}", result.stderr)
+    };
+    ExecResult::new(String::new(), stderr, result.exit_code)
+}
+
 /// Handle the 'command' builtin
 fn handle_command_builtin(
     dispatch_ctx: &mut BuiltinDispatchContext,
     args: &[String],
     stdin: &str,
-) -> ExecResult {
+) -> Result<ExecResult, InterpreterError> {
     if args.is_empty() {
-        return OK;
+        return Ok(OK);
     }
 
     // Parse options
@@ -223,18 +316,25 @@ fn handle_command_builtin(
     }
 
     if cmd_args.is_empty() {
-        return OK;
+        return Ok(OK);
     }
 
     // Handle -v and -V: describe commands without executing
     if show_path || verbose_describe {
-        return handle_command_v_stub(dispatch_ctx.state, &cmd_args, show_path, verbose_describe);
+        return Ok(handle_command_v_stub(
+            dispatch_ctx.state,
+            &cmd_args,
+            show_path,
+            verbose_describe,
+            use_default_path,
+            dispatch_ctx.resolve_command,
+        ));
     }
 
     // Run command without checking functions
     let cmd = &cmd_args[0];
     let rest: Vec<String> = cmd_args[1..].to_vec();
-    (dispatch_ctx.run_command)(cmd, &rest, &[], stdin, true, use_default_path, -1)
+    (dispatch_ctx.run_command)(dispatch_ctx.state, cmd, &rest, &[], stdin, true, use_default_path, -1)
 }
 
 /// Handle the 'builtin' builtin
@@ -242,250 +342,134 @@ fn handle_builtin_builtin(
     dispatch_ctx: &mut BuiltinDispatchContext,
     args: &[String],
     stdin: &str,
-) -> ExecResult {
+) -> Result<ExecResult, InterpreterError> {
     if args.is_empty() {
-        return OK;
+        return Ok(OK);
     }
 
     let mut cmd_args = args.to_vec();
     if cmd_args[0] == "--" {
         cmd_args.remove(0);
         if cmd_args.is_empty() {
-            return OK;
+            return Ok(OK);
         }
     }
 
     let cmd = &cmd_args[0];
 
     if !SHELL_BUILTINS.contains(cmd.as_str()) {
-        return failure(format!("bash: builtin: {}: not a shell builtin\n", cmd));
+        return Ok(failure(format!("bash: builtin: {}: not a shell builtin\n", cmd)));
     }
 
     let rest: Vec<String> = cmd_args[1..].to_vec();
-    (dispatch_ctx.run_command)(cmd, &rest, &[], stdin, true, false, -1)
-}
-
-// ============================================================================
-// Stub functions for builtins not yet migrated
-// ============================================================================
-
-fn handle_export_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    // Simplified export implementation
-    for arg in args {
-        if let Some(eq_pos) = arg.find('=') {
-            let name = &arg[..eq_pos];
-            let value = &arg[eq_pos + 1..];
-            state.env.insert(name.to_string(), value.to_string());
-            if state.exported_vars.is_none() {
-                state.exported_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut exported) = state.exported_vars {
-                exported.insert(name.to_string());
-            }
-        } else {
-            // Just mark as exported
-            if state.exported_vars.is_none() {
-                state.exported_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut exported) = state.exported_vars {
-                exported.insert(arg.clone());
-            }
-        }
-    }
-    OK
-}
-
-fn handle_exit_stub(_state: &InterpreterState, args: &[String]) -> ExecResult {
-    let exit_code = args.first()
-        .and_then(|s| s.parse::<i32>().ok())
-        .unwrap_or(0);
-    ExecResult::new(String::new(), String::new(), exit_code)
+    (dispatch_ctx.run_command)(dispatch_ctx.state, cmd, &rest, &[], stdin, true, false, -1)
 }
 
-fn handle_set_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    for arg in args {
-        match arg.as_str() {
-            "-e" => state.options.errexit = true,
-            "+e" => state.options.errexit = false,
-            "-x" => state.options.xtrace = true,
-            "+x" => state.options.xtrace = false,
-            "-u" => state.options.nounset = true,
-            "+u" => state.options.nounset = false,
-            "-o" => {}
-            _ => {}
-        }
-    }
-    OK
-}
-
-fn handle_break_stub(state: &InterpreterState, args: &[String]) -> ExecResult {
-    let n = args.first()
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(1);
-    if state.loop_depth == 0 {
-        return failure("bash: break: only meaningful in a `for', `while', or `until' loop\n");
-    }
-    OK
-}
+/// Handle `eval`: parse the joined arguments and run them in the current
+/// shell, one command-substitution level deeper for `set -x`.
+fn handle_eval(
+    dispatch_ctx: &mut BuiltinDispatchContext,
+    args: &[String],
+    stdin: &str,
+) -> Result<ExecResult, InterpreterError> {
+    let command = match handle_eval_parse(args) {
+        Ok(Some(command)) => command,
+        Ok(None) => return Ok(OK),
+        Err(error) => return Ok(from_tuple(error)),
+    };
+    let script = match crate::parser::parse(&command.command) {
+        Ok(script) => script,
+        Err(error) => return Ok(eval_parse_error(&format!("syntax error: {}", error))),
+    };
 
-fn handle_continue_stub(state: &InterpreterState, args: &[String]) -> ExecResult {
-    let n = args.first()
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(1);
-    if state.loop_depth == 0 {
-        return failure("bash: continue: only meaningful in a `for', `while', or `until' loop\n");
+    let state = &mut *dispatch_ctx.state;
+    if state.eval_depth >= dispatch_ctx.max_nesting_depth {
+        return Err(nesting_limit_error(format!(
+            "eval: maximum eval nesting level exceeded ({})",
+            dispatch_ctx.max_nesting_depth
+        )));
     }
-    OK
+    state.indirection_level += 1;
+    state.eval_depth += 1;
+    // The string's lines are numbered from the line of the eval
+    let line_offset = std::mem::replace(&mut state.line_offset, state.current_line.saturating_sub(1));
+    let result = (dispatch_ctx.run_script)(state, &script, stdin);
+    state.line_offset = line_offset;
+    state.eval_depth -= 1;
+    state.indirection_level -= 1;
+    result
 }
 
-fn handle_return_stub(state: &InterpreterState, args: &[String]) -> ExecResult {
-    let exit_code = args.first()
-        .and_then(|s| s.parse::<i32>().ok())
-        .unwrap_or(state.last_exit_code);
-    if state.call_depth == 0 && state.source_depth == 0 {
-        return failure("bash: return: can only `return' from a function or sourced script\n");
-    }
-    ExecResult::new(String::new(), String::new(), exit_code)
+fn nesting_limit_error(message: String) -> InterpreterError {
+    InterpreterError::ExecutionLimit(ExecutionLimitError::simple(message, LimitType::Recursion))
 }
 
-fn handle_shift_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    let n = args.first()
-        .and_then(|s| s.parse::<usize>().ok())
-        .unwrap_or(1);
-
-    let argc: usize = state.env.get("#")
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(0);
-
-    if n > argc {
-        return failure("bash: shift: shift count out of range\n");
-    }
-
-    // Shift positional parameters
-    for i in 1..=(argc - n) {
-        let src_key = format!("{}", i + n);
-        let dst_key = format!("{}", i);
-        if let Some(val) = state.env.get(&src_key).cloned() {
-            state.env.insert(dst_key, val);
-        }
-    }
-
-    // Remove old parameters
-    for i in (argc - n + 1)..=argc {
-        state.env.remove(&format!("{}", i));
-    }
-
-    state.env.insert("#".to_string(), (argc - n).to_string());
-    OK
-}
+/// Handle `source`/`.`: run a file from the virtual filesystem in the
+/// current shell. A name without a slash is looked up in PATH first.
+/// `return` at the top level of the file ends it.
+fn handle_source(
+    dispatch_ctx: &mut BuiltinDispatchContext,
+    args: &[String],
+    stdin: &str,
+) -> Result<ExecResult, InterpreterError> {
+    let mut cmd = match handle_source_parse(args) {
+        Ok(cmd) => cmd,
+        Err(error) => return Ok(from_tuple(error)),
+    };
 
-fn handle_shopt_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    let mut set_mode = false;
-    let mut unset_mode = false;
-    let mut options: Vec<&str> = Vec::new();
+    let state = &mut *dispatch_ctx.state;
+    if state.source_depth >= dispatch_ctx.max_nesting_depth {
+        return Err(nesting_limit_error(format!(
+            "{}: maximum source nesting level exceeded ({})",
+            cmd.filename, dispatch_ctx.max_nesting_depth
+        )));
+    }
+    let fs = dispatch_ctx.fs;
+    let candidates = resolve_source_paths(&state.cwd, &cmd.filename, state.env.get("PATH").map(String::as_str));
+    let found = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| fs.is_file(path))
+        .find_map(|(index, path)| fs.read_file(path).ok().map(|content| (index, path, content)));
+    let Some((index, path, content)) = found else {
+        return Ok(source_file_not_found(&cmd.filename));
+    };
+    let script = match crate::parser::parse(&content) {
+        Ok(script) => script,
+        Err(error) => return Ok(source_parse_error(&cmd.filename, &format!("syntax error: {}", error))),
+    };
 
-    for arg in args {
-        match arg.as_str() {
-            "-s" => set_mode = true,
-            "-u" => unset_mode = true,
-            _ => options.push(arg),
-        }
+    // A file found through PATH is known by its full path
+    if !cmd.filename.contains('/') && index + 1 < candidates.len() {
+        cmd.filename = path.clone();
     }
-
-    for opt in options {
-        match opt {
-            "extglob" => {
-                if set_mode { state.shopt_options.extglob = true; }
-                if unset_mode { state.shopt_options.extglob = false; }
-            }
-            "nullglob" => {
-                if set_mode { state.shopt_options.nullglob = true; }
-                if unset_mode { state.shopt_options.nullglob = false; }
-            }
-            "dotglob" => {
-                if set_mode { state.shopt_options.dotglob = true; }
-                if unset_mode { state.shopt_options.dotglob = false; }
-            }
-            "globstar" => {
-                if set_mode { state.shopt_options.globstar = true; }
-                if unset_mode { state.shopt_options.globstar = false; }
-            }
-            _ => {}
-        }
+    let saved = prepare_source_state(state, &cmd);
+    let result = (dispatch_ctx.run_script)(state, &script, stdin);
+    restore_source_state(state, saved);
+    match result {
+        Err(InterpreterError::Return(e)) => Ok(ExecResult::new(e.stdout, e.stderr, e.exit_code)),
+        result => result,
     }
-    OK
 }
 
-fn handle_help_stub(_state: &InterpreterState, _args: &[String]) -> ExecResult {
-    let help_text = "GNU bash, version 5.2\n\
-        These shell commands are defined internally.\n\
-        Type `help name' to find out more about the function `name'.\n";
-    ExecResult::new(help_text.to_string(), String::new(), 0)
-}
-
-fn handle_test_stub(_state: &InterpreterState, args: &[String]) -> ExecResult {
-    // Simplified test implementation
-    if args.is_empty() {
-        return test_result(false);
-    }
-
-    if args.len() == 1 {
-        // [ string ] - true if string is non-empty
-        return test_result(!args[0].is_empty());
-    }
-
-    if args.len() == 2 {
-        match args[0].as_str() {
-            "-n" => return test_result(!args[1].is_empty()),
-            "-z" => return test_result(args[1].is_empty()),
-            "!" => return test_result(args[1].is_empty()),
-            _ => {}
-        }
-    }
-
-    if args.len() == 3 {
-        let left = &args[0];
-        let op = &args[1];
-        let right = &args[2];
+// ============================================================================
+// Stub functions for builtins not yet migrated
+// ============================================================================
 
-        match op.as_str() {
-            "=" | "==" => return test_result(left == right),
-            "!=" => return test_result(left != right),
-            "-eq" => {
-                let l: i64 = left.parse().unwrap_or(0);
-                let r: i64 = right.parse().unwrap_or(0);
-                return test_result(l == r);
-            }
-            "-ne" => {
-                let l: i64 = left.parse().unwrap_or(0);
-                let r: i64 = right.parse().unwrap_or(0);
-                return test_result(l != r);
-            }
-            "-lt" => {
-                let l: i64 = left.parse().unwrap_or(0);
-                let r: i64 = right.parse().unwrap_or(0);
-                return test_result(l < r);
-            }
-            "-le" => {
-                let l: i64 = left.parse().unwrap_or(0);
-                let r: i64 = right.parse().unwrap_or(0);
-                return test_result(l <= r);
-            }
-            "-gt" => {
-                let l: i64 = left.parse().unwrap_or(0);
-                let r: i64 = right.parse().unwrap_or(0);
-                return test_result(l > r);
-            }
-            "-ge" => {
-                let l: i64 = left.parse().unwrap_or(0);
-                let r: i64 = right.parse().unwrap_or(0);
-                return test_result(l >= r);
-            }
-            _ => {}
+/// Where running `name` would find it, as `type` and `command -v` show it:
+/// a path as given, anything else as found in PATH.
+fn command_location(
+    state: &InterpreterState,
+    name: &str,
+    use_default_path: bool,
+    resolve_command: ResolveCommandFn,
+) -> Option<String> {
+    match resolve_command(state, name, use_default_path) {
+        ResolveCommandResult::Command { path } | ResolveCommandResult::Script { path } => {
+            Some(if name.contains('/') { name.to_string() } else { path })
         }
+        _ => None,
     }
-
-    test_result(false)
 }
 
 fn handle_command_v_stub(
@@ -493,6 +477,8 @@ fn handle_command_v_stub(
     names: &[String],
     show_path: bool,
     verbose_describe: bool,
+    use_default_path: bool,
+    resolve_command: ResolveCommandFn,
 ) -> ExecResult {
     let mut stdout = String::new();
     let mut exit_code = 0;
@@ -510,6 +496,12 @@ fn handle_command_v_stub(
             } else {
                 stdout.push_str(&format!("{}\n", name));
             }
+        } else if let Some(location) = command_location(state, name, use_default_path, resolve_command) {
+            if verbose_describe {
+                stdout.push_str(&format!("{} is {}\n", name, location));
+            } else {
+                stdout.push_str(&format!("{}\n", location));
+            }
         } else {
             exit_code = 1;
         }
@@ -518,430 +510,7 @@ fn handle_command_v_stub(
     ExecResult::new(stdout, String::new(), exit_code)
 }
 
-fn handle_unset_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    let mut unset_functions = false;
-    let mut unset_variables = true;
-    let mut names: Vec<&str> = Vec::new();
-
-    for arg in args {
-        match arg.as_str() {
-            "-f" => {
-                unset_functions = true;
-                unset_variables = false;
-            }
-            "-v" => {
-                unset_variables = true;
-                unset_functions = false;
-            }
-            "-n" => {
-                // unset nameref attribute - stub: treat as -v
-            }
-            _ if arg.starts_with('-') => {}
-            _ => names.push(arg),
-        }
-    }
-
-    for name in names {
-        if unset_functions {
-            state.functions.remove(name);
-        }
-        if unset_variables {
-            state.env.remove(name);
-            if let Some(ref mut exported) = state.exported_vars {
-                exported.remove(name);
-            }
-            if let Some(ref mut readonly) = state.readonly_vars {
-                if readonly.contains(name) {
-                    return failure(format!("bash: unset: {}: cannot unset: readonly variable\n", name));
-                }
-            }
-        }
-    }
-    OK
-}
-
-fn handle_local_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    if state.call_depth == 0 {
-        return failure("bash: local: can only be used in a function\n");
-    }
-
-    for arg in args {
-        if arg.starts_with('-') {
-            continue;
-        }
-        if let Some(eq_pos) = arg.find('=') {
-            let name = &arg[..eq_pos];
-            let value = &arg[eq_pos + 1..];
-            state.env.insert(name.to_string(), value.to_string());
-        } else {
-            // Declare as local without value
-            if !state.env.contains_key(arg) {
-                state.env.insert(arg.clone(), String::new());
-            }
-        }
-    }
-    OK
-}
-
-fn handle_cd_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    let target = if args.is_empty() {
-        state.env.get("HOME").cloned().unwrap_or_else(|| "/".to_string())
-    } else if args[0] == "-" {
-        let prev = state.previous_dir.clone();
-        if prev.is_empty() {
-            return failure("bash: cd: OLDPWD not set\n");
-        }
-        prev
-    } else {
-        args[0].clone()
-    };
-
-    state.previous_dir = state.cwd.clone();
-    state.cwd = target;
-    state.env.insert("PWD".to_string(), state.cwd.clone());
-    state.env.insert("OLDPWD".to_string(), state.previous_dir.clone());
-    OK
-}
-
-fn handle_eval_stub(_state: &mut InterpreterState, _args: &[String], _stdin: &str) -> ExecResult {
-    // Stub: eval requires re-parsing and executing concatenated args
-    // Full implementation would call the parser and interpreter recursively
-    OK
-}
-
-fn handle_getopts_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    if args.len() < 2 {
-        return failure("bash: getopts: usage: getopts optstring name [arg ...]\n");
-    }
-
-    let _optstring = &args[0];
-    let varname = &args[1];
-
-    // Stub: set variable to '?' to indicate end of options
-    state.env.insert(varname.clone(), "?".to_string());
-    test_result(false)
-}
-
-fn handle_compgen_stub(_state: &mut InterpreterState, _args: &[String]) -> ExecResult {
-    // Stub: compgen generates possible completions
-    OK
-}
-
-fn handle_complete_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    use crate::interpreter::types::CompletionSpec;
-    
-    let mut wordlist: Option<String> = None;
-    let mut function: Option<String> = None;
-    let mut command_names: Vec<String> = Vec::new();
-    let mut iter = args.iter().peekable();
-
-    while let Some(arg) = iter.next() {
-        match arg.as_str() {
-            "-W" => {
-                if let Some(val) = iter.next() {
-                    wordlist = Some(val.clone());
-                }
-            }
-            "-F" => {
-                if let Some(val) = iter.next() {
-                    function = Some(val.clone());
-                }
-            }
-            _ if arg.starts_with('-') => {
-                // Skip other options
-            }
-            _ => command_names.push(arg.clone()),
-        }
-    }
-
-    if state.completion_specs.is_none() {
-        state.completion_specs = Some(std::collections::HashMap::new());
-    }
-
-    for cmd in command_names {
-        let spec = CompletionSpec {
-            wordlist: wordlist.clone(),
-            function: function.clone(),
-            ..Default::default()
-        };
-        if let Some(ref mut specs) = state.completion_specs {
-            specs.insert(cmd, spec);
-        }
-    }
-    OK
-}
-
-fn handle_compopt_stub(_state: &mut InterpreterState, _args: &[String]) -> ExecResult {
-    // Stub: compopt modifies completion options
-    OK
-}
-
-fn handle_pushd_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    if state.directory_stack.is_none() {
-        state.directory_stack = Some(Vec::new());
-    }
-
-    let dir = if args.is_empty() {
-        // Swap top two directories
-        if let Some(ref mut stack) = state.directory_stack {
-            if stack.is_empty() {
-                return failure("bash: pushd: no other directory\n");
-            }
-            let top = stack.pop().unwrap();
-            let current = state.cwd.clone();
-            stack.push(current);
-            top
-        } else {
-            return failure("bash: pushd: no other directory\n");
-        }
-    } else {
-        args[0].clone()
-    };
-
-    if let Some(ref mut stack) = state.directory_stack {
-        stack.push(state.cwd.clone());
-    }
-    state.previous_dir = state.cwd.clone();
-    state.cwd = dir;
-    state.env.insert("PWD".to_string(), state.cwd.clone());
-    OK
-}
-
-fn handle_popd_stub(state: &mut InterpreterState, _args: &[String]) -> ExecResult {
-    if state.directory_stack.is_none() {
-        return failure("bash: popd: directory stack empty\n");
-    }
-
-    if let Some(ref mut stack) = state.directory_stack {
-        if stack.is_empty() {
-            return failure("bash: popd: directory stack empty\n");
-        }
-        let dir = stack.pop().unwrap();
-        state.previous_dir = state.cwd.clone();
-        state.cwd = dir;
-        state.env.insert("PWD".to_string(), state.cwd.clone());
-    }
-    OK
-}
-
-fn handle_dirs_stub(state: &InterpreterState, _args: &[String]) -> ExecResult {
-    let mut stdout = state.cwd.clone();
-    if let Some(ref stack) = state.directory_stack {
-        for dir in stack.iter().rev() {
-            stdout.push(' ');
-            stdout.push_str(dir);
-        }
-    }
-    stdout.push('\n');
-    ExecResult::new(stdout, String::new(), 0)
-}
-
-fn handle_source_stub(_state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    if args.is_empty() {
-        return failure("bash: source: filename argument required\n");
-    }
-    // Stub: full implementation would read and execute the file
-    OK
-}
-
-fn handle_read_stub(state: &mut InterpreterState, args: &[String], stdin: &str) -> ExecResult {
-    let mut varnames: Vec<&str> = Vec::new();
-    let mut prompt: Option<&str> = None;
-    let mut iter = args.iter().peekable();
-
-    while let Some(arg) = iter.next() {
-        match arg.as_str() {
-            "-p" => {
-                if let Some(p) = iter.next() {
-                    prompt = Some(p.as_str());
-                }
-            }
-            "-r" | "-s" | "-n" | "-t" | "-d" | "-a" | "-u" => {
-                // Skip option and its argument if present
-                if matches!(arg.as_str(), "-n" | "-t" | "-d" | "-a" | "-u") {
-                    iter.next();
-                }
-            }
-            _ if arg.starts_with('-') => {}
-            _ => varnames.push(arg),
-        }
-    }
-
-    if varnames.is_empty() {
-        varnames.push("REPLY");
-    }
-
-    let _ = prompt; // Would print prompt in interactive mode
-
-    let line = stdin.lines().next().unwrap_or("");
-    let parts: Vec<&str> = line.split_whitespace().collect();
-
-    for (i, varname) in varnames.iter().enumerate() {
-        if i < varnames.len() - 1 {
-            let value = parts.get(i).unwrap_or(&"");
-            state.env.insert(varname.to_string(), value.to_string());
-        } else {
-            // Last variable gets the rest of the line
-            let rest: Vec<&str> = parts.iter().skip(i).copied().collect();
-            state.env.insert(varname.to_string(), rest.join(" "));
-        }
-    }
-
-    if stdin.is_empty() {
-        test_result(false)
-    } else {
-        OK
-    }
-}
-
-fn handle_mapfile_stub(state: &mut InterpreterState, args: &[String], stdin: &str) -> ExecResult {
-    let mut array_name = "MAPFILE".to_string();
-    let mut iter = args.iter().peekable();
-
-    while let Some(arg) = iter.next() {
-        match arg.as_str() {
-            "-t" | "-s" | "-n" | "-O" | "-d" | "-c" | "-C" | "-u" => {
-                // Skip options that take arguments
-                if matches!(arg.as_str(), "-s" | "-n" | "-O" | "-d" | "-c" | "-C" | "-u") {
-                    iter.next();
-                }
-            }
-            _ if arg.starts_with('-') => {}
-            _ => array_name = arg.clone(),
-        }
-    }
-
-    // Store lines as indexed array elements
-    for (i, line) in stdin.lines().enumerate() {
-        let key = format!("{}[{}]", array_name, i);
-        state.env.insert(key, line.to_string());
-    }
-
-    OK
-}
-
-fn handle_declare_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    let mut is_integer = false;
-    let mut is_readonly = false;
-    let mut is_export = false;
-    let mut is_lowercase = false;
-    let mut is_uppercase = false;
-    let mut names_and_values: Vec<&str> = Vec::new();
-
-    for arg in args {
-        if arg.starts_with('-') {
-            for ch in arg[1..].chars() {
-                match ch {
-                    'i' => is_integer = true,
-                    'r' => is_readonly = true,
-                    'x' => is_export = true,
-                    'l' => is_lowercase = true,
-                    'u' => is_uppercase = true,
-                    'a' | 'A' | 'n' | 'g' | 'f' | 'p' => {}
-                    _ => {}
-                }
-            }
-        } else if arg.starts_with('+') {
-            // Remove attributes - stub
-        } else {
-            names_and_values.push(arg);
-        }
-    }
-
-    for item in names_and_values {
-        let (name, value) = if let Some(eq_pos) = item.find('=') {
-            (&item[..eq_pos], Some(&item[eq_pos + 1..]))
-        } else {
-            (item, None)
-        };
-
-        if let Some(val) = value {
-            let mut final_val = val.to_string();
-            if is_lowercase {
-                final_val = final_val.to_lowercase();
-            }
-            if is_uppercase {
-                final_val = final_val.to_uppercase();
-            }
-            state.env.insert(name.to_string(), final_val);
-        }
-
-        if is_integer {
-            if state.integer_vars.is_none() {
-                state.integer_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut set) = state.integer_vars {
-                set.insert(name.to_string());
-            }
-        }
-
-        if is_readonly {
-            if state.readonly_vars.is_none() {
-                state.readonly_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut set) = state.readonly_vars {
-                set.insert(name.to_string());
-            }
-        }
-
-        if is_export {
-            if state.exported_vars.is_none() {
-                state.exported_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut set) = state.exported_vars {
-                set.insert(name.to_string());
-            }
-        }
-
-        if is_lowercase {
-            if state.lowercase_vars.is_none() {
-                state.lowercase_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut set) = state.lowercase_vars {
-                set.insert(name.to_string());
-            }
-        }
-
-        if is_uppercase {
-            if state.uppercase_vars.is_none() {
-                state.uppercase_vars = Some(std::collections::HashSet::new());
-            }
-            if let Some(ref mut set) = state.uppercase_vars {
-                set.insert(name.to_string());
-            }
-        }
-    }
-
-    OK
-}
-
-fn handle_readonly_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    for arg in args {
-        if arg.starts_with('-') {
-            continue;
-        }
-
-        let (name, value) = if let Some(eq_pos) = arg.find('=') {
-            (&arg[..eq_pos], Some(&arg[eq_pos + 1..]))
-        } else {
-            (arg.as_str(), None)
-        };
-
-        if let Some(val) = value {
-            state.env.insert(name.to_string(), val.to_string());
-        }
-
-        if state.readonly_vars.is_none() {
-            state.readonly_vars = Some(std::collections::HashSet::new());
-        }
-        if let Some(ref mut set) = state.readonly_vars {
-            set.insert(name.to_string());
-        }
-    }
-    OK
-}
-
-fn handle_type_stub(state: &InterpreterState, args: &[String]) -> ExecResult {
+fn handle_type_stub(state: &InterpreterState, args: &[String], resolve_command: ResolveCommandFn) -> ExecResult {
     let mut stdout = String::new();
     let mut exit_code = 0;
 
@@ -954,13 +523,10 @@ fn handle_type_stub(state: &InterpreterState, args: &[String]) -> ExecResult {
             stdout.push_str(&format!("{} is a shell builtin\n", name));
         } else if state.functions.contains_key(name) {
             stdout.push_str(&format!("{} is a function\n", name));
-        } else if let Some(ref aliases) = state.aliases {
-            if let Some(alias_val) = aliases.get(name) {
-                stdout.push_str(&format!("{} is aliased to `{}'\n", name, alias_val));
-            } else {
-                stdout.push_str(&format!("bash: type: {}: not found\n", name));
-                exit_code = 1;
-            }
+        } else if let Some(alias_val) = state.aliases.as_ref().and_then(|aliases| aliases.get(name)) {
+            stdout.push_str(&format!("{} is aliased to `{}'\n", name, alias_val));
+        } else if let Some(location) = command_location(state, name, false, resolve_command) {
+            stdout.push_str(&format!("{} is {}\n", name, location));
         } else {
             stdout.push_str(&format!("bash: type: {}: not found\n", name));
             exit_code = 1;
@@ -970,92 +536,133 @@ fn handle_type_stub(state: &InterpreterState, args: &[String]) -> ExecResult {
     ExecResult::new(stdout, String::new(), exit_code)
 }
 
-fn handle_hash_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    if args.is_empty() {
-        // List hash table
-        let mut stdout = String::new();
-        if let Some(ref table) = state.hash_table {
-            for (name, path) in table {
-                stdout.push_str(&format!("{}\t{}\n", name, path));
-            }
-        }
-        return ExecResult::new(stdout, String::new(), 0);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{FileSystem as _, InMemoryFs};
+    use crate::interpreter::sync_fs_adapter::SyncFsAdapter;
+    use std::sync::Arc;
+
+    fn dispatch(
+        state: &mut InterpreterState,
+        fs: &dyn FileSystem,
+        name: &str,
+        args: &[&str],
+    ) -> Option<Result<ExecResult, InterpreterError>> {
+        let run_command: RunCommandFn = &|_, name, _, _, _, _, _, _| {
+            Ok(ExecResult::new(format!("ran {}\n", name), String::new(), 0))
+        };
+        let call_function: CallFunctionFn = &|_, func, args, _| {
+            Ok(ExecResult::new(format!("called {} {}\n", func.name, args.join(" ")), String::new(), 0))
+        };
+        let run_script: RunScriptFn = &|_, script, _| {
+            Ok(ExecResult::new(format!("ran {} statements\n", script.statements.len()), String::new(), 0))
+        };
+        let execute_statement: RunStatementFn = &mut |_, _| Ok(ExecResult::new(String::new(), String::new(), 0));
+        let resolve_command: ResolveCommandFn = &|_, _, _| ResolveCommandResult::NotFound { path: None };
+        let mut ctx = BuiltinDispatchContext {
+            state,
+            fs,
+            max_nesting_depth: 1000,
+            run_command,
+            call_function,
+            run_script,
+            execute_statement,
+            resolve_command,
+        };
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        dispatch_builtin(&mut ctx, name, &args, &[], "", false, false, -1)
     }
 
-    for arg in args {
-        match arg.as_str() {
-            "-r" => {
-                // Clear hash table
-                state.hash_table = None;
-            }
-            "-d" => {
-                // Next arg is name to delete - simplified
-            }
-            "-t" | "-l" | "-p" => {
-                // Other options - stub
-            }
-            _ if arg.starts_with('-') => {}
-            _ => {
-                // Would look up command in PATH and cache
-            }
-        }
+    fn make_fs() -> SyncFsAdapter {
+        SyncFsAdapter::new(Arc::new(InMemoryFs::new()), tokio::runtime::Handle::current())
     }
-    OK
-}
 
-fn handle_let_stub(state: &mut InterpreterState, args: &[String]) -> ExecResult {
-    if args.is_empty() {
-        return test_result(false);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_export() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        let result = dispatch(&mut state, &fs, "export", &["FOO=bar"]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(state.env.get("FOO"), Some(&"bar".to_string()));
     }
 
-    let mut last_result: i64 = 0;
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_test_string() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        let result = dispatch(&mut state, &fs, "test", &["hello"]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 0);
 
-    for expr in args {
-        // Simple arithmetic evaluation - handles basic cases
-        if let Some(eq_pos) = expr.find('=') {
-            let name = &expr[..eq_pos];
-            let value_str = &expr[eq_pos + 1..];
-            if let Ok(val) = value_str.parse::<i64>() {
-                state.env.insert(name.to_string(), val.to_string());
-                last_result = val;
-            }
-        } else if let Ok(val) = expr.parse::<i64>() {
-            last_result = val;
-        }
+        let result = dispatch(&mut state, &fs, "test", &[""]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 1);
     }
 
-    test_result(last_result != 0)
-}
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_test_comparison() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        let result = dispatch(&mut state, &fs, "[", &["a", "=", "a", "]"]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 0);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let result = dispatch(&mut state, &fs, "[", &["a", "!=", "b", "]"]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 0);
+
+        let result = dispatch(&mut state, &fs, "[", &["a", "=", "a"]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 2);
+    }
 
-    #[test]
-    fn test_handle_export_stub() {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_test_file_uses_vfs() {
+        let inner = Arc::new(InMemoryFs::new());
+        inner.write_file("/data.txt", b"x").await.unwrap();
+        let fs = SyncFsAdapter::new(inner, tokio::runtime::Handle::current());
         let mut state = InterpreterState::default();
-        let result = handle_export_stub(&mut state, &["FOO=bar".to_string()]);
+        state.cwd = "/".to_string();
+
+        let result = dispatch(&mut state, &fs, "test", &["-f", "data.txt"]).unwrap().unwrap();
         assert_eq!(result.exit_code, 0);
-        assert_eq!(state.env.get("FOO"), Some(&"bar".to_string()));
+        let result = dispatch(&mut state, &fs, "test", &["-d", "data.txt"]).unwrap().unwrap();
+        assert_eq!(result.exit_code, 1);
     }
 
-    #[test]
-    fn test_handle_test_stub_string() {
-        let state = InterpreterState::default();
-        let result = handle_test_stub(&state, &["hello".to_string()]);
-        assert_eq!(result.exit_code, 0);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_exit_is_control_flow() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        match dispatch(&mut state, &fs, "exit", &["3"]).unwrap() {
+            Err(InterpreterError::Exit(e)) => assert_eq!(e.exit_code, 3),
+            other => panic!("expected exit error, got {:?}", other),
+        }
+    }
 
-        let result = handle_test_stub(&state, &["".to_string()]);
-        assert_eq!(result.exit_code, 1);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_command_skips_to_run_command() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        let result = dispatch(&mut state, &fs, "command", &["ls"]).unwrap().unwrap();
+        assert_eq!(result.stdout, "ran ls\n");
     }
 
-    #[test]
-    fn test_handle_test_stub_comparison() {
-        let state = InterpreterState::default();
-        let result = handle_test_stub(&state, &["a".to_string(), "=".to_string(), "a".to_string()]);
-        assert_eq!(result.exit_code, 0);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_unknown_falls_through() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        assert!(dispatch(&mut state, &fs, "ls", &[]).is_none());
+    }
 
-        let result = handle_test_stub(&state, &["a".to_string(), "!=".to_string(), "b".to_string()]);
-        assert_eq!(result.exit_code, 0);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dispatch_function_overrides_simple_builtin() {
+        let fs = make_fs();
+        let mut state = InterpreterState::default();
+        let ast = crate::parser::parse("true() { :; }").unwrap();
+        if let crate::ast::types::CommandNode::FunctionDef(func) = &ast.statements[0].pipelines[0].commands[0] {
+            state.functions.insert(func.name.clone(), func.clone());
+        }
+
+        let result = dispatch(&mut state, &fs, "true", &["a"]).unwrap().unwrap();
+        assert_eq!(result.stdout, "called true a\n");
+        let result = dispatch(&mut state, &fs, "command", &["true"]).unwrap().unwrap();
+        assert_eq!(result.stdout, "ran true\n");
     }
 }
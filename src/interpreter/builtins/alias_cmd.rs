@@ -0,0 +1,152 @@
+//! alias, unalias - Define and remove aliases
+//!
+//! alias [-p] [name[=value] ...]
+//! unalias [-a] name [name ...]
+//!
+//! Options:
+//!   (no args)  Display the aliases in a form that can be reused as input
+//!   -p         Same, before defining or showing the given names
+//!   -a         (unalias) Remove all aliases
+//!
+//! The builtins only maintain the definitions; they are expanded at command
+//! position as each line is read, while `shopt -s expand_aliases` is in effect.
+
+use std::collections::HashMap;
+
+use crate::interpreter::alias_expansion::{get_all_aliases, is_valid_alias_name, set_alias, unset_alias};
+use crate::interpreter::builtins::break_cmd::BuiltinResult;
+use crate::interpreter::types::InterpreterState;
+
+const ALIAS_USAGE: &str = "alias: usage: alias [-p] [name[=value] ... ]\n";
+const UNALIAS_USAGE: &str = "unalias: usage: unalias [-a] name [name ...]\n";
+
+fn format_alias(name: &str, value: &str) -> String {
+    format!("alias {}='{}'\n", name, value.replace('\'', "'\\''"))
+}
+
+/// Handle the `alias` builtin.
+pub fn handle_alias(state: &mut InterpreterState, args: &[String]) -> BuiltinResult {
+    let mut print = false;
+    let mut rest = args;
+    while let Some(first) = rest.first() {
+        match first.as_str() {
+            "-p" => print = true,
+            "--" => {
+                rest = &rest[1..];
+                break;
+            }
+            opt if opt.starts_with('-') && opt.len() > 1 => {
+                return BuiltinResult::failure(&format!("bash: alias: {}: invalid option\n{}", opt, ALIAS_USAGE), 2);
+            }
+            _ => break,
+        }
+        rest = &rest[1..];
+    }
+
+    let aliases = state.aliases.get_or_insert_with(HashMap::new);
+    let mut result = BuiltinResult::ok();
+    if print || rest.is_empty() {
+        for (name, value) in get_all_aliases(aliases) {
+            result.stdout.push_str(&format_alias(&name, &value));
+        }
+    }
+
+    for arg in rest {
+        match arg.split_once('=') {
+            Some((name, value)) if is_valid_alias_name(name) => set_alias(aliases, name, value),
+            Some((name, _)) => {
+                result.stderr.push_str(&format!("bash: alias: `{}': invalid alias name\n", name));
+                result.exit_code = 1;
+            }
+            None => match aliases.get(arg.as_str()) {
+                Some(value) => result.stdout.push_str(&format_alias(arg, value)),
+                None => {
+                    result.stderr.push_str(&format!("bash: alias: {}: not found\n", arg));
+                    result.exit_code = 1;
+                }
+            },
+        }
+    }
+    result
+}
+
+/// Handle the `unalias` builtin.
+pub fn handle_unalias(state: &mut InterpreterState, args: &[String]) -> BuiltinResult {
+    let mut remove_all = false;
+    let mut rest = args;
+    while let Some(first) = rest.first() {
+        match first.as_str() {
+            "-a" => remove_all = true,
+            "--" => {
+                rest = &rest[1..];
+                break;
+            }
+            opt if opt.starts_with('-') && opt.len() > 1 => {
+                return BuiltinResult::failure(&format!("bash: unalias: {}: invalid option\n{}", opt, UNALIAS_USAGE), 2);
+            }
+            _ => break,
+        }
+        rest = &rest[1..];
+    }
+
+    let aliases = state.aliases.get_or_insert_with(HashMap::new);
+    if remove_all {
+        aliases.clear();
+        return BuiltinResult::ok();
+    }
+    if rest.is_empty() {
+        return BuiltinResult::failure(UNALIAS_USAGE, 2);
+    }
+
+    let mut result = BuiltinResult::ok();
+    for name in rest {
+        if !unset_alias(aliases, name) {
+            result.stderr.push_str(&format!("bash: unalias: {}: not found\n", name));
+            result.exit_code = 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_alias_define_and_print() {
+        let mut state = InterpreterState::default();
+        let result = handle_alias(&mut state, &args(&["ll=ls -l", "q=it's"]));
+        assert_eq!(result.exit_code, 0);
+
+        let result = handle_alias(&mut state, &[]);
+        assert_eq!(result.stdout, "alias ll='ls -l'\nalias q='it'\\''s'\n");
+
+        let result = handle_alias(&mut state, &args(&["ll", "nope"]));
+        assert_eq!(result.stdout, "alias ll='ls -l'\n");
+        assert_eq!(result.stderr, "bash: alias: nope: not found\n");
+        assert_eq!(result.exit_code, 1);
+
+        let result = handle_alias(&mut state, &args(&["a/b=x"]));
+        assert_eq!(result.stderr, "bash: alias: `a/b': invalid alias name\n");
+        assert_eq!(result.exit_code, 1);
+    }
+
+    #[test]
+    fn test_unalias() {
+        let mut state = InterpreterState::default();
+        handle_alias(&mut state, &args(&["a=1", "b=2", "c=3"]));
+
+        let result = handle_unalias(&mut state, &args(&["a", "x"]));
+        assert_eq!(result.stderr, "bash: unalias: x: not found\n");
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(handle_alias(&mut state, &[]).stdout, "alias b='2'\nalias c='3'\n");
+
+        assert_eq!(handle_unalias(&mut state, &[]).exit_code, 2);
+        assert_eq!(handle_unalias(&mut state, &args(&["-a"])).exit_code, 0);
+        assert_eq!(handle_alias(&mut state, &[]).stdout, "");
+    }
+}
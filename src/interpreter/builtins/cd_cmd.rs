@@ -8,13 +8,17 @@
 //! - cd -P - use physical path (resolve symlinks)
 //! - CDPATH support for relative paths
 
+use crate::interpreter::interpreter::FileSystem;
 use crate::interpreter::types::{ExecResult, InterpreterState};
 use crate::interpreter::helpers::result::{result, failure};
 
-/// Handle the cd builtin command
+/// Handle the cd builtin command.
+///
+/// Directory checks go through the virtual filesystem, never the host.
 pub fn handle_cd(
     state: &mut InterpreterState,
     args: &[String],
+    fs: &dyn FileSystem,
 ) -> ExecResult {
     let mut target: String;
     let mut print_path = false;
@@ -71,9 +75,7 @@ pub fn handle_cd(
                 } else {
                     format!("{}/{}/{}", state.cwd, dir, target)
                 };
-                // In a real implementation, we would check if the directory exists
-                // For now, we just use the first CDPATH entry
-                if std::path::Path::new(&candidate).is_dir() {
+                if fs.is_dir(&normalize_path(&candidate)) {
                     target = candidate;
                     print_path = true;
                     break;
@@ -92,20 +94,17 @@ pub fn handle_cd(
     let new_dir = normalize_path(&path_to_check);
 
     // Check if the directory exists
-    let path = std::path::Path::new(&new_dir);
-    if !path.exists() {
+    if !fs.exists(&new_dir) {
         return failure(&format!("bash: cd: {}: No such file or directory\n", target));
     }
-    if !path.is_dir() {
+    if !fs.is_dir(&new_dir) {
         return failure(&format!("bash: cd: {}: Not a directory\n", target));
     }
 
     // If -P is specified, resolve symlinks to get the physical path
     let final_dir = if physical {
-        match std::fs::canonicalize(&new_dir) {
-            Ok(canonical) => canonical.to_string_lossy().to_string(),
-            Err(_) => new_dir.clone(), // If canonicalize fails, use the logical path
-        }
+        // If the path can't be resolved, fall back to the logical path
+        fs.realpath(&new_dir).unwrap_or_else(|_| new_dir.clone())
     } else {
         new_dir.clone()
     };
@@ -147,6 +146,9 @@ fn normalize_path(path: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fs::{FileSystem as _, InMemoryFs, MkdirOptions};
+    use crate::interpreter::sync_fs_adapter::SyncFsAdapter;
+    use std::sync::Arc;
 
     #[test]
     fn test_normalize_path() {
@@ -159,55 +161,80 @@ mod tests {
         assert_eq!(normalize_path("/foo//bar"), "/foo/bar");
     }
 
-    #[test]
-    fn test_handle_cd_to_tmp() {
+    async fn make_fs(dirs: &[&str]) -> SyncFsAdapter {
+        let fs = Arc::new(InMemoryFs::new());
+        for dir in dirs {
+            fs.mkdir(dir, &MkdirOptions { recursive: true }).await.unwrap();
+        }
+        SyncFsAdapter::new(fs, tokio::runtime::Handle::current())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handle_cd_to_tmp() {
+        let fs = make_fs(&["/tmp"]).await;
         let mut state = InterpreterState::default();
         state.cwd = "/".to_string();
         state.env.insert("HOME".to_string(), "/tmp".to_string());
 
         // cd to /tmp
-        let result = handle_cd(&mut state, &["tmp".to_string()]);
+        let result = handle_cd(&mut state, &["tmp".to_string()], &fs);
         assert_eq!(result.exit_code, 0);
         assert_eq!(state.cwd, "/tmp");
         assert_eq!(state.env.get("PWD"), Some(&"/tmp".to_string()));
     }
 
-    #[test]
-    fn test_handle_cd_home() {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handle_cd_home() {
+        let fs = make_fs(&["/tmp", "/var"]).await;
         let mut state = InterpreterState::default();
         state.cwd = "/var".to_string();
         state.env.insert("HOME".to_string(), "/tmp".to_string());
 
         // cd with no args goes to HOME
-        let result = handle_cd(&mut state, &[]);
+        let result = handle_cd(&mut state, &[], &fs);
         assert_eq!(result.exit_code, 0);
         assert_eq!(state.cwd, "/tmp");
     }
 
-    #[test]
-    fn test_handle_cd_previous() {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handle_cd_previous() {
+        let fs = make_fs(&["/tmp", "/var"]).await;
         let mut state = InterpreterState::default();
         state.cwd = "/tmp".to_string();
         state.previous_dir = "/var".to_string();
 
         // cd - goes to previous directory
-        let result = handle_cd(&mut state, &["-".to_string()]);
+        let result = handle_cd(&mut state, &["-".to_string()], &fs);
         assert_eq!(result.exit_code, 0);
         assert_eq!(state.cwd, "/var");
         // Should print the new directory
         assert!(result.stdout.contains("/var"));
     }
 
-    #[test]
-    fn test_handle_cd_physical_option() {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handle_cd_physical_option() {
+        let fs = Arc::new(InMemoryFs::new());
+        fs.mkdir("/private/tmp", &MkdirOptions { recursive: true }).await.unwrap();
+        fs.symlink("/private/tmp", "/tmp").await.unwrap();
+        let fs = SyncFsAdapter::new(fs, tokio::runtime::Handle::current());
         let mut state = InterpreterState::default();
         state.cwd = "/".to_string();
 
-        // cd -P to /tmp (should resolve symlinks)
-        let result = handle_cd(&mut state, &["-P".to_string(), "tmp".to_string()]);
+        // cd -P to /tmp resolves the symlink
+        let result = handle_cd(&mut state, &["-P".to_string(), "tmp".to_string()], &fs);
         assert_eq!(result.exit_code, 0);
-        // The path should be resolved (on macOS /tmp is a symlink to /private/tmp)
-        // We just check that it succeeded and the path is set
-        assert!(!state.cwd.is_empty());
+        assert_eq!(state.cwd, "/private/tmp");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handle_cd_missing_directory() {
+        let fs = make_fs(&["/tmp"]).await;
+        let mut state = InterpreterState::default();
+        state.cwd = "/".to_string();
+
+        let result = handle_cd(&mut state, &["nope".to_string()], &fs);
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.stderr, "bash: cd: nope: No such file or directory\n");
+        assert_eq!(state.cwd, "/");
     }
 }
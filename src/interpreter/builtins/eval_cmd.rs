@@ -100,7 +100,7 @@ pub fn restore_eval_stdin(state: &mut InterpreterState, saved_group_stdin: Optio
 
 /// Create an ExecResult from a parse error message.
 pub fn eval_parse_error(message: &str) -> ExecResult {
-    ExecResult::failure(format!("bash: eval: {}\n", message))
+    ExecResult::new(String::new(), format!("bash: eval: {}\n", message), 2)
 }
 
 // ============================================================================
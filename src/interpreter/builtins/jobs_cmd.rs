@@ -0,0 +1,569 @@
+//! wait, jobs, kill - Job control over the virtual job table
+//!
+//! wait [-n] [-p var] [id ...]
+//! jobs [-lp] [jobspec ...]
+//! kill [-s sigspec | -n signum | -sigspec] id ... | kill -l
+//!
+//! An id is a virtual PID (`$!`) or a jobspec: `%N`, `%%`, `%+` or `%-`.
+//! Background jobs don't run until the shell waits for them or reaches a
+//! point where it would notice their effects (see `Job`); `wait` runs them,
+//! releases their held output and reports their exit status, and `kill`
+//! cancels a job that hasn't run, or discards the output of one that has not
+//! been waited for.
+
+use std::collections::HashMap;
+
+use crate::ast::types::StatementNode;
+use crate::interpreter::builtins::break_cmd::BuiltinResult;
+use crate::interpreter::errors::InterpreterError;
+use crate::interpreter::helpers::signals::{format_signal_list, parse_signal, signal_name, SIGKILL, SIGTERM};
+use crate::interpreter::subshell_group::execute_subshell;
+use crate::interpreter::types::{ExecResult, InterpreterState, Job, JobStatus, PendingJob};
+
+/// Runs a statement of a job in the job's shell state.
+pub type RunStatementFn<'a> =
+    &'a mut dyn FnMut(&mut InterpreterState, &StatementNode) -> Result<ExecResult, InterpreterError>;
+
+const KILL_USAGE: &str =
+    "kill: usage: kill [-s sigspec | -n signum | -sigspec] pid | jobspec ... or kill -l [sigspec]\n";
+
+/// Run the jobs that haven't run yet, then remove every job from the table
+/// and return their combined output.
+pub fn drain_jobs(state: &mut InterpreterState, execute_statement: RunStatementFn) -> (String, String) {
+    run_pending_jobs(state, execute_statement);
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    for job in std::mem::take(&mut state.jobs) {
+        stdout.push_str(&job.stdout);
+        stderr.push_str(&job.stderr);
+    }
+    (stdout, stderr)
+}
+
+/// Add a background job to the table and make it `$!`.
+pub fn register_job<'a>(
+    state: &'a mut InterpreterState,
+    pid: u32,
+    command: &str,
+    status: JobStatus,
+    stdout: String,
+    stderr: String,
+) -> &'a mut Job {
+    let id = state.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+    state.jobs.push(Job {
+        id,
+        pid,
+        command: command.trim_end().trim_end_matches('&').trim_end().to_string(),
+        status,
+        stdout,
+        stderr,
+        traps: HashMap::new(),
+        pending: None,
+    });
+    state.last_background_pid = pid;
+    state.jobs.last_mut().expect("job was just added")
+}
+
+/// Run the jobs that haven't run yet, in the order they were started. Their
+/// output stays in the table until the shell collects it.
+pub fn run_pending_jobs(state: &mut InterpreterState, execute_statement: RunStatementFn) {
+    for index in 0..state.jobs.len() {
+        run_pending_job(state, index, execute_statement);
+    }
+}
+
+/// Add a job that runs `statement` once the shell waits for it, with the
+/// next virtual PID as its PID.
+pub fn defer_job(state: &mut InterpreterState, command: &str, statement: StatementNode) {
+    // The job's subshell takes the PID when it runs, and starts with an empty
+    // job table, so the other jobs (and the shells they hold) aren't copied
+    let jobs = std::mem::take(&mut state.jobs);
+    let shell = state.clone();
+    state.jobs = jobs;
+    let pid = state.next_virtual_pid;
+    state.next_virtual_pid += 1;
+    // Signals ignored at launch stay ignored in the job
+    let ignored = state.traps.iter().filter(|(_, handler)| handler.is_empty());
+    let traps = ignored.map(|(name, handler)| (name.clone(), handler.clone())).collect();
+    let job = register_job(state, pid, command, JobStatus::Running, String::new(), String::new());
+    job.traps = traps;
+    job.pending = Some(Box::new(PendingJob { statement, shell }));
+}
+
+/// Run a job that hasn't run yet to completion, in a subshell of the shell
+/// it was started from.
+fn run_pending_job(state: &mut InterpreterState, index: usize, execute_statement: RunStatementFn) {
+    let Some(pending) = state.jobs[index].pending.take() else {
+        return;
+    };
+    let PendingJob { statement, mut shell } = *pending;
+    // The job's work counts against the same limits as the shell's
+    shell.command_count = state.command_count;
+
+    // Keep the handlers the job installed, so `kill` can still run them
+    let mut traps = HashMap::new();
+    let result = execute_subshell(&mut shell, std::slice::from_ref(&statement), None, |shell, stmt| {
+        let result = execute_statement(shell, stmt);
+        traps = shell.traps.clone();
+        result
+    });
+    state.command_count = shell.command_count;
+    state.next_virtual_pid = state.next_virtual_pid.max(shell.next_virtual_pid);
+
+    let job = &mut state.jobs[index];
+    let result = match result {
+        Ok(result) => result,
+        // A safety limit stops the job, and the shell at its next command
+        Err(InterpreterError::ExecutionLimit(e)) => {
+            job.status = JobStatus::Killed(SIGKILL);
+            job.stdout = e.stdout;
+            job.stderr = e.stderr;
+            return;
+        }
+        Err(InterpreterError::Nounset(e)) => ExecResult::new(e.stdout, e.stderr, 1),
+        Err(e) => ExecResult::failure(format!("bash: {}\n", e)),
+    };
+    job.status = JobStatus::Done(result.exit_code);
+    job.stdout = result.stdout;
+    job.stderr = result.stderr;
+    job.traps = traps;
+}
+
+/// Why a job id did not resolve.
+enum LookupError {
+    /// A PID that is not one of our jobs
+    NoProcess(String),
+    /// A jobspec that names no job
+    NoJob(String),
+    /// Neither a PID nor a jobspec
+    Invalid(String),
+}
+
+/// Resolve a PID or jobspec to an index into the job table.
+fn find_job(state: &InterpreterState, spec: &str) -> Result<usize, LookupError> {
+    if let Some(job_spec) = spec.strip_prefix('%') {
+        let index = match job_spec {
+            "" | "%" | "+" => state.jobs.len().checked_sub(1),
+            "-" => state.jobs.len().checked_sub(2),
+            _ => match job_spec.parse::<usize>() {
+                Ok(id) => state.jobs.iter().position(|job| job.id == id),
+                // %string: the job whose command starts with string
+                Err(_) => state.jobs.iter().position(|job| job.command.starts_with(job_spec)),
+            },
+        };
+        return index.ok_or_else(|| LookupError::NoJob(spec.to_string()));
+    }
+    match spec.parse::<u32>() {
+        Ok(pid) => state
+            .jobs
+            .iter()
+            .position(|job| job.pid == pid)
+            .ok_or_else(|| LookupError::NoProcess(spec.to_string())),
+        Err(_) => Err(LookupError::Invalid(spec.to_string())),
+    }
+}
+
+/// Handle the `wait` builtin. `execute_statement` runs the jobs waited for
+/// that haven't run yet.
+pub fn handle_wait(state: &mut InterpreterState, args: &[String], execute_statement: RunStatementFn) -> BuiltinResult {
+    let mut wait_any = false;
+    let mut pid_var: Option<String> = None;
+    let mut ids: Vec<&String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        match arg.as_str() {
+            "--" => {
+                ids.extend(&args[i + 1..]);
+                break;
+            }
+            "-n" => wait_any = true,
+            "-f" => {}
+            "-p" => {
+                i += 1;
+                match args.get(i) {
+                    Some(name) => pid_var = Some(name.clone()),
+                    None => return BuiltinResult::failure("bash: wait: -p: option requires an argument\n", 2),
+                }
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return BuiltinResult::failure(
+                    &format!("bash: wait: {}: invalid option\nwait: usage: wait [-fn] [-p var] [id ...]\n", arg),
+                    2,
+                );
+            }
+            _ => ids.push(arg),
+        }
+        i += 1;
+    }
+
+    let mut result = BuiltinResult::ok();
+
+    if wait_any {
+        // The first job in the table (or among the given ids) finishes first
+        let index = if ids.is_empty() {
+            (!state.jobs.is_empty()).then_some(0)
+        } else {
+            ids.iter().filter_map(|id| find_job(state, id).ok()).min()
+        };
+        match index {
+            Some(index) => {
+                run_pending_job(state, index, execute_statement);
+                let job = state.jobs.remove(index);
+                if let Some(name) = pid_var {
+                    state.env.insert(name, job.pid.to_string());
+                }
+                result.stdout = job.stdout;
+                result.stderr = job.stderr;
+                result.exit_code = job.status.exit_code();
+            }
+            None => result.exit_code = 127,
+        }
+        return result;
+    }
+
+    if ids.is_empty() {
+        let (stdout, stderr) = drain_jobs(state, execute_statement);
+        result.stdout = stdout;
+        result.stderr = stderr;
+        return result;
+    }
+
+    for id in ids {
+        match find_job(state, id) {
+            Ok(index) => {
+                run_pending_job(state, index, execute_statement);
+                let job = state.jobs.remove(index);
+                if let Some(ref name) = pid_var {
+                    state.env.insert(name.clone(), job.pid.to_string());
+                }
+                result.stdout.push_str(&job.stdout);
+                result.stderr.push_str(&job.stderr);
+                result.exit_code = job.status.exit_code();
+            }
+            Err(LookupError::NoProcess(pid)) => {
+                result.stderr.push_str(&format!("bash: wait: pid {} is not a child of this shell\n", pid));
+                result.exit_code = 127;
+            }
+            Err(LookupError::NoJob(spec)) => {
+                result.stderr.push_str(&format!("bash: wait: {}: no such job\n", spec));
+                result.exit_code = 127;
+            }
+            Err(LookupError::Invalid(spec)) => {
+                result.stderr.push_str(&format!("bash: wait: `{}': not a pid or valid job spec\n", spec));
+                result.exit_code = 2;
+            }
+        }
+    }
+    result
+}
+
+/// Handle the `jobs` builtin.
+pub fn handle_jobs(state: &mut InterpreterState, args: &[String]) -> BuiltinResult {
+    let mut long = false;
+    let mut pids_only = false;
+    let mut running_only = false;
+    let mut specs: Vec<&String> = Vec::new();
+
+    for arg in args {
+        if arg.starts_with('-') && arg.len() > 1 && specs.is_empty() {
+            for ch in arg[1..].chars() {
+                match ch {
+                    'l' => long = true,
+                    'p' => pids_only = true,
+                    'r' => running_only = true,
+                    'n' | 's' => {}
+                    _ => {
+                        return BuiltinResult::failure(
+                            &format!("bash: jobs: -{}: invalid option\njobs: usage: jobs [-lnprs] [jobspec ...]\n", ch),
+                            2,
+                        );
+                    }
+                }
+            }
+        } else {
+            specs.push(arg);
+        }
+    }
+
+    let mut result = BuiltinResult::ok();
+    let indices: Vec<usize> = if specs.is_empty() {
+        (0..state.jobs.len()).collect()
+    } else {
+        let mut indices = Vec::new();
+        for spec in specs {
+            match find_job(state, spec) {
+                Ok(index) => indices.push(index),
+                Err(_) => {
+                    result.stderr.push_str(&format!("bash: jobs: {}: no such job\n", spec));
+                    result.exit_code = 1;
+                }
+            }
+        }
+        indices
+    };
+
+    let count = state.jobs.len();
+    for index in indices {
+        let job = &state.jobs[index];
+        if running_only && job.status != JobStatus::Running {
+            continue;
+        }
+        if pids_only {
+            result.stdout.push_str(&format!("{}\n", job.pid));
+            continue;
+        }
+        let marker = if index + 1 == count {
+            '+'
+        } else if index + 2 == count {
+            '-'
+        } else {
+            ' '
+        };
+        let status = describe_status(job.status);
+        let background = if job.status == JobStatus::Running { " &" } else { "" };
+        if long {
+            result.stdout.push_str(&format!(
+                "[{}]{} {} {:<24}{}{}\n",
+                job.id, marker, job.pid, status, job.command, background
+            ));
+        } else {
+            result.stdout.push_str(&format!("[{}]{}  {:<24}{}{}\n", job.id, marker, status, job.command, background));
+        }
+    }
+    result
+}
+
+/// Status column of `jobs`.
+fn describe_status(status: JobStatus) -> String {
+    match status {
+        JobStatus::Running => "Running".to_string(),
+        JobStatus::Done(0) => "Done".to_string(),
+        JobStatus::Done(code) => format!("Exit {}", code),
+        JobStatus::Killed(signal) => match signal {
+            1 => "Hangup".to_string(),
+            2 => "Interrupt".to_string(),
+            9 => "Killed".to_string(),
+            15 => "Terminated".to_string(),
+            _ => signal_name(signal).map_or_else(|| format!("Signal {}", signal), |name| format!("SIG{}", name)),
+        },
+    }
+}
+
+/// Handle the `kill` builtin.
+pub fn handle_kill(state: &mut InterpreterState, args: &[String]) -> BuiltinResult {
+    if args.is_empty() {
+        return BuiltinResult::failure(KILL_USAGE, 2);
+    }
+
+    let mut signal = SIGTERM;
+    let mut rest = args;
+    match args[0].as_str() {
+        "-l" | "-L" => return list_signals(&args[1..]),
+        "-s" | "-n" => {
+            let Some(spec) = args.get(1) else {
+                return BuiltinResult::failure(
+                    &format!("bash: kill: {}: option requires an argument\n{}", args[0], KILL_USAGE),
+                    2,
+                );
+            };
+            match parse_signal(spec) {
+                Some(number) => signal = number,
+                None => {
+                    return BuiltinResult::failure(&format!("bash: kill: {}: invalid signal specification\n", spec), 1)
+                }
+            }
+            rest = &args[2..];
+        }
+        "--" => rest = &args[1..],
+        first if first.starts_with('-') && first.len() > 1 => {
+            match parse_signal(&first[1..]) {
+                Some(number) => signal = number,
+                None => {
+                    return BuiltinResult::failure(
+                        &format!("bash: kill: {}: invalid signal specification\n", &first[1..]),
+                        1,
+                    )
+                }
+            }
+            rest = &args[1..];
+        }
+        _ => {}
+    }
+
+    if rest.is_empty() {
+        return BuiltinResult::failure(KILL_USAGE, 2);
+    }
+
+    let mut result = BuiltinResult::ok();
+    for id in rest {
+        // The shell itself handles the signal after the current command
+        if id.parse::<u32>().is_ok_and(|pid| pid == state.bash_pid || pid == state.identity.pid) {
+            if signal != 0 {
+                state.pending_signals.push((state.bash_pid, signal));
+            }
+            continue;
+        }
+        match find_job(state, id) {
+            Ok(index) => {
+                // Signal 0 only checks that the job exists; a job that was
+                // already killed keeps its first signal
+                let job = &mut state.jobs[index];
+                if signal == 0 || matches!(job.status, JobStatus::Killed(_)) {
+                    continue;
+                }
+                let handler = job.traps.get(signal_name(signal).unwrap_or_default());
+                if job.status == JobStatus::Running {
+                    // Cancelled before it has done anything
+                    if !handler.is_some_and(|handler| handler.is_empty()) {
+                        job.status = JobStatus::Killed(signal);
+                        job.pending = None;
+                    }
+                    continue;
+                }
+                match handler {
+                    // Ignored by the job
+                    Some(handler) if handler.is_empty() => {}
+                    // Caught: the job's handler runs once the command is done
+                    Some(_) => state.pending_signals.push((job.pid, signal)),
+                    None => {
+                        job.status = JobStatus::Killed(signal);
+                        job.stdout.clear();
+                        job.stderr.clear();
+                    }
+                }
+            }
+            Err(LookupError::NoProcess(pid)) => {
+                result.stderr.push_str(&format!("bash: kill: ({}) - No such process\n", pid));
+                result.exit_code = 1;
+            }
+            Err(LookupError::NoJob(spec)) => {
+                result.stderr.push_str(&format!("bash: kill: {}: no such job\n", spec));
+                result.exit_code = 1;
+            }
+            Err(LookupError::Invalid(spec)) => {
+                result.stderr.push_str(&format!("bash: kill: {}: arguments must be process or job IDs\n", spec));
+                result.exit_code = 1;
+            }
+        }
+    }
+    result
+}
+
+/// `kill -l [sigspec ...]`: list signals, or translate between names and numbers.
+fn list_signals(specs: &[String]) -> BuiltinResult {
+    let mut result = BuiltinResult::ok();
+    if specs.is_empty() {
+        result.stdout = format_signal_list();
+        return result;
+    }
+    for spec in specs {
+        // A number (or exit status above 128) prints the name, a name prints the number
+        let translated = match spec.parse::<i32>() {
+            Ok(number) => signal_name(if number > 128 { number - 128 } else { number }).map(str::to_string),
+            Err(_) => parse_signal(spec).map(|number| number.to_string()),
+        };
+        match translated {
+            Some(text) => result.stdout.push_str(&format!("{}\n", text)),
+            None => {
+                result.stderr.push_str(&format!("bash: kill: {}: invalid signal specification\n", spec));
+                result.exit_code = 1;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with_jobs() -> InterpreterState {
+        let mut state = InterpreterState::default();
+        register_job(&mut state, 1001, "echo a &", JobStatus::Done(0), "a\n".to_string(), String::new());
+        register_job(&mut state, 1002, "false &", JobStatus::Done(1), String::new(), String::new());
+        state
+    }
+
+    fn no_jobs(_: &mut InterpreterState, _: &StatementNode) -> Result<ExecResult, InterpreterError> {
+        panic!("no job should run")
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_register_job_sets_last_background_pid() {
+        let state = state_with_jobs();
+        assert_eq!(state.last_background_pid, 1002);
+        assert_eq!(state.jobs[0].id, 1);
+        assert_eq!(state.jobs[1].command, "false");
+    }
+
+    #[test]
+    fn test_wait_for_pid_and_jobspec() {
+        let mut state = state_with_jobs();
+        let result = handle_wait(&mut state, &args(&["1002"]), &mut no_jobs);
+        assert_eq!(result.exit_code, 1);
+
+        let result = handle_wait(&mut state, &args(&["%1"]), &mut no_jobs);
+        assert_eq!(result.stdout, "a\n");
+        assert_eq!(result.exit_code, 0);
+        assert!(state.jobs.is_empty());
+
+        let result = handle_wait(&mut state, &args(&["1001"]), &mut no_jobs);
+        assert_eq!(result.exit_code, 127);
+        assert_eq!(result.stderr, "bash: wait: pid 1001 is not a child of this shell\n");
+    }
+
+    #[test]
+    fn test_wait_n_takes_first_job() {
+        let mut state = state_with_jobs();
+        let result = handle_wait(&mut state, &args(&["-n", "-p", "pid"]), &mut no_jobs);
+        assert_eq!(result.stdout, "a\n");
+        assert_eq!(state.env.get("pid"), Some(&"1001".to_string()));
+        assert_eq!(handle_wait(&mut state, &args(&["-n"]), &mut no_jobs).exit_code, 1);
+        assert_eq!(handle_wait(&mut state, &args(&["-n"]), &mut no_jobs).exit_code, 127);
+    }
+
+    #[test]
+    fn test_jobs_listing() {
+        let mut state = state_with_jobs();
+        let result = handle_jobs(&mut state, &[]);
+        assert_eq!(
+            result.stdout,
+            "[1]-  Done                    echo a\n[2]+  Exit 1                  false\n"
+        );
+        let result = handle_jobs(&mut state, &args(&["-l", "%2"]));
+        assert_eq!(result.stdout, "[2]+ 1002 Exit 1                  false\n");
+        let result = handle_jobs(&mut state, &args(&["-p"]));
+        assert_eq!(result.stdout, "1001\n1002\n");
+    }
+
+    #[test]
+    fn test_kill_cancels_job() {
+        let mut state = state_with_jobs();
+        let result = handle_kill(&mut state, &args(&["%1"]));
+        assert_eq!(result.exit_code, 0);
+        let result = handle_kill(&mut state, &args(&["-9", "1002"]));
+        assert_eq!(result.exit_code, 0);
+
+        let result = handle_wait(&mut state, &args(&["1001"]), &mut no_jobs);
+        assert_eq!(result.stdout, "");
+        assert_eq!(result.exit_code, 143);
+        assert_eq!(handle_wait(&mut state, &args(&["1002"]), &mut no_jobs).exit_code, 137);
+
+        let result = handle_kill(&mut state, &args(&["4242"]));
+        assert_eq!(result.stderr, "bash: kill: (4242) - No such process\n");
+        assert_eq!(result.exit_code, 1);
+    }
+
+    #[test]
+    fn test_kill_list_signals() {
+        assert_eq!(list_signals(&args(&["15"])).stdout, "TERM\n");
+        assert_eq!(list_signals(&args(&["SIGKILL"])).stdout, "9\n");
+        assert_eq!(list_signals(&args(&["143"])).stdout, "TERM\n");
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! This module contains implementations of shell builtin commands.
 
+pub mod alias_cmd;
 pub mod break_cmd;
 pub mod cd_cmd;
 pub mod compgen_cmd;
@@ -18,6 +19,7 @@ pub mod export_cmd;
 pub mod getopts_cmd;
 pub mod hash_cmd;
 pub mod help_cmd;
+pub mod jobs_cmd;
 pub mod let_cmd;
 pub mod local_cmd;
 pub mod mapfile_cmd;
@@ -27,9 +29,11 @@ pub mod set_cmd;
 pub mod shift_cmd;
 pub mod shopt_cmd;
 pub mod source_cmd;
+pub mod trap_cmd;
 pub mod unset_cmd;
 pub mod variable_assignment;
 
+pub use alias_cmd::{handle_alias, handle_unalias};
 pub use break_cmd::{handle_break, BuiltinResult};
 pub use cd_cmd::handle_cd;
 pub use compgen_cmd::{handle_compgen, SHELL_BUILTINS, SHELL_KEYWORDS, SHOPT_OPTIONS};
@@ -55,6 +59,7 @@ pub use export_cmd::handle_export;
 pub use getopts_cmd::handle_getopts;
 pub use hash_cmd::{handle_hash, hash_add, hash_lookup};
 pub use help_cmd::handle_help;
+pub use jobs_cmd::{defer_job, drain_jobs, handle_jobs, handle_kill, handle_wait, register_job, run_pending_jobs};
 pub use let_cmd::handle_let;
 pub use local_cmd::handle_local;
 pub use mapfile_cmd::handle_mapfile;
@@ -68,6 +73,7 @@ pub use source_cmd::{
     resolve_source_paths, source_file_not_found, source_parse_error,
     SourceCommand, SourceSavedState,
 };
+pub use trap_cmd::handle_trap;
 pub use unset_cmd::handle_unset;
 pub use variable_assignment::{
     parse_assignment, set_variable, get_local_var_depth, clear_local_var_depth,
@@ -12,37 +12,7 @@
 
 use crate::interpreter::builtins::BuiltinResult;
 use crate::interpreter::helpers::{clear_array, get_ifs, split_by_ifs_for_read, strip_trailing_ifs_whitespace};
-use crate::interpreter::types::InterpreterState;
-
-/// Parse the content of a read-write file descriptor.
-/// Format: __rw__:pathLength:path:position:content
-fn parse_rw_fd_content(fd_content: &str) -> Option<(String, usize, String)> {
-    if !fd_content.starts_with("__rw__:") {
-        return None;
-    }
-    let after_prefix = &fd_content[7..];
-    let first_colon_idx = after_prefix.find(':')?;
-    let path_length: usize = after_prefix[..first_colon_idx].parse().ok()?;
-    let path_start = first_colon_idx + 1;
-    if path_start + path_length > after_prefix.len() {
-        return None;
-    }
-    let path = after_prefix[path_start..path_start + path_length].to_string();
-    let position_start = path_start + path_length + 1;
-    if position_start >= after_prefix.len() {
-        return None;
-    }
-    let remaining = &after_prefix[position_start..];
-    let pos_colon_idx = remaining.find(':')?;
-    let position: usize = remaining[..pos_colon_idx].parse().ok()?;
-    let content = remaining[pos_colon_idx + 1..].to_string();
-    Some((path, position, content))
-}
-
-/// Encode read-write file descriptor content.
-fn encode_rw_fd_content(path: &str, position: usize, content: &str) -> String {
-    format!("__rw__:{}:{}:{}:{}", path.len(), path, position, content)
-}
+use crate::interpreter::types::{FileDescriptor, InterpreterState};
 
 pub fn handle_read(
     state: &mut InterpreterState,
@@ -280,11 +250,11 @@ pub fn handle_read(
 
     if file_descriptor >= 0 {
         // Read from specified file descriptor
-        if let Some(ref fds) = state.file_descriptors {
-            effective_stdin = fds.get(&file_descriptor).cloned().unwrap_or_default();
-        } else {
-            effective_stdin = String::new();
-        }
+        effective_stdin = match state.file_descriptors.as_ref().and_then(|fds| fds.get(&file_descriptor)) {
+            Some(FileDescriptor::Content(content)) => content.clone(),
+            Some(FileDescriptor::PipeRead(pipe)) => pipe.contents(),
+            _ => String::new(),
+        };
     } else if effective_stdin.is_empty() {
         if let Some(ref group_stdin) = state.group_stdin {
             effective_stdin = group_stdin.clone();
@@ -313,18 +283,17 @@ pub fn handle_read(
             .unwrap_or(effective_stdin.len());
         if file_descriptor >= 0 {
             if let Some(ref mut fds) = state.file_descriptors {
-                fds.insert(file_descriptor, effective_stdin[bytes_consumed..].to_string());
+                match fds.get_mut(&file_descriptor) {
+                    Some(FileDescriptor::PipeRead(pipe)) => pipe.consume(bytes_consumed),
+                    Some(entry) => *entry = FileDescriptor::Content(effective_stdin[bytes_consumed..].to_string()),
+                    None => {}
+                }
             }
         } else if stdin_source_fd >= 0 {
-            if let Some(ref mut fds) = state.file_descriptors {
-                if let Some(fd_content) = fds.get(&stdin_source_fd).cloned() {
-                    if fd_content.starts_with("__rw__:") {
-                        if let Some((path, position, content)) = parse_rw_fd_content(&fd_content) {
-                            let new_position = position + bytes_consumed;
-                            fds.insert(stdin_source_fd, encode_rw_fd_content(&path, new_position, &content));
-                        }
-                    }
-                }
+            if let Some(FileDescriptor::ReadWrite { position, .. }) =
+                state.file_descriptors.as_mut().and_then(|fds| fds.get_mut(&stdin_source_fd))
+            {
+                *position += bytes_consumed;
             }
         } else if state.group_stdin.is_some() && stdin.is_empty() {
             state.group_stdin = Some(effective_stdin[bytes_consumed..].to_string());
@@ -39,8 +39,10 @@ fn get_short_option(flag: char) -> Option<&'static str> {
         'C' => Some("noclobber"),
         'a' => Some("allexport"),
         'n' => Some("noexec"),
+        'E' => Some("errtrace"),
+        'T' => Some("functrace"),
         // No-ops (accepted for compatibility)
-        'h' | 'b' | 'm' | 'B' | 'H' | 'P' | 'T' | 'E' | 'p' => Some(""),
+        'h' | 'b' | 'm' | 'B' | 'H' | 'P' | 'p' => Some(""),
         _ => None,
     }
 }
@@ -60,9 +62,11 @@ fn get_long_option(name: &str) -> Option<&'static str> {
         "posix" => Some("posix"),
         "vi" => Some("vi"),
         "emacs" => Some("emacs"),
+        "errtrace" => Some("errtrace"),
+        "functrace" => Some("functrace"),
         // No-ops (accepted for compatibility)
         "notify" | "monitor" | "braceexpand" | "histexpand" | "physical" |
-        "functrace" | "errtrace" | "privileged" | "hashall" | "ignoreeof" |
+        "privileged" | "hashall" | "ignoreeof" |
         "interactive-comments" | "keyword" | "onecmd" => Some(""),
         _ => None,
     }
@@ -70,13 +74,13 @@ fn get_long_option(name: &str) -> Option<&'static str> {
 
 /// List of implemented options to display
 const DISPLAY_OPTIONS: &[&str] = &[
-    "allexport", "emacs", "errexit", "noclobber", "noexec", "noglob",
-    "nounset", "pipefail", "posix", "verbose", "vi", "xtrace",
+    "allexport", "emacs", "errexit", "errtrace", "functrace", "noclobber", "noexec",
+    "noglob", "nounset", "pipefail", "posix", "verbose", "vi", "xtrace",
 ];
 
 /// List of no-op options to display (always off)
 const NOOP_DISPLAY_OPTIONS: &[&str] = &[
-    "braceexpand", "errtrace", "functrace", "hashall", "histexpand",
+    "braceexpand", "hashall", "histexpand",
     "history", "ignoreeof", "interactive-comments", "keyword", "monitor",
     "nolog", "notify", "onecmd", "physical", "privileged",
 ];
@@ -109,6 +113,8 @@ fn set_shell_option(state: &mut InterpreterState, option: &str, value: bool) {
         "posix" => state.options.posix = value,
         "vi" => state.options.vi = value,
         "emacs" => state.options.emacs = value,
+        "errtrace" => state.options.errtrace = value,
+        "functrace" => state.options.functrace = value,
         _ => {}
     }
 
@@ -130,6 +136,8 @@ fn get_shell_option(state: &InterpreterState, option: &str) -> bool {
         "posix" => state.options.posix,
         "vi" => state.options.vi,
         "emacs" => state.options.emacs,
+        "errtrace" => state.options.errtrace,
+        "functrace" => state.options.functrace,
         _ => false,
     }
 }
@@ -371,7 +379,22 @@ pub fn handle_set(state: &mut InterpreterState, args: &[String]) -> Result<Built
         // Handle combined short flags like -eu or +eu
         if arg.len() > 1 && (arg.starts_with('-') || arg.starts_with('+')) && !arg.starts_with("--") {
             let enable = arg.starts_with('-');
+            let mut consumed = 1;
             for flag in arg[1..].chars() {
+                // `o` takes the next argument as an option name (`set -euo pipefail`)
+                if flag == 'o' && i + consumed < args.len() {
+                    let opt_name = &args[i + consumed];
+                    let Some(option) = get_long_option(opt_name) else {
+                        let error_msg = format!("bash: set: {}: invalid option name\n{}", opt_name, SET_USAGE);
+                        if state.options.posix {
+                            return Err(PosixFatalError::new(1, String::new(), error_msg).into());
+                        }
+                        return Ok(BuiltinResult::failure(&error_msg, 1));
+                    };
+                    set_shell_option(state, option, enable);
+                    consumed += 1;
+                    continue;
+                }
                 match get_short_option(flag) {
                     Some(option) => {
                         set_shell_option(state, option, enable);
@@ -385,7 +408,7 @@ pub fn handle_set(state: &mut InterpreterState, args: &[String]) -> Result<Built
                     }
                 }
             }
-            i += 1;
+            i += consumed;
             continue;
         }
 
@@ -498,6 +521,19 @@ mod tests {
         assert!(state.options.xtrace);
     }
 
+    #[test]
+    fn test_set_combined_flags_with_o() {
+        let mut state = InterpreterState::default();
+
+        let args = ["-euo".to_string(), "pipefail".to_string(), "x".to_string()];
+        let result = handle_set(&mut state, &args).unwrap();
+        assert_eq!(result.exit_code, 0);
+        assert!(state.options.errexit);
+        assert!(state.options.nounset);
+        assert!(state.options.pipefail);
+        assert_eq!(state.env.get("1").unwrap(), "x");
+    }
+
     #[test]
     fn test_set_positional_params() {
         let mut state = InterpreterState::default();
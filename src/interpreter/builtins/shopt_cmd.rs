@@ -14,6 +14,7 @@ const SHOPT_OPTIONS: &[&str] = &[
     "failglob",
     "globskipdots",
     "globstar",
+    "inherit_errexit",
     "lastpipe",
     "nocaseglob",
     "nocasematch",
@@ -51,7 +52,6 @@ const STUB_OPTIONS: &[&str] = &[
     "histverify",
     "hostcomplete",
     "huponexit",
-    "inherit_errexit",
     "interactive_comments",
     "lithist",
     "localvar_inherit",
@@ -87,6 +87,7 @@ fn get_shopt_value(state: &InterpreterState, name: &str) -> bool {
         "nocaseglob" => state.shopt_options.nocaseglob,
         "nocasematch" => state.shopt_options.nocasematch,
         "expand_aliases" => state.shopt_options.expand_aliases,
+        "inherit_errexit" => state.shopt_options.inherit_errexit,
         "lastpipe" => state.shopt_options.lastpipe,
         "xpg_echo" => state.shopt_options.xpg_echo,
         _ => false,
@@ -105,6 +106,7 @@ fn set_shopt_value(state: &mut InterpreterState, name: &str, value: bool) {
         "nocaseglob" => state.shopt_options.nocaseglob = value,
         "nocasematch" => state.shopt_options.nocasematch = value,
         "expand_aliases" => state.shopt_options.expand_aliases = value,
+        "inherit_errexit" => state.shopt_options.inherit_errexit = value,
         "lastpipe" => state.shopt_options.lastpipe = value,
         "xpg_echo" => state.shopt_options.xpg_echo = value,
         _ => {}
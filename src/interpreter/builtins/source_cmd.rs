@@ -28,6 +28,8 @@ pub struct SourceSavedState {
     pub current_source: Option<String>,
     /// Whether positional parameters were changed
     pub changed_positional: bool,
+    /// Saved line offset; the file numbers its own lines from 1
+    pub line_offset: u32,
 }
 
 /// Parse and validate source arguments.
@@ -44,7 +46,7 @@ pub fn parse_source_args(args: &[String]) -> Result<SourceCommand, BuiltinResult
     if source_args.is_empty() {
         return Err((
             String::new(),
-            "bash: source: filename argument required\n".to_string(),
+            "bash: source: filename argument required\nsource: usage: source filename [arguments]\n".to_string(),
             2,
         ));
     }
@@ -78,13 +80,15 @@ pub fn prepare_source_state(
         positional: HashMap::new(),
         current_source: state.current_source.clone(),
         changed_positional: !cmd.script_args.is_empty(),
+        line_offset: std::mem::take(&mut state.line_offset),
     };
 
     if !cmd.script_args.is_empty() {
-        // Save current positional parameters
-        for i in 1..=9 {
+        // Save current positional parameters; the caller's extra ones are hidden
+        let outer_count: usize = state.env.get("#").and_then(|n| n.parse().ok()).unwrap_or(0);
+        for i in 1..=outer_count.max(cmd.script_args.len()) {
             let key = i.to_string();
-            saved.positional.insert(key.clone(), state.env.get(&key).cloned());
+            saved.positional.insert(key.clone(), state.env.remove(&key));
         }
         saved.positional.insert("#".to_string(), state.env.get("#").cloned());
         saved.positional.insert("@".to_string(), state.env.get("@").cloned());
@@ -93,19 +97,14 @@ pub fn prepare_source_state(
         state.env.insert("#".to_string(), cmd.script_args.len().to_string());
         state.env.insert("@".to_string(), cmd.script_args.join(" "));
         for (i, arg) in cmd.script_args.iter().enumerate() {
-            if i < 9 {
-                state.env.insert((i + 1).to_string(), arg.clone());
-            }
-        }
-        // Clear any remaining positional parameters
-        for i in (cmd.script_args.len() + 1)..=9 {
-            state.env.remove(&i.to_string());
+            state.env.insert((i + 1).to_string(), arg.clone());
         }
     }
 
-    // Update source tracking
+    // Update source tracking; the file is BASH_SOURCE[0] while it runs
     state.source_depth += 1;
     state.current_source = Some(cmd.filename.clone());
+    state.source_stack.get_or_insert_with(Vec::new).insert(0, cmd.filename.clone());
 
     saved
 }
@@ -114,6 +113,10 @@ pub fn prepare_source_state(
 pub fn restore_source_state(state: &mut InterpreterState, saved: SourceSavedState) {
     state.source_depth -= 1;
     state.current_source = saved.current_source;
+    state.line_offset = saved.line_offset;
+    if let Some(stack) = state.source_stack.as_mut().filter(|stack| !stack.is_empty()) {
+        stack.remove(0);
+    }
 
     // Restore positional parameters if we changed them
     if saved.changed_positional {
@@ -137,7 +140,7 @@ pub fn source_file_not_found(filename: &str) -> ExecResult {
 
 /// Create an ExecResult for parse error.
 pub fn source_parse_error(filename: &str, message: &str) -> ExecResult {
-    ExecResult::failure(format!("bash: {}: {}\n", filename, message))
+    ExecResult::new(String::new(), format!("bash: {}: {}\n", filename, message), 2)
 }
 
 /// Resolve a source filename to a path.
@@ -175,7 +178,7 @@ pub fn resolve_source_paths(
         }
 
         // Then try current directory
-        candidates.push(format!("{}/{}", cwd, filename));
+        candidates.push(normalize_path(&format!("{}/{}", cwd, filename)));
     }
 
     candidates
@@ -294,6 +297,7 @@ mod tests {
 
         assert_eq!(state.source_depth, 1);
         assert_eq!(state.current_source, Some("test.sh".to_string()));
+        assert_eq!(state.source_stack, Some(vec!["test.sh".to_string()]));
         assert_eq!(state.env.get("1"), Some(&"new1".to_string()));
         assert_eq!(state.env.get("2"), Some(&"new2".to_string()));
         assert_eq!(state.env.get("#"), Some(&"2".to_string()));
@@ -301,6 +305,7 @@ mod tests {
         restore_source_state(&mut state, saved);
 
         assert_eq!(state.source_depth, 0);
+        assert_eq!(state.source_stack, Some(vec![]));
         assert_eq!(state.env.get("1"), Some(&"old1".to_string()));
         assert_eq!(state.env.get("2"), None);
         assert_eq!(state.env.get("#"), Some(&"1".to_string()));
@@ -0,0 +1,190 @@
+//! trap - Set handlers for signals and shell events
+//!
+//! trap [-lp] [[action] sigspec ...]
+//!
+//! Options:
+//!   (no args)  Display the handlers in a form that can be reused as input
+//!   -p         Same, for the given sigspecs only
+//!   -l         List signal names and numbers
+//!
+//! A sigspec is a signal name or number, or one of the pseudo-signals EXIT
+//! (also 0), ERR, DEBUG and RETURN. An empty action ignores the signal, and
+//! an action of `-` (or none at all) resets it. The builtin only records the
+//! handlers; the execution engine runs them.
+
+use crate::interpreter::builtins::break_cmd::BuiltinResult;
+use crate::interpreter::helpers::signals::{format_signal_list, parse_signal, signal_name, SIGKILL};
+use crate::interpreter::types::InterpreterState;
+
+const TRAP_USAGE: &str = "trap: usage: trap [-lp] [[arg] signal_spec ...]\n";
+
+/// Pseudo-signals, with the order they are listed in after the real signals.
+const PSEUDO_SIGNALS: &[&str] = &["DEBUG", "ERR", "RETURN"];
+
+/// Map a sigspec to the key its handler is stored under.
+pub fn normalize_condition(spec: &str) -> Option<String> {
+    let upper = spec.to_ascii_uppercase();
+    if upper == "EXIT" || upper == "SIGEXIT" || spec == "0" {
+        return Some("EXIT".to_string());
+    }
+    if PSEUDO_SIGNALS.contains(&upper.as_str()) {
+        return Some(upper);
+    }
+    parse_signal(spec).and_then(signal_name).map(str::to_string)
+}
+
+/// Sort key listing EXIT first, then signals by number, then the other
+/// pseudo-signals.
+fn condition_order(condition: &str) -> i32 {
+    if condition == "EXIT" {
+        return 0;
+    }
+    match PSEUDO_SIGNALS.iter().position(|name| *name == condition) {
+        Some(index) => 100 + index as i32,
+        None => parse_signal(condition).unwrap_or(99),
+    }
+}
+
+/// Name shown by `trap -p`: signals get their SIG prefix.
+fn display_name(condition: &str) -> String {
+    if condition == "EXIT" || PSEUDO_SIGNALS.contains(&condition) {
+        condition.to_string()
+    } else {
+        format!("SIG{}", condition)
+    }
+}
+
+fn format_trap(condition: &str, handler: &str) -> String {
+    format!("trap -- '{}' {}\n", handler.replace('\'', "'\\''"), display_name(condition))
+}
+
+/// Handle the `trap` builtin.
+pub fn handle_trap(state: &mut InterpreterState, args: &[String]) -> BuiltinResult {
+    let mut print = false;
+    let mut rest = args;
+    while let Some(first) = rest.first() {
+        match first.as_str() {
+            "-l" => return BuiltinResult { stdout: format_signal_list(), stderr: String::new(), exit_code: 0 },
+            "-p" => print = true,
+            "--" => {
+                rest = &rest[1..];
+                break;
+            }
+            opt if opt.starts_with('-') && opt.len() > 1 && normalize_condition(&opt[1..]).is_none() => {
+                return BuiltinResult::failure(&format!("bash: trap: {}: invalid option\n{}", opt, TRAP_USAGE), 2);
+            }
+            _ => break,
+        }
+        rest = &rest[1..];
+    }
+
+    if print || rest.is_empty() {
+        return print_traps(state, rest);
+    }
+
+    // A lone sigspec, or a leading number, resets instead of setting
+    let (action, specs) = if rest.len() == 1 || rest[0].parse::<u32>().is_ok() {
+        ("-", rest)
+    } else {
+        (rest[0].as_str(), &rest[1..])
+    };
+
+    let mut result = BuiltinResult::ok();
+    for spec in specs {
+        let Some(condition) = normalize_condition(spec) else {
+            result.stderr.push_str(&format!("bash: trap: {}: invalid signal specification\n", spec));
+            result.exit_code = 1;
+            continue;
+        };
+        if action == "-" {
+            state.traps.remove(&condition);
+        } else if !parse_signal(&condition).is_some_and(is_uncatchable) {
+            state.traps.insert(condition, action.to_string());
+        }
+    }
+    result
+}
+
+/// `trap` / `trap -p [sigspec ...]`
+fn print_traps(state: &InterpreterState, specs: &[String]) -> BuiltinResult {
+    let mut result = BuiltinResult::ok();
+    if specs.is_empty() {
+        let mut traps: Vec<(&String, &String)> = state.traps.iter().collect();
+        traps.sort_by_key(|(condition, _)| condition_order(condition));
+        for (condition, handler) in traps {
+            result.stdout.push_str(&format_trap(condition, handler));
+        }
+        return result;
+    }
+    for spec in specs {
+        match normalize_condition(spec) {
+            Some(condition) => {
+                if let Some(handler) = state.traps.get(&condition) {
+                    result.stdout.push_str(&format_trap(&condition, handler));
+                }
+            }
+            None => {
+                result.stderr.push_str(&format!("bash: trap: {}: invalid signal specification\n", spec));
+                result.exit_code = 1;
+            }
+        }
+    }
+    result
+}
+
+/// SIGKILL and SIGSTOP cannot be trapped or ignored.
+fn is_uncatchable(signal: i32) -> bool {
+    signal == SIGKILL || signal_name(signal) == Some("STOP")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_condition() {
+        assert_eq!(normalize_condition("exit"), Some("EXIT".to_string()));
+        assert_eq!(normalize_condition("0"), Some("EXIT".to_string()));
+        assert_eq!(normalize_condition("SIGINT"), Some("INT".to_string()));
+        assert_eq!(normalize_condition("15"), Some("TERM".to_string()));
+        assert_eq!(normalize_condition("err"), Some("ERR".to_string()));
+        assert_eq!(normalize_condition("FOO"), None);
+    }
+
+    #[test]
+    fn test_trap_set_print_and_reset() {
+        let mut state = InterpreterState::default();
+        handle_trap(&mut state, &args(&["echo 'bye'", "EXIT", "TERM"]));
+        handle_trap(&mut state, &args(&["", "INT"]));
+        handle_trap(&mut state, &args(&["echo err", "ERR"]));
+
+        let result = handle_trap(&mut state, &[]);
+        assert_eq!(
+            result.stdout,
+            "trap -- 'echo '\\''bye'\\''' EXIT\ntrap -- '' SIGINT\ntrap -- 'echo '\\''bye'\\''' SIGTERM\ntrap -- 'echo err' ERR\n"
+        );
+        let result = handle_trap(&mut state, &args(&["-p", "INT"]));
+        assert_eq!(result.stdout, "trap -- '' SIGINT\n");
+
+        handle_trap(&mut state, &args(&["-", "EXIT"]));
+        handle_trap(&mut state, &args(&["TERM"]));
+        handle_trap(&mut state, &args(&["2"]));
+        assert_eq!(state.traps.len(), 1);
+        assert!(state.traps.contains_key("ERR"));
+    }
+
+    #[test]
+    fn test_trap_invalid_signal() {
+        let mut state = InterpreterState::default();
+        let result = handle_trap(&mut state, &args(&["echo", "NOPE"]));
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.stderr, "bash: trap: NOPE: invalid signal specification\n");
+
+        handle_trap(&mut state, &args(&["echo", "KILL"]));
+        assert!(state.traps.is_empty());
+    }
+}
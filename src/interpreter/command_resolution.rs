@@ -13,8 +13,10 @@ pub enum ResolveCommandResult {
     Script { path: String },
     /// Command not found
     NotFound { path: Option<String> },
-    /// Permission denied (file exists but not executable, or is a directory)
+    /// Permission denied (file exists but is not executable)
     PermissionDenied { path: String },
+    /// The path names a directory
+    IsDirectory { path: String },
 }
 
 impl ResolveCommandResult {
@@ -30,6 +32,7 @@ impl ResolveCommandResult {
             ResolveCommandResult::Script { path } => Some(path),
             ResolveCommandResult::NotFound { path } => path.as_deref(),
             ResolveCommandResult::PermissionDenied { path } => Some(path),
+            ResolveCommandResult::IsDirectory { path } => Some(path),
         }
     }
 }
@@ -141,6 +144,23 @@ pub struct FileStat {
     pub mode: u32,
 }
 
+impl CommandResolutionFs for std::sync::Arc<dyn crate::fs::FileSystem> {
+    fn resolve_path(&self, cwd: &str, path: &str) -> String {
+        crate::fs::FileSystem::resolve_path(self.as_ref(), cwd, path)
+    }
+
+    async fn exists(&self, path: &str) -> bool {
+        crate::fs::FileSystem::exists(self.as_ref(), path).await
+    }
+
+    async fn stat(&self, path: &str) -> Option<FileStat> {
+        crate::fs::FileSystem::stat(self.as_ref(), path)
+            .await
+            .ok()
+            .map(|s| FileStat { is_directory: s.is_directory, mode: s.mode })
+    }
+}
+
 /// Resolve a command name to its implementation via PATH lookup.
 ///
 /// Resolution order:
@@ -186,7 +206,7 @@ pub async fn resolve_command<F: CommandResolutionFs>(
             Some(stat) => {
                 if stat.is_directory {
                     // Trying to execute a directory
-                    return ResolveCommandResult::PermissionDenied { path: resolved_path };
+                    return ResolveCommandResult::IsDirectory { path: resolved_path };
                 }
 
                 // For registered commands (like /bin/echo), skip execute check
@@ -234,6 +254,9 @@ pub async fn resolve_command<F: CommandResolutionFs>(
         // Search PATH directories (use override if provided, for command -p)
         let path_env = path_override.unwrap_or_else(|| env_path.unwrap_or(DEFAULT_PATH));
         let path_dirs = split_path(path_env);
+        // bash remembers the first non-executable match and reports it when
+        // nothing executable turns up later in PATH
+        let mut non_executable: Option<String> = None;
 
         for dir in path_dirs {
             // Resolve relative PATH directories against cwd
@@ -270,6 +293,8 @@ pub async fn resolve_command<F: CommandResolutionFs>(
                             // No registered handler - treat as user script
                             return ResolveCommandResult::Script { path: full_path };
                         }
+                    } else if non_executable.is_none() {
+                        non_executable = Some(full_path);
                     }
                 }
             }
@@ -282,7 +307,10 @@ pub async fn resolve_command<F: CommandResolutionFs>(
             };
         }
 
-        ResolveCommandResult::NotFound { path: None }
+        match non_executable {
+            Some(path) => ResolveCommandResult::PermissionDenied { path },
+            None => ResolveCommandResult::NotFound { path: None },
+        }
     }
 }
 
@@ -467,6 +495,10 @@ mod tests {
         let denied = ResolveCommandResult::PermissionDenied { path: "/bin/ls".to_string() };
         assert!(!denied.is_found());
         assert_eq!(denied.path(), Some("/bin/ls"));
+
+        let directory = ResolveCommandResult::IsDirectory { path: "/bin".to_string() };
+        assert!(!directory.is_found());
+        assert_eq!(directory.path(), Some("/bin"));
     }
 
     #[test]
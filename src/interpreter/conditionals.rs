@@ -8,8 +8,18 @@
 //! - Numeric comparisons (-eq, -ne, -lt, etc.)
 //! - Pattern matching (==, =~)
 
+use crate::ast::types::{
+    CondBinaryNode, CondBinaryOperator, CondUnaryOperator, ConditionalExpressionNode, WordNode,
+};
 use crate::interpreter::expansion::pattern::pattern_to_regex;
+use crate::interpreter::helpers::array::{clear_array, set_array_element};
+use crate::interpreter::helpers::nameref::is_nameref;
+use crate::interpreter::helpers::variable_tests::evaluate_variable_test;
+use crate::interpreter::interpreter::FileSystem as SyncFileSystem;
 use crate::interpreter::types::InterpreterState;
+use crate::interpreter::word_expansion::{
+    expand_word, expand_word_for_pattern, expand_word_for_regex, CommandSubstFn,
+};
 use regex_lite::Regex;
 
 /// Match a value against a glob pattern.
@@ -522,7 +532,8 @@ use crate::interpreter::helpers::string_tests::{evaluate_string_test_str, is_str
 use crate::interpreter::helpers::file_tests::{
     evaluate_file_test_str, is_file_test_operator,
     evaluate_binary_file_test_str, is_binary_file_test_operator,
-    FileSystem,
+    evaluate_file_test, evaluate_binary_file_test, BinaryFileTestOperator, FileTestOperator,
+    FileSystem, InterpreterFs,
 };
 
 /// Result of a test expression evaluation.
@@ -583,6 +594,17 @@ where
     F: FileSystem,
     V: Fn(&InterpreterState, &str) -> bool,
 {
+    evaluate_test_args_dyn(state, args, fs, &variable_test)
+}
+
+/// Non-generic over the variable test so the recursive cases don't
+/// instantiate a new closure type at every level.
+fn evaluate_test_args_dyn<F: FileSystem>(
+    state: &InterpreterState,
+    args: &[String],
+    fs: &F,
+    variable_test: &dyn Fn(&InterpreterState, &str) -> bool,
+) -> TestResult {
     if args.is_empty() {
         return TestResult::failure();
     }
@@ -686,7 +708,7 @@ where
 
         // If $1 is '!', negate the 2-argument test
         if left == "!" {
-            let neg_result = evaluate_test_args(state, &[op.clone(), right.clone()], fs, &variable_test);
+            let neg_result = evaluate_test_args_dyn(state, &[op.clone(), right.clone()], fs, variable_test);
             return TestResult::new(match neg_result.exit_code {
                 0 => 1,
                 1 => 0,
@@ -704,7 +726,7 @@ where
     if args.len() == 4 {
         // If $1 is '!', negate the 3-argument expression
         if args[0] == "!" {
-            let neg_result = evaluate_test_args(state, &args[1..].to_vec(), fs, &variable_test);
+            let neg_result = evaluate_test_args_dyn(state, &args[1..], fs, variable_test);
             return TestResult::new(match neg_result.exit_code {
                 0 => 1,
                 1 => 0,
@@ -714,7 +736,7 @@ where
 
         // If $1 is '(' and $4 is ')', evaluate $2 $3 as 2-argument test
         if args[0] == "(" && args[3] == ")" {
-            return evaluate_test_args(state, &[args[1].clone(), args[2].clone()], fs, &variable_test);
+            return evaluate_test_args_dyn(state, &[args[1].clone(), args[2].clone()], fs, variable_test);
         }
     }
 
@@ -723,6 +745,209 @@ where
     TestResult::failure()
 }
 
+/// Evaluate a `[[ ... ]]` conditional expression.
+///
+/// Operands are expanded here, so unlike `evaluate_test_args` this takes the
+/// parsed expression tree. The right-hand side of `==`/`!=` is a pattern unless
+/// quoted, and a successful `=~` fills `BASH_REMATCH` with the match and its
+/// capture groups. An invalid regex yields exit code 2.
+///
+/// Under `set -x`, each test that is evaluated adds a line to `trace` in the
+/// form bash prints it, with the operands expanded (`[[ a b == a* ]]`).
+pub fn evaluate_conditional(
+    state: &mut InterpreterState,
+    expr: &ConditionalExpressionNode,
+    fs: &dyn SyncFileSystem,
+    cmd_subst: Option<CommandSubstFn>,
+    trace: &mut Vec<String>,
+) -> TestResult {
+    let mut stderr = String::new();
+    let mut result = match evaluate_conditional_expr(state, expr, fs, cmd_subst, &mut stderr, trace) {
+        Ok(value) => TestResult::from_bool(value),
+        Err(message) => TestResult::error(&message),
+    };
+    result.stderr = format!("{}{}", stderr, result.stderr);
+    result
+}
+
+fn evaluate_conditional_expr(
+    state: &mut InterpreterState,
+    expr: &ConditionalExpressionNode,
+    fs: &dyn SyncFileSystem,
+    cmd_subst: Option<CommandSubstFn>,
+    stderr: &mut String,
+    trace: &mut Vec<String>,
+) -> Result<bool, String> {
+    match expr {
+        ConditionalExpressionNode::And(node) => {
+            Ok(evaluate_conditional_expr(state, &node.left, fs, cmd_subst, stderr, trace)?
+                && evaluate_conditional_expr(state, &node.right, fs, cmd_subst, stderr, trace)?)
+        }
+        ConditionalExpressionNode::Or(node) => {
+            Ok(evaluate_conditional_expr(state, &node.left, fs, cmd_subst, stderr, trace)?
+                || evaluate_conditional_expr(state, &node.right, fs, cmd_subst, stderr, trace)?)
+        }
+        ConditionalExpressionNode::Not(node) => {
+            let traced = trace.len();
+            let value = evaluate_conditional_expr(state, &node.operand, fs, cmd_subst, stderr, trace)?;
+            // bash shows a negated test as `[[ ! -z x ]]`
+            let primitive = matches!(
+                node.operand,
+                ConditionalExpressionNode::Word(_) | ConditionalExpressionNode::Unary(_) | ConditionalExpressionNode::Binary(_)
+            );
+            if let Some(line) = trace.get_mut(traced).filter(|_| primitive) {
+                line.insert_str("[[ ".len(), "! ");
+            }
+            Ok(!value)
+        }
+        ConditionalExpressionNode::Group(node) => {
+            evaluate_conditional_expr(state, &node.expression, fs, cmd_subst, stderr, trace)
+        }
+        ConditionalExpressionNode::Word(node) => {
+            let value = expand_operand(state, &node.word, cmd_subst, stderr);
+            trace_test(state, trace, &format!("-n {}", value));
+            Ok(!value.is_empty())
+        }
+        ConditionalExpressionNode::Unary(node) => {
+            let operand = expand_operand(state, &node.operand, cmd_subst, stderr);
+            trace_test(state, trace, &format!("{} {}", node.operator, operand));
+            Ok(evaluate_conditional_unary(state, node.operator, &operand, fs))
+        }
+        ConditionalExpressionNode::Binary(node) => {
+            evaluate_conditional_binary(state, node, fs, cmd_subst, stderr, trace)
+        }
+    }
+}
+
+/// Record a test for `set -x`.
+fn trace_test(state: &InterpreterState, trace: &mut Vec<String>, test: &str) {
+    if state.options.xtrace {
+        trace.push(format!("[[ {} ]]", test));
+    }
+}
+
+fn expand_operand(
+    state: &mut InterpreterState,
+    word: &WordNode,
+    cmd_subst: Option<CommandSubstFn>,
+    stderr: &mut String,
+) -> String {
+    let result = expand_word(state, word, cmd_subst);
+    stderr.push_str(&result.stderr);
+    result.value
+}
+
+fn evaluate_conditional_unary(
+    state: &InterpreterState,
+    operator: CondUnaryOperator,
+    operand: &str,
+    fs: &dyn SyncFileSystem,
+) -> bool {
+    let file_op = match operator {
+        CondUnaryOperator::Z => return operand.is_empty(),
+        CondUnaryOperator::LowerN => return !operand.is_empty(),
+        CondUnaryOperator::V => return evaluate_variable_test(state, &state.env, operand, None).0,
+        CondUnaryOperator::LowerO => return evaluate_shell_option(state, operand),
+        CondUnaryOperator::UpperR => return is_nameref(state, operand),
+        CondUnaryOperator::A | CondUnaryOperator::E => FileTestOperator::Exists,
+        CondUnaryOperator::B => FileTestOperator::BlockSpecial,
+        CondUnaryOperator::C => FileTestOperator::CharSpecial,
+        CondUnaryOperator::D => FileTestOperator::Directory,
+        CondUnaryOperator::F => FileTestOperator::RegularFile,
+        CondUnaryOperator::G => FileTestOperator::SetGid,
+        CondUnaryOperator::H => FileTestOperator::SymbolicLinkH,
+        CondUnaryOperator::K => FileTestOperator::StickyBit,
+        CondUnaryOperator::P => FileTestOperator::NamedPipe,
+        CondUnaryOperator::R => FileTestOperator::Readable,
+        CondUnaryOperator::S => FileTestOperator::NonEmpty,
+        CondUnaryOperator::T => FileTestOperator::Terminal,
+        CondUnaryOperator::U => FileTestOperator::SetUid,
+        CondUnaryOperator::W => FileTestOperator::Writable,
+        CondUnaryOperator::X => FileTestOperator::Executable,
+        CondUnaryOperator::UpperG => FileTestOperator::OwnedByGroup,
+        CondUnaryOperator::L => FileTestOperator::SymbolicLink,
+        CondUnaryOperator::N => FileTestOperator::ModifiedSinceRead,
+        CondUnaryOperator::UpperO => FileTestOperator::OwnedByUser,
+        CondUnaryOperator::UpperS => FileTestOperator::Socket,
+    };
+    evaluate_file_test(&InterpreterFs(fs), &state.cwd, file_op, operand)
+}
+
+fn evaluate_conditional_binary(
+    state: &mut InterpreterState,
+    node: &CondBinaryNode,
+    fs: &dyn SyncFileSystem,
+    cmd_subst: Option<CommandSubstFn>,
+    stderr: &mut String,
+    trace: &mut Vec<String>,
+) -> Result<bool, String> {
+    let left = expand_operand(state, &node.left, cmd_subst, stderr);
+    let nocasematch = state.shopt_options.nocasematch;
+
+    let file_op = match node.operator {
+        CondBinaryOperator::Eq | CondBinaryOperator::EqEq | CondBinaryOperator::Ne => {
+            let pattern = expand_word_for_pattern(state, &node.right, cmd_subst);
+            stderr.push_str(&pattern.stderr);
+            trace_test(state, trace, &format!("{} {} {}", left, node.operator, pattern.value));
+            let matched = match_pattern(&left, &pattern.value, nocasematch, state.shopt_options.extglob);
+            return Ok(matched != (node.operator == CondBinaryOperator::Ne));
+        }
+        CondBinaryOperator::Match => {
+            let pattern = expand_word_for_regex(state, &node.right, cmd_subst);
+            stderr.push_str(&pattern.stderr);
+            trace_test(state, trace, &format!("{} {} {}", left, node.operator, pattern.value));
+            return match_regex(state, &left, &pattern.value, nocasematch);
+        }
+        CondBinaryOperator::Nt => BinaryFileTestOperator::NewerThan,
+        CondBinaryOperator::Ot => BinaryFileTestOperator::OlderThan,
+        CondBinaryOperator::Ef => BinaryFileTestOperator::SameFile,
+        _ => {
+            let right = expand_operand(state, &node.right, cmd_subst, stderr);
+            trace_test(state, trace, &format!("{} {} {}", left, node.operator, right));
+            return Ok(match node.operator {
+                CondBinaryOperator::Lt => left < right,
+                CondBinaryOperator::Gt => left > right,
+                CondBinaryOperator::NumEq => parse_numeric(&left) == parse_numeric(&right),
+                CondBinaryOperator::NumNe => parse_numeric(&left) != parse_numeric(&right),
+                CondBinaryOperator::NumLt => parse_numeric(&left) < parse_numeric(&right),
+                CondBinaryOperator::NumLe => parse_numeric(&left) <= parse_numeric(&right),
+                CondBinaryOperator::NumGt => parse_numeric(&left) > parse_numeric(&right),
+                _ => parse_numeric(&left) >= parse_numeric(&right),
+            });
+        }
+    };
+
+    let right = expand_operand(state, &node.right, cmd_subst, stderr);
+    trace_test(state, trace, &format!("{} {} {}", left, node.operator, right));
+    Ok(evaluate_binary_file_test(&InterpreterFs(fs), &state.cwd, file_op, &left, &right))
+}
+
+/// Match `value` against a POSIX ERE, updating `BASH_REMATCH` like bash does.
+fn match_regex(
+    state: &mut InterpreterState,
+    value: &str,
+    pattern: &str,
+    nocasematch: bool,
+) -> Result<bool, String> {
+    let regex_str = if nocasematch {
+        format!("(?i){}", posix_ere_to_regex(pattern))
+    } else {
+        posix_ere_to_regex(pattern)
+    };
+    // bash reports a bad regex only through exit status 2
+    let re = Regex::new(&regex_str).map_err(|_| String::new())?;
+
+    clear_array(&mut state.env, "BASH_REMATCH");
+    let Some(caps) = re.captures(value) else {
+        return Ok(false);
+    };
+    for (index, group) in caps.iter().enumerate() {
+        let text = group.map(|m| m.as_str().to_string()).unwrap_or_default();
+        set_array_element(&mut state.env, "BASH_REMATCH", index as i64, text);
+    }
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,13 +7,15 @@
 //! - while loops
 //! - until loops
 //! - case statements
+//! - select menus
 //! - break/continue
 
 use regex_lite::Regex;
-use crate::interpreter::types::{InterpreterState, ExecResult};
+use crate::interpreter::types::{InterpreterState, ExecResult, ExecutionLimits};
 use crate::interpreter::helpers::condition::{execute_condition, ConditionResult};
 use crate::interpreter::helpers::loop_helpers::{handle_loop_error, LoopAction};
 use crate::interpreter::errors::{InterpreterError, ExecutionLimitError, LimitType};
+use crate::interpreter::interpreter::check_visible_output_limit;
 
 /// Validate that a variable name is a valid identifier.
 /// Returns true if valid, false otherwise.
@@ -22,6 +24,25 @@ pub fn is_valid_identifier(name: &str) -> bool {
     re.is_match(name)
 }
 
+/// Stop a loop whose accumulated output reaching the script's output has
+/// grown past the output limit.
+fn check_loop_output(
+    state: &InterpreterState,
+    stdout: &str,
+    stderr: &str,
+    limits: &ExecutionLimits,
+) -> Result<(), InterpreterError> {
+    match check_visible_output_limit(state, stdout, stderr, limits) {
+        Some(msg) => Err(InterpreterError::ExecutionLimit(ExecutionLimitError::after_output(
+            msg,
+            LimitType::Output,
+            stdout.to_string(),
+            stderr,
+        ))),
+        None => Ok(()),
+    }
+}
+
 /// Case statement terminator types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CaseTerminator {
@@ -54,10 +75,28 @@ impl CaseTerminator {
     }
 }
 
+impl From<crate::ast::types::CaseTerminator> for CaseTerminator {
+    fn from(terminator: crate::ast::types::CaseTerminator) -> Self {
+        use crate::ast::types::CaseTerminator as Ast;
+        match terminator {
+            Ast::DoubleSemi => CaseTerminator::Break,
+            Ast::SemiAnd => CaseTerminator::FallThrough,
+            Ast::SemiSemiAnd => CaseTerminator::ContinueMatching,
+        }
+    }
+}
+
 // ============================================================================
 // Control Flow Execution Functions
 // ============================================================================
 
+/// Attach output produced before a control flow error (e.g. `return`) so it
+/// is not lost while the error unwinds.
+fn with_output(mut error: InterpreterError, stdout: &str, stderr: &str) -> InterpreterError {
+    error.as_control_flow_mut().prepend_output(stdout, stderr);
+    error
+}
+
 /// Result of executing an if statement.
 #[derive(Debug, Clone)]
 pub struct IfResult {
@@ -83,23 +122,24 @@ impl IfResult {
 ///
 /// # Returns
 /// Result with accumulated stdout, stderr, and exit code
-pub fn execute_if<C, B, F1, F2, E>(
+pub fn execute_if<C, B, F1, F2>(
     state: &mut InterpreterState,
     clauses: &[(Vec<C>, Vec<B>)],
     else_body: Option<&[B]>,
     mut condition_executor: F1,
     mut body_executor: F2,
-) -> Result<IfResult, E>
+) -> Result<IfResult, InterpreterError>
 where
-    F1: FnMut(&mut InterpreterState, &C) -> Result<ConditionResult, E>,
-    F2: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, E>,
+    F1: FnMut(&mut InterpreterState, &C) -> Result<ConditionResult, InterpreterError>,
+    F2: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, InterpreterError>,
 {
     let mut stdout = String::new();
     let mut stderr = String::new();
 
     for (condition, body) in clauses {
         // Execute condition with in_condition flag set
-        let cond_result = execute_condition(state, condition, &mut condition_executor)?;
+        let cond_result = execute_condition(state, condition, &mut condition_executor)
+            .map_err(|e| with_output(e, &stdout, &stderr))?;
         stdout.push_str(&cond_result.stdout);
         stderr.push_str(&cond_result.stderr);
 
@@ -107,7 +147,7 @@ where
             // Condition is true, execute body
             let mut exit_code = 0;
             for stmt in body {
-                let res = body_executor(state, stmt)?;
+                let res = body_executor(state, stmt).map_err(|e| with_output(e, &stdout, &stderr))?;
                 stdout.push_str(&res.stdout);
                 stderr.push_str(&res.stderr);
                 exit_code = res.exit_code;
@@ -120,7 +160,7 @@ where
     if let Some(else_stmts) = else_body {
         let mut exit_code = 0;
         for stmt in else_stmts {
-            let res = body_executor(state, stmt)?;
+            let res = body_executor(state, stmt).map_err(|e| with_output(e, &stdout, &stderr))?;
             stdout.push_str(&res.stdout);
             stderr.push_str(&res.stderr);
             exit_code = res.exit_code;
@@ -152,20 +192,23 @@ impl ForResult {
 /// * `variable` - Loop variable name
 /// * `words` - List of words to iterate over
 /// * `body` - Body statements
-/// * `max_iterations` - Maximum allowed iterations
+/// * `limits` - Iteration and output limits
+/// * `trace` - Function producing the `set -x` output for each iteration
 /// * `executor` - Function to execute body statements
 ///
 /// # Returns
 /// Result with accumulated stdout, stderr, and exit code
-pub fn execute_for<B, F>(
+pub fn execute_for<B, T, F>(
     state: &mut InterpreterState,
     variable: &str,
     words: &[String],
     body: &[B],
-    max_iterations: u64,
+    limits: &ExecutionLimits,
+    mut trace: T,
     mut executor: F,
 ) -> Result<ForResult, InterpreterError>
 where
+    T: FnMut(&mut InterpreterState) -> ExecResult,
     F: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, InterpreterError>,
 {
     // Validate variable name
@@ -187,15 +230,19 @@ where
     let result = (|| {
         for value in words {
             iterations += 1;
-            if iterations > max_iterations {
+            if iterations > limits.max_iterations {
                 return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::new(
-                    format!("for loop: too many iterations ({})", max_iterations),
+                    format!("for loop: too many iterations ({})", limits.max_iterations),
                     LimitType::Iterations,
                     stdout.clone(),
                     stderr.clone(),
                 )));
             }
+            check_loop_output(state, &stdout, &stderr, limits)?;
 
+            let traced = trace(state);
+            stdout.push_str(&traced.stdout);
+            stderr.push_str(&traced.stderr);
             state.env.insert(variable.to_string(), value.clone());
 
             for stmt in body {
@@ -215,8 +262,11 @@ where
                         stdout = loop_result.stdout;
                         stderr = loop_result.stderr;
                         match loop_result.action {
-                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, exit_code)),
-                            LoopAction::Continue => break,
+                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, 0)),
+                            LoopAction::Continue => {
+                                exit_code = 0;
+                                break;
+                            }
                             LoopAction::Error => {
                                 return Ok(ForResult::new(stdout, stderr, loop_result.exit_code.unwrap_or(1)));
                             }
@@ -235,13 +285,141 @@ where
     result
 }
 
+/// Execute a C-style for loop: `for ((init; condition; update))`.
+///
+/// # Arguments
+/// * `state` - Interpreter state
+/// * `init` - Expression evaluated once before the loop
+/// * `condition` - Expression checked before each iteration (missing means true)
+/// * `update` - Expression evaluated after each iteration, including after `continue`
+/// * `body` - Body statements
+/// * `limits` - Iteration and output limits
+/// * `trace` - Function producing the `set -x` output for an expression
+///   about to be evaluated
+/// * `evaluator` - Function to evaluate an arithmetic expression
+/// * `executor` - Function to execute body statements
+///
+/// # Returns
+/// Result with accumulated stdout, stderr, and exit code. An arithmetic error
+/// stops the loop with its message on stderr and exit code 1.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_c_style_for<A, B, T, F1, F2>(
+    state: &mut InterpreterState,
+    init: Option<&A>,
+    condition: Option<&A>,
+    update: Option<&A>,
+    body: &[B],
+    limits: &ExecutionLimits,
+    mut trace: T,
+    mut evaluator: F1,
+    mut executor: F2,
+) -> Result<ForResult, InterpreterError>
+where
+    T: FnMut(&mut InterpreterState, &A) -> ExecResult,
+    F1: FnMut(&mut InterpreterState, &A) -> Result<i64, String>,
+    F2: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, InterpreterError>,
+{
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    let mut exit_code = 0;
+    let mut iterations = 0u64;
+
+    if let Some(expr) = init {
+        let traced = trace(state, expr);
+        stdout.push_str(&traced.stdout);
+        stderr.push_str(&traced.stderr);
+        if let Err(message) = evaluator(state, expr) {
+            stderr.push_str(&message);
+            return Ok(ForResult::new(stdout, stderr, 1));
+        }
+    }
+
+    state.loop_depth += 1;
+
+    let result = (|| {
+        loop {
+            iterations += 1;
+            if iterations > limits.max_iterations {
+                return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::new(
+                    format!("for loop: too many iterations ({})", limits.max_iterations),
+                    LimitType::Iterations,
+                    stdout.clone(),
+                    stderr.clone(),
+                )));
+            }
+            check_loop_output(state, &stdout, &stderr, limits)?;
+
+            if let Some(expr) = condition {
+                let traced = trace(state, expr);
+                stdout.push_str(&traced.stdout);
+                stderr.push_str(&traced.stderr);
+                match evaluator(state, expr) {
+                    Ok(0) => break,
+                    Ok(_) => {}
+                    Err(message) => {
+                        stderr.push_str(&message);
+                        return Ok(ForResult::new(stdout, stderr, 1));
+                    }
+                }
+            }
+
+            for stmt in body {
+                match executor(state, stmt) {
+                    Ok(res) => {
+                        stdout.push_str(&res.stdout);
+                        stderr.push_str(&res.stderr);
+                        exit_code = res.exit_code;
+                    }
+                    Err(error) => {
+                        let loop_result = handle_loop_error(
+                            error,
+                            stdout.clone(),
+                            stderr.clone(),
+                            state.loop_depth,
+                        );
+                        stdout = loop_result.stdout;
+                        stderr = loop_result.stderr;
+                        match loop_result.action {
+                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, 0)),
+                            LoopAction::Continue => {
+                                exit_code = 0;
+                                break;
+                            }
+                            LoopAction::Error => {
+                                return Ok(ForResult::new(stdout, stderr, loop_result.exit_code.unwrap_or(1)));
+                            }
+                            LoopAction::Rethrow => {
+                                return Err(loop_result.error.unwrap());
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(expr) = update {
+                let traced = trace(state, expr);
+                stdout.push_str(&traced.stdout);
+                stderr.push_str(&traced.stderr);
+                if let Err(message) = evaluator(state, expr) {
+                    stderr.push_str(&message);
+                    return Ok(ForResult::new(stdout, stderr, 1));
+                }
+            }
+        }
+        Ok(ForResult::new(stdout, stderr, exit_code))
+    })();
+
+    state.loop_depth -= 1;
+    result
+}
+
 /// Execute a while loop.
 ///
 /// # Arguments
 /// * `state` - Interpreter state
 /// * `condition` - Condition statements
 /// * `body` - Body statements
-/// * `max_iterations` - Maximum allowed iterations
+/// * `limits` - Iteration and output limits
 /// * `condition_executor` - Function to execute condition statements
 /// * `body_executor` - Function to execute body statements
 ///
@@ -251,7 +429,7 @@ pub fn execute_while<C, B, F1, F2>(
     state: &mut InterpreterState,
     condition: &[C],
     body: &[B],
-    max_iterations: u64,
+    limits: &ExecutionLimits,
     mut condition_executor: F1,
     mut body_executor: F2,
 ) -> Result<ForResult, InterpreterError>
@@ -269,17 +447,19 @@ where
     let result = (|| {
         loop {
             iterations += 1;
-            if iterations > max_iterations {
+            if iterations > limits.max_iterations {
                 return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::new(
-                    format!("while loop: too many iterations ({})", max_iterations),
+                    format!("while loop: too many iterations ({})", limits.max_iterations),
                     LimitType::Iterations,
                     stdout.clone(),
                     stderr.clone(),
                 )));
             }
+            check_loop_output(state, &stdout, &stderr, limits)?;
 
             // Execute condition
-            let cond_result = execute_condition(state, condition, &mut condition_executor)?;
+            let cond_result = execute_condition(state, condition, &mut condition_executor)
+            .map_err(|e| with_output(e, &stdout, &stderr))?;
             stdout.push_str(&cond_result.stdout);
             stderr.push_str(&cond_result.stderr);
 
@@ -305,8 +485,11 @@ where
                         stdout = loop_result.stdout;
                         stderr = loop_result.stderr;
                         match loop_result.action {
-                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, exit_code)),
-                            LoopAction::Continue => break,
+                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, 0)),
+                            LoopAction::Continue => {
+                                exit_code = 0;
+                                break;
+                            }
                             LoopAction::Error => {
                                 return Ok(ForResult::new(stdout, stderr, loop_result.exit_code.unwrap_or(1)));
                             }
@@ -331,7 +514,7 @@ where
 /// * `state` - Interpreter state
 /// * `condition` - Condition statements
 /// * `body` - Body statements
-/// * `max_iterations` - Maximum allowed iterations
+/// * `limits` - Iteration and output limits
 /// * `condition_executor` - Function to execute condition statements
 /// * `body_executor` - Function to execute body statements
 ///
@@ -341,7 +524,7 @@ pub fn execute_until<C, B, F1, F2>(
     state: &mut InterpreterState,
     condition: &[C],
     body: &[B],
-    max_iterations: u64,
+    limits: &ExecutionLimits,
     mut condition_executor: F1,
     mut body_executor: F2,
 ) -> Result<ForResult, InterpreterError>
@@ -359,17 +542,19 @@ where
     let result = (|| {
         loop {
             iterations += 1;
-            if iterations > max_iterations {
+            if iterations > limits.max_iterations {
                 return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::new(
-                    format!("until loop: too many iterations ({})", max_iterations),
+                    format!("until loop: too many iterations ({})", limits.max_iterations),
                     LimitType::Iterations,
                     stdout.clone(),
                     stderr.clone(),
                 )));
             }
+            check_loop_output(state, &stdout, &stderr, limits)?;
 
             // Execute condition
-            let cond_result = execute_condition(state, condition, &mut condition_executor)?;
+            let cond_result = execute_condition(state, condition, &mut condition_executor)
+            .map_err(|e| with_output(e, &stdout, &stderr))?;
             stdout.push_str(&cond_result.stdout);
             stderr.push_str(&cond_result.stderr);
 
@@ -396,8 +581,11 @@ where
                         stdout = loop_result.stdout;
                         stderr = loop_result.stderr;
                         match loop_result.action {
-                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, exit_code)),
-                            LoopAction::Continue => break,
+                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, 0)),
+                            LoopAction::Continue => {
+                                exit_code = 0;
+                                break;
+                            }
                             LoopAction::Error => {
                                 return Ok(ForResult::new(stdout, stderr, loop_result.exit_code.unwrap_or(1)));
                             }
@@ -416,6 +604,190 @@ where
     result
 }
 
+/// Format the numbered menu `select` prints, laid out in columns like bash
+/// for a terminal `columns` wide. Short lists come out one item per line.
+pub fn format_select_menu(items: &[String], columns: usize) -> String {
+    const TAB_SIZE: usize = 8;
+    if items.is_empty() {
+        return String::new();
+    }
+    let indices_len = items.len().to_string().len();
+    let max_elem_len = items.iter().map(|item| item.chars().count()).max().unwrap_or(0) + indices_len + 4;
+
+    let cols = (columns / max_elem_len).max(1);
+    let mut rows = items.len().div_ceil(cols);
+    if rows == 1 {
+        rows = items.len();
+    }
+
+    let mut menu = String::new();
+    for row in 0..rows {
+        let mut index = row;
+        let mut pos = 0;
+        loop {
+            let item = &items[index];
+            menu.push_str(&format!("{:>width$}) {}", index + 1, item, width = indices_len));
+            let elem_len = item.chars().count() + indices_len + 2;
+            index += rows;
+            if index >= items.len() {
+                break;
+            }
+            // Pad to the next column with tabs, then spaces
+            let mut from = pos + elem_len;
+            let to = pos + max_elem_len;
+            while to / TAB_SIZE > from / TAB_SIZE {
+                menu.push('\t');
+                from += TAB_SIZE - from % TAB_SIZE;
+            }
+            menu.push_str(&" ".repeat(to.saturating_sub(from)));
+            pos += max_elem_len;
+        }
+        menu.push('\n');
+    }
+    menu
+}
+
+/// Take the next line from the stdin shared by the loop body. Returns None at
+/// end of input; a final line without a newline also counts as end of input,
+/// though it still becomes the reply.
+fn read_select_reply(state: &mut InterpreterState) -> Option<String> {
+    let input = state.group_stdin.take().unwrap_or_default();
+    match input.split_once('\n') {
+        Some((line, rest)) => {
+            let line = line.to_string();
+            state.group_stdin = Some(rest.to_string());
+            Some(line)
+        }
+        None => {
+            state.group_stdin = Some(String::new());
+            state.env.insert("REPLY".to_string(), input);
+            None
+        }
+    }
+}
+
+/// Execute a select loop.
+///
+/// The menu and the `PS3` prompt go to stderr and replies are read line by
+/// line from the loop's stdin. Each reply is stored in `REPLY`; the variable
+/// gets the chosen item, or an empty string when the reply is not a valid
+/// number. An empty reply shows the menu again without running the body.
+/// The loop ends at end of input (status 1) or on `break`.
+///
+/// # Arguments
+/// * `state` - Interpreter state
+/// * `variable` - Loop variable name
+/// * `words` - Menu items
+/// * `body` - Body statements
+/// * `limits` - Iteration and output limits
+/// * `executor` - Function to execute body statements
+pub fn execute_select<B, F>(
+    state: &mut InterpreterState,
+    variable: &str,
+    words: &[String],
+    body: &[B],
+    limits: &ExecutionLimits,
+    mut executor: F,
+) -> Result<ForResult, InterpreterError>
+where
+    F: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, InterpreterError>,
+{
+    if !is_valid_identifier(variable) {
+        return Ok(ForResult::new(
+            String::new(),
+            format!("bash: `{}': not a valid identifier\n", variable),
+            1,
+        ));
+    }
+    if words.is_empty() {
+        return Ok(ForResult::new(String::new(), String::new(), 0));
+    }
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    let mut exit_code = 0;
+    let mut iterations = 0u64;
+    let mut show_menu = true;
+
+    state.loop_depth += 1;
+
+    let result = (|| {
+        loop {
+            iterations += 1;
+            if iterations > limits.max_iterations {
+                return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::new(
+                    format!("select loop: too many iterations ({})", limits.max_iterations),
+                    LimitType::Iterations,
+                    stdout.clone(),
+                    stderr.clone(),
+                )));
+            }
+            check_loop_output(state, &stdout, &stderr, limits)?;
+
+            if show_menu {
+                let columns = state.env.get("COLUMNS").and_then(|c| c.parse().ok()).unwrap_or(80);
+                stderr.push_str(&format_select_menu(words, columns));
+            }
+            stderr.push_str(state.env.get("PS3").map(String::as_str).unwrap_or("#? "));
+
+            let Some(reply) = read_select_reply(state) else {
+                stdout.push('\n');
+                return Ok(ForResult::new(stdout, stderr, 1));
+            };
+            state.env.insert("REPLY".to_string(), reply.clone());
+            if reply.is_empty() {
+                show_menu = true;
+                continue;
+            }
+
+            let choice = match reply.trim().parse::<usize>() {
+                Ok(n) if (1..=words.len()).contains(&n) => words[n - 1].clone(),
+                _ => String::new(),
+            };
+            state.env.insert(variable.to_string(), choice);
+
+            for stmt in body {
+                match executor(state, stmt) {
+                    Ok(res) => {
+                        stdout.push_str(&res.stdout);
+                        stderr.push_str(&res.stderr);
+                        exit_code = res.exit_code;
+                    }
+                    Err(error) => {
+                        let loop_result = handle_loop_error(
+                            error,
+                            stdout.clone(),
+                            stderr.clone(),
+                            state.loop_depth,
+                        );
+                        stdout = loop_result.stdout;
+                        stderr = loop_result.stderr;
+                        match loop_result.action {
+                            LoopAction::Break => return Ok(ForResult::new(stdout, stderr, 0)),
+                            LoopAction::Continue => {
+                                exit_code = 0;
+                                break;
+                            }
+                            LoopAction::Error => {
+                                return Ok(ForResult::new(stdout, stderr, loop_result.exit_code.unwrap_or(1)));
+                            }
+                            LoopAction::Rethrow => {
+                                return Err(loop_result.error.unwrap());
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Like ksh, the menu comes back only if the body cleared REPLY
+            show_menu = state.env.get("REPLY").is_none_or(|reply| reply.is_empty());
+        }
+    })();
+
+    state.loop_depth -= 1;
+    result
+}
+
 /// Case item with pattern and body.
 pub struct CaseItem<'a, P, B> {
     pub patterns: &'a [P],
@@ -434,16 +806,16 @@ pub struct CaseItem<'a, P, B> {
 ///
 /// # Returns
 /// Result with accumulated stdout, stderr, and exit code
-pub fn execute_case<P, B, F1, F2, E>(
+pub fn execute_case<P, B, F1, F2>(
     state: &mut InterpreterState,
     value: &str,
     items: &[CaseItem<P, B>],
     mut pattern_matcher: F1,
     mut body_executor: F2,
-) -> Result<ForResult, E>
+) -> Result<ForResult, InterpreterError>
 where
-    F1: FnMut(&InterpreterState, &str, &P) -> Result<bool, E>,
-    F2: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, E>,
+    F1: FnMut(&mut InterpreterState, &str, &P) -> Result<bool, InterpreterError>,
+    F2: FnMut(&mut InterpreterState, &B) -> Result<ExecResult, InterpreterError>,
 {
     let mut stdout = String::new();
     let mut stderr = String::new();
@@ -456,7 +828,7 @@ where
         if !fall_through {
             // Normal pattern matching
             for pattern in item.patterns {
-                if pattern_matcher(state, value, pattern)? {
+                if pattern_matcher(state, value, pattern).map_err(|e| with_output(e, &stdout, &stderr))? {
                     matched = true;
                     break;
                 }
@@ -466,7 +838,7 @@ where
         if matched {
             // Execute body
             for stmt in item.body {
-                let res = body_executor(state, stmt)?;
+                let res = body_executor(state, stmt).map_err(|e| with_output(e, &stdout, &stderr))?;
                 stdout.push_str(&res.stdout);
                 stderr.push_str(&res.stderr);
                 exit_code = res.exit_code;
@@ -502,6 +874,17 @@ mod tests {
         assert!(!is_valid_identifier(""));
     }
 
+    #[test]
+    fn test_format_select_menu() {
+        let items: Vec<String> = ["apple", "banana", "cherry"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(format_select_menu(&items, 80), "1) apple\n2) banana\n3) cherry\n");
+
+        let items: Vec<String> = (1..=12).map(|i| format!("item{}", i)).collect();
+        let menu = format_select_menu(&items, 40);
+        assert_eq!(menu.lines().next(), Some(" 1) item1    5) item5\t 9) item9"));
+        assert_eq!(menu.lines().count(), 4);
+    }
+
     #[test]
     fn test_case_terminator() {
         assert_eq!(CaseTerminator::from_str(";;"), Some(CaseTerminator::Break));
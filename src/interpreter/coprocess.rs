@@ -0,0 +1,240 @@
+//! Coprocesses
+//!
+//! `coproc [NAME] command` starts `command` asynchronously with a pipe in
+//! each direction: the shell writes to `${NAME[1]}`, which the coprocess
+//! reads as stdin, and reads its output from `${NAME[0]}` (`read -u`,
+//! `<&fd`). `NAME_PID` and `$!` hold its PID, and it appears in the job
+//! table like a background job.
+//!
+//! The coprocess runs when the shell first needs it, and again each time the
+//! shell reads from it after writing more input (see `Coprocess`). The engine
+//! checks before each command whether the command reads its output, waits
+//! for it, or whether its input has been closed.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::ast::types::{CoprocNode, StatementNode, AST};
+use crate::interpreter::builtins::jobs_cmd::register_job;
+use crate::interpreter::helpers::array::{clear_array, set_array_element};
+use crate::interpreter::redirections::allocate_fd;
+use crate::interpreter::types::{Coprocess, ExecResult, FileDescriptor, InterpreterState, JobStatus, Pipe};
+
+/// Start the coprocess described by `node`. It doesn't run until
+/// `pump_coprocesses` finds it is needed.
+pub fn start_coprocess(state: &mut InterpreterState, node: &CoprocNode) -> ExecResult {
+    let mut stderr = String::new();
+    if let Some(existing) = state.coprocs.iter().find(|coproc| lock(coproc).name == node.name) {
+        let existing = lock(existing);
+        stderr.push_str(&format!(
+            "bash: warning: execute_coproc: coproc [{}:{}] still exists\n",
+            existing.pid, existing.name
+        ));
+    }
+
+    // The coprocess does not hold on to the pipes of other coprocesses
+    let mut shell = state.clone();
+    shell.coprocs.clear();
+    if let Some(fds) = shell.file_descriptors.as_mut() {
+        fds.retain(|_, entry| !matches!(entry, FileDescriptor::PipeRead(_) | FileDescriptor::PipeWrite(_)));
+    }
+
+    let pid = state.next_virtual_pid;
+    state.next_virtual_pid += 1;
+    let input = Pipe::default();
+    let output = Pipe::default();
+    let read_fd = allocate_fd(state);
+    let write_fd = allocate_fd(state);
+    let fds = state.file_descriptors.get_or_insert_with(Default::default);
+    fds.insert(read_fd, FileDescriptor::PipeRead(output.clone()));
+    fds.insert(write_fd, FileDescriptor::PipeWrite(input.clone()));
+
+    clear_array(&mut state.env, &node.name);
+    set_array_element(&mut state.env, &node.name, 0, read_fd.to_string());
+    set_array_element(&mut state.env, &node.name, 1, write_fd.to_string());
+    state.env.insert(format!("{}_PID", node.name), pid.to_string());
+
+    let pipeline = AST::pipeline(vec![(*node.body).clone()], false, false, false, None);
+    let command = AST::statement(vec![pipeline], vec![], false, None, None);
+    let job_command = format!("coproc {}", node.name);
+    register_job(state, pid, &job_command, JobStatus::Done(0), String::new(), String::new());
+    state.coprocs.push(Arc::new(Mutex::new(Coprocess {
+        name: node.name.clone(),
+        pid,
+        parent_pid: state.bash_pid,
+        command,
+        shell,
+        input,
+        output,
+        ran: false,
+    })));
+    ExecResult::new(String::new(), stderr, 0)
+}
+
+/// Run the coprocesses the shell needs before its next command: those whose
+/// output the command reads from one of the `reading` fds, those whose input
+/// has been closed, and with `waiting`, all of them. A coprocess that has
+/// already run only runs again on input written since. Returns what they
+/// wrote to stderr, which is the shell's stderr.
+///
+/// `run` executes the coprocess command as a subshell of the given state,
+/// with the given stdin.
+pub fn pump_coprocesses<F>(state: &mut InterpreterState, reading: &[i32], waiting: bool, mut run: F) -> String
+where
+    F: FnMut(&mut InterpreterState, &StatementNode, &str) -> ExecResult,
+{
+    let mut stderr = String::new();
+    for shared in std::mem::take(&mut state.coprocs) {
+        let mut coproc = lock(&shared);
+
+        // Only the shell that started it has it in its job table; once
+        // `wait` or `kill` has reaped it there, it is gone
+        let owner = state.bash_pid == coproc.parent_pid;
+        let job = state.jobs.iter().position(|job| job.pid == coproc.pid);
+        if owner && !job.is_some_and(|job| matches!(state.jobs[job].status, JobStatus::Done(_))) {
+            continue;
+        }
+
+        let read = reading.iter().any(|fd| {
+            let entry = state.file_descriptors.as_ref().and_then(|fds| fds.get(fd));
+            matches!(entry, Some(FileDescriptor::PipeRead(pipe)) if pipe.same_pipe(&coproc.output))
+        });
+        let finished = waiting || !coproc.input.is_shared();
+        if !read && !finished {
+            drop(coproc);
+            state.coprocs.push(shared);
+            continue;
+        }
+
+        let input = coproc.input.take();
+        if !coproc.ran || !input.is_empty() {
+            let mut shell = coproc.shell.clone();
+            let result = run(&mut shell, &coproc.command, &input);
+            // Work done by the coprocess still counts against the limits
+            state.command_count += shell.command_count.saturating_sub(coproc.shell.command_count);
+            coproc.output.write(&result.stdout);
+            coproc.ran = true;
+            stderr.push_str(&result.stderr);
+            if let Some(job) = job {
+                state.jobs[job].status = JobStatus::Done(result.exit_code);
+            }
+        }
+        if !finished {
+            drop(coproc);
+            state.coprocs.push(shared);
+        }
+    }
+    stderr
+}
+
+/// The fd a `read` or `mapfile` with arguments `args` reads from (`-u FD`).
+pub fn input_fd_option(args: &[String]) -> Option<i32> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" || !arg.starts_with('-') || arg == "-" {
+            break;
+        }
+        for (i, flag) in arg.char_indices().skip(1) {
+            if !"uadinNptOsCc".contains(flag) {
+                continue;
+            }
+            let value = match &arg[i + 1..] {
+                "" => args.next().map(String::as_str).unwrap_or_default(),
+                rest => rest,
+            };
+            if flag == 'u' {
+                return value.parse().ok();
+            }
+            break;
+        }
+    }
+    None
+}
+
+fn lock(coproc: &Mutex<Coprocess>) -> std::sync::MutexGuard<'_, Coprocess> {
+    coproc.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::types::CommandNode;
+
+    /// Stand-in for a `while read line; do echo "<$line>"; done` coprocess
+    /// that counts its runs.
+    fn echo_lines(runs: &mut usize) -> impl FnMut(&mut InterpreterState, &StatementNode, &str) -> ExecResult + '_ {
+        move |_, _, input| {
+            *runs += 1;
+            let stdout: String = input.lines().map(|line| format!("<{}>\n", line)).collect();
+            ExecResult::new(stdout, String::new(), 0)
+        }
+    }
+
+    fn start(state: &mut InterpreterState) -> (Pipe, Pipe) {
+        let body = CommandNode::Simple(AST::simple_command(None, vec![], vec![], vec![]));
+        start_coprocess(state, &AST::coproc("CO", body));
+        let fds = state.file_descriptors.as_ref().unwrap();
+        match (&fds[&10], &fds[&11]) {
+            (FileDescriptor::PipeRead(output), FileDescriptor::PipeWrite(input)) => (output.clone(), input.clone()),
+            other => panic!("unexpected fds {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_coprocess_runs_on_new_input_when_output_is_read() {
+        let mut state = InterpreterState::default();
+        let mut runs = 0;
+        let (output, input) = start(&mut state);
+        assert_eq!(state.env["CO_0"], "10");
+        assert_eq!(state.env["CO_1"], "11");
+        assert_eq!(state.env["CO_PID"], state.last_background_pid.to_string());
+
+        input.write("a\n");
+        pump_coprocesses(&mut state, &[], false, echo_lines(&mut runs));
+        input.write("b\n");
+        pump_coprocesses(&mut state, &[0, 11], false, echo_lines(&mut runs));
+        assert_eq!(runs, 0);
+
+        pump_coprocesses(&mut state, &[10], false, echo_lines(&mut runs));
+        assert_eq!(output.take(), "<a>\n<b>\n");
+        pump_coprocesses(&mut state, &[10], false, echo_lines(&mut runs));
+        assert_eq!(runs, 1);
+
+        input.write("c\n");
+        pump_coprocesses(&mut state, &[10], false, echo_lines(&mut runs));
+        assert_eq!(output.take(), "<c>\n");
+        assert_eq!(state.coprocs.len(), 1);
+
+        pump_coprocesses(&mut state, &[], true, echo_lines(&mut runs));
+        assert_eq!(runs, 2);
+        assert!(state.coprocs.is_empty());
+    }
+
+    #[test]
+    fn test_coprocess_runs_when_input_is_closed_or_waited_for() {
+        let mut state = InterpreterState::default();
+        let mut runs = 0;
+        let (_output, input) = start(&mut state);
+        drop(input);
+        state.file_descriptors.as_mut().unwrap().remove(&11);
+        pump_coprocesses(&mut state, &[], false, echo_lines(&mut runs));
+        assert_eq!(runs, 1);
+        assert!(state.coprocs.is_empty());
+        assert_eq!(state.jobs[0].status, JobStatus::Done(0));
+
+        let mut state = InterpreterState::default();
+        start(&mut state);
+        pump_coprocesses(&mut state, &[], true, echo_lines(&mut runs));
+        assert_eq!(runs, 2);
+        assert!(state.coprocs.is_empty());
+    }
+
+    #[test]
+    fn test_input_fd_option() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(input_fd_option(&args(&["-u", "3", "x"])), Some(3));
+        assert_eq!(input_fd_option(&args(&["-ru4"])), Some(4));
+        assert_eq!(input_fd_option(&args(&["-p", "-u 5", "x"])), None);
+        assert_eq!(input_fd_option(&args(&["-d", "u", "-r", "-u", "6"])), Some(6));
+        assert_eq!(input_fd_option(&args(&["x", "-u", "3"])), None);
+    }
+}
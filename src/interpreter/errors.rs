@@ -390,6 +390,16 @@ pub enum LimitType {
     Recursion,
     Commands,
     Iterations,
+    /// The wall-clock deadline of the execution passed
+    Timeout,
+    /// The caller cancelled the execution
+    Cancelled,
+    /// Too much stdout/stderr was produced
+    Output,
+    /// A variable value or expanded word grew too long
+    StringLength,
+    /// An array or expanded word list grew too large
+    ArraySize,
 }
 
 impl fmt::Display for LimitType {
@@ -398,13 +408,19 @@ impl fmt::Display for LimitType {
             LimitType::Recursion => write!(f, "recursion"),
             LimitType::Commands => write!(f, "commands"),
             LimitType::Iterations => write!(f, "iterations"),
+            LimitType::Timeout => write!(f, "timeout"),
+            LimitType::Cancelled => write!(f, "cancelled"),
+            LimitType::Output => write!(f, "output"),
+            LimitType::StringLength => write!(f, "string length"),
+            LimitType::ArraySize => write!(f, "array size"),
         }
     }
 }
 
 /// Error thrown when execution limits are exceeded (recursion depth, command count, loop iterations).
 /// This should ALWAYS be thrown before Rust's native stack overflow kicks in.
-/// Exit code 126 indicates a limit was exceeded.
+/// Exit code 126 indicates a limit was exceeded; a timeout or cancellation
+/// exits with 124, like `timeout(1)`.
 #[derive(Debug, Clone)]
 pub struct ExecutionLimitError {
     pub message: String,
@@ -415,6 +431,7 @@ pub struct ExecutionLimitError {
 
 impl ExecutionLimitError {
     pub const EXIT_CODE: i32 = 126;
+    pub const TIMEOUT_EXIT_CODE: i32 = 124;
 
     pub fn new(message: String, limit_type: LimitType, stdout: String, stderr: String) -> Self {
         let stderr = if stderr.is_empty() {
@@ -429,6 +446,33 @@ impl ExecutionLimitError {
         let msg = message.into();
         Self::new(msg.clone(), limit_type, String::new(), format!("bash: {}\n", msg))
     }
+
+    /// A limit error that keeps the output produced so far, with the
+    /// message after it on stderr.
+    pub fn after_output(message: impl Into<String>, limit_type: LimitType, stdout: String, stderr: &str) -> Self {
+        let msg = message.into();
+        let stderr = format!("{}bash: {}\n", stderr, msg);
+        Self::new(msg, limit_type, stdout, stderr)
+    }
+
+    /// Cut stdout down to at most `max` bytes.
+    pub fn truncate_stdout(&mut self, max: usize) {
+        if self.stdout.len() > max {
+            let mut end = max;
+            while !self.stdout.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.stdout.truncate(end);
+        }
+    }
+
+    /// The exit status a script stopped by this limit ends with.
+    pub fn exit_code(&self) -> i32 {
+        match self.limit_type {
+            LimitType::Timeout | LimitType::Cancelled => Self::TIMEOUT_EXIT_CODE,
+            _ => Self::EXIT_CODE,
+        }
+    }
 }
 
 impl fmt::Display for ExecutionLimitError {
@@ -556,6 +600,46 @@ impl fmt::Display for InterpreterError {
 
 impl std::error::Error for InterpreterError {}
 
+impl InterpreterError {
+    /// The output carried by the error, whichever variant it is.
+    pub fn as_control_flow(&self) -> &dyn ControlFlowError {
+        match self {
+            InterpreterError::Break(e) => e,
+            InterpreterError::Continue(e) => e,
+            InterpreterError::Return(e) => e,
+            InterpreterError::Errexit(e) => e,
+            InterpreterError::Nounset(e) => e,
+            InterpreterError::Exit(e) => e,
+            InterpreterError::Arithmetic(e) => e,
+            InterpreterError::BadSubstitution(e) => e,
+            InterpreterError::Glob(e) => e,
+            InterpreterError::BraceExpansion(e) => e,
+            InterpreterError::ExecutionLimit(e) => e,
+            InterpreterError::SubshellExit(e) => e,
+            InterpreterError::PosixFatal(e) => e,
+        }
+    }
+
+    /// Access the output carried by the error, whichever variant it is.
+    pub fn as_control_flow_mut(&mut self) -> &mut dyn ControlFlowError {
+        match self {
+            InterpreterError::Break(e) => e,
+            InterpreterError::Continue(e) => e,
+            InterpreterError::Return(e) => e,
+            InterpreterError::Errexit(e) => e,
+            InterpreterError::Nounset(e) => e,
+            InterpreterError::Exit(e) => e,
+            InterpreterError::Arithmetic(e) => e,
+            InterpreterError::BadSubstitution(e) => e,
+            InterpreterError::Glob(e) => e,
+            InterpreterError::BraceExpansion(e) => e,
+            InterpreterError::ExecutionLimit(e) => e,
+            InterpreterError::SubshellExit(e) => e,
+            InterpreterError::PosixFatal(e) => e,
+        }
+    }
+}
+
 /// Check if an error is a scope exit error (return, break, continue).
 /// These need special handling vs errexit/nounset which terminate execution.
 pub fn is_scope_exit_error(error: &InterpreterError) -> bool {
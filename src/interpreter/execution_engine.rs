@@ -5,22 +5,70 @@
 //!
 //! execute_script -> execute_statement -> execute_pipeline -> execute_command
 
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::audit::{elapsed_ms, AuditEvent, AuditSink, CommandEvent, CommandKind};
 use crate::ast::types::{
-    CommandNode, CompoundCommandNode, PipelineNode, ScriptNode, SimpleCommandNode, StatementNode,
-    StatementOperator,
+    ArithmeticExpressionNode, CommandNode, CompoundCommandNode, FunctionDefNode, PipelineNode, ScriptNode, SimpleCommandNode, StatementNode,
+    RedirectionNode, RedirectionOperator, StatementOperator, WordNode,
+};
+use crate::commands::registry::CommandRegistry;
+use crate::commands::types::{Command, CommandContext, ExecFn, FetchFn, StdinUse};
+use crate::policy::{CommandPolicy, CommandRequest, PolicyDecision, RedirectionRequest};
+use crate::fs::FileSystem as AsyncFileSystem;
+use crate::interpreter::alias_expansion::AliasReader;
+use crate::interpreter::builtin_dispatch::{dispatch_builtin, BuiltinDispatchContext};
+use crate::interpreter::builtins::jobs_cmd::{defer_job, drain_jobs, run_pending_jobs};
+use crate::interpreter::command_resolution::{resolve_command, CommandHashTable, ResolveCommandResult};
+use crate::interpreter::conditionals::{evaluate_conditional, match_pattern};
+use crate::interpreter::control_flow::{
+    execute_c_style_for, execute_case, execute_for, execute_if, execute_select, execute_until,
+    execute_while,
+    CaseItem,
 };
-use crate::interpreter::control_flow::{execute_for, execute_if, execute_while, execute_until, ForResult};
-use crate::interpreter::errors::{InterpreterError, ErrexitError, ExitError, ControlFlowError};
-use crate::interpreter::functions::execute_function_def;
+use crate::interpreter::coprocess::{input_fd_option, pump_coprocesses, start_coprocess};
+use crate::interpreter::errors::{
+    InterpreterError, ErrexitError, ExitError, ControlFlowError, ExecutionLimitError, LimitType, NounsetError,
+};
+use crate::interpreter::expansion::command_substitution::get_file_read_shorthand;
+use crate::interpreter::functions::{call_function, execute_function_def};
+use crate::interpreter::helpers::array::{get_array_indices, get_assoc_array_keys};
 use crate::interpreter::helpers::condition::ConditionResult;
+use crate::interpreter::expansion::prompt::expand_prompt;
+use crate::interpreter::helpers::signals::{is_fatal_by_default, signal_name};
+use crate::interpreter::helpers::xtrace::{format_trace_line, get_xtrace_prefix_expanded, quote_for_trace, xtrace_prefix_for_level};
 use crate::interpreter::interpreter::{
-    build_exported_env, check_command_limit, should_trigger_errexit, update_exit_code,
-    FileSystem as SyncFileSystem,
+    build_exported_env, check_array_limit, check_command_limit, check_visible_output_limit, check_string_limit,
+    is_reportable_failure, should_trigger_errexit,
+    update_exit_code, FileSystem as SyncFileSystem,
+};
+use crate::interpreter::pipeline_execution::{
+    execute_pipeline, runs_in_subshell, set_pipestatus, PipelineOptions, PipelineState,
+};
+use crate::interpreter::process_substitution::{
+    close_process_substitutions, open_process_substitutions, open_redirection_process_substitutions,
+    ProcessSubstitutions,
+};
+use crate::interpreter::redirections::{
+    apply_exec_redirections, apply_persistent_fds, apply_redirections, pre_expand_redirect_targets, ExpandedRedirectTargets,
+    pre_open_output_redirects, process_fd_variable_redirections, read_input_redirections,
+    redirected_output_fds, with_fds_set_aside,
+};
+use crate::interpreter::simple_command_assignments::{process_assignments, restore_temp_assignments};
+use crate::interpreter::subshell_group::{
+    execute_group, execute_subshell, parse_shebang, prepare_group, prepare_subshell,
+};
+use crate::interpreter::traps::{
+    enter_function_traps, has_trap, leave_function_traps, pipeline_reports_failure, run_exit_trap, run_trap,
 };
-use crate::interpreter::pipeline_execution::{execute_pipeline, PipelineOptions, PipelineState, set_pipestatus};
-use crate::interpreter::subshell_group::{execute_group, execute_subshell};
-use crate::interpreter::types::{ExecResult, ExecutionLimits, InterpreterState};
-use crate::interpreter::word_expansion::{expand_word, expand_word_with_glob, CommandSubstFn};
+use crate::interpreter::types::{
+    CancellationToken, ExecResult, ExecutionLimits, FileDescriptor, InterpreterState, JobStatus, ShellNesting,
+};
+use crate::interpreter::word_expansion::{expand_word, expand_word_for_pattern, expand_word_with_fs_glob};
 
 /// The execution engine that ties all interpreter components together.
 pub struct ExecutionEngine<'a> {
@@ -28,12 +76,134 @@ pub struct ExecutionEngine<'a> {
     pub limits: &'a ExecutionLimits,
     /// Sync filesystem interface
     pub fs: &'a dyn SyncFileSystem,
+    /// Registered commands (ls, grep, ...) reachable through PATH
+    pub registry: Option<Arc<CommandRegistry>>,
+    /// Async filesystem handed to registered commands
+    pub command_fs: Option<Arc<dyn AsyncFileSystem>>,
+    /// Callback used by commands that run nested scripts (bash -c, xargs, find -exec)
+    pub exec_fn: Option<ExecFn>,
+    /// Counters shared with the nested shells `exec_fn` starts
+    pub nesting: Option<ShellNesting>,
+    /// Callback used by network commands (curl)
+    pub fetch_fn: Option<FetchFn>,
+    /// Wall-clock time after which the script is stopped
+    pub deadline: Option<Instant>,
+    /// Set by the caller to stop the script
+    pub cancellation: Option<CancellationToken>,
+    /// Shared with nested shells, which stop at the same deadline or
+    /// cancellation; set by the first one to report it
+    pub interrupt_reported: Option<Arc<AtomicBool>>,
+    /// Receives a record of every command the script runs
+    pub audit: Option<AuditSink>,
+    /// Consulted before every command runs
+    pub policy: Option<Arc<dyn CommandPolicy>>,
+    /// Set when the policy already allowed the command `run_command` is
+    /// about to run, together with its redirections
+    policy_checked: Cell<bool>,
 }
 
 impl<'a> ExecutionEngine<'a> {
     /// Create a new execution engine.
     pub fn new(limits: &'a ExecutionLimits, fs: &'a dyn SyncFileSystem) -> Self {
-        Self { limits, fs }
+        Self {
+            limits,
+            fs,
+            registry: None,
+            command_fs: None,
+            exec_fn: None,
+            nesting: None,
+            fetch_fn: None,
+            deadline: None,
+            cancellation: None,
+            interrupt_reported: None,
+            audit: None,
+            policy: None,
+            policy_checked: Cell::new(false),
+        }
+    }
+
+    /// Set the command registry and the filesystem its commands operate on.
+    pub fn with_registry(
+        mut self,
+        registry: Arc<CommandRegistry>,
+        fs: Arc<dyn AsyncFileSystem>,
+    ) -> Self {
+        self.registry = Some(registry);
+        self.command_fs = Some(fs);
+        self
+    }
+
+    /// Set the callback for nested script execution.
+    pub fn with_exec_fn(mut self, exec_fn: ExecFn) -> Self {
+        self.exec_fn = Some(exec_fn);
+        self
+    }
+
+    /// Share the command count and depth with the nested shells `exec_fn`
+    /// starts through `nesting`.
+    pub fn with_nesting(mut self, nesting: ShellNesting) -> Self {
+        self.nesting = Some(nesting);
+        self
+    }
+
+    /// Set the callback for network requests.
+    pub fn with_fetch_fn(mut self, fetch_fn: FetchFn) -> Self {
+        self.fetch_fn = Some(fetch_fn);
+        self
+    }
+
+    /// Report every command the script runs to `sink`.
+    pub fn with_audit(mut self, sink: AuditSink) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    /// Let `policy` allow, deny or rewrite every command before it runs.
+    pub fn with_policy(mut self, policy: Arc<dyn CommandPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Stop the script once `deadline` has passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop the script once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Report a deadline or cancellation only if no shell sharing `flag`
+    /// has reported it yet.
+    pub fn with_interrupt_reported(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt_reported = Some(flag);
+        self
+    }
+
+    /// Fail with an execution limit error if the script was cancelled or
+    /// ran past its deadline. Checked before every statement and command,
+    /// so loop iterations and pipeline stages are covered too.
+    pub fn check_interrupted(&self) -> Result<(), InterpreterError> {
+        if self.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(self.interrupted("execution cancelled", LimitType::Cancelled));
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(self.interrupted("execution timed out", LimitType::Timeout));
+        }
+        Ok(())
+    }
+
+    /// The error that stops the script. A nested shell that stopped first
+    /// has already printed the message.
+    fn interrupted(&self, message: &str, limit_type: LimitType) -> InterpreterError {
+        let mut error = ExecutionLimitError::simple(message, limit_type);
+        if self.interrupt_reported.as_ref().is_some_and(|flag| flag.swap(true, Ordering::SeqCst)) {
+            error.stderr.clear();
+        }
+        InterpreterError::ExecutionLimit(error)
     }
 
     /// Execute a complete script (list of statements).
@@ -46,13 +216,22 @@ impl<'a> ExecutionEngine<'a> {
         let mut stderr = String::new();
         let mut exit_code = 0;
 
+        let mut reader = AliasReader::default();
         for statement in &ast.statements {
-            match self.execute_statement(state, statement) {
+            match self.execute_read_statement(state, &mut reader, statement) {
                 Ok(result) => {
                     stdout.push_str(&result.stdout);
                     stderr.push_str(&result.stderr);
                     exit_code = result.exit_code;
                     update_exit_code(state, exit_code);
+                    if let Some(msg) = check_visible_output_limit(state, &stdout, &stderr, self.limits) {
+                        return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::after_output(
+                            msg,
+                            LimitType::Output,
+                            stdout,
+                            &stderr,
+                        )));
+                    }
                 }
                 Err(InterpreterError::Exit(e)) => {
                     // ExitError propagates up to terminate the script
@@ -60,8 +239,10 @@ impl<'a> ExecutionEngine<'a> {
                     err.prepend_output(&stdout, &stderr);
                     return Err(InterpreterError::Exit(err));
                 }
-                Err(InterpreterError::ExecutionLimit(e)) => {
-                    // ExecutionLimitError must always propagate
+                Err(InterpreterError::ExecutionLimit(mut e)) => {
+                    // ExecutionLimitError must always propagate, with what
+                    // the script printed before it
+                    e.prepend_output(&stdout, &stderr);
                     return Err(InterpreterError::ExecutionLimit(e));
                 }
                 Err(InterpreterError::Errexit(e)) => {
@@ -71,6 +252,12 @@ impl<'a> ExecutionEngine<'a> {
                     exit_code = e.exit_code;
                     return Ok(ExecResult::new(stdout, stderr, exit_code));
                 }
+                Err(InterpreterError::Nounset(e)) => {
+                    // So does expanding an unset variable under set -u
+                    stdout.push_str(&e.stdout);
+                    stderr.push_str(&e.stderr);
+                    return Ok(ExecResult::new(stdout, stderr, 1));
+                }
                 Err(InterpreterError::Break(mut e)) => {
                     // Break/continue outside loops - silently continue
                     e.prepend_output(&stdout, &stderr);
@@ -100,6 +287,20 @@ impl<'a> ExecutionEngine<'a> {
         Ok(ExecResult::new(stdout, stderr, exit_code))
     }
 
+    /// Read a statement of a script with `reader`, expanding its aliases,
+    /// and execute it. An alias that does not parse fails the statement.
+    fn execute_read_statement(
+        &self,
+        state: &mut InterpreterState,
+        reader: &mut AliasReader,
+        statement: &StatementNode,
+    ) -> Result<ExecResult, InterpreterError> {
+        match reader.read(state, statement) {
+            Ok(statement) => self.execute_statement(state, &statement),
+            Err(message) => Ok(ExecResult::new(String::new(), format!("bash: syntax error: {}\n", message), 2)),
+        }
+    }
+
     /// Execute a single statement (list of pipelines with && || operators).
     pub fn execute_statement(
         &self,
@@ -119,6 +320,7 @@ impl<'a> ExecutionEngine<'a> {
         if state.options.noexec {
             return Ok(ExecResult::ok());
         }
+        self.check_interrupted()?;
 
         // Reset errexit_safe at start of each statement
         state.errexit_safe = Some(false);
@@ -134,9 +336,16 @@ impl<'a> ExecutionEngine<'a> {
             }
         }
 
+        if stmt.background {
+            let result = self.run_background_job(state, stmt);
+            stderr.push_str(&result.stderr);
+            return Ok(ExecResult::new(stdout, stderr, result.exit_code));
+        }
+
         let mut exit_code = 0;
         let mut last_executed_index: i32 = -1;
         let mut last_pipeline_negated = false;
+        let mut last_pipeline_reports = false;
 
         for (i, pipeline) in stmt.pipelines.iter().enumerate() {
             // Check && / || short-circuit
@@ -159,12 +368,27 @@ impl<'a> ExecutionEngine<'a> {
                 }
             }
 
-            let result = self.execute_pipeline_node(state, pipeline)?;
+            // Everything but the last pipeline of an && / || list, and any
+            // negated pipeline, runs with errexit ignored
+            let exempt = pipeline.negated
+                || matches!(stmt.operators.get(i), Some(StatementOperator::And | StatementOperator::Or));
+            let saved_in_condition = state.in_condition;
+            state.in_condition |= exempt;
+            let result = self.execute_pipeline_node(state, pipeline);
+            state.in_condition = saved_in_condition;
+            let result = match result.and_then(|result| take_unbound_variable(state).map(|_| result)) {
+                Ok(result) => result,
+                Err(mut error) => {
+                    error.as_control_flow_mut().prepend_output(&stdout, &stderr);
+                    return Err(error);
+                }
+            };
             stdout.push_str(&result.stdout);
             stderr.push_str(&result.stderr);
             exit_code = result.exit_code;
             last_executed_index = i as i32;
             last_pipeline_negated = pipeline.negated;
+            last_pipeline_reports = pipeline_reports_failure(pipeline);
 
             update_exit_code(state, exit_code);
         }
@@ -173,6 +397,23 @@ impl<'a> ExecutionEngine<'a> {
         let was_short_circuited = last_executed_index < (stmt.pipelines.len() as i32 - 1);
         let inner_was_safe = state.errexit_safe.unwrap_or(false);
 
+        // The ERR trap runs before errexit gets to end the shell
+        if last_pipeline_reports
+            && is_reportable_failure(state, exit_code, was_short_circuited, last_pipeline_negated)
+            && has_trap(state, "ERR")
+        {
+            match self.run_trap(state, "ERR") {
+                Ok(trap) => {
+                    stdout.push_str(&trap.stdout);
+                    stderr.push_str(&trap.stderr);
+                }
+                Err(mut error) => {
+                    error.as_control_flow_mut().prepend_output(&stdout, &stderr);
+                    return Err(error);
+                }
+            }
+        }
+
         if should_trigger_errexit(state, exit_code, was_short_circuited, last_pipeline_negated)
             && !inner_was_safe
         {
@@ -199,6 +440,7 @@ impl<'a> ExecutionEngine<'a> {
             runs_in_subshell: false,
             time_pipeline: pipeline.timed,
             time_posix_format: pipeline.time_posix,
+            clock: state.clock.clone(),
         };
 
         // We need to pass state through the closure, but execute_pipeline
@@ -213,7 +455,22 @@ impl<'a> ExecutionEngine<'a> {
             &options,
             |cmd, stdin| {
                 let state = &mut *state_cell.borrow_mut();
-                self.execute_command(state, cmd, stdin)
+                let index = pipeline.commands.iter().position(|c| std::ptr::eq(c, cmd)).unwrap_or(0);
+                let count = pipeline.commands.len();
+                let run = |state: &mut InterpreterState| match runs_in_subshell(count, index, options.lastpipe) {
+                    true => self.run_pipeline_stage(state, cmd, stdin),
+                    false => self.execute_command(state, cmd, stdin),
+                };
+                // All but the last command write into the pipe, not to the
+                // fds `exec` opened
+                if index + 1 == count {
+                    return run(state);
+                }
+                let piped: &[i32] = match pipe_stderr.get(index) {
+                    Some(true) => &[1, 2],
+                    _ => &[1],
+                };
+                with_fds_set_aside(state, piped, run)
             },
         )?;
 
@@ -240,6 +497,8 @@ impl<'a> ExecutionEngine<'a> {
         cmd: &CommandNode,
         stdin: &str,
     ) -> Result<ExecResult, InterpreterError> {
+        self.check_interrupted()?;
+
         // Check command limit
         if let Some(msg) = check_command_limit(state, self.limits) {
             return Err(InterpreterError::ExecutionLimit(
@@ -250,7 +509,7 @@ impl<'a> ExecutionEngine<'a> {
             ));
         }
 
-        match cmd {
+        let result = match cmd {
             CommandNode::Simple(simple) => self.execute_simple_command(state, simple, stdin),
             CommandNode::Compound(compound) => {
                 self.execute_compound_command(state, compound, stdin)
@@ -260,6 +519,22 @@ impl<'a> ExecutionEngine<'a> {
                 execute_function_def(state, func_def, current_source.as_deref())
                     .map_err(InterpreterError::Exit)
             }
+            CommandNode::Coproc(coproc) => Ok(start_coprocess(state, coproc)),
+        }?;
+        self.check_output(state, result)
+    }
+
+    /// Fail with an execution limit error if more of `result`'s output
+    /// reaches the script's output than the output limit allows.
+    fn check_output(&self, state: &InterpreterState, result: ExecResult) -> Result<ExecResult, InterpreterError> {
+        match check_visible_output_limit(state, &result.stdout, &result.stderr, self.limits) {
+            Some(msg) => Err(InterpreterError::ExecutionLimit(ExecutionLimitError::after_output(
+                msg,
+                LimitType::Output,
+                result.stdout,
+                &result.stderr,
+            ))),
+            None => Ok(result),
         }
     }
 
@@ -268,264 +543,1568 @@ impl<'a> ExecutionEngine<'a> {
         &self,
         state: &mut InterpreterState,
         cmd: &SimpleCommandNode,
-        _stdin: &str,
+        stdin: &str,
     ) -> Result<ExecResult, InterpreterError> {
         // Set line number for $LINENO
         if let Some(line) = cmd.line {
-            state.current_line = line as u32;
+            state.current_line = state.line_offset + line as u32;
+        }
+
+        // $BASH_COMMAND is the command about to run; a trap handler keeps
+        // seeing the command that triggered it
+        if let (None, Some(text)) = (&state.running_trap, &cmd.source_text) {
+            state.env.insert("BASH_COMMAND".to_string(), text.clone());
         }
 
-        // For now, implement basic echo command for testing
-        // Full implementation would handle:
-        // 1. Process assignments
-        // 2. Expand command name and args
-        // 3. Check for builtins
-        // 4. Check for functions
-        // 5. Execute external command
-        // 6. Apply redirections
+        let mut debug = self.run_trap(state, "DEBUG")?;
+        debug.stderr.push_str(&self.pump_coprocesses(state, &[], false));
+        let result = self.execute_simple_command_body(state, cmd, stdin);
+        let result = self.deliver_pending_signals(state, result);
+        prepend_output(result, &debug.stdout, &debug.stderr)
+    }
 
-        // Get command name
-        let cmd_name = match &cmd.name {
-            Some(word) => {
-                let result = expand_word(state, word, None);
-                result.value
-            }
-            None => {
-                // Assignment-only command
-                // TODO: Process assignments
-                return Ok(ExecResult::ok());
-            }
+    fn execute_simple_command_body(
+        &self,
+        state: &mut InterpreterState,
+        cmd: &SimpleCommandNode,
+        stdin: &str,
+    ) -> Result<ExecResult, InterpreterError> {
+        let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+        let expand_fields = |state: &mut InterpreterState, word: &WordNode| {
+            let result = expand_word_with_fs_glob(state, word, Some(&subst), self.fs);
+            result.split_words.unwrap_or_else(|| vec![result.value])
         };
 
-        // Expand arguments
-        let mut args: Vec<String> = Vec::new();
-        for arg in &cmd.args {
-            let result = expand_word_with_glob(state, arg, None);
-            if let Some(words) = result.split_words {
-                args.extend(words);
-            } else {
-                args.push(result.value);
-            }
-        }
-
-        // Handle basic builtins
-        match cmd_name.as_str() {
-            "echo" => {
-                let output = if args.is_empty() {
-                    "\n".to_string()
-                } else {
-                    format!("{}\n", args.join(" "))
+        let name_word = match &cmd.name {
+            Some(word) => word,
+            None => {
+                // Assignment-only command: its status is that of the last
+                // command substitution in the values, or 0
+                let subst_status = Cell::new(0);
+                let tracked_subst = |body: &ScriptNode, state: &mut InterpreterState| {
+                    let result = self.command_substitution(state, body);
+                    subst_status.set(result.exit_code);
+                    result
                 };
-                Ok(ExecResult::new(output, String::new(), 0))
-            }
-            "true" | ":" => Ok(ExecResult::ok()),
-            "false" => Ok(ExecResult::new(String::new(), String::new(), 1)),
-            "exit" => {
-                let code = args.first()
-                    .and_then(|s| s.parse::<i32>().ok())
-                    .unwrap_or(state.last_exit_code);
-                Err(InterpreterError::Exit(ExitError::new(
-                    code,
-                    String::new(),
-                    String::new(),
-                )))
-            }
-            "export" => {
-                // Basic export implementation
-                for arg in &args {
-                    if let Some((name, value)) = arg.split_once('=') {
-                        state.env.insert(name.to_string(), value.to_string());
-                        if state.exported_vars.is_none() {
-                            state.exported_vars = Some(std::collections::HashSet::new());
-                        }
-                        state.exported_vars.as_mut().unwrap().insert(name.to_string());
-                    } else {
-                        // Just mark as exported
-                        if state.exported_vars.is_none() {
-                            state.exported_vars = Some(std::collections::HashSet::new());
-                        }
-                        state.exported_vars.as_mut().unwrap().insert(arg.clone());
-                    }
-                }
-                Ok(ExecResult::ok())
-            }
-            "cd" => {
-                let target = args.first()
-                    .map(|s| s.as_str())
-                    .or_else(|| state.env.get("HOME").map(|s| s.as_str()))
-                    .unwrap_or("/");
-
-                let new_cwd = if target.starts_with('/') {
-                    target.to_string()
-                } else {
-                    self.fs.resolve_path(&state.cwd, target)
+                let trace_prefix = state.options.xtrace.then(|| self.xtrace_prefix(state));
+                let expansion_stderr = RefCell::new(String::new());
+                let assigned = process_assignments(
+                    state,
+                    cmd,
+                    |state, word| {
+                        let result = expand_word(state, word, Some(&tracked_subst));
+                        expansion_stderr.borrow_mut().push_str(&result.stderr);
+                        result.value
+                    },
+                    |state, word| {
+                        let result = expand_word_with_fs_glob(state, word, Some(&tracked_subst), self.fs);
+                        expansion_stderr.borrow_mut().push_str(&result.stderr);
+                        result.split_words.unwrap_or_else(|| vec![result.value])
+                    },
+                );
+                let trace = match trace_prefix {
+                    Some(trace_prefix) => self.write_xtrace(state, &trace_prefix, &assigned.trace_lines),
+                    None => ExecResult::ok(),
                 };
-
-                if self.fs.is_dir(&new_cwd) {
-                    state.cwd = new_cwd.clone();
-                    state.env.insert("PWD".to_string(), new_cwd);
-                    Ok(ExecResult::ok())
-                } else {
-                    Ok(ExecResult::new(
-                        String::new(),
-                        format!("bash: cd: {}: No such file or directory\n", target),
-                        1,
-                    ))
+                let stderr = expansion_stderr.into_inner() + &trace.stderr;
+                prepend_output(take_unbound_variable(state).map(|_| ExecResult::ok()), &trace.stdout, &stderr)?;
+                prepend_output(self.check_assignment_sizes(state, cmd).map(|_| ExecResult::ok()), &trace.stdout, &stderr)?;
+                if let Some(error) = assigned.error {
+                    return prepend_output(Ok(error), &trace.stdout, &stderr);
                 }
+                let exit_code = subst_status.get();
+                // A bare `> file` still creates or truncates the file
+                let result = self.execute_with_redirections(state, "", &cmd.redirections, stdin, |_, _| {
+                    Ok(ExecResult::new(String::new(), String::new(), exit_code))
+                });
+                return prepend_output(result, &trace.stdout, &stderr);
             }
-            "pwd" => {
-                Ok(ExecResult::new(
-                    format!("{}\n", state.cwd),
-                    String::new(),
-                    0,
-                ))
+        };
+
+        // Prefix assignments (`FOO=1 cmd`) are bound and exported only for
+        // the duration of the command
+        let mut temp_assignments = HashMap::new();
+        let mut prefix = ExecResult::ok();
+        let saved_temp_exports = state.temp_exported_vars.clone();
+        if !cmd.assignments.is_empty() {
+            // Traced with PS4 as it was before the assignments
+            let trace_prefix = state.options.xtrace.then(|| self.xtrace_prefix(state));
+            let expansion_stderr = RefCell::new(String::new());
+            let assigned = process_assignments(
+                state,
+                cmd,
+                |state, word| {
+                    let result = expand_word(state, word, Some(&subst));
+                    expansion_stderr.borrow_mut().push_str(&result.stderr);
+                    result.value
+                },
+                expand_fields,
+            );
+            if let Some(trace_prefix) = trace_prefix {
+                prefix = self.write_xtrace(state, &trace_prefix, &assigned.trace_lines);
             }
-            "test" | "[" => {
-                // Basic test implementation - just return success for now
-                // Full implementation would evaluate conditions
-                Ok(ExecResult::ok())
+            prefix.stderr.insert_str(0, &expansion_stderr.into_inner());
+            temp_assignments = assigned.temp_assignments;
+            if let Err(error) = take_unbound_variable(state).and_then(|_| self.check_assignment_sizes(state, cmd)) {
+                restore_temp_assignments(state, temp_assignments);
+                state.temp_exported_vars = saved_temp_exports;
+                return prepend_output(Err(error), &prefix.stdout, &prefix.stderr);
             }
-            _ => {
-                // Unknown command - return error
-                Ok(ExecResult::new(
-                    String::new(),
-                    format!("bash: {}: command not found\n", cmd_name),
-                    127,
-                ))
+            if let Some(error) = assigned.error {
+                restore_temp_assignments(state, temp_assignments);
+                return prepend_output(Ok(error), &prefix.stdout, &prefix.stderr);
             }
+            prefix.stderr.push_str(&assigned.xtrace_output);
+            let exports = state.temp_exported_vars.get_or_insert_with(HashSet::new);
+            exports.extend(temp_assignments.keys().cloned());
         }
+
+        let result = self.execute_simple_command_with_words(state, cmd, name_word, stdin);
+
+        restore_temp_assignments(state, temp_assignments);
+        state.temp_exported_vars = saved_temp_exports;
+        prepend_output(result, &prefix.stdout, &prefix.stderr)
     }
 
-    /// Execute a compound command (if, for, while, etc.).
-    pub fn execute_compound_command(
+    fn execute_simple_command_with_words(
         &self,
         state: &mut InterpreterState,
-        compound: &CompoundCommandNode,
+        cmd: &SimpleCommandNode,
+        name_word: &WordNode,
         stdin: &str,
     ) -> Result<ExecResult, InterpreterError> {
-        match compound {
-            CompoundCommandNode::If(if_node) => {
-                // Build clauses for execute_if
-                let clauses: Vec<(Vec<&StatementNode>, Vec<&StatementNode>)> = if_node
-                    .clauses
-                    .iter()
-                    .map(|c| {
-                        (
-                            c.condition.iter().collect(),
-                            c.body.iter().collect(),
-                        )
-                    })
-                    .collect();
+        // Process substitutions become paths before the words are expanded
+        let mut subs = ProcessSubstitutions::default();
+        let run = |state: &mut InterpreterState, body: &ScriptNode, stdin: Option<&str>| {
+            with_fds_set_aside(state, &[1], |state| self.run_in_subshell_copy(state, body, stdin))
+        };
+        let raw_words: Vec<&WordNode> = std::iter::once(name_word).chain(cmd.args.iter()).collect();
+        let replaced = open_process_substitutions(state, &raw_words, self.fs, &mut subs, run);
+        let raw_words: Vec<&WordNode> = match &replaced {
+            Some(replaced) => replaced.iter().collect(),
+            None => raw_words,
+        };
 
-                let else_body: Option<Vec<&StatementNode>> =
-                    if_node.else_body.as_ref().map(|b| b.iter().collect());
+        // Expand the command name together with its arguments: an unquoted
+        // expansion in the name position may split into several words.
+        let mut words: Vec<String> = Vec::new();
+        let mut expansion_stderr = String::new();
+        let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+        for word in raw_words {
+            let result = expand_word_with_fs_glob(state, word, Some(&subst), self.fs);
+            expansion_stderr.push_str(&result.stderr);
+            if let Some(split) = result.split_words {
+                words.extend(split);
+            } else {
+                words.push(result.value);
+            }
+        }
 
-                let result = execute_if(
-                    state,
-                    &clauses,
-                    else_body.as_deref(),
-                    |state, stmt| {
-                        let res = self.execute_statement(state, stmt)?;
-                        Ok(ConditionResult {
-                            stdout: res.stdout,
-                            stderr: res.stderr,
-                            exit_code: res.exit_code,
-                        })
-                    },
-                    |state, stmt| self.execute_statement(state, stmt),
-                )?;
+        if let Err(error) = take_unbound_variable(state).and_then(|_| self.check_word_sizes(&words)) {
+            let result = self.finish_process_substitutions(state, subs, Err(error));
+            return prepend_output(result, "", &expansion_stderr);
+        }
 
-                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+        // Expansion errors and the trace are written by the shell itself,
+        // outside the command's redirections
+        let trace = match words.is_empty() {
+            true => ExecResult::ok(),
+            false => {
+                let parts: Vec<&str> = words.iter().map(String::as_str).collect();
+                self.xtrace(state, &[format_trace_line(&parts)])
             }
+        };
+        // A bare `exec` applies its redirections to the shell itself
+        if words.len() == 1 && words[0] == "exec" && !cmd.redirections.is_empty() {
+            let result = self.execute_exec_redirections(state, &cmd.redirections, stdin);
+            let result = self.finish_process_substitutions(state, subs, result);
+            return prepend_output(result, &trace.stdout, &(expansion_stderr + &trace.stderr));
+        }
 
-            CompoundCommandNode::For(for_node) => {
-                // Expand words
-                let mut words: Vec<String> = Vec::new();
-                if let Some(ref word_list) = for_node.words {
-                    for word in word_list {
-                        let result = expand_word_with_glob(state, word, None);
-                        if let Some(split) = result.split_words {
-                            words.extend(split);
-                        } else {
-                            words.push(result.value);
-                        }
-                    }
-                } else {
-                    // Default to positional parameters
-                    let argc: usize = state.env.get("#")
-                        .and_then(|s| s.parse().ok())
-                        .unwrap_or(0);
-                    for i in 1..=argc {
-                        if let Some(val) = state.env.get(&i.to_string()) {
-                            words.push(val.clone());
-                        }
-                    }
+        // Output of commands without redirections of their own goes through
+        // the fds `exec` opened. Functions, `eval` and `source` are made of
+        // commands that already did that.
+        let routes_output = cmd.redirections.is_empty()
+            && words.first().is_some_and(|name| {
+                !state.functions.contains_key(name) && !matches!(name.as_str(), "eval" | "source" | ".")
+            });
+        // The policy sees the files the redirections open before they are
+        // opened; the arguments it substitutes are what the command gets
+        let rewritten: RefCell<Option<Vec<String>>> = RefCell::new(None);
+        let allowed = Cell::new(false);
+        let check = |state: &mut InterpreterState, redirections: &[RedirectionNode], targets: &ExpandedRedirectTargets| {
+            let (cmd_name, args) = words.split_first()?;
+            self.policy.as_ref()?;
+            let redirections = self.policy_redirections(state, redirections, targets);
+            match self.consult_policy(state, cmd_name, args, &redirections) {
+                PolicyDecision::Allow => {}
+                PolicyDecision::Deny { stderr, exit_code } => {
+                    return Some(ExecResult::new(String::new(), stderr, exit_code));
                 }
+                PolicyDecision::Rewrite { args } => *rewritten.borrow_mut() = Some(args),
+            }
+            allowed.set(true);
+            None
+        };
+        let result = self.execute_with_checked_redirections(state, &cmd.redirections, stdin, check, |state, stdin| {
+            let Some((cmd_name, args)) = words.split_first() else {
+                return Ok(ExecResult::ok());
+            };
+            self.policy_checked.set(allowed.get());
+            match rewritten.borrow_mut().take() {
+                Some(args) => self.run_command(state, cmd_name, &args, &[], stdin, false, false, -1),
+                None => self.run_command(state, cmd_name, args, &[], stdin, false, false, -1),
+            }
+        });
+        let result = match routes_output {
+            true => self.route_through_persistent_fds(state, result),
+            false => result,
+        };
+        let result = self.finish_process_substitutions(state, subs, result);
+        prepend_output(result, &trace.stdout, &(expansion_stderr + &trace.stderr))
+    }
 
-                let body: Vec<&StatementNode> = for_node.body.iter().collect();
+    /// Fail with an execution limit error if an assignment of `cmd` left a
+    /// value or an array larger than the limits allow.
+    fn check_assignment_sizes(&self, state: &InterpreterState, cmd: &SimpleCommandNode) -> Result<(), InterpreterError> {
+        for assignment in &cmd.assignments {
+            let name = assignment.name.split('[').next().unwrap_or(&assignment.name);
+            if let Some(msg) = state.env.get(name).and_then(|value| check_string_limit(value, self.limits)) {
+                return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::simple(msg, LimitType::StringLength)));
+            }
+            if assignment.array.is_some() {
+                let len = get_array_indices(&state.env, name).len().max(get_assoc_array_keys(&state.env, name).len());
+                if let Some(msg) = check_array_limit(len, self.limits) {
+                    return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::simple(msg, LimitType::ArraySize)));
+                }
+            }
+        }
+        Ok(())
+    }
 
-                let result = execute_for(
-                    state,
-                    &for_node.variable,
-                    &words,
-                    &body,
-                    self.limits.max_iterations,
-                    |state, stmt| self.execute_statement(state, stmt),
-                )?;
+    /// Fail with an execution limit error if the expanded words of a
+    /// command are too many or too long.
+    fn check_word_sizes(&self, words: &[String]) -> Result<(), InterpreterError> {
+        if let Some(msg) = check_array_limit(words.len(), self.limits) {
+            return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::simple(msg, LimitType::ArraySize)));
+        }
+        if let Some(msg) = words.iter().find_map(|word| check_string_limit(word, self.limits)) {
+            return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::simple(msg, LimitType::StringLength)));
+        }
+        Ok(())
+    }
 
-                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+    /// Apply the redirections of a bare `exec` to the shell's fd table.
+    ///
+    /// When the policy rewrites it with arguments, the result is run as
+    /// `exec ARGS` with the redirections applied to that command instead.
+    fn execute_exec_redirections(
+        &self,
+        state: &mut InterpreterState,
+        redirections: &[RedirectionNode],
+        stdin: &str,
+    ) -> Result<ExecResult, InterpreterError> {
+        let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+        let expand = |state: &mut InterpreterState, word: &WordNode| expand_word(state, word, Some(&subst)).value;
+        let targets = pre_expand_redirect_targets(state, redirections, expand).targets;
+        take_unbound_variable(state)?;
+
+        if self.policy.is_some() {
+            let requests = self.policy_redirections(state, redirections, &targets);
+            match self.consult_policy(state, "exec", &[], &requests) {
+                PolicyDecision::Allow => {}
+                PolicyDecision::Rewrite { args } if args.is_empty() => {}
+                PolicyDecision::Deny { stderr, exit_code } => {
+                    return Ok(ExecResult::new(String::new(), stderr, exit_code));
+                }
+                PolicyDecision::Rewrite { args } => {
+                    let allowed = |_: &mut InterpreterState, _: &[RedirectionNode], _: &ExpandedRedirectTargets| None;
+                    return self.execute_with_checked_redirections(state, redirections, stdin, allowed, |state, stdin| {
+                        self.policy_checked.set(true);
+                        self.run_command(state, "exec", &args, &[], stdin, false, false, -1)
+                    });
+                }
             }
+        }
+        if let Some(error) = process_fd_variable_redirections(state, redirections, self.fs, expand) {
+            return Ok(error);
+        }
+        Ok(apply_exec_redirections(state, redirections, Some(&targets), self.fs, expand))
+    }
 
-            CompoundCommandNode::While(while_node) => {
-                let condition: Vec<&StatementNode> = while_node.condition.iter().collect();
-                let body: Vec<&StatementNode> = while_node.body.iter().collect();
-
-                let result = execute_while(
-                    state,
-                    &condition,
-                    &body,
-                    self.limits.max_iterations,
-                    |state, stmt| {
-                        let res = self.execute_statement(state, stmt)?;
-                        Ok(ConditionResult {
-                            stdout: res.stdout,
-                            stderr: res.stderr,
-                            exit_code: res.exit_code,
-                        })
-                    },
-                    |state, stmt| self.execute_statement(state, stmt),
-                )?;
+    /// Send a command's output, including output carried by a control flow
+    /// error, through the fds `exec` left open on stdout and stderr.
+    fn route_through_persistent_fds(
+        &self,
+        state: &InterpreterState,
+        result: Result<ExecResult, InterpreterError>,
+    ) -> Result<ExecResult, InterpreterError> {
+        match result {
+            Ok(result) => Ok(apply_persistent_fds(state, result, self.fs)),
+            Err(mut error) => {
+                let output = error.as_control_flow_mut();
+                let carried = ExecResult::new(
+                    std::mem::take(output.stdout_mut()),
+                    std::mem::take(output.stderr_mut()),
+                    0,
+                );
+                let routed = apply_persistent_fds(state, carried, self.fs);
+                *output.stdout_mut() = routed.stdout;
+                *output.stderr_mut() = routed.stderr;
+                Err(error)
+            }
+        }
+    }
 
-                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+    /// Run `body` with `redirections` in effect.
+    ///
+    /// Targets are expanded once, output files are opened (and noclobber
+    /// checked) before the body runs, input redirections replace `stdin`, and
+    /// the body's output - including output carried by a control flow error
+    /// such as `exit` - is routed to its destinations afterwards. The policy
+    /// sees the files the redirections open as a command called `name`.
+    pub fn execute_with_redirections<F>(
+        &self,
+        state: &mut InterpreterState,
+        name: &str,
+        redirections: &[RedirectionNode],
+        stdin: &str,
+        body: F,
+    ) -> Result<ExecResult, InterpreterError>
+    where
+        F: FnOnce(&mut InterpreterState, &str) -> Result<ExecResult, InterpreterError>,
+    {
+        let check = |state: &mut InterpreterState, redirections: &[RedirectionNode], targets: &ExpandedRedirectTargets| {
+            self.policy.as_ref()?;
+            let requests = self.policy_redirections(state, redirections, targets);
+            if requests.is_empty() {
+                return None;
             }
+            // There are no arguments to rewrite, so a rewrite allows
+            match self.consult_policy(state, name, &[], &requests) {
+                PolicyDecision::Deny { stderr, exit_code } => Some(ExecResult::new(String::new(), stderr, exit_code)),
+                PolicyDecision::Allow | PolicyDecision::Rewrite { .. } => None,
+            }
+        };
+        self.execute_with_checked_redirections(state, redirections, stdin, check, body)
+    }
 
-            CompoundCommandNode::Until(until_node) => {
-                let condition: Vec<&StatementNode> = until_node.condition.iter().collect();
-                let body: Vec<&StatementNode> = until_node.body.iter().collect();
+    /// Like `execute_with_redirections`, but `check` sees the expanded
+    /// redirection targets before any file is opened. When it returns a
+    /// result, that is the outcome and `body` doesn't run.
+    pub fn execute_with_checked_redirections<C, F>(
+        &self,
+        state: &mut InterpreterState,
+        redirections: &[RedirectionNode],
+        stdin: &str,
+        check: C,
+        body: F,
+    ) -> Result<ExecResult, InterpreterError>
+    where
+        C: FnOnce(&mut InterpreterState, &[RedirectionNode], &ExpandedRedirectTargets) -> Option<ExecResult>,
+        F: FnOnce(&mut InterpreterState, &str) -> Result<ExecResult, InterpreterError>,
+    {
+        if redirections.is_empty() {
+            return body(state, stdin);
+        }
 
-                let result = execute_until(
-                    state,
-                    &condition,
-                    &body,
-                    self.limits.max_iterations,
-                    |state, stmt| {
-                        let res = self.execute_statement(state, stmt)?;
-                        Ok(ConditionResult {
-                            stdout: res.stdout,
-                            stderr: res.stderr,
-                            exit_code: res.exit_code,
-                        })
-                    },
-                    |state, stmt| self.execute_statement(state, stmt),
-                )?;
+        // `< <(cmd)` and `> >(cmd)` redirect from/to the substitution's file
+        let mut subs = ProcessSubstitutions::default();
+        let run = |state: &mut InterpreterState, body: &ScriptNode, stdin: Option<&str>| {
+            with_fds_set_aside(state, &[1], |state| self.run_in_subshell_copy(state, body, stdin))
+        };
+        let replaced = open_redirection_process_substitutions(state, redirections, self.fs, &mut subs, run);
+        let redirections = replaced.as_deref().unwrap_or(redirections);
+        let result = self.execute_with_redirections_in_effect(state, redirections, stdin, check, body);
+        self.finish_process_substitutions(state, subs, result)
+    }
 
-                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
-            }
+    fn execute_with_redirections_in_effect<C, F>(
+        &self,
+        state: &mut InterpreterState,
+        redirections: &[RedirectionNode],
+        stdin: &str,
+        check: C,
+        body: F,
+    ) -> Result<ExecResult, InterpreterError>
+    where
+        C: FnOnce(&mut InterpreterState, &[RedirectionNode], &ExpandedRedirectTargets) -> Option<ExecResult>,
+        F: FnOnce(&mut InterpreterState, &str) -> Result<ExecResult, InterpreterError>,
+    {
+        let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+        let expand = |state: &mut InterpreterState, word: &WordNode| expand_word(state, word, Some(&subst)).value;
+        let targets = pre_expand_redirect_targets(state, redirections, expand).targets;
+        take_unbound_variable(state)?;
+        if let Some(denied) = check(state, redirections, &targets) {
+            return Ok(denied);
+        }
 
-            CompoundCommandNode::Case(_case_node) => {
-                // Case statement - TODO: implement full case matching
-                // For now, return success
-                Ok(ExecResult::ok())
+        if let Some(error) = process_fd_variable_redirections(state, redirections, self.fs, expand) {
+            return Ok(error);
+        }
+        if let Some(error) = pre_open_output_redirects(state, redirections, Some(&targets), self.fs, expand) {
+            return Ok(error);
+        }
+        let reading: Vec<i32> = redirections
+            .iter()
+            .enumerate()
+            .filter(|(_, redir)| redir.operator == RedirectionOperator::LessAnd && redir.fd.unwrap_or(0) == 0)
+            .filter_map(|(i, _)| targets.get(&i)?.trim_end_matches('-').parse().ok())
+            .collect();
+        let reads_file = redirections.iter().any(|redir| {
+            matches!(
+                redir.operator,
+                RedirectionOperator::Less | RedirectionOperator::LessAnd | RedirectionOperator::LessGreat
+            )
+        });
+        if reads_file {
+            self.run_pending_jobs(state);
+        }
+        let coproc_stderr = self.pump_coprocesses(state, &reading, false);
+        let stdin = match read_input_redirections(state, redirections, Some(&targets), self.fs, expand) {
+            Ok(Some(redirected)) => redirected,
+            Ok(None) => stdin.to_string(),
+            Err(error) => return prepend_output(Ok(error), "", &coproc_stderr),
+        };
+
+        // Inside the body, redirected streams no longer reach the fds `exec` opened
+        let set_aside = redirected_output_fds(redirections);
+        let result = with_fds_set_aside(state, &set_aside, |state| body(state, &stdin));
+        let result = match result {
+            Ok(result) => Ok(apply_redirections(state, result, redirections, Some(&targets), self.fs, expand)),
+            Err(mut error) => {
+                let output = error.as_control_flow_mut();
+                let carried = ExecResult::new(
+                    std::mem::take(output.stdout_mut()),
+                    std::mem::take(output.stderr_mut()),
+                    0,
+                );
+                let routed = apply_redirections(state, carried, redirections, Some(&targets), self.fs, expand);
+                *output.stdout_mut() = routed.stdout;
+                *output.stderr_mut() = routed.stderr;
+                Err(error)
+            }
+        };
+        prepend_output(result, "", &coproc_stderr)
+    }
+
+    /// Run a command by name: builtins and functions first, then PATH lookup.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_command(
+        &self,
+        state: &mut InterpreterState,
+        command_name: &str,
+        args: &[String],
+        quoted_args: &[bool],
+        stdin: &str,
+        skip_functions: bool,
+        use_default_path: bool,
+        stdin_source_fd: i32,
+    ) -> Result<ExecResult, InterpreterError> {
+        let run_command = |state: &mut InterpreterState,
+                           name: &str,
+                           args: &[String],
+                           quoted: &[bool],
+                           stdin: &str,
+                           skip_functions: bool,
+                           use_default_path: bool,
+                           stdin_source_fd: i32| {
+            self.run_command(
+                state,
+                name,
+                args,
+                quoted,
+                stdin,
+                skip_functions,
+                use_default_path,
+                stdin_source_fd,
+            )
+        };
+        // Read before dispatch, so `cd` is logged in the directory it left
+        let start = self.audit.as_ref().map(|_| (state.clock.peek(), state.cwd.clone()));
+        let rewritten;
+        let args = match self.policy.is_some() && !self.policy_checked.replace(false) {
+            true => match self.consult_policy(state, command_name, args, &[]) {
+                PolicyDecision::Allow => args,
+                PolicyDecision::Deny { stderr, exit_code } => {
+                    return Ok(ExecResult::new(String::new(), stderr, exit_code));
+                }
+                PolicyDecision::Rewrite { args } => {
+                    rewritten = args;
+                    &rewritten
+                }
+            },
+            false => args,
+        };
+        let called_function = Cell::new(false);
+        let call_function = |state: &mut InterpreterState,
+                             func: &FunctionDefNode,
+                             args: &[String],
+                             stdin: &str| {
+            called_function.set(true);
+            self.call_function(state, func, args, stdin)
+        };
+        let run_script = |state: &mut InterpreterState, script: &ScriptNode, stdin: &str| {
+            let mut reader = AliasReader::default();
+            execute_group(state, &script.statements, Some(stdin), |state, stmt| {
+                self.execute_read_statement(state, &mut reader, stmt)
+            })
+        };
+        let mut execute_statement = |state: &mut InterpreterState, stmt: &StatementNode| {
+            self.execute_statement(state, stmt)
+        };
+        let resolve_command = |state: &InterpreterState, name: &str, use_default_path: bool| {
+            self.resolve_command_path(state, name, use_default_path, &mut command_hash_table(state))
+        };
+        // Background jobs get to run while the shell sleeps or reads input
+        if matches!(command_name, "sleep" | "read" | "mapfile" | "readarray") {
+            self.run_pending_jobs(state);
+        }
+        // A coprocess runs when something reads its output or waits for it
+        let coproc_stderr = match command_name {
+            "read" | "mapfile" | "readarray" => {
+                let reading: Vec<i32> = input_fd_option(args).into_iter().collect();
+                self.pump_coprocesses(state, &reading, false)
+            }
+            "wait" => self.pump_coprocesses(state, &[], true),
+            _ => String::new(),
+        };
+        let mut dispatch_ctx = BuiltinDispatchContext {
+            state,
+            fs: self.fs,
+            max_nesting_depth: self.limits.max_recursion_depth,
+            run_command: &run_command,
+            call_function: &call_function,
+            run_script: &run_script,
+            execute_statement: &mut execute_statement,
+            resolve_command: &resolve_command,
+        };
+        if let Some(result) = dispatch_builtin(
+            &mut dispatch_ctx,
+            command_name,
+            args,
+            quoted_args,
+            stdin,
+            skip_functions,
+            use_default_path,
+            stdin_source_fd,
+        ) {
+            if let Some((start, cwd)) = start {
+                let kind = if called_function.get() { CommandKind::Function } else { CommandKind::Builtin };
+                self.audit_command(state, (start, &cwd), command_name, args, stdin, (kind, None), &result);
+            }
+            return prepend_output(result, "", &coproc_stderr);
+        }
+
+        let mut resolution = (CommandKind::NotFound, None);
+        let result = self.run_external_command(state, command_name, args, stdin, use_default_path, &mut resolution);
+        if let Some((start, cwd)) = start {
+            self.audit_command(state, (start, &cwd), command_name, args, stdin, resolution, &result);
+        }
+        result
+    }
+
+    /// Ask the policy about a command. A denial is reported to the audit sink.
+    fn consult_policy(
+        &self,
+        state: &InterpreterState,
+        command_name: &str,
+        args: &[String],
+        redirections: &[RedirectionRequest],
+    ) -> PolicyDecision {
+        let Some(policy) = &self.policy else {
+            return PolicyDecision::Allow;
+        };
+        let request = CommandRequest {
+            name: command_name.rsplit('/').next().unwrap_or(command_name),
+            invoked_as: command_name,
+            args,
+            cwd: &state.cwd,
+            redirections,
+        };
+        let decision = policy.check(&request);
+        if let (PolicyDecision::Deny { stderr, exit_code }, Some(_)) = (&decision, &self.audit) {
+            let result = Ok(ExecResult::new(String::new(), stderr.clone(), *exit_code));
+            let now = state.clock.peek();
+            let start = (now, state.cwd.as_str());
+            self.audit_command(state, start, command_name, args, "", (CommandKind::Denied, None), &result);
+        }
+        decision
+    }
+
+    /// The files `redirections` open, as the policy sees them. Duplicated
+    /// and closed descriptors and here-strings open no file.
+    fn policy_redirections(
+        &self,
+        state: &InterpreterState,
+        redirections: &[RedirectionNode],
+        targets: &ExpandedRedirectTargets,
+    ) -> Vec<RedirectionRequest> {
+        redirections
+            .iter()
+            .enumerate()
+            .filter_map(|(i, redirection)| {
+                let target = targets.get(&i)?;
+                let opens_file = match redirection.operator {
+                    RedirectionOperator::TLess => false,
+                    RedirectionOperator::GreatAnd | RedirectionOperator::LessAnd => {
+                        target != "-" && target.parse::<i32>().is_err()
+                    }
+                    _ => true,
+                };
+                opens_file.then(|| RedirectionRequest {
+                    operator: redirection.operator.to_string(),
+                    path: self.fs.resolve_path(&state.cwd, target),
+                })
+            })
+            .collect()
+    }
+
+    /// Report a finished command to the audit sink, with the time it
+    /// started and the directory it started in.
+    #[allow(clippy::too_many_arguments)]
+    fn audit_command(
+        &self,
+        state: &InterpreterState,
+        (start, cwd): (std::time::SystemTime, &str),
+        command_name: &str,
+        args: &[String],
+        stdin: &str,
+        (kind, path): (CommandKind, Option<String>),
+        result: &Result<ExecResult, InterpreterError>,
+    ) {
+        let Some(sink) = &self.audit else { return };
+        let (exit_code, stdout, stderr) = match result {
+            Ok(result) => (result.exit_code, result.stdout.len(), result.stderr.len()),
+            Err(error) => {
+                let exit_code = match error {
+                    InterpreterError::Exit(e) => e.exit_code,
+                    InterpreterError::Return(e) => e.exit_code,
+                    InterpreterError::Errexit(e) => e.exit_code,
+                    InterpreterError::ExecutionLimit(e) => e.exit_code(),
+                    _ => 1,
+                };
+                let output = error.as_control_flow();
+                (exit_code, output.stdout().len(), output.stderr().len())
+            }
+        };
+        sink(AuditEvent::Command(CommandEvent {
+            name: command_name.to_string(),
+            kind,
+            path,
+            argv: std::iter::once(command_name.to_string()).chain(args.iter().cloned()).collect(),
+            cwd: cwd.to_string(),
+            exit_code,
+            duration_ms: elapsed_ms(start, state.clock.peek()),
+            stdin_bytes: stdin.len(),
+            stdout_bytes: stdout,
+            stderr_bytes: stderr,
+        }));
+    }
+
+    /// Invoke a shell function with its own positional parameters and local
+    /// scope. Redirections on the definition apply to every call.
+    ///
+    /// The RETURN trap in effect when the body finishes runs while the
+    /// function is still on the call stack.
+    pub fn call_function(
+        &self,
+        state: &mut InterpreterState,
+        func: &FunctionDefNode,
+        args: &[String],
+        stdin: &str,
+    ) -> Result<ExecResult, InterpreterError> {
+        let saved_traps = enter_function_traps(state);
+        // The body's lines were numbered when the script defining it was parsed
+        let line_offset = std::mem::take(&mut state.line_offset);
+        let result = call_function(state, func, args, stdin, None, self.limits.max_recursion_depth, |state, stdin| {
+            let result = self.execute_with_redirections(state, &func.name, &func.redirections, stdin, |state, stdin| {
+                self.execute_compound_command(state, &func.body, stdin)
+            });
+            if !has_trap(state, "RETURN") {
+                return result;
+            }
+            // The handler sees the function's status in $?
+            match &result {
+                Ok(result) => update_exit_code(state, result.exit_code),
+                Err(InterpreterError::Return(e)) => update_exit_code(state, e.exit_code),
+                Err(_) => {}
+            }
+            let trap = self.run_trap(state, "RETURN");
+            match (result, trap) {
+                (Ok(mut result), Ok(trap)) => {
+                    result.stdout.push_str(&trap.stdout);
+                    result.stderr.push_str(&trap.stderr);
+                    Ok(result)
+                }
+                (Err(mut error), Ok(trap)) => {
+                    let output = error.as_control_flow_mut();
+                    output.stdout_mut().push_str(&trap.stdout);
+                    output.stderr_mut().push_str(&trap.stderr);
+                    Err(error)
+                }
+                (Ok(result), Err(mut error)) => {
+                    error.as_control_flow_mut().prepend_output(&result.stdout, &result.stderr);
+                    Err(error)
+                }
+                (Err(error), Err(_)) => Err(error),
+            }
+        });
+        state.line_offset = line_offset;
+        leave_function_traps(state, saved_traps);
+        result
+    }
+
+    /// Run the trap handler for `condition`, if one is set.
+    pub fn run_trap(&self, state: &mut InterpreterState, condition: &str) -> Result<ExecResult, InterpreterError> {
+        run_trap(state, condition, |state, stmt| self.execute_statement(state, stmt))
+    }
+
+    /// Run the EXIT trap of the top-level shell and fold its output and
+    /// status into the script's final `result`.
+    pub fn run_exit_trap(&self, state: &mut InterpreterState, mut result: ExecResult) -> ExecResult {
+        let trap = run_exit_trap(state, result.exit_code, |state, stmt| self.execute_statement(state, stmt));
+        result.stdout.push_str(&trap.stdout);
+        result.stderr.push_str(&trap.stderr);
+        result.exit_code = trap.exit_code;
+        result
+    }
+
+    /// Deliver the signals `kill` sent during the last command.
+    ///
+    /// A signal for the shell itself runs its trap handler, is dropped if
+    /// ignored, and otherwise ends the shell with status 128+signal. A
+    /// signal for a job that trapped it runs the job's handler in a subshell;
+    /// the job then finishes with the handler's status and output.
+    pub fn deliver_pending_signals(
+        &self,
+        state: &mut InterpreterState,
+        result: Result<ExecResult, InterpreterError>,
+    ) -> Result<ExecResult, InterpreterError> {
+        if state.pending_signals.is_empty() {
+            return result;
+        }
+        let mut result = result?;
+        for (pid, signal) in std::mem::take(&mut state.pending_signals) {
+            let name = signal_name(signal).unwrap_or_default();
+            if pid != state.bash_pid {
+                let Some(index) = state.jobs.iter().position(|job| job.pid == pid) else {
+                    continue;
+                };
+                let Ok(handler) = crate::parser::parse(&state.jobs[index].traps[name]) else {
+                    continue;
+                };
+                let trap = self.run_in_subshell_copy(state, &handler, None);
+                if let Some(job) = state.jobs.iter_mut().find(|job| job.pid == pid) {
+                    job.stdout.push_str(&trap.stdout);
+                    job.stderr.push_str(&trap.stderr);
+                    job.status = JobStatus::Done(trap.exit_code);
+                }
+                continue;
+            }
+            match state.traps.get(name) {
+                Some(handler) if handler.is_empty() => {}
+                Some(_) => {
+                    let trap = self.run_trap(state, name).map_err(|mut error| {
+                        error.as_control_flow_mut().prepend_output(&result.stdout, &result.stderr);
+                        error
+                    })?;
+                    result.stdout.push_str(&trap.stdout);
+                    result.stderr.push_str(&trap.stderr);
+                }
+                None if is_fatal_by_default(signal) => {
+                    return Err(InterpreterError::Exit(ExitError::new(
+                        128 + signal,
+                        result.stdout,
+                        result.stderr,
+                    )));
+                }
+                None => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Write `set -x` trace lines, each behind the expanded PS4, to the
+    /// descriptor named by `BASH_XTRACEFD` (stderr by default). Lines for the
+    /// shell's own stdout or stderr are returned, to be put ahead of the
+    /// traced command's output.
+    fn xtrace(&self, state: &mut InterpreterState, lines: &[String]) -> ExecResult {
+        if !state.options.xtrace || lines.is_empty() {
+            return ExecResult::ok();
+        }
+        let prefix = self.xtrace_prefix(state);
+        self.write_xtrace(state, &prefix, lines)
+    }
+
+    /// Write trace lines behind an already expanded PS4.
+    fn write_xtrace(&self, state: &InterpreterState, prefix: &str, lines: &[String]) -> ExecResult {
+        let text: String = lines.iter().map(|line| format!("{}{}\n", prefix, line)).collect();
+
+        let fd = state.env.get("BASH_XTRACEFD").and_then(|fd| fd.parse::<i32>().ok()).unwrap_or(2);
+        match state.file_descriptors.as_ref().and_then(|fds| fds.get(&fd)) {
+            Some(FileDescriptor::File(path)) => {
+                let _ = self.fs.append_file(path, &text);
+                ExecResult::ok()
+            }
+            Some(FileDescriptor::PipeWrite(pipe)) => {
+                pipe.write(&text);
+                ExecResult::ok()
+            }
+            Some(FileDescriptor::Dup(1)) => ExecResult::new(text, String::new(), 0),
+            _ if fd == 1 => ExecResult::new(text, String::new(), 0),
+            _ => ExecResult::new(String::new(), text, 0),
+        }
+    }
+
+    /// Expand PS4 for a trace line. It is decoded like a prompt and then
+    /// goes through parameter, command and arithmetic expansion, without
+    /// being traced itself or changing `$?`.
+    fn xtrace_prefix(&self, state: &mut InterpreterState) -> String {
+        let env = state.env.clone();
+        let ps4 = get_xtrace_prefix_expanded(&env, |ps4| {
+            let decoded = expand_prompt(state, ps4);
+            let word = crate::parser::Parser::new().parse_word_from_string(&decoded, false, false, false, true);
+            let saved_status = state.last_exit_code;
+            state.options.xtrace = false;
+            let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+            let expanded = expand_word(state, &word, Some(&subst)).value;
+            state.options.xtrace = true;
+            update_exit_code(state, saved_status);
+            Ok(expanded)
+        });
+        xtrace_prefix_for_level(&ps4, state.indirection_level)
+    }
+
+    /// Run the body of a `$(...)` or backtick substitution.
+    ///
+    /// The body runs in a copy of the shell state, like a subshell, so its
+    /// variable, function and directory changes are discarded; `$?` is set to
+    /// its exit status. `$(< file)` reads the file without running a command.
+    pub fn command_substitution(&self, state: &mut InterpreterState, body: &ScriptNode) -> ExecResult {
+        let result = if let Some(target) = get_file_read_shorthand(body) {
+            let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+            let target = expand_word(state, target, Some(&subst)).value;
+            match self.fs.read_file(&self.fs.resolve_path(&state.cwd, &target)) {
+                Ok(content) => ExecResult::new(content, String::new(), 0),
+                Err(_) => ExecResult::failure(format!("bash: {}: No such file or directory\n", target)),
+            }
+        } else {
+            // Without inherit_errexit, set -e is off inside the substitution
+            let errexit = state.options.errexit;
+            state.options.errexit &= state.shopt_options.inherit_errexit || state.options.posix;
+            // The body was parsed on its own; number its lines from this one
+            let line_offset = std::mem::replace(&mut state.line_offset, state.current_line.saturating_sub(1));
+            let result = with_fds_set_aside(state, &[1], |state| self.run_in_subshell_copy(state, body, None));
+            state.line_offset = line_offset;
+            state.options.errexit = errexit;
+            result
+        };
+        update_exit_code(state, result.exit_code);
+        result
+    }
+
+    /// Run `body` in a copy of the shell state, as a subshell whose changes
+    /// are discarded.
+    fn run_in_subshell_copy(&self, state: &mut InterpreterState, body: &ScriptNode, stdin: Option<&str>) -> ExecResult {
+        let mut subshell_state = state.clone();
+        subshell_state.indirection_level += 1;
+        let result = execute_subshell(&mut subshell_state, &body.statements, stdin, |state, stmt| {
+            self.execute_statement(state, stmt)
+        });
+        // Work done inside the subshell still counts against the limits
+        state.command_count = subshell_state.command_count;
+        state.next_virtual_pid = subshell_state.next_virtual_pid;
+        subshell_exec_result(result)
+    }
+
+    /// Run one stage of a pipeline in a copy of the shell state, as bash
+    /// runs it in a subshell: `exit`, `cd` and assignments in the stage end
+    /// with it. Only safety limits escape.
+    fn run_pipeline_stage(
+        &self,
+        state: &mut InterpreterState,
+        cmd: &CommandNode,
+        stdin: &str,
+    ) -> Result<ExecResult, InterpreterError> {
+        let mut stage_state = state.clone();
+        prepare_subshell(&mut stage_state, None);
+        let result = match self.execute_command(&mut stage_state, cmd, stdin) {
+            Ok(result) => result,
+            Err(InterpreterError::ExecutionLimit(e)) => {
+                state.command_count = stage_state.command_count;
+                return Err(InterpreterError::ExecutionLimit(e));
+            }
+            Err(InterpreterError::Exit(e)) => ExecResult::new(e.stdout, e.stderr, e.exit_code),
+            Err(InterpreterError::Return(e)) => ExecResult::new(e.stdout, e.stderr, e.exit_code),
+            Err(InterpreterError::Errexit(e)) => ExecResult::new(e.stdout, e.stderr, e.exit_code),
+            Err(InterpreterError::Nounset(e)) => ExecResult::new(e.stdout, e.stderr, 1),
+            Err(InterpreterError::Break(e)) => ExecResult::new(e.stdout, e.stderr, 0),
+            Err(InterpreterError::Continue(e)) => ExecResult::new(e.stdout, e.stderr, 0),
+            Err(InterpreterError::SubshellExit(e)) => ExecResult::new(e.stdout, e.stderr, 0),
+            Err(e) => ExecResult::failure(format!("{}\n", e)),
+        };
+        let mut result = self.run_exit_trap(&mut stage_state, result);
+        let (stdout, stderr) = drain_jobs(&mut stage_state, &mut |state, stmt| self.execute_statement(state, stmt));
+        result.stdout.push_str(&stdout);
+        result.stderr.push_str(&stderr);
+        // Work done inside the stage still counts against the limits
+        state.command_count = stage_state.command_count;
+        state.next_virtual_pid = stage_state.next_virtual_pid;
+        Ok(result)
+    }
+
+    /// Run a coprocess command in `shell`, a copy of the state it was
+    /// started from, on the input written to it since its last run.
+    fn run_coprocess(&self, shell: &mut InterpreterState, command: &StatementNode, stdin: &str) -> ExecResult {
+        let result = execute_subshell(shell, std::slice::from_ref(command), Some(stdin), |state, stmt| {
+            self.execute_statement(state, stmt)
+        });
+        subshell_exec_result(result)
+    }
+
+    /// Run the coprocesses a command needs: those it reads from through one
+    /// of the `reading` fds, or with `waiting`, all of them. Returns their stderr.
+    fn pump_coprocesses(&self, state: &mut InterpreterState, reading: &[i32], waiting: bool) -> String {
+        if state.coprocs.is_empty() {
+            return String::new();
+        }
+        pump_coprocesses(state, reading, waiting, |shell, command, stdin| self.run_coprocess(shell, command, stdin))
+    }
+
+    /// Run the coprocesses still waiting for input once the script is done,
+    /// as closing its end of their pipes would. Returns their stderr.
+    pub fn finish_coprocesses(&self, state: &mut InterpreterState) -> String {
+        self.pump_coprocesses(state, &[], true)
+    }
+
+    /// Start `stmt &` as a virtual job. The job doesn't run until the shell
+    /// waits for it or reaches a point where it could see its effects (see
+    /// `run_pending_jobs`), so `kill` can still cancel it; its output is then
+    /// held in the job table until collected, so it never interleaves with
+    /// foreground output.
+    fn run_background_job(&self, state: &mut InterpreterState, stmt: &StatementNode) -> ExecResult {
+        let mut foreground = stmt.clone();
+        foreground.background = false;
+        let command = stmt.source_text.as_deref().unwrap_or_default();
+        defer_job(state, command, foreground);
+        update_exit_code(state, 0);
+        ExecResult::ok()
+    }
+
+    /// Run the background jobs that haven't run yet, before the shell sleeps,
+    /// reads a file or fd, or checks a loop condition again: points where it
+    /// could notice what they did.
+    fn run_pending_jobs(&self, state: &mut InterpreterState) {
+        if state.jobs.iter().any(|job| job.pending.is_some()) {
+            run_pending_jobs(state, &mut |state, stmt| self.execute_statement(state, stmt));
+        }
+    }
+
+    /// Feed the output written to `>(...)` files to their commands and remove
+    /// the files backing the substitutions of a finished command.
+    fn finish_process_substitutions(
+        &self,
+        state: &mut InterpreterState,
+        mut subs: ProcessSubstitutions,
+        result: Result<ExecResult, InterpreterError>,
+    ) -> Result<ExecResult, InterpreterError> {
+        if subs.is_empty() {
+            return result;
+        }
+        let input_stderr = std::mem::take(&mut subs.stderr);
+        let run = |state: &mut InterpreterState, body: &ScriptNode, stdin: Option<&str>| {
+            self.run_in_subshell_copy(state, body, stdin)
+        };
+        let output = close_process_substitutions(state, subs, self.fs, run);
+        match result {
+            Ok(mut result) => {
+                result.stdout.push_str(&output.stdout);
+                result.stderr = format!("{}{}{}", input_stderr, result.stderr, output.stderr);
+                Ok(result)
+            }
+            Err(mut error) => {
+                let flow = error.as_control_flow_mut();
+                flow.prepend_output("", &input_stderr);
+                flow.stdout_mut().push_str(&output.stdout);
+                flow.stderr_mut().push_str(&output.stderr);
+                Err(error)
+            }
+        }
+    }
+
+    /// Resolve a command through PATH and the hash table, then run it from the registry.
+    /// Find `command_name` the way running it would: a path is checked as
+    /// given, any other name is searched for in PATH, or in the default PATH
+    /// with `use_default_path`.
+    pub fn resolve_command_path(
+        &self,
+        state: &InterpreterState,
+        command_name: &str,
+        use_default_path: bool,
+        hash_table: &mut CommandHashTable,
+    ) -> ResolveCommandResult {
+        let (registry, command_fs) = match (&self.registry, &self.command_fs) {
+            (Some(registry), Some(fs)) => (registry, fs),
+            _ => return ResolveCommandResult::NotFound { path: None },
+        };
+        let handle = tokio::runtime::Handle::current();
+        let path_override = if use_default_path {
+            Some(crate::interpreter::command_resolution::DEFAULT_PATH)
+        } else {
+            None
+        };
+        tokio::task::block_in_place(|| {
+            handle.block_on(async {
+                let usr_bin_exists = command_fs.exists("/usr/bin").await;
+                resolve_command(
+                    command_fs,
+                    &state.cwd,
+                    state.env.get("PATH").map(|s| s.as_str()),
+                    Some(hash_table),
+                    command_name,
+                    path_override,
+                    |name| registry.contains(name),
+                    usr_bin_exists,
+                )
+                .await
+            })
+        })
+    }
+
+    fn run_external_command(
+        &self,
+        state: &mut InterpreterState,
+        command_name: &str,
+        args: &[String],
+        stdin: &str,
+        use_default_path: bool,
+        resolution: &mut (CommandKind, Option<String>),
+    ) -> Result<ExecResult, InterpreterError> {
+        let (Some(registry), Some(_)) = (&self.registry, &self.command_fs) else {
+            return Ok(command_not_found(command_name));
+        };
+
+        let mut hash_table = command_hash_table(state);
+        let resolved = self.resolve_command_path(state, command_name, use_default_path, &mut hash_table);
+
+        let (path, is_script) = match resolved {
+            ResolveCommandResult::Command { path } => (path, false),
+            ResolveCommandResult::Script { path } => (path, true),
+            ResolveCommandResult::NotFound { path: Some(_) } => {
+                return Ok(ExecResult::new(
+                    String::new(),
+                    format!("bash: {}: No such file or directory\n", command_name),
+                    127,
+                ));
+            }
+            ResolveCommandResult::NotFound { path: None } => {
+                return Ok(command_not_found(command_name));
+            }
+            ResolveCommandResult::PermissionDenied { path } => {
+                let shown = if command_name.contains('/') { command_name } else { path.as_str() };
+                return Ok(ExecResult::new(
+                    String::new(),
+                    format!("bash: {}: Permission denied\n", shown),
+                    126,
+                ));
+            }
+            ResolveCommandResult::IsDirectory { .. } => {
+                return Ok(ExecResult::new(
+                    String::new(),
+                    format!("bash: {}: Is a directory\n", command_name),
+                    126,
+                ));
+            }
+        };
+
+        *resolution = (if is_script { CommandKind::Script } else { CommandKind::Registry }, Some(path.clone()));
+
+        // Remember where the command was found, like bash's `hash`
+        if !command_name.contains('/') {
+            hash_table.insert(command_name, &path);
+        }
+        state.hash_table = Some(
+            hash_table
+                .entries()
+                .map(|(name, path)| (name.to_string(), path.to_string()))
+                .collect(),
+        );
+
+        // Piped input wins; otherwise commands inside a redirected group or
+        // loop see what's left of the group's stdin. A command that reads it
+        // uses it up, while one that doesn't (`ls`, `mkdir`) leaves it for
+        // the next `read`.
+        let from_group = stdin.is_empty() && state.group_stdin.is_some();
+        let mut effective_stdin = if from_group {
+            state.group_stdin.clone().unwrap_or_default()
+        } else {
+            stdin.to_string()
+        };
+
+        let resolved_name = path.rsplit('/').next().unwrap_or(command_name);
+        let result = if is_script {
+            self.run_script_file(state, command_name, &path, args, &mut effective_stdin)
+        } else {
+            match registry.get(resolved_name) {
+                Some(command) => {
+                    let input = if self.reads_stdin(state, command.stdin_use(), args) {
+                        std::mem::take(&mut effective_stdin)
+                    } else {
+                        effective_stdin.clone()
+                    };
+                    self.run_registry_command(state, command, args.to_vec(), input)
+                }
+                None => Ok(command_not_found(command_name)),
+            }
+        };
+        if from_group {
+            state.group_stdin = Some(effective_stdin);
+        }
+        result
+    }
+
+    /// Whether a registry command reads its standard input, as it reports
+    /// through `Command::stdin_use`. Filters like `cat` and `grep` read it
+    /// unless an operand names a file, and `-` always means stdin.
+    fn reads_stdin(&self, state: &InterpreterState, stdin_use: StdinUse, args: &[String]) -> bool {
+        match stdin_use {
+            StdinUse::Ignored => return false,
+            StdinUse::Always => return true,
+            StdinUse::Filter => {}
+        }
+        let mut operands = Vec::new();
+        let mut options_done = false;
+        for arg in args {
+            if !options_done && arg == "--" {
+                options_done = true;
+            } else if options_done || arg == "-" || !arg.starts_with('-') {
+                operands.push(arg.as_str());
+            }
+        }
+        operands.contains(&"-")
+            || !operands
+                .iter()
+                .any(|operand| self.fs.exists(&self.fs.resolve_path(&state.cwd, operand)))
+    }
+
+    /// Run a registered command with the shell's cwd and exported variables.
+    ///
+    /// A `sleep` inside the command is cut short when the script is
+    /// interrupted, which is reported once the command returns.
+    fn run_registry_command(
+        &self,
+        state: &mut InterpreterState,
+        command: &dyn Command,
+        args: Vec<String>,
+        stdin: String,
+    ) -> Result<ExecResult, InterpreterError> {
+        let command_fs = match &self.command_fs {
+            Some(fs) => fs.clone(),
+            None => return Ok(command_not_found(command.name())),
+        };
+        let clock = if self.deadline.is_some() || self.cancellation.is_some() {
+            let (deadline, cancellation) = (self.deadline, self.cancellation.clone());
+            state.clock.with_interrupt(move || is_interrupted(deadline, cancellation.as_ref()))
+        } else {
+            state.clock.clone()
+        };
+        let ctx = CommandContext {
+            args,
+            stdin,
+            cwd: state.cwd.clone(),
+            env: build_exported_env(state),
+            fs: command_fs,
+            exec_fn: self.exec_fn.clone(),
+            fetch_fn: self.fetch_fn.clone(),
+            clock: Some(clock),
+            random: Some(state.random.clone()),
+        };
+        if let Some(nesting) = &self.nesting {
+            nesting.enter(state.command_count, state.script_depth, &state.captured_fds);
+        }
+        let handle = tokio::runtime::Handle::current();
+        let result = tokio::task::block_in_place(|| handle.block_on(command.execute(ctx)));
+        if let Some(nesting) = &self.nesting {
+            state.command_count = nesting.command_count();
+        }
+        let result = ExecResult::new(result.stdout, result.stderr, result.exit_code);
+        self.check_interrupted().map_err(|mut error| {
+            error.as_control_flow_mut().prepend_output(&result.stdout, &result.stderr);
+            error
+        })?;
+        Ok(result)
+    }
+
+    /// Run an executable file from the virtual filesystem.
+    ///
+    /// The shebang picks the interpreter: shell scripts (no shebang, `bash`
+    /// or `sh`) run in a child shell, while interpreters found in the
+    /// registry (`#!/usr/bin/env jq -f`, `#!/bin/awk -f`, ...) get the
+    /// shebang arguments, the script path and then `args`.
+    fn run_script_file(
+        &self,
+        state: &mut InterpreterState,
+        command_name: &str,
+        path: &str,
+        args: &[String],
+        stdin: &mut String,
+    ) -> Result<ExecResult, InterpreterError> {
+        // Like the kernel, hand the interpreter the path the script was run by
+        let script_name = if command_name.contains('/') { command_name } else { path };
+        let content = match self.fs.read_file(path) {
+            Ok(content) => content,
+            Err(_) => {
+                return Ok(ExecResult::new(
+                    String::new(),
+                    format!("bash: {}: Permission denied\n", script_name),
+                    126,
+                ));
+            }
+        };
+
+        let shebang: Vec<&str> = parse_shebang(&content)
+            .map(|line| line.split_whitespace().collect())
+            .unwrap_or_default();
+        let mut interpreter = shebang.as_slice();
+        let via_env = interpreter.first().is_some_and(|program| program.rsplit('/').next() == Some("env"));
+        if via_env {
+            interpreter = &interpreter[1..];
+            while interpreter.first().is_some_and(|arg| arg.starts_with('-')) {
+                interpreter = &interpreter[1..];
+            }
+        }
+
+        let (program, interpreter_args) = match interpreter.split_first() {
+            Some((program, rest)) => (*program, rest),
+            None => return self.run_shell_script(state, script_name, &content, args, stdin),
+        };
+        let name = program.rsplit('/').next().unwrap_or(program);
+        if name == "bash" || name == "sh" {
+            return self.run_shell_script(state, script_name, &content, args, stdin);
+        }
+
+        match self.registry.as_ref().and_then(|registry| registry.get(name)) {
+            Some(command) => {
+                let mut command_args: Vec<String> = interpreter_args.iter().map(|arg| arg.to_string()).collect();
+                command_args.push(script_name.to_string());
+                command_args.extend(args.iter().cloned());
+                self.run_registry_command(state, command, command_args, std::mem::take(stdin))
+            }
+            None if via_env => Ok(ExecResult::new(
+                String::new(),
+                format!("/usr/bin/env: '{}': No such file or directory\n", program),
+                127,
+            )),
+            None => Ok(ExecResult::new(
+                String::new(),
+                format!("bash: {}: cannot execute: required file not found\n", script_name),
+                127,
+            )),
+        }
+    }
+
+    /// Run a shell script in a child shell that sees only the exported
+    /// variables, with `$0` set to the script and its own positional args.
+    /// What the script leaves of `stdin` unread is left in it.
+    fn run_shell_script(
+        &self,
+        state: &mut InterpreterState,
+        script_name: &str,
+        content: &str,
+        args: &[String],
+        stdin: &mut String,
+    ) -> Result<ExecResult, InterpreterError> {
+        if state.script_depth >= self.limits.max_recursion_depth {
+            return Err(InterpreterError::ExecutionLimit(ExecutionLimitError::simple(
+                format!(
+                    "{}: maximum recursion depth ({}) exceeded",
+                    script_name, self.limits.max_recursion_depth
+                ),
+                LimitType::Recursion,
+            )));
+        }
+        let ast = match crate::parser::parse(content) {
+            Ok(ast) => ast,
+            Err(e) => {
+                return Ok(ExecResult::new(
+                    String::new(),
+                    format!("bash: {}: syntax error: {}\n", script_name, e),
+                    2,
+                ));
+            }
+        };
+
+        let env = build_exported_env(state);
+        let mut child = InterpreterState {
+            exported_vars: Some(env.keys().cloned().collect()),
+            env,
+            cwd: state.cwd.clone(),
+            script_depth: state.script_depth + 1,
+            command_count: state.command_count,
+            next_virtual_pid: state.next_virtual_pid,
+            start_time: state.clock.peek_unix_secs(),
+            clock: state.clock.clone(),
+            random: state.random.clone(),
+            identity: state.identity.clone(),
+            bash_pid: state.bash_pid,
+            captured_fds: state.captured_fds.clone(),
+            ..Default::default()
+        };
+        child.env.insert("PWD".to_string(), state.cwd.clone());
+        child.env.insert("0".to_string(), script_name.to_string());
+        child.env.insert("#".to_string(), args.len().to_string());
+        child.env.insert("@".to_string(), args.join(" "));
+        for (i, arg) in args.iter().enumerate() {
+            child.env.insert((i + 1).to_string(), arg.clone());
+        }
+        if !stdin.is_empty() {
+            child.group_stdin = Some(std::mem::take(stdin));
+        }
+
+        let result = self.execute_script(&mut child, &ast);
+        *stdin = child.group_stdin.take().unwrap_or_default();
+        // Work done by the script still counts against the limits
+        state.command_count = child.command_count;
+        state.next_virtual_pid = child.next_virtual_pid;
+        let mut result = match result {
+            Ok(result) => self.run_exit_trap(&mut child, result),
+            Err(InterpreterError::Exit(e)) => {
+                self.run_exit_trap(&mut child, ExecResult::new(e.stdout, e.stderr, e.exit_code))
+            }
+            Err(InterpreterError::ExecutionLimit(e)) => return Err(InterpreterError::ExecutionLimit(e)),
+            // `return` at the top level of a script ends it like `exit`
+            Err(InterpreterError::Return(e)) => {
+                self.run_exit_trap(&mut child, ExecResult::new(e.stdout, e.stderr, e.exit_code))
+            }
+            Err(e) => self.run_exit_trap(&mut child, ExecResult::new(String::new(), format!("{}\n", e), 1)),
+        };
+        let (stdout, stderr) = drain_jobs(&mut child, &mut |state, stmt| self.execute_statement(state, stmt));
+        result.stdout.push_str(&stdout);
+        result.stderr.push_str(&stderr);
+        Ok(result)
+    }
+
+    /// Execute a compound command (if, for, while, etc.).
+    pub fn execute_compound_command(
+        &self,
+        state: &mut InterpreterState,
+        compound: &CompoundCommandNode,
+        stdin: &str,
+    ) -> Result<ExecResult, InterpreterError> {
+        self.execute_with_redirections(state, compound.keyword(), compound.redirections(), stdin, |state, stdin| {
+            match compound {
+                CompoundCommandNode::Subshell(_) | CompoundCommandNode::Group(_) => {
+                    self.execute_compound_body(state, compound, stdin)
+                }
+                _ => {
+                    // Piped or redirected input is shared by every command in
+                    // the body (`producer | while read line; do ...; done`)
+                    let saved = prepare_group(state, Some(stdin));
+                    let result = self.execute_compound_body(state, compound, stdin);
+                    saved.restore(state);
+                    result
+                }
+            }
+        })
+    }
+
+    /// Expand the word list of a `for` or `select` loop; without one, the
+    /// loop runs over the positional parameters.
+    fn expand_loop_words(&self, state: &mut InterpreterState, word_list: Option<&[WordNode]>) -> Vec<String> {
+        let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+        let mut words: Vec<String> = Vec::new();
+        if let Some(word_list) = word_list {
+            for word in word_list {
+                let result = expand_word_with_fs_glob(state, word, Some(&subst), self.fs);
+                if let Some(split) = result.split_words {
+                    words.extend(split);
+                } else {
+                    words.push(result.value);
+                }
+            }
+        } else {
+            let argc: usize = state.env.get("#")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0);
+            for i in 1..=argc {
+                if let Some(val) = state.env.get(&i.to_string()) {
+                    words.push(val.clone());
+                }
+            }
+        }
+        words
+    }
+
+    fn execute_compound_body(
+        &self,
+        state: &mut InterpreterState,
+        compound: &CompoundCommandNode,
+        stdin: &str,
+    ) -> Result<ExecResult, InterpreterError> {
+        let subst = |body: &ScriptNode, state: &mut InterpreterState| self.command_substitution(state, body);
+        match compound {
+            CompoundCommandNode::If(if_node) => {
+                // Build clauses for execute_if
+                let clauses: Vec<(Vec<&StatementNode>, Vec<&StatementNode>)> = if_node
+                    .clauses
+                    .iter()
+                    .map(|c| {
+                        (
+                            c.condition.iter().collect(),
+                            c.body.iter().collect(),
+                        )
+                    })
+                    .collect();
+
+                let else_body: Option<Vec<&StatementNode>> =
+                    if_node.else_body.as_ref().map(|b| b.iter().collect());
+
+                let result = execute_if(
+                    state,
+                    &clauses,
+                    else_body.as_deref(),
+                    |state, stmt| {
+                        let res = self.execute_statement(state, stmt)?;
+                        Ok(ConditionResult {
+                            stdout: res.stdout,
+                            stderr: res.stderr,
+                            exit_code: res.exit_code,
+                        })
+                    },
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
+
+                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+            }
+
+            CompoundCommandNode::For(for_node) => {
+                let words = self.expand_loop_words(state, for_node.words.as_deref());
+                take_unbound_variable(state)?;
+                let body: Vec<&StatementNode> = for_node.body.iter().collect();
+                let header = trace_loop_header("for", &for_node.variable, &words);
+
+                let result = execute_for(
+                    state,
+                    &for_node.variable,
+                    &words,
+                    &body,
+                    self.limits,
+                    |state| self.xtrace(state, std::slice::from_ref(&header)),
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
+
+                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+            }
+
+            CompoundCommandNode::Select(select_node) => {
+                let words = self.expand_loop_words(state, select_node.words.as_deref());
+                take_unbound_variable(state)?;
+                let body: Vec<&StatementNode> = select_node.body.iter().collect();
+                let trace = self.xtrace(state, &[trace_loop_header("select", &select_node.variable, &words)]);
+
+                let result = execute_select(
+                    state,
+                    &select_node.variable,
+                    &words,
+                    &body,
+                    self.limits,
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
+
+                Ok(ExecResult::new(
+                    trace.stdout + &result.stdout,
+                    trace.stderr + &result.stderr,
+                    result.exit_code,
+                ))
+            }
+
+            CompoundCommandNode::While(while_node) => {
+                let condition: Vec<&StatementNode> = while_node.condition.iter().collect();
+                let body: Vec<&StatementNode> = while_node.body.iter().collect();
+
+                let result = execute_while(
+                    state,
+                    &condition,
+                    &body,
+                    self.limits,
+                    |state, stmt| {
+                        // A loop may be polling for what a job does
+                        self.run_pending_jobs(state);
+                        let res = self.execute_statement(state, stmt)?;
+                        Ok(ConditionResult {
+                            stdout: res.stdout,
+                            stderr: res.stderr,
+                            exit_code: res.exit_code,
+                        })
+                    },
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
+
+                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+            }
+
+            CompoundCommandNode::Until(until_node) => {
+                let condition: Vec<&StatementNode> = until_node.condition.iter().collect();
+                let body: Vec<&StatementNode> = until_node.body.iter().collect();
+
+                let result = execute_until(
+                    state,
+                    &condition,
+                    &body,
+                    self.limits,
+                    |state, stmt| {
+                        // A loop may be polling for what a job does
+                        self.run_pending_jobs(state);
+                        let res = self.execute_statement(state, stmt)?;
+                        Ok(ConditionResult {
+                            stdout: res.stdout,
+                            stderr: res.stderr,
+                            exit_code: res.exit_code,
+                        })
+                    },
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
+
+                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
+            }
+
+            CompoundCommandNode::Case(case_node) => {
+                let subject = expand_word(state, &case_node.word, Some(&subst));
+                prepend_output(take_unbound_variable(state).map(|_| ExecResult::ok()), "", &subject.stderr)?;
+                let trace = self.xtrace(state, &[format!("case {} in", quote_for_trace(&subject.value))]);
+                let items: Vec<CaseItem<WordNode, StatementNode>> = case_node
+                    .items
+                    .iter()
+                    .map(|item| CaseItem {
+                        patterns: &item.patterns,
+                        body: &item.body,
+                        terminator: item.terminator.into(),
+                    })
+                    .collect();
+
+                let result = execute_case(
+                    state,
+                    &subject.value,
+                    &items,
+                    |state, value, pattern: &WordNode| {
+                        // Quoted parts of a pattern come back escaped, so they match literally
+                        let pattern = expand_word_for_pattern(state, pattern, Some(&subst));
+                        Ok(match_pattern(
+                            value,
+                            &pattern.value,
+                            state.shopt_options.nocasematch,
+                            state.shopt_options.extglob,
+                        ))
+                    },
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
+
+                Ok(ExecResult::new(
+                    trace.stdout + &result.stdout,
+                    format!("{}{}{}", subject.stderr, trace.stderr, result.stderr),
+                    result.exit_code,
+                ))
             }
 
             CompoundCommandNode::Subshell(subshell_node) => {
@@ -550,37 +2129,157 @@ impl<'a> ExecutionEngine<'a> {
                 use crate::interpreter::arithmetic::evaluate_arithmetic;
                 use crate::interpreter::types::InterpreterContext;
 
+                if let Some(line) = arith.line {
+                    state.current_line = state.line_offset + line as u32;
+                }
+                let trace = self.xtrace(state, &[trace_arithmetic(&arith.expression)]);
                 let mut ctx = InterpreterContext::new(state, self.limits);
-                match evaluate_arithmetic(&mut ctx, &arith.expression.expression, false, None) {
+                let value = evaluate_arithmetic(&mut ctx, &arith.expression.expression, false, None);
+                prepend_output(take_unbound_variable(state).map(|_| ExecResult::ok()), &trace.stdout, &trace.stderr)?;
+                match value {
                     Ok(value) => {
                         // Arithmetic command: exit 0 if non-zero, exit 1 if zero
                         let exit_code = if value != 0 { 0 } else { 1 };
-                        Ok(ExecResult::new(String::new(), String::new(), exit_code))
+                        Ok(ExecResult::new(trace.stdout, trace.stderr, exit_code))
                     }
                     Err(e) => {
                         Ok(ExecResult::new(
-                            String::new(),
-                            format!("bash: {}\n", e),
+                            trace.stdout,
+                            format!("{}bash: {}\n", trace.stderr, e),
                             1,
                         ))
                     }
                 }
             }
 
-            CompoundCommandNode::ConditionalCommand(_cond) => {
-                // Conditional command [[ ... ]] - TODO: implement full evaluation
-                // For now, return success
-                Ok(ExecResult::ok())
-            }
+            CompoundCommandNode::ConditionalCommand(cond) => {
+                if let Some(line) = cond.line {
+                    state.current_line = state.line_offset + line as u32;
+                }
+                let mut traced = Vec::new();
+                let result = evaluate_conditional(state, &cond.expression, self.fs, Some(&subst), &mut traced);
+                take_unbound_variable(state)?;
+                let trace = self.xtrace(state, &traced);
+                Ok(ExecResult::new(
+                    trace.stdout + &result.stdout,
+                    trace.stderr + &result.stderr,
+                    result.exit_code,
+                ))
+            }
+
+            CompoundCommandNode::CStyleFor(cfor) => {
+                use crate::interpreter::arithmetic::evaluate_arithmetic;
+                use crate::interpreter::types::InterpreterContext;
+
+                if let Some(line) = cfor.line {
+                    state.current_line = state.line_offset + line as u32;
+                }
+                let result = execute_c_style_for(
+                    state,
+                    cfor.init.as_ref(),
+                    cfor.condition.as_ref(),
+                    cfor.update.as_ref(),
+                    &cfor.body,
+                    self.limits,
+                    |state, expr| self.xtrace(state, &[trace_arithmetic(expr)]),
+                    |state, expr| {
+                        let mut ctx = InterpreterContext::new(state, self.limits);
+                        evaluate_arithmetic(&mut ctx, &expr.expression, false, None)
+                            .map_err(|e| format!("bash: {}\n", e))
+                    },
+                    |state, stmt| self.execute_statement(state, stmt),
+                )?;
 
-            CompoundCommandNode::CStyleFor(_cfor) => {
-                // C-style for loop - TODO: implement
-                Ok(ExecResult::ok())
+                Ok(ExecResult::new(result.stdout, result.stderr, result.exit_code))
             }
         }
     }
 }
 
+/// Under `set -u`, an unset variable read by the expansions just done ends
+/// the shell.
+fn take_unbound_variable(state: &mut InterpreterState) -> Result<(), InterpreterError> {
+    match state.unbound_variable.take() {
+        // Positional parameters are reported as `$1`
+        Some(name) if name.chars().all(|c| c.is_ascii_digit()) => {
+            Err(NounsetError::new(format!("${}", name), String::new()).into())
+        }
+        Some(name) => Err(NounsetError::new(name, String::new()).into()),
+        None => Ok(()),
+    }
+}
+
+/// Map the outcome of a subshell run on a copy of the state to its result.
+fn subshell_exec_result(result: Result<ExecResult, InterpreterError>) -> ExecResult {
+    match result {
+        Ok(result) => result,
+        // Only safety limits escape a subshell; they end it
+        Err(InterpreterError::ExecutionLimit(e)) => {
+            let exit_code = e.exit_code();
+            ExecResult::new(e.stdout, e.stderr, exit_code)
+        }
+        Err(InterpreterError::Nounset(e)) => ExecResult::new(e.stdout, e.stderr, 1),
+        Err(e) => ExecResult::failure(format!("bash: {}\n", e)),
+    }
+}
+
+/// `for`/`select` header as `set -x` shows it, with the expanded words.
+fn trace_loop_header(keyword: &str, variable: &str, words: &[String]) -> String {
+    let parts: Vec<&str> = words.iter().map(String::as_str).collect();
+    format!("{} {} in {}", keyword, variable, format_trace_line(&parts))
+}
+
+/// `((...))` as `set -x` shows it, with the expression's original text.
+fn trace_arithmetic(expr: &ArithmeticExpressionNode) -> String {
+    format!("(( {} ))", expr.original_text.as_deref().unwrap_or_default())
+}
+
+/// Put output produced ahead of a command, such as a DEBUG trap's, in front
+/// of the command's own output.
+fn prepend_output(
+    result: Result<ExecResult, InterpreterError>,
+    stdout: &str,
+    stderr: &str,
+) -> Result<ExecResult, InterpreterError> {
+    if stdout.is_empty() && stderr.is_empty() {
+        return result;
+    }
+    match result {
+        Ok(mut result) => {
+            result.stdout.insert_str(0, stdout);
+            result.stderr.insert_str(0, stderr);
+            Ok(result)
+        }
+        Err(mut error) => {
+            error.as_control_flow_mut().prepend_output(stdout, stderr);
+            Err(error)
+        }
+    }
+}
+
+/// Whether a script with this deadline and cancellation token must stop.
+fn is_interrupted(deadline: Option<Instant>, cancellation: Option<&CancellationToken>) -> bool {
+    cancellation.is_some_and(|token| token.is_cancelled()) || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Result for a command that is neither a builtin, a function nor on PATH.
+/// The shell's remembered command locations, as `resolve_command` uses them.
+fn command_hash_table(state: &InterpreterState) -> CommandHashTable {
+    let mut hash_table = CommandHashTable::new();
+    for (name, path) in state.hash_table.iter().flatten() {
+        hash_table.insert(name, path);
+    }
+    hash_table
+}
+
+fn command_not_found(command_name: &str) -> ExecResult {
+    ExecResult::new(
+        String::new(),
+        format!("bash: {}: command not found\n", command_name),
+        127,
+    )
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -600,8 +2299,10 @@ mod tests {
         let handle = tokio::runtime::Handle::current();
         let adapter = Box::leak(Box::new(SyncFsAdapter::new(fs.clone(), handle)));
 
-        let engine = ExecutionEngine::new(limits, adapter);
-        let state = InterpreterState::default();
+        let registry = Arc::new(crate::commands::registry::create_batch_abcdefghijklmno_registry());
+        let engine = ExecutionEngine::new(limits, adapter).with_registry(registry, fs.clone());
+        let mut state = InterpreterState::default();
+        state.cwd = "/".to_string();
 
         (engine, state, fs)
     }
@@ -684,6 +2385,71 @@ mod tests {
         assert_eq!(result.stdout, "a\nb\nc\n");
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_select() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = "PS3='> '\nselect f in a b c; do echo \"$REPLY:$f\"; done <<EOF\n2\n\n9\nEOF\necho $?";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "2:b\n9:\n\n1\n");
+        assert_eq!(result.stderr, "1) a\n2) b\n3) c\n> > 1) a\n2) b\n3) c\n> > ");
+
+        let ast = crate::parser::parse("select f in a b; do echo $f; break; done <<< 1; echo $?").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "a\n0\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_coproc() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = r#"
+coproc DOUBLE { while read -r n; do echo $((n * 2)); done; }
+echo 4 >&"${DOUBLE[1]}"
+echo 5 >&"${DOUBLE[1]}"
+read -r -u "${DOUBLE[0]}" a
+read -r b <&"${DOUBLE[0]}"
+exec {DOUBLE[1]}>&-
+wait "$DOUBLE_PID"
+echo "$a $b $?"
+"#;
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "8 10 0\n");
+        assert!(state.coprocs.is_empty());
+
+        // Its side effects happen once, with all the input written by then
+        let script = r#"
+coproc { read x; echo "got $x"; echo ran >> /coproc.log; }
+echo 1 >&${COPROC[1]}
+read -u ${COPROC[0]} y
+echo "$y"
+read -r -u ${COPROC[0]} z
+cat /coproc.log
+"#;
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "got 1\nran\n");
+
+        // It keeps reading until its input is closed
+        let script = r#"
+coproc C { while read l; do echo "r:$((l*2))"; done; }
+echo 1 >&${C[1]}
+read a <&${C[0]}
+echo 2 >&${C[1]}
+read b <&${C[0]}
+echo "$a $b"
+"#;
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "r:2 r:4\n");
+
+        let ast = crate::parser::parse("coproc printf 'a\\nb\\n'; cat <&${COPROC[0]}").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "a\nb\n");
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_execute_while() {
         let (engine, mut state, _fs) = make_engine_and_state();
@@ -746,4 +2512,629 @@ mod tests {
         let result = engine.execute_script(&mut state, &ast).unwrap();
         assert_eq!(result.stdout, "/home/user\n");
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_registry_pipeline() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse("echo foo bar | tr a-z A-Z").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "FOO BAR\n");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_commands_reading_group_stdin_use_it_up() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.write_file("/list.txt", b"x\n").await.unwrap();
+        fs.write_file("/first.sh", b"read a; echo \"script $a\"").await.unwrap();
+        fs.chmod("/first.sh", 0o755).await.unwrap();
+
+        let script = "while read l; do echo \"[$l]\"; cat; done <<EOF\na\nb\nc\nEOF\n\
+                      while read l; do echo \"[$l]\"; wc -l /list.txt >/dev/null; done <<EOF\na\nb\nEOF\n\
+                      while read l; do echo \"[$l]\"; /first.sh; done <<EOF\na\nb\nc\nEOF\n";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "[a]\nb\nc\n[a]\n[b]\n[a]\nscript b\n[c]\nscript \n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_pipeline_stages_run_in_subshells() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.mkdir("/tmp", &crate::fs::MkdirOptions { recursive: true }).await.unwrap();
+
+        let script = "exit 3 | true; echo $?; cd /tmp | true; pwd; x=1 | y=2; echo \"[$x$y]\"; \
+                      echo hi | read v; echo \"[$v]\"; true | exit 4; echo after";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "0\n/\n[]\n[]\nafter\n");
+        assert_eq!(state.cwd, "/");
+
+        // With lastpipe, the last stage runs in the shell itself
+        let ast = crate::parser::parse("shopt -s lastpipe; echo hi | read v; echo \"[$v]\"; x=1 | y=2; echo \"[$x$y]\"").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "[hi]\n[2]\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_registry_reads_vfs() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.write_file("/notes.txt", b"apple\nbanana\ncherry\n").await.unwrap();
+
+        let ast = crate::parser::parse("cat notes.txt | grep an").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "banana\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_command_not_found() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse("nosuchcmd arg").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stderr, "bash: nosuchcmd: command not found\n");
+        assert_eq!(result.exit_code, 127);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_path_lookup_and_hash() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.mkdir("/usr/bin", &crate::fs::MkdirOptions { recursive: true }).await.unwrap();
+        fs.write_file("/usr/bin/echo", b"").await.unwrap();
+        fs.write_file("/usr/bin/wc", b"").await.unwrap();
+
+        // With /usr/bin present, only commands that have a stub on PATH resolve
+        let ast = crate::parser::parse("echo a b | wc -w").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout.trim(), "2");
+        // Pipeline stages run in subshells, which hash commands for themselves
+        assert_eq!(state.hash_table.as_ref().and_then(|t| t.get("wc")), None);
+
+        let ast = crate::parser::parse("wc -w <<< 'a b'").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout.trim(), "2");
+        assert_eq!(
+            state.hash_table.as_ref().and_then(|t| t.get("wc")),
+            Some(&"/usr/bin/wc".to_string())
+        );
+
+        let ast = crate::parser::parse("echo hi | tr a-z A-Z").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.exit_code, 127);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_exported_env_reaches_commands() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse("export GREETING=hi; env").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert!(result.stdout.contains("GREETING=hi\n"), "{:?}", result);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_builtin_exit_stops_script() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse("echo before; exit 3; echo after").unwrap();
+        match engine.execute_script(&mut state, &ast) {
+            Err(InterpreterError::Exit(e)) => {
+                assert_eq!(e.exit_code, 3);
+                assert_eq!(e.stdout, "before\n");
+            }
+            other => panic!("expected exit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_pipe_into_while_read() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "printf 'a\\nb\\n' | while read line; do echo \"got $line\"; done",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "got a\ngot b\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_glob_uses_vfs() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.mkdir("/src", &Default::default()).await.unwrap();
+        fs.write_file("/src/b.rs", b"").await.unwrap();
+        fs.write_file("/src/a.rs", b"").await.unwrap();
+        fs.write_file("/src/.hidden.rs", b"").await.unwrap();
+        fs.write_file("/src/notes.txt", b"").await.unwrap();
+
+        let ast = crate::parser::parse("echo src/*.rs; echo /src/*.md; echo 'src/*.rs'").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "src/a.rs src/b.rs\n/src/*.md\nsrc/*.rs\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_case_terminators() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "for a in start -v foo; do case \"$a\" in start|stop) echo cmd;; -v) echo v;& --*) echo long;; f*) echo f;;& *) echo any;; esac; done",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "cmd\nv\nlong\nf\nany\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_case_pattern_options() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "case x in '*') echo literal;; *) echo glob;; esac; shopt -s nocasematch extglob; case ABC in @(x|abc)) echo ext;; esac",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "glob\next\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_conditional_file_tests_use_vfs() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.write_file("/data.txt", b"x").await.unwrap();
+        fs.symlink("/data.txt", "/link").await.unwrap();
+
+        let ast = crate::parser::parse(
+            "[[ -f data.txt ]] && echo file; [[ -d data.txt ]] || echo notdir; [[ -f /etc/passwd ]] || echo nohost; [[ link -ef /data.txt && data.txt -nt missing ]] && echo same",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "file\nnotdir\nnohost\nsame\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_conditional_patterns_and_regex() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "[[ abc == a* ]] && echo glob; [[ abc == \"a*\" ]] || echo literal; [[ 10 -gt 9 && ! -z x ]] && echo num; [[ foo-12 =~ ^([a-z]+)-([0-9]+)$ ]] && echo \"${BASH_REMATCH[1]} ${BASH_REMATCH[2]}\"",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "glob\nliteral\nnum\nfoo 12\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_c_style_for() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "for ((i=0; i<5; i++)); do ((i==1)) && continue; ((i==3)) && break; echo $i; done; for ((j=0; j<2; j++)); do for ((;;)); do echo $j; continue 2; done; done",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "0\n2\n0\n1\n");
+        assert_eq!(state.loop_depth, 0);
+
+        // break and continue succeed, so the loop does too
+        let ast = crate::parser::parse(
+            "for ((i=0; i<2; i++)); do false; break; done; echo $?; \
+             for i in 1 2; do false; continue; done; echo $?; \
+             n=0; while [ $n -lt 2 ]; do n=$((n+1)); false; continue; done; echo $?; \
+             until false; do false; break; done; echo $?",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "0\n0\n0\n0\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_output_redirections() {
+        let (engine, mut state, fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "echo one > /out.txt; echo two >> /out.txt; ls /missing > /both.txt 2>&1; ls /missing 2>&1 > /only.txt; echo gone > /dev/null; echo err 1>&2",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "ls: cannot access '/missing': No such file or directory\n");
+        assert_eq!(result.stderr, "err\n");
+        assert_eq!(fs.read_file("/out.txt").await.unwrap(), "one\ntwo\n");
+        assert!(fs.read_file("/both.txt").await.unwrap().contains("/missing"));
+        assert_eq!(fs.read_file("/only.txt").await.unwrap(), "");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_input_redirections_and_heredocs() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.write_file("/in.txt", b"a\nb\n").await.unwrap();
+        state.env.insert("HOME".to_string(), "/home/user".to_string());
+
+        let ast = crate::parser::parse(
+            "cat <<EOF\nhome=$HOME\nEOF\ncat <<-'EOF'\n\t$HOME\n\tEOF\ncat <<< word; while read l; do echo \"got $l\"; done < /in.txt; cat < /nope",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "home=/home/user\n$HOME\nword\ngot a\ngot b\n");
+        assert_eq!(result.stderr, "bash: /nope: No such file or directory\n");
+        assert_eq!(result.exit_code, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_function_calls() {
+        let (engine, mut state, fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "greet() { echo \"hi $1 $# ${FUNCNAME[0]}\"; }\n\
+             outer() { local v=in; inner; }\n\
+             inner() { echo \"$v ${FUNCNAME[*]}\"; }\n\
+             early() { if true; then echo before; return 3; fi; echo never; }\n\
+             logged() { echo to-file; } > /log.txt\n\
+             greet bob x; outer; echo \"[$v]\"; early; echo \"rc=$?\"; logged",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "hi bob 2 greet\nin inner outer\n[]\nbefore\nrc=3\n");
+        assert_eq!(fs.read_file("/log.txt").await.unwrap(), "to-file\n");
+        assert_eq!(state.call_depth, 0);
+        assert!(state.local_scopes.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_line_numbers() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = "x=1\n\
+                      f() {\n  echo \"f ${BASH_LINENO[0]} $LINENO\"\n  g\n}\n\
+                      g() { echo \"g ${BASH_LINENO[0]} ${BASH_LINENO[1]} $LINENO\"; }\n\
+                      f\n\
+                      [[ $LINENO == 8 ]] && echo \"cond $LINENO\"\n\
+                      (( LINENO == 9 )) && echo arith\n\
+                      for ((i = LINENO; i < 11; i++)); do echo \"for $i\"; done\n\
+                      echo \"$LINENO $(echo $LINENO)\" \\\n  $LINENO\n\
+                      eval $'true\\necho \"eval $LINENO\"'";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "f 7 3\ng 4 7 6\ncond 8\narith\nfor 10\n11 11 11\neval 14\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_function_recursion_limit() {
+        let (_, mut state, fs) = make_engine_and_state();
+        let limits = Box::leak(Box::new(ExecutionLimits {
+            max_recursion_depth: 5,
+            ..ExecutionLimits::default()
+        }));
+        let adapter = SyncFsAdapter::new(fs, tokio::runtime::Handle::current());
+        let engine = ExecutionEngine::new(limits, &adapter);
+
+        let ast = crate::parser::parse("f() { echo $1; f $(($1 + 1)); }; f 1").unwrap();
+        match engine.execute_script(&mut state, &ast) {
+            Err(InterpreterError::ExecutionLimit(e)) => {
+                assert!(e.message.contains("f: maximum recursion depth (5) exceeded"));
+            }
+            other => panic!("expected recursion limit error, got {:?}", other),
+        }
+        assert_eq!(state.call_depth, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_command_substitution() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.write_file("/words.txt", b"a b\nc\n").await.unwrap();
+
+        let ast = crate::parser::parse(
+            "echo \"n=$(cat /words.txt | wc -l)\"; for w in $(cat /words.txt); do echo \"[$w]\"; done; \
+             echo \"$(< /words.txt)\" `echo tick`; f() { echo \"f:$1\"; cd /tmp; }; echo \"$(f x) $(pwd)\"; \
+             echo $(exit 4) $?; echo \"[$(true)]\" $(true)",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "n=2\n[a]\n[b]\n[c]\na b\nc tick\nf:x /\n4\n[]\n"
+        );
+        assert_eq!(state.cwd, "/");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_command_substitution_errors() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse("echo \"[$(< /missing)]\" $?; [[ $(echo yes) == y* ]] && echo matched").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "[] 1\nmatched\n");
+        assert_eq!(result.stderr, "bash: /missing: No such file or directory\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_assignments() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = "x=hello; x+=' world'; echo \"$x\"\n\
+                      a=(1 \"b c\" 3); a[5]=x; a+=(y); echo \"${a[1]}\" ${a[6]} ${a[5]}\n\
+                      declare -i n; n=2+3; n+=4; declare -u u; u=shout; echo $n $u\n\
+                      false; s=$?; t=$(exit 3); echo $s $?";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "hello world\nb c y x\n9 SHOUT\n1 3\n");
+        assert_eq!(state.env.get("a__length"), Some(&"7".to_string()));
+
+        let ast = crate::parser::parse("readonly r=1; r=2; echo $? $r").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "1 1\n");
+        assert_eq!(result.stderr, "bash: r: readonly variable\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_prefix_assignments() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse("FOO=outer; FOO=inner BAR=1 env; echo \"$FOO [$BAR]\"").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert!(result.stdout.contains("FOO=inner\n"));
+        assert!(result.stdout.contains("BAR=1\n"));
+        assert!(result.stdout.ends_with("outer []\n"));
+        assert!(!state.env.contains_key("BAR"));
+        assert!(state.temp_exported_vars.map_or(true, |vars| vars.is_empty()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_process_substitution() {
+        let (engine, mut state, fs) = make_engine_and_state();
+
+        let script = "cat <(echo one) <(echo two)\n\
+                      while read l; do echo \"got $l\"; done < <(echo a; echo b)\n\
+                      echo hello | tee >(tr a-z A-Z) > /dev/null";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "one\ntwo\ngot a\ngot b\nHELLO\n");
+        assert!(!fs.exists("/dev/fd/63").await);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_background_jobs() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = "(echo job; exit 3) & p=$!\n\
+                      echo start\n\
+                      wait $p; echo \"status $?\"\n\
+                      echo never & kill %1; wait %1; echo \"killed $?\"\n\
+                      echo late &";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "start\njob\nstatus 3\nkilled 143\n");
+        assert_eq!(state.jobs.len(), 1);
+        assert_eq!(state.jobs[0].status, JobStatus::Running);
+        assert_eq!(state.last_background_pid, state.jobs[0].pid);
+
+        // A job doesn't run until it is waited for, so it can be cancelled
+        let script = "wait; while :; do :; done & echo \"$! $BASHPID\" > /dev/null\n\
+                      jobs; jobs -r %1; kill %1; jobs\n\
+                      wait %1; echo \"killed $?\"\n\
+                      echo \"$BASHPID\" > /pid & p=$!; wait $p; [ \"$(cat /pid)\" = \"$p\" ] && echo same pid";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "late\n\
+             [1]+  Running                 while :; do :; done &\n\
+             [1]+  Running                 while :; do :; done &\n\
+             [1]+  Terminated              while :; do :; done\n\
+             killed 143\nsame pid\n"
+        );
+        assert!(state.jobs.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_traps() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = "trap 'echo \"err $?\"' ERR\n\
+                      false; false && true; if false; then :; fi; { (exit 4); }\n\
+                      f() { trap 'echo \"return $?\"' RETURN; return 5; }; f\n\
+                      trap 'echo debug' DEBUG; echo cmd; trap - DEBUG\n\
+                      trap 'echo caught' TERM; kill $BASHPID; echo after";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "err 1\nerr 4\nreturn 5\nerr 5\ndebug\ncmd\ndebug\ncaught\nafter\n"
+        );
+
+        let ast = crate::parser::parse("trap - TERM; kill -INT $BASHPID; echo unreachable").unwrap();
+        match engine.execute_script(&mut state, &ast) {
+            Err(InterpreterError::Exit(e)) => assert_eq!(e.exit_code, 130),
+            other => panic!("expected exit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_bash_command() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let script = "x=1\n\
+                      trap 'echo \"debug: $BASH_COMMAND\"' DEBUG\n\
+                      echo $x; y=2 true && false\n\
+                      trap - DEBUG\n\
+                      trap 'echo \"err: $BASH_COMMAND\"' ERR\n\
+                      false \"$x\"\n\
+                      f() { return 3; }\n\
+                      f";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "debug: echo $x\n1\ndebug: y=2 true\ndebug: false\ndebug: trap - DEBUG\n\
+             err: false \"$x\"\nerr: return 3\n"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_xtrace() {
+        let (engine, mut state, fs) = make_engine_and_state();
+
+        let script = "set -x; v=\"a b\"; echo $v 2>/dev/null\n\
+                      [[ $v == a* ]] && echo $(echo in)\n\
+                      for i in 1; do ((i)); done\n\
+                      PS4='[$?] '; false; set +x; echo quiet";
+        let ast = crate::parser::parse(script).unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "a b\nin\nquiet\n");
+        assert_eq!(
+            result.stderr,
+            "+ v='a b'\n+ echo a b\n+ [[ a b == a* ]]\n++ echo in\n+ echo in\n\
+             + for i in 1\n+ (( i ))\n+ PS4='[$?] '\n[0] false\n[1] set +x\n"
+        );
+
+        state.file_descriptors = Some(HashMap::from([(5, FileDescriptor::File("/trace.log".to_string()))]));
+        let ast = crate::parser::parse("BASH_XTRACEFD=5; PS4='+ '; set -x; echo hi").unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stderr, "");
+        assert_eq!(fs.read_file("/trace.log").await.unwrap(), "+ echo hi\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_errexit_and_nounset() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        let ast = crate::parser::parse(
+            "set -eo pipefail; f() { false; echo in-f; }; f && echo ok; ! f; false || true; \
+             echo \"[$(false; echo still)]\"; false | true; echo no",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "in-f\nok\nin-f\n[still]\n");
+        assert_eq!(result.exit_code, 1);
+
+        let (engine, mut state, _fs) = make_engine_and_state();
+        let ast = crate::parser::parse(
+            "set -u; x=set; echo $x; (echo $unset); echo \"sub=$?\"; f() { echo $1; }; f; echo no",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "set\nsub=1\n");
+        assert_eq!(
+            result.stderr,
+            "bash: unset: unbound variable\nbash: $1: unbound variable\n"
+        );
+        assert_eq!(result.exit_code, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_aliases() {
+        let (engine, mut state, _fs) = make_engine_and_state();
+
+        // Aliases are expanded as each line is read, before it runs
+        let ast = crate::parser::parse(
+            "alias say='echo said'\n\
+             say off; shopt -s expand_aliases; say same-line\n\
+             say on\n\
+             alias echo='echo e:' both='echo one; echo two' loop1=loop2 loop2=loop1\n\
+             v=val; say \"$v x\"; both three; loop1\n\
+             f() { say in-f; late; }\n\
+             alias late='echo late'\n\
+             f; echo \"$(say sub)\"\n\
+             unalias say; say still-read\n\
+             say\n\
+             alias sep='echo one;'\n\
+             sep two",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "said on\ne: said val x\ne: one\ne: two three\ne: said in-f\ne: e: said sub\ne: said still-read\ne: one\n"
+        );
+        assert_eq!(
+            result.stderr,
+            "bash: say: command not found\nbash: say: command not found\nbash: loop1: command not found\n\
+             bash: late: command not found\nbash: say: command not found\nbash: two: command not found\n"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_source_and_eval() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.mkdir("/lib", &Default::default()).await.unwrap();
+        fs.write_file(
+            "/lib/helpers.sh",
+            b"greet() { echo \"hello $1\"; }\nloaded=\"$# ${BASH_SOURCE[0]}\"\nreturn 4\necho unreachable\n",
+        )
+        .await
+        .unwrap();
+
+        let ast = crate::parser::parse(
+            "set -- outer; source /lib/helpers.sh a b; echo \"$? $loaded $1\"; greet you; \
+             PATH=/lib; . helpers.sh; echo \"$loaded\"; source /nope.sh; echo $?; \
+             cmd='echo evaluated'; eval \"$cmd; x=2\"; echo \"x=$x\"; eval 'if'; echo $?",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "4 2 /lib/helpers.sh outer\nhello you\n1 /lib/helpers.sh\n1\nevaluated\nx=2\n2\n"
+        );
+        assert!(result.stderr.starts_with("bash: /nope.sh: No such file or directory\nbash: eval: syntax error"));
+        assert_eq!(state.source_depth, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_script_files() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.mkdir("/usr/local/bin", &crate::fs::MkdirOptions { recursive: true }).await.unwrap();
+        fs.write_file("/build.sh", b"#!/bin/bash\necho \"$0 $# $1\"\nexit 3\n").await.unwrap();
+        fs.chmod("/build.sh", 0o755).await.unwrap();
+        fs.write_file("/usr/local/bin/greet", b"echo \"hi $1 $X\"\n").await.unwrap();
+        fs.chmod("/usr/local/bin/greet", 0o755).await.unwrap();
+        fs.write_file("/upper", b"#!/usr/bin/env awk -f\n{ print toupper($0) }\n").await.unwrap();
+        fs.chmod("/upper", 0o755).await.unwrap();
+        fs.write_file("/plain.sh", b"echo nope\n").await.unwrap();
+        fs.chmod("/plain.sh", 0o644).await.unwrap();
+
+        let ast = crate::parser::parse(
+            "set -- outer; ./build.sh one two; echo \"$? $1\"; X=1; export Y=2; \
+             PATH=/usr/local/bin greet you; echo abc | ./upper; ./plain.sh; echo $?",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "./build.sh 2 one\n3 outer\nhi you \nABC\n126\n");
+        assert_eq!(result.stderr, "bash: ./plain.sh: Permission denied\n");
+
+        // Lookup builtins find commands where running them would
+        let ast = crate::parser::parse(
+            "PATH=/usr/local/bin; type greet; command -v greet; command -V greet; \
+             command -v ./build.sh; command -v cat; command -v nosuch; echo $?; /usr; echo $?",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(
+            result.stdout,
+            "greet is /usr/local/bin/greet\n/usr/local/bin/greet\ngreet is /usr/local/bin/greet\n\
+             ./build.sh\n/usr/bin/cat\n1\n126\n"
+        );
+        assert_eq!(result.stderr, "bash: /usr: Is a directory\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_exec_redirections() {
+        let (engine, mut state, fs) = make_engine_and_state();
+        fs.write_file("/in.txt", b"l1\nl2\n").await.unwrap();
+
+        let ast = crate::parser::parse(
+            "exec 3>out.log; echo one >&3; echo two >&3; exec 3>&-; echo three >&3; echo $?; \
+             exec 4<in.txt; read -u 4 a; read -u 4 b; echo \"$a $b\"; \
+             exec {log}>named.log; echo named >&$log; exec {log}>&-; \
+             exec 5>&1 6>&2 >all.log 2>&1; echo hidden; cd /nope; echo captured=$(echo sub); \
+             echo piped | cat; echo saved >&5; exec 1>&5 2>&6 5>&- 6>&-; echo back",
+        )
+        .unwrap();
+        let result = engine.execute_script(&mut state, &ast).unwrap();
+        assert_eq!(result.stdout, "1\nl1 l2\nsaved\nback\n");
+        assert_eq!(result.stderr, "bash: 3: Bad file descriptor\n");
+        assert_eq!(fs.read_file("/out.log").await.unwrap(), "one\ntwo\n");
+        assert_eq!(fs.read_file("/named.log").await.unwrap(), "named\n");
+        assert_eq!(
+            fs.read_file("/all.log").await.unwrap(),
+            "hidden\nbash: cd: /nope: No such file or directory\ncaptured=sub\npiped\n"
+        );
+    }
 }
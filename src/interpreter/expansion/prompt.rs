@@ -129,7 +129,7 @@ pub fn expand_prompt(state: &InterpreterState, value: &str) -> String {
     let pwd_basename = pwd.rsplit('/').next().unwrap_or(pwd);
 
     // Get date/time values
-    let now = Local::now();
+    let now: chrono::DateTime<Local> = state.clock.now().into();
     let weekdays = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
     let months = [
         "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
@@ -151,7 +151,7 @@ pub fn is_array(state: &InterpreterState, name: &str) -> bool {
 /// Constants for special variables that are always set
 const ALWAYS_SET_SPECIAL_VARS: &[&str] = &[
     "?", "$", "#", "_", "-", "0", "PPID", "UID", "EUID", "RANDOM", "SECONDS", "BASH_VERSION", "!",
-    "BASHPID", "LINENO",
+    "BASHPID", "LINENO", "EPOCHSECONDS", "EPOCHREALTIME",
 ];
 
 /// Get the value of a variable.
@@ -162,7 +162,7 @@ pub fn get_variable(state: &InterpreterState, name: &str) -> String {
     // Special variables
     match name {
         "?" => return state.last_exit_code.to_string(),
-        "$" => return std::process::id().to_string(),
+        "$" => return state.identity.pid.to_string(),
         "#" => return state.env.get("#").map(|s| s.as_str()).unwrap_or("0").to_string(),
         "@" => return state.env.get("@").map(|s| s.as_str()).unwrap_or("").to_string(),
         "_" => return state.last_arg.clone(),
@@ -209,37 +209,24 @@ pub fn get_variable(state: &InterpreterState, name: &str) -> String {
         "0" => return state.env.get("0").map(|s| s.as_str()).unwrap_or("bash").to_string(),
         "PWD" => return state.env.get("PWD").cloned().unwrap_or_default(),
         "OLDPWD" => return state.env.get("OLDPWD").cloned().unwrap_or_default(),
-        "PPID" => return std::os::unix::process::parent_id().to_string(),
-        "UID" => {
-            #[cfg(unix)]
-            {
-                return unsafe { libc::getuid() }.to_string();
-            }
-            #[cfg(not(unix))]
-            {
-                return "0".to_string();
-            }
-        }
-        "EUID" => {
-            #[cfg(unix)]
-            {
-                return unsafe { libc::geteuid() }.to_string();
-            }
-            #[cfg(not(unix))]
-            {
-                return "0".to_string();
-            }
-        }
-        "RANDOM" => return (rand::random::<u16>() % 32768).to_string(),
+        "PPID" => return state.identity.ppid.to_string(),
+        "UID" => return state.identity.uid.to_string(),
+        "EUID" => return state.identity.euid.to_string(),
+        "RANDOM" => return state.random.below(32768).to_string(),
         "SECONDS" => {
-            let elapsed = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0)
-                .saturating_sub(state.start_time);
+            let elapsed = (state.clock.unix_secs_f64() as u64).saturating_sub(state.start_time);
             return elapsed.to_string();
         }
+        "EPOCHSECONDS" => return (state.clock.unix_secs_f64() as u64).to_string(),
+        "EPOCHREALTIME" => {
+            let now = state.clock.now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+            return format!("{}.{:06}", now.as_secs(), now.subsec_micros());
+        }
         "BASH_VERSION" => return "5.0.0".to_string(),
+        // Unset until the first background job is started
+        "!" if state.last_background_pid == 0 => return String::new(),
         "!" => return state.last_background_pid.to_string(),
         "BASHPID" => return state.bash_pid.to_string(),
         "LINENO" => return state.current_line.to_string(),
@@ -10,6 +10,8 @@
 
 use crate::interpreter::expansion::{has_glob_pattern, unescape_glob_pattern};
 use crate::interpreter::helpers::{get_ifs, split_by_ifs_for_expansion};
+use crate::interpreter::conditionals::match_pattern;
+use crate::interpreter::interpreter::FileSystem as SyncFileSystem;
 use crate::interpreter::InterpreterState;
 use std::fs;
 use std::path::Path;
@@ -185,6 +187,140 @@ fn glob_pattern_with_options(
     Ok(matches)
 }
 
+/// Expand a glob pattern against the interpreter's filesystem.
+///
+/// Unlike `expand_glob_pattern`, which walks the host filesystem, this reads
+/// directories through `fs` one path segment at a time, so a sandboxed shell
+/// only ever sees its own files.
+pub fn expand_glob_pattern_in_fs(
+    pattern: &str,
+    cwd: &str,
+    fs: &dyn SyncFileSystem,
+    options: &WordExpansionOptions,
+) -> Result<GlobExpansionResult, String> {
+    if options.noglob || !has_glob_pattern(pattern, options.extglob) {
+        return Ok(GlobExpansionResult {
+            values: vec![unescape_glob_pattern(pattern)],
+            quoted: false,
+        });
+    }
+
+    let absolute = pattern.starts_with('/');
+    let dirs_only = pattern.ends_with('/');
+    let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+
+    // Each candidate is (filesystem path, path as it will be displayed)
+    let start = if absolute { "/".to_string() } else { cwd.to_string() };
+    let mut candidates = vec![(start, if absolute { "/".to_string() } else { String::new() })];
+
+    for (index, segment) in segments.iter().enumerate() {
+        let last = index + 1 == segments.len();
+        let mut next = Vec::new();
+        for (path, display) in &candidates {
+            if *segment == "**" && options.globstar {
+                next.push((path.clone(), display.clone()));
+                collect_descendants(fs, path, display, last, options.dotglob, &mut next);
+            } else if has_glob_pattern(segment, options.extglob) {
+                let Ok(mut names) = fs.read_dir(path) else {
+                    continue;
+                };
+                names.sort();
+                let allow_hidden = options.dotglob || segment.starts_with('.') || segment.starts_with("\\.");
+                for name in names {
+                    if name == "." || name == ".." || (name.starts_with('.') && !allow_hidden) {
+                        continue;
+                    }
+                    if !match_pattern(&name, segment, false, options.extglob) {
+                        continue;
+                    }
+                    let child = join_fs_path(path, &name);
+                    if !last && !fs.is_dir(&child) {
+                        continue;
+                    }
+                    next.push((child, join_display_path(display, &name)));
+                }
+            } else {
+                let name = unescape_glob_pattern(segment);
+                let child = join_fs_path(path, &name);
+                if fs.exists(&child) {
+                    next.push((child, join_display_path(display, &name)));
+                }
+            }
+        }
+        candidates = next;
+    }
+
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|(path, display)| !display.is_empty() && (!dirs_only || fs.is_dir(path)))
+        .map(|(_, display)| if dirs_only { format!("{}/", display) } else { display })
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    if matches.is_empty() {
+        if options.failglob {
+            return Err(format!("no match: {}", unescape_glob_pattern(pattern)));
+        }
+        let values = if options.nullglob {
+            vec![]
+        } else {
+            vec![unescape_glob_pattern(pattern)]
+        };
+        return Ok(GlobExpansionResult { values, quoted: false });
+    }
+
+    Ok(GlobExpansionResult {
+        values: matches,
+        quoted: false,
+    })
+}
+
+/// Collect everything below `path` for a `**` segment. Files are only
+/// included when `**` is the final segment of the pattern.
+fn collect_descendants(
+    fs: &dyn SyncFileSystem,
+    path: &str,
+    display: &str,
+    include_files: bool,
+    dotglob: bool,
+    out: &mut Vec<(String, String)>,
+) {
+    let Ok(mut names) = fs.read_dir(path) else {
+        return;
+    };
+    names.sort();
+    for name in names {
+        if name == "." || name == ".." || (name.starts_with('.') && !dotglob) {
+            continue;
+        }
+        let child = join_fs_path(path, &name);
+        let child_display = join_display_path(display, &name);
+        if fs.is_dir(&child) {
+            out.push((child.clone(), child_display.clone()));
+            collect_descendants(fs, &child, &child_display, include_files, dotglob, out);
+        } else if include_files {
+            out.push((child, child_display));
+        }
+    }
+}
+
+fn join_fs_path(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn join_display_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        join_fs_path(prefix, name)
+    }
+}
+
 /// Legacy glob pattern matching for backward compatibility.
 #[allow(dead_code)]
 fn glob_pattern(pattern: &str, cwd: &Path) -> Result<Vec<String>, String> {
@@ -123,13 +123,18 @@ pub fn setup_function_call(
     }
     state.local_exported_vars.as_mut().unwrap().push(std::collections::HashSet::new());
 
-    // Save and set positional parameters
+    // Save and set positional parameters; the caller's extra ones are hidden
     let mut saved_positional = HashMap::new();
     for (i, arg) in args.iter().enumerate() {
         let key = (i + 1).to_string();
         saved_positional.insert(key.clone(), state.env.get(&key).cloned());
         state.env.insert(key, arg.clone());
     }
+    let outer_count: usize = state.env.get("#").and_then(|n| n.parse().ok()).unwrap_or(0);
+    for i in args.len() + 1..=outer_count {
+        let key = i.to_string();
+        saved_positional.insert(key.clone(), state.env.remove(&key));
+    }
     saved_positional.insert("@".to_string(), state.env.get("@").cloned());
     saved_positional.insert("#".to_string(), state.env.get("#").cloned());
     state.env.insert("@".to_string(), args.join(" "));
@@ -389,6 +394,25 @@ mod tests {
         assert_eq!(state.call_depth, 0);
     }
 
+    #[test]
+    fn test_setup_function_call_hides_extra_caller_params() {
+        let mut state = make_state();
+        for (key, value) in [("1", "a"), ("2", "b"), ("3", "c"), ("#", "3")] {
+            state.env.insert(key.to_string(), value.to_string());
+        }
+        let func = make_function("myfunc");
+
+        let ctx = setup_function_call(&mut state, &func, &["x".to_string()], None, 1000).unwrap();
+        assert_eq!(state.env.get("1"), Some(&"x".to_string()));
+        assert!(state.env.get("2").is_none());
+        assert!(state.env.get("3").is_none());
+
+        cleanup_function_call(&mut state, ctx);
+        assert_eq!(state.env.get("1"), Some(&"a".to_string()));
+        assert_eq!(state.env.get("3"), Some(&"c".to_string()));
+        assert_eq!(state.env.get("#"), Some(&"3".to_string()));
+    }
+
     #[test]
     fn test_recursion_depth_limit() {
         let mut state = make_state();
@@ -190,6 +190,48 @@ pub trait FileSystem {
     fn lstat(&self, path: &Path) -> Option<FileStat>;
     /// Resolve a path relative to cwd.
     fn resolve_path(&self, cwd: &str, path: &str) -> String;
+    /// Canonical path with symlinks followed, if the filesystem can tell.
+    fn realpath(&self, _path: &Path) -> Option<String> {
+        None
+    }
+}
+
+/// Bridges the interpreter's sync filesystem to the file test trait.
+pub struct InterpreterFs<'a>(pub &'a dyn crate::interpreter::interpreter::FileSystem);
+
+impl InterpreterFs<'_> {
+    fn convert(stat: crate::interpreter::interpreter::FileStat) -> FileStat {
+        FileStat {
+            is_file: stat.is_file,
+            is_directory: stat.is_dir,
+            is_symlink: stat.is_symlink,
+            size: stat.size,
+            mode: stat.mode,
+            mtime: stat.mtime as i64,
+        }
+    }
+}
+
+impl FileSystem for InterpreterFs<'_> {
+    fn exists(&self, path: &Path) -> bool {
+        self.0.exists(&path.to_string_lossy())
+    }
+
+    fn stat(&self, path: &Path) -> Option<FileStat> {
+        self.0.stat(&path.to_string_lossy()).ok().map(Self::convert)
+    }
+
+    fn lstat(&self, path: &Path) -> Option<FileStat> {
+        self.0.lstat(&path.to_string_lossy()).ok().map(Self::convert)
+    }
+
+    fn resolve_path(&self, cwd: &str, path: &str) -> String {
+        self.0.resolve_path(cwd, path)
+    }
+
+    fn realpath(&self, path: &Path) -> Option<String> {
+        self.0.realpath(&path.to_string_lossy()).ok()
+    }
 }
 
 /// Evaluate a file test operator against a path.
@@ -321,17 +363,19 @@ pub fn evaluate_binary_file_test<F: FileSystem>(
 
     match operator {
         BinaryFileTestOperator::NewerThan => {
-            // left is newer than right
+            // left is newer than right, or left exists and right does not
             match (fs.stat(left_path), fs.stat(right_path)) {
                 (Some(left_stat), Some(right_stat)) => left_stat.mtime > right_stat.mtime,
+                (Some(_), None) => true,
                 _ => false,
             }
         }
 
         BinaryFileTestOperator::OlderThan => {
-            // left is older than right
+            // left is older than right, or right exists and left does not
             match (fs.stat(left_path), fs.stat(right_path)) {
                 (Some(left_stat), Some(right_stat)) => left_stat.mtime < right_stat.mtime,
+                (None, Some(_)) => true,
                 _ => false,
             }
         }
@@ -342,8 +386,10 @@ pub fn evaluate_binary_file_test<F: FileSystem>(
             if !fs.exists(left_path) || !fs.exists(right_path) {
                 return false;
             }
-            // Compare canonical paths
-            left_path_str == right_path_str
+            // Compare canonical paths, following symlinks where possible
+            let left_real = fs.realpath(left_path).unwrap_or(left_path_str.clone());
+            let right_real = fs.realpath(right_path).unwrap_or(right_path_str.clone());
+            left_real == right_real
         }
     }
 }
@@ -132,6 +132,11 @@ pub fn handle_loop_error(
             LoopErrorResult::rethrow(stdout, stderr, InterpreterError::Errexit(e))
         }
 
+        InterpreterError::Nounset(mut e) => {
+            e.prepend_output(&stdout, &stderr);
+            LoopErrorResult::rethrow(stdout, stderr, InterpreterError::Nounset(e))
+        }
+
         InterpreterError::Exit(mut e) => {
             e.prepend_output(&stdout, &stderr);
             LoopErrorResult::rethrow(stdout, stderr, InterpreterError::Exit(e))
@@ -14,6 +14,7 @@ pub mod regex_utils;
 pub mod result;
 pub mod shell_constants;
 pub mod shellopts;
+pub mod signals;
 pub mod statements;
 pub mod string_compare;
 pub mod string_tests;
@@ -37,6 +38,7 @@ pub use regex_utils::*;
 pub use result::*;
 pub use shell_constants::*;
 pub use shellopts::*;
+pub use signals::*;
 pub use statements::*;
 pub use string_compare::*;
 pub use string_tests::*;
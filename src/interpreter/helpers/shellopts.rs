@@ -10,6 +10,8 @@ use crate::interpreter::types::{ShellOptions, ShoptOptions};
 const SHELLOPTS_OPTIONS: &[(&str, fn(&ShellOptions) -> bool)] = &[
     ("allexport", |o| o.allexport),
     ("errexit", |o| o.errexit),
+    ("errtrace", |o| o.errtrace),
+    ("functrace", |o| o.functrace),
     ("noglob", |o| o.noglob),
     ("noclobber", |o| o.noclobber),
     ("noexec", |o| o.noexec),
@@ -66,6 +68,7 @@ const BASHOPTS_OPTIONS: &[(&str, fn(&ShoptOptions) -> bool)] = &[
     ("failglob", |o| o.failglob),
     ("globskipdots", |o| o.globskipdots),
     ("globstar", |o| o.globstar),
+    ("inherit_errexit", |o| o.inherit_errexit),
     ("lastpipe", |o| o.lastpipe),
     ("nocaseglob", |o| o.nocaseglob),
     ("nocasematch", |o| o.nocasematch),
@@ -0,0 +1,95 @@
+//! Signal names and numbers shared by `kill` and `trap`.
+//!
+//! There are no real processes in the sandbox, so signals are only ever
+//! simulated; the numbering follows Linux.
+
+/// Signals by number, without the `SIG` prefix.
+pub const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", 1),
+    ("INT", 2),
+    ("QUIT", 3),
+    ("ILL", 4),
+    ("TRAP", 5),
+    ("ABRT", 6),
+    ("BUS", 7),
+    ("FPE", 8),
+    ("KILL", 9),
+    ("USR1", 10),
+    ("SEGV", 11),
+    ("USR2", 12),
+    ("PIPE", 13),
+    ("ALRM", 14),
+    ("TERM", 15),
+    ("STKFLT", 16),
+    ("CHLD", 17),
+    ("CONT", 18),
+    ("STOP", 19),
+    ("TSTP", 20),
+    ("TTIN", 21),
+    ("TTOU", 22),
+    ("URG", 23),
+    ("XCPU", 24),
+    ("XFSZ", 25),
+    ("VTALRM", 26),
+    ("PROF", 27),
+    ("WINCH", 28),
+    ("IO", 29),
+    ("PWR", 30),
+    ("SYS", 31),
+];
+
+pub const SIGINT: i32 = 2;
+pub const SIGKILL: i32 = 9;
+pub const SIGTERM: i32 = 15;
+
+/// Name of a signal number, without the `SIG` prefix.
+pub fn signal_name(signal: i32) -> Option<&'static str> {
+    SIGNALS.iter().find(|(_, number)| *number == signal).map(|(name, _)| *name)
+}
+
+/// Parse a signal given by name (`TERM`, `SIGTERM`, `sigterm`) or number.
+pub fn parse_signal(spec: &str) -> Option<i32> {
+    if let Ok(number) = spec.parse::<i32>() {
+        return (0..=64).contains(&number).then_some(number);
+    }
+    let upper = spec.to_ascii_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS.iter().find(|(n, _)| *n == name).map(|(_, number)| *number)
+}
+
+/// Whether a signal nobody handles ends the shell. Job control signals and
+/// the ones ignored by default do not.
+pub fn is_fatal_by_default(signal: i32) -> bool {
+    !matches!(signal_name(signal), Some("CHLD" | "CONT" | "STOP" | "TSTP" | "TTIN" | "TTOU" | "URG" | "WINCH"))
+}
+
+/// The signal table printed by `kill -l` and `trap -l`.
+pub fn format_signal_list() -> String {
+    let mut out = String::new();
+    for (i, (name, number)) in SIGNALS.iter().enumerate() {
+        out.push_str(&format!("{:>2}) SIG{}", number, name));
+        out.push(if i % 5 == 4 || i + 1 == SIGNALS.len() { '\n' } else { '\t' });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(parse_signal("TERM"), Some(15));
+        assert_eq!(parse_signal("sigint"), Some(2));
+        assert_eq!(parse_signal("9"), Some(9));
+        assert_eq!(parse_signal("BOGUS"), None);
+        assert_eq!(parse_signal("99"), None);
+    }
+
+    #[test]
+    fn test_format_signal_list() {
+        let list = format_signal_list();
+        assert!(list.starts_with(" 1) SIGHUP\t 2) SIGINT\t"));
+        assert_eq!(list.lines().count(), 7);
+    }
+}
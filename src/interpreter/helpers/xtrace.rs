@@ -84,6 +84,16 @@ where
     }
 }
 
+/// Prefix for a trace line made at `level` levels of indirection: the
+/// expanded PS4 with its first character repeated once more per level, so a
+/// command run by a command substitution shows as `++ cmd`.
+pub fn xtrace_prefix_for_level(ps4: &str, level: u32) -> String {
+    match ps4.chars().next() {
+        Some(first) => format!("{}{}", first.to_string().repeat(level as usize), ps4),
+        None => String::new(),
+    }
+}
+
 /// Quote a value for trace output if needed.
 /// Follows bash conventions for xtrace output quoting.
 pub fn quote_for_trace(value: &str) -> String {
@@ -229,6 +239,14 @@ mod tests {
         assert_eq!(get_xtrace_prefix(&env), "");
     }
 
+    #[test]
+    fn test_xtrace_prefix_for_level() {
+        assert_eq!(xtrace_prefix_for_level("+ ", 0), "+ ");
+        assert_eq!(xtrace_prefix_for_level("+ ", 2), "+++ ");
+        assert_eq!(xtrace_prefix_for_level("[1] ", 1), "[[1] ");
+        assert_eq!(xtrace_prefix_for_level("", 3), "");
+    }
+
     #[test]
     fn test_quote_for_trace_simple() {
         assert_eq!(quote_for_trace("hello"), "hello");
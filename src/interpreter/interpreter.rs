@@ -52,6 +52,9 @@ pub trait FileSystem: Send + Sync {
     /// Append contents to a file.
     fn append_file(&self, path: &str, contents: &str) -> Result<(), std::io::Error>;
 
+    /// Remove a file.
+    fn remove_file(&self, path: &str) -> Result<(), std::io::Error>;
+
     /// Check if a path exists.
     fn exists(&self, path: &str) -> bool;
 
@@ -67,6 +70,12 @@ pub trait FileSystem: Send + Sync {
     /// Get file metadata.
     fn stat(&self, path: &str) -> Result<FileStat, std::io::Error>;
 
+    /// Get file metadata without following a trailing symlink.
+    fn lstat(&self, path: &str) -> Result<FileStat, std::io::Error>;
+
+    /// Resolve a path to its canonical form, following symlinks.
+    fn realpath(&self, path: &str) -> Result<String, std::io::Error>;
+
     /// List directory contents.
     fn read_dir(&self, path: &str) -> Result<Vec<String>, std::io::Error>;
 
@@ -201,8 +210,18 @@ pub fn should_trigger_errexit(
     was_short_circuited: bool,
     was_negated: bool,
 ) -> bool {
-    state.options.errexit
-        && exit_code != 0
+    state.options.errexit && is_reportable_failure(state, exit_code, was_short_circuited, was_negated)
+}
+
+/// Check if a statement failed in a way errexit and the ERR trap react to,
+/// with the exemptions listed on `should_trigger_errexit`.
+pub fn is_reportable_failure(
+    state: &InterpreterState,
+    exit_code: i32,
+    was_short_circuited: bool,
+    was_negated: bool,
+) -> bool {
+    exit_code != 0
         && !was_short_circuited
         && !was_negated
         && !state.in_condition
@@ -244,6 +263,68 @@ pub fn check_recursion_limit(state: &InterpreterState, limits: &ExecutionLimits)
     }
 }
 
+/// Check the combined size of stdout and stderr against the output limit.
+///
+/// Returns an error message if the limit is exceeded.
+pub fn check_output_limit(stdout: &str, stderr: &str, limits: &ExecutionLimits) -> Option<String> {
+    check_output_size(stdout.len() + stderr.len(), limits)
+}
+
+/// Check what of stdout and stderr reaches the script's output against the
+/// output limit. A stream captured by a pipe or `$(...)`, or redirected to a
+/// file, only counts once it reaches the output.
+pub fn check_visible_output_limit(
+    state: &InterpreterState,
+    stdout: &str,
+    stderr: &str,
+    limits: &ExecutionLimits,
+) -> Option<String> {
+    let visible = |fd: i32, output: &str| match state.captured_fds.contains(&fd) {
+        true => 0,
+        false => output.len(),
+    };
+    check_output_size(visible(1, stdout) + visible(2, stderr), limits)
+}
+
+fn check_output_size(size: usize, limits: &ExecutionLimits) -> Option<String> {
+    if size > limits.max_output_size {
+        Some(format!(
+            "output size limit exceeded (>{} bytes), increase executionLimits.maxOutputSize",
+            limits.max_output_size
+        ))
+    } else {
+        None
+    }
+}
+
+/// Check the length of a variable value or expanded word.
+///
+/// Returns an error message if the limit is exceeded.
+pub fn check_string_limit(value: &str, limits: &ExecutionLimits) -> Option<String> {
+    if value.len() > limits.max_string_length {
+        Some(format!(
+            "string length limit exceeded (>{} bytes), increase executionLimits.maxStringLength",
+            limits.max_string_length
+        ))
+    } else {
+        None
+    }
+}
+
+/// Check the element count of an array or expanded word list.
+///
+/// Returns an error message if the limit is exceeded.
+pub fn check_array_limit(len: usize, limits: &ExecutionLimits) -> Option<String> {
+    if len > limits.max_array_elements {
+        Some(format!(
+            "array size limit exceeded (>{} elements), increase executionLimits.maxArrayElements",
+            limits.max_array_elements
+        ))
+    } else {
+        None
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -342,4 +423,21 @@ mod tests {
         state.call_depth = 6;
         assert!(check_recursion_limit(&state, &limits).is_some());
     }
+
+    #[test]
+    fn test_check_size_limits() {
+        let limits = ExecutionLimits {
+            max_output_size: 4,
+            max_string_length: 3,
+            max_array_elements: 2,
+            ..Default::default()
+        };
+
+        assert!(check_output_limit("ab", "cd", &limits).is_none());
+        assert!(check_output_limit("abc", "de", &limits).is_some());
+        assert!(check_string_limit("abc", &limits).is_none());
+        assert!(check_string_limit("abcd", &limits).is_some());
+        assert!(check_array_limit(2, &limits).is_none());
+        assert!(check_array_limit(3, &limits).is_some());
+    }
 }
@@ -10,6 +10,7 @@ pub mod builtins;
 pub mod command_resolution;
 pub mod conditionals;
 pub mod control_flow;
+pub mod coprocess;
 pub mod errors;
 pub mod execution_engine;
 pub mod expansion;
@@ -17,10 +18,12 @@ pub mod functions;
 pub mod helpers;
 pub mod interpreter;
 pub mod pipeline_execution;
+pub mod process_substitution;
 pub mod redirections;
 pub mod simple_command_assignments;
 pub mod subshell_group;
 pub mod sync_fs_adapter;
+pub mod traps;
 pub mod type_command;
 pub mod types;
 pub mod word_expansion;
@@ -33,6 +36,7 @@ pub use builtins::*;
 pub use command_resolution::*;
 pub use conditionals::*;
 pub use control_flow::*;
+pub use coprocess::*;
 pub use errors::*;
 pub use execution_engine::*;
 pub use expansion::*;
@@ -40,10 +44,12 @@ pub use functions::*;
 pub use helpers::*;
 pub use interpreter::*;
 pub use pipeline_execution::*;
+pub use process_substitution::*;
 pub use redirections::*;
 pub use simple_command_assignments::*;
 pub use subshell_group::*;
 pub use sync_fs_adapter::*;
+pub use traps::*;
 pub use type_command::*;
 pub use types::*;
 pub use word_expansion::*;
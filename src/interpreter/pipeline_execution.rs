@@ -26,7 +26,8 @@
 //! handles shell state management.
 
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+use crate::clock::Clock;
 use crate::interpreter::types::ExecResult;
 use crate::interpreter::errors::InterpreterError;
 use crate::ast::types::CommandNode;
@@ -39,6 +40,8 @@ pub struct PipelineOptions {
     pub runs_in_subshell: bool,
     pub time_pipeline: bool,
     pub time_posix_format: bool,
+    /// Clock used to time the pipeline
+    pub clock: Clock,
 }
 
 /// Result of pipeline execution.
@@ -287,7 +290,7 @@ where
     F: FnMut(&CommandNode, &str) -> Result<ExecResult, InterpreterError>,
 {
     let start_time = if options.time_pipeline {
-        Some(Instant::now())
+        Some(options.clock.now())
     } else {
         None
     };
@@ -332,7 +335,7 @@ where
     let final_exit_code = calculate_pipefail_exit_code(&exit_codes, options.pipefail);
 
     // Calculate elapsed time if timing was requested
-    let elapsed_time = start_time.map(|t| t.elapsed());
+    let elapsed_time = start_time.map(|t| options.clock.now().duration_since(t).unwrap_or_default());
 
     // Append timing output to stderr if timing was requested
     if let Some(elapsed) = elapsed_time {
@@ -563,6 +566,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let cmd2 = CommandNode::Simple(SimpleCommandNode {
@@ -573,6 +577,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let commands = vec![cmd1, cmd2];
@@ -630,6 +635,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let cmd2 = CommandNode::Simple(SimpleCommandNode {
@@ -640,6 +646,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let cmd3 = CommandNode::Simple(SimpleCommandNode {
@@ -650,6 +657,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let commands = vec![cmd1, cmd2, cmd3];
@@ -695,6 +703,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let cmd2 = CommandNode::Simple(SimpleCommandNode {
@@ -705,6 +714,7 @@ mod tests {
             assignments: vec![],
             redirections: vec![],
             line: None,
+            source_text: None,
         });
 
         let commands = vec![cmd1, cmd2];
@@ -0,0 +1,172 @@
+//! Process Substitution
+//!
+//! Handles `<(cmd)` and `>(cmd)` in command arguments and redirection targets.
+//! There are no real pipes in the sandbox, so each substitution is backed by a
+//! virtual file under /dev/fd:
+//! - `<(cmd)` runs `cmd` first and stores its output in the file
+//! - `>(cmd)` hands the outer command an empty file, then feeds whatever was
+//!   written to it to `cmd` once the outer command has finished
+
+use crate::ast::types::{
+    ProcessDirection, ProcessSubstitutionPart, RedirectionNode, RedirectionTarget, ScriptNode,
+    WordNode, WordPart, AST,
+};
+use crate::interpreter::interpreter::FileSystem;
+use crate::interpreter::types::{ExecResult, InterpreterState};
+
+/// Highest descriptor number handed out, matching bash's first choice.
+const FIRST_FD: u32 = 63;
+
+/// Files created for the substitutions of one command.
+#[derive(Debug, Default)]
+pub struct ProcessSubstitutions {
+    /// Every file created, removed when the command is done
+    files: Vec<String>,
+    /// `>(...)` bodies waiting for their file to be written
+    outputs: Vec<(String, ScriptNode)>,
+    /// Error output of the `<(...)` bodies
+    pub stderr: String,
+}
+
+impl ProcessSubstitutions {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Check if a word contains a process substitution.
+pub fn has_process_substitution(word: &WordNode) -> bool {
+    word.parts.iter().any(|part| matches!(part, WordPart::ProcessSubstitution(_)))
+}
+
+/// Replace every process substitution in `words` with the path of its
+/// backing file. Returns None when there is nothing to replace.
+///
+/// `run` executes a substitution body with the given stdin.
+pub fn open_process_substitutions<F>(
+    state: &mut InterpreterState,
+    words: &[&WordNode],
+    fs: &dyn FileSystem,
+    subs: &mut ProcessSubstitutions,
+    mut run: F,
+) -> Option<Vec<WordNode>>
+where
+    F: FnMut(&mut InterpreterState, &ScriptNode, Option<&str>) -> ExecResult,
+{
+    if !words.iter().any(|word| has_process_substitution(word)) {
+        return None;
+    }
+
+    let mut replaced = Vec::with_capacity(words.len());
+    for word in words {
+        let mut parts = Vec::with_capacity(word.parts.len());
+        for part in &word.parts {
+            match part {
+                WordPart::ProcessSubstitution(ProcessSubstitutionPart { body, direction }) => {
+                    let path = allocate_fd_path(fs);
+                    let content = match direction {
+                        ProcessDirection::Input => {
+                            let result = run(state, body, None);
+                            subs.stderr.push_str(&result.stderr);
+                            result.stdout
+                        }
+                        ProcessDirection::Output => {
+                            subs.outputs.push((path.clone(), body.clone()));
+                            String::new()
+                        }
+                    };
+                    let _ = fs.write_file(&path, &content);
+                    subs.files.push(path.clone());
+                    parts.push(AST::literal(&path));
+                }
+                other => parts.push(other.clone()),
+            }
+        }
+        replaced.push(WordNode { parts });
+    }
+    Some(replaced)
+}
+
+/// Like `open_process_substitutions`, for the targets of `redirections`.
+pub fn open_redirection_process_substitutions<F>(
+    state: &mut InterpreterState,
+    redirections: &[RedirectionNode],
+    fs: &dyn FileSystem,
+    subs: &mut ProcessSubstitutions,
+    run: F,
+) -> Option<Vec<RedirectionNode>>
+where
+    F: FnMut(&mut InterpreterState, &ScriptNode, Option<&str>) -> ExecResult,
+{
+    let targets: Vec<&WordNode> = redirections
+        .iter()
+        .filter_map(|redir| match &redir.target {
+            RedirectionTarget::Word(word) => Some(word),
+            RedirectionTarget::HereDoc(_) => None,
+        })
+        .collect();
+    let mut replaced = open_process_substitutions(state, &targets, fs, subs, run)?.into_iter();
+
+    Some(
+        redirections
+            .iter()
+            .map(|redir| {
+                let mut redir = redir.clone();
+                if let RedirectionTarget::Word(word) = &mut redir.target {
+                    if let Some(path_word) = replaced.next() {
+                        *word = path_word;
+                    }
+                }
+                redir
+            })
+            .collect(),
+    )
+}
+
+/// Run the pending `>(...)` bodies on what the command wrote to their files,
+/// then remove every file. Returns the bodies' combined output.
+pub fn close_process_substitutions<F>(
+    state: &mut InterpreterState,
+    subs: ProcessSubstitutions,
+    fs: &dyn FileSystem,
+    mut run: F,
+) -> ExecResult
+where
+    F: FnMut(&mut InterpreterState, &ScriptNode, Option<&str>) -> ExecResult,
+{
+    let mut output = ExecResult::ok();
+    for (path, body) in &subs.outputs {
+        let input = fs.read_file(path).unwrap_or_default();
+        let result = run(state, body, Some(&input));
+        output.stdout.push_str(&result.stdout);
+        output.stderr.push_str(&result.stderr);
+    }
+    for path in &subs.files {
+        let _ = fs.remove_file(path);
+    }
+    output
+}
+
+/// Pick the highest free /dev/fd path, counting down from 63 like bash.
+fn allocate_fd_path(fs: &dyn FileSystem) -> String {
+    (0..=FIRST_FD)
+        .rev()
+        .map(|fd| format!("/dev/fd/{}", fd))
+        .find(|path| !fs.exists(path))
+        .unwrap_or_else(|| format!("/dev/fd/{}", FIRST_FD))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_process_substitution() {
+        let plain = AST::word(vec![AST::literal("file")]);
+        assert!(!has_process_substitution(&plain));
+
+        let body = AST::script(vec![]);
+        let subst = AST::word(vec![AST::process_substitution(body, ProcessDirection::Input)]);
+        assert!(has_process_substitution(&subst));
+    }
+}
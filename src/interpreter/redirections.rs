@@ -1,6 +1,8 @@
 //! Redirection Handling
 //!
-//! Handles output redirections:
+//! Handles input and output redirections:
+//! - < <> : Read stdin from file
+//! - << <<- <<< : Here-documents and here-strings
 //! - > : Write stdout to file
 //! - >> : Append stdout to file
 //! - 2> : Write stderr to file
@@ -9,8 +11,9 @@
 //! - {fd}>file : Allocate FD and store in variable
 
 use std::collections::HashMap;
-use crate::ast::types::{RedirectionNode, RedirectionTarget, RedirectionOperator, WordNode};
-use crate::interpreter::types::{ExecResult, InterpreterState};
+use crate::audit::unaudited;
+use crate::ast::types::{RedirectionNode, RedirectionTarget, RedirectionOperator, WordNode, WordPart};
+use crate::interpreter::types::{ExecResult, FileDescriptor, InterpreterState, Pipe};
 use crate::interpreter::interpreter::FileSystem;
 
 /// Pre-expanded redirect targets, keyed by index into the redirections array.
@@ -136,6 +139,15 @@ pub fn pre_expand_redirect_targets(
     PreExpandResult { targets, error: None }
 }
 
+/// Environment key of the variable in `{varname}>file`, which may be an
+/// array element (`{arr[1]}`).
+fn fd_variable_key(name: &str) -> String {
+    match name.strip_suffix(']').and_then(|rest| rest.split_once('[')) {
+        Some((array, index)) => format!("{}_{}", array, index),
+        None => name.to_string(),
+    }
+}
+
 /// Process FD variable redirections ({varname}>file syntax).
 /// This allocates FDs and sets variables before command execution.
 /// Returns an error result if there's an issue, or None if successful.
@@ -147,7 +159,7 @@ pub fn process_fd_variable_redirections(
 ) -> Option<ExecResult> {
     for redir in redirections {
         let fd_variable = match &redir.fd_variable {
-            Some(v) => v,
+            Some(v) => fd_variable_key(v),
             None => continue,
         };
 
@@ -162,7 +174,7 @@ pub fn process_fd_variable_redirections(
                 let target = expand_word_fn(state, word);
                 if target == "-" {
                     // Close operation - look up the FD from the variable and close it
-                    if let Some(existing_fd) = state.env.get(fd_variable) {
+                    if let Some(existing_fd) = state.env.get(&fd_variable) {
                         if let Ok(fd_num) = existing_fd.parse::<i32>() {
                             if let Some(ref mut fds) = state.file_descriptors {
                                 fds.remove(&fd_num);
@@ -179,7 +191,7 @@ pub fn process_fd_variable_redirections(
         let fd = allocate_fd(state);
 
         // Set the variable to the allocated FD number
-        state.env.insert(fd_variable.clone(), fd.to_string());
+        state.env.insert(fd_variable, fd.to_string());
 
         // For file redirections, store the file path mapping
         if let RedirectionTarget::Word(ref word) = redir.target {
@@ -189,12 +201,9 @@ pub fn process_fd_variable_redirections(
             if matches!(redir.operator, RedirectionOperator::GreatAnd | RedirectionOperator::LessAnd) {
                 if let Ok(source_fd) = target.parse::<i32>() {
                     // Duplicate the source FD's content to the new FD
-                    if let Some(ref fds) = state.file_descriptors {
-                        if let Some(content) = fds.get(&source_fd) {
-                            let content = content.clone();
-                            if let Some(ref mut fds) = state.file_descriptors {
-                                fds.insert(fd, content);
-                            }
+                    if let Some(ref mut fds) = state.file_descriptors {
+                        if let Some(entry) = fds.get(&source_fd).cloned() {
+                            fds.insert(fd, entry);
                         }
                     }
                     continue;
@@ -219,14 +228,13 @@ pub fn process_fd_variable_redirections(
                 ) {
                     let _ = fs.write_file(&file_path, "");
                 }
-                let file_marker = format!("__file__:{}", file_path);
                 if let Some(ref mut fds) = state.file_descriptors {
-                    fds.insert(fd, file_marker);
+                    fds.insert(fd, FileDescriptor::File(file_path));
                 }
             } else if redir.operator == RedirectionOperator::TLess {
                 // For here-strings, store the target value plus newline as the FD content
                 if let Some(ref mut fds) = state.file_descriptors {
-                    fds.insert(fd, format!("{}\n", target));
+                    fds.insert(fd, FileDescriptor::Content(format!("{}\n", target)));
                 }
             } else if matches!(redir.operator, RedirectionOperator::Less | RedirectionOperator::LessGreat) {
                 // For input redirections, read the file content
@@ -234,7 +242,7 @@ pub fn process_fd_variable_redirections(
                 match fs.read_file(&file_path) {
                     Ok(content) => {
                         if let Some(ref mut fds) = state.file_descriptors {
-                            fds.insert(fd, content);
+                            fds.insert(fd, FileDescriptor::Content(content));
                         }
                     }
                     Err(_) => {
@@ -266,10 +274,11 @@ pub fn process_fd_variable_redirections(
 pub fn pre_open_output_redirects(
     state: &mut InterpreterState,
     redirections: &[RedirectionNode],
+    pre_expanded_targets: Option<&ExpandedRedirectTargets>,
     fs: &dyn FileSystem,
     expand_word_fn: impl Fn(&mut InterpreterState, &WordNode) -> String,
 ) -> Option<ExecResult> {
-    for redir in redirections {
+    for (i, redir) in redirections.iter().enumerate() {
         if matches!(redir.target, RedirectionTarget::HereDoc(_)) {
             continue;
         }
@@ -286,15 +295,15 @@ pub fn pre_open_output_redirects(
             continue;
         }
 
-        let target = if let RedirectionTarget::Word(ref word) = redir.target {
-            expand_word_fn(state, word)
-        } else {
-            continue;
+        let target = match (&redir.target, pre_expanded_targets.and_then(|targets| targets.get(&i))) {
+            (RedirectionTarget::Word(_), Some(target)) => target.clone(),
+            (RedirectionTarget::Word(word), None) => expand_word_fn(state, word),
+            _ => continue,
         };
 
         // For >&, check if it's an FD redirect (number or -)
         if is_greater_ampersand {
-            if target == "-" || target.parse::<i32>().is_ok() || redir.fd.is_some() {
+            if target == "-" || target.parse::<i32>().is_ok() {
                 continue;
             }
         }
@@ -348,8 +357,146 @@ pub fn pre_open_output_redirects(
     None // Success - no error
 }
 
+/// Read the input redirections (`<`, `<<`, `<<-`, `<<<`, `<>`, `<&N`) of a command.
+///
+/// Returns the stdin the command should see, or `None` when none of the
+/// redirections touch fd 0. Here-documents on other fds are stored in
+/// `state.file_descriptors` so `<&N` and `read -u N` can find them.
+pub fn read_input_redirections(
+    state: &mut InterpreterState,
+    redirections: &[RedirectionNode],
+    pre_expanded_targets: Option<&ExpandedRedirectTargets>,
+    fs: &dyn FileSystem,
+    expand_word_fn: impl Fn(&mut InterpreterState, &WordNode) -> String,
+) -> Result<Option<String>, ExecResult> {
+    let mut stdin = None;
+
+    for (i, redir) in redirections.iter().enumerate() {
+        if redir.fd_variable.is_some() {
+            continue;
+        }
+        let fd = redir.fd.unwrap_or(0);
+
+        if let RedirectionTarget::HereDoc(ref heredoc) = redir.target {
+            let mut content = expand_word_fn(state, &heredoc.content);
+            if heredoc.strip_tabs {
+                content = content
+                    .split('\n')
+                    .map(|line| line.trim_start_matches('\t'))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            }
+            if fd == 0 {
+                stdin = Some(content);
+            } else {
+                state.file_descriptors.get_or_insert_with(HashMap::new).insert(fd, FileDescriptor::Content(content));
+            }
+            continue;
+        }
+
+        let word = match &redir.target {
+            RedirectionTarget::Word(w) => w,
+            _ => continue,
+        };
+        if !matches!(redir.operator,
+            RedirectionOperator::Less |
+            RedirectionOperator::LessGreat |
+            RedirectionOperator::TLess |
+            RedirectionOperator::LessAnd
+        ) || fd != 0 {
+            continue;
+        }
+
+        let target = match pre_expanded_targets.and_then(|targets| targets.get(&i)) {
+            Some(target) => target.clone(),
+            None => expand_word_fn(state, word),
+        };
+
+        match redir.operator {
+            RedirectionOperator::TLess => {
+                stdin = Some(format!("{}\n", target));
+            }
+            RedirectionOperator::Less | RedirectionOperator::LessGreat => {
+                if target == "/dev/null" {
+                    stdin = Some(String::new());
+                    continue;
+                }
+                let file_path = fs.resolve_path(&state.cwd, &target);
+                match fs.read_file(&file_path) {
+                    Ok(content) => stdin = Some(content),
+                    Err(_) if redir.operator == RedirectionOperator::LessGreat => {
+                        // <> creates the file when it doesn't exist
+                        let _ = fs.write_file(&file_path, "");
+                        stdin = Some(String::new());
+                    }
+                    Err(_) => {
+                        return Err(ExecResult::new(
+                            String::new(),
+                            format!("bash: {}: No such file or directory\n", target),
+                            1,
+                        ));
+                    }
+                }
+            }
+            _ => {
+                // <&N duplicates an input fd; <&- closes stdin
+                if target == "-" {
+                    stdin = Some(String::new());
+                } else if let Ok(source_fd) = target.trim_end_matches('-').parse::<i32>() {
+                    if source_fd == 0 {
+                        continue;
+                    }
+                    let entry = state.file_descriptors.as_ref().and_then(|fds| fds.get(&source_fd)).cloned();
+                    match entry {
+                        Some(entry) => stdin = Some(read_fd(&entry)),
+                        None => {
+                            return Err(ExecResult::new(
+                                String::new(),
+                                format!("bash: {}: Bad file descriptor\n", source_fd),
+                                1,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(stdin)
+}
+
+/// What a command reading from an fd table entry gets. A pipe is drained,
+/// the way a reader takes in everything buffered in a real pipe.
+fn read_fd(entry: &FileDescriptor) -> String {
+    match entry {
+        FileDescriptor::Content(content) => content.clone(),
+        FileDescriptor::ReadWrite { position, content, .. } => content.chars().skip(*position).collect(),
+        FileDescriptor::PipeRead(pipe) => pipe.take(),
+        FileDescriptor::File(_) | FileDescriptor::Dup(_) | FileDescriptor::PipeWrite(_) => String::new(),
+    }
+}
+
+/// Where a command's stdout or stderr ends up once its redirections are applied.
+#[derive(Debug, Clone)]
+enum OutputSink {
+    Stdout,
+    Stderr,
+    Discard,
+    /// `/dev/full`: every write fails
+    Full,
+    /// A file (resolved path), truncated on first write when `truncate` is set
+    File { path: String, truncate: bool },
+    /// The write end of a pipe
+    Pipe(Pipe),
+}
+
 /// Apply redirections to an execution result.
-/// This handles the actual redirection of stdout/stderr to files or other FDs.
+///
+/// Redirections are processed left to right into a table saying where fd 1
+/// and fd 2 point, the way bash opens them, so `> file 2>&1` sends both
+/// streams to the file while `2>&1 > file` leaves stderr on stdout. The
+/// command's output is then routed through that table. Noclobber is checked by
+/// `pre_open_output_redirects`, which should run before the command.
 pub fn apply_redirections(
     state: &mut InterpreterState,
     result: ExecResult,
@@ -358,8 +505,10 @@ pub fn apply_redirections(
     fs: &dyn FileSystem,
     expand_word_fn: impl Fn(&mut InterpreterState, &WordNode) -> String,
 ) -> ExecResult {
-    let mut stdout = result.stdout;
-    let mut stderr = result.stderr;
+    let mut out_sink = fd_sink(state, 1);
+    let mut err_sink = fd_sink(state, 2);
+    let mut stdout = String::new();
+    let mut stderr = String::new();
     let mut exit_code = result.exit_code;
 
     for (i, redir) in redirections.iter().enumerate() {
@@ -368,254 +517,483 @@ pub fn apply_redirections(
             continue;
         }
 
+        // Skip FD variable redirections - they're already handled
+        if redir.fd_variable.is_some() {
+            continue;
+        }
+
         // Get target word
         let word = match &redir.target {
             RedirectionTarget::Word(w) => w,
             _ => continue,
         };
 
+        let is_output = matches!(redir.operator,
+            RedirectionOperator::Great |
+            RedirectionOperator::Clobber |
+            RedirectionOperator::DGreat |
+            RedirectionOperator::GreatAnd |
+            RedirectionOperator::AndGreat |
+            RedirectionOperator::AndDGreat
+        ) || (redir.operator == RedirectionOperator::LessAnd && redir.fd.is_some_and(|fd| fd > 0));
+        if !is_output {
+            continue;
+        }
+
         // Use pre-expanded target if available, otherwise expand now
-        let target = if let Some(targets) = pre_expanded_targets {
-            targets.get(&i).cloned().unwrap_or_else(|| expand_word_fn(state, word))
-        } else {
-            expand_word_fn(state, word)
+        let target = match pre_expanded_targets.and_then(|targets| targets.get(&i)) {
+            Some(target) => target.clone(),
+            None => expand_word_fn(state, word),
         };
 
-        // Skip FD variable redirections - they're already handled
-        if redir.fd_variable.is_some() {
-            continue;
-        }
+        let file_sink = |state: &InterpreterState, truncate: bool, out: &OutputSink, err: &OutputSink| {
+            match target.as_str() {
+                "/dev/stdout" => out.clone(),
+                "/dev/stderr" => err.clone(),
+                "/dev/null" => OutputSink::Discard,
+                "/dev/full" => OutputSink::Full,
+                _ => OutputSink::File { path: fs.resolve_path(&state.cwd, &target), truncate },
+            }
+        };
 
         match redir.operator {
-            RedirectionOperator::Great | RedirectionOperator::Clobber => {
-                let fd = redir.fd.unwrap_or(1);
-                let is_clobber = redir.operator == RedirectionOperator::Clobber;
-
-                if fd == 1 {
-                    // Handle special devices
-                    if target == "/dev/stdout" {
-                        // No-op for stdout
-                    } else if target == "/dev/stderr" {
-                        stderr.push_str(&stdout);
-                        stdout.clear();
-                    } else if target == "/dev/full" {
-                        stderr.push_str("bash: echo: write error: No space left on device\n");
-                        exit_code = 1;
-                        stdout.clear();
-                    } else if target == "/dev/null" {
-                        stdout.clear();
-                    } else {
-                        let file_path = fs.resolve_path(&state.cwd, &target);
-                        if let Some(err) = check_output_redirect_target(state, fs, &file_path, &target, true, is_clobber) {
-                            stderr.push_str(&err);
-                            exit_code = 1;
-                            stdout.clear();
-                        } else {
-                            let _ = fs.write_file(&file_path, &stdout);
-                            stdout.clear();
-                        }
-                    }
-                } else if fd == 2 {
-                    if target == "/dev/stderr" {
-                        // No-op for stderr
-                    } else if target == "/dev/stdout" {
-                        stdout.push_str(&stderr);
-                        stderr.clear();
-                    } else if target == "/dev/full" {
-                        stderr.push_str("bash: echo: write error: No space left on device\n");
-                        exit_code = 1;
-                    } else if target == "/dev/null" {
-                        stderr.clear();
-                    } else {
-                        let file_path = fs.resolve_path(&state.cwd, &target);
-                        if let Some(err) = check_output_redirect_target(state, fs, &file_path, &target, true, is_clobber) {
-                            stderr.push_str(&err);
-                            exit_code = 1;
-                        } else {
-                            let _ = fs.write_file(&file_path, &stderr);
-                            stderr.clear();
-                        }
+            RedirectionOperator::Great | RedirectionOperator::Clobber | RedirectionOperator::DGreat => {
+                let truncate = redir.operator != RedirectionOperator::DGreat;
+                let sink = file_sink(state, truncate, &out_sink, &err_sink);
+                if let OutputSink::File { ref path, .. } = sink {
+                    if let Some(err) = check_output_redirect_target(state, fs, path, &target, false, false) {
+                        stderr.push_str(&err);
+                        return ExecResult::new(stdout, stderr, 1);
                     }
                 }
+                match redir.fd.unwrap_or(1) {
+                    1 => out_sink = sink,
+                    2 => err_sink = sink,
+                    _ => {}
+                }
             }
 
-            RedirectionOperator::DGreat => {
-                let fd = redir.fd.unwrap_or(1);
-
-                if fd == 1 {
-                    if target == "/dev/stdout" {
-                        // No-op
-                    } else if target == "/dev/stderr" {
-                        stderr.push_str(&stdout);
-                        stdout.clear();
-                    } else if target == "/dev/full" {
-                        stderr.push_str("bash: echo: write error: No space left on device\n");
-                        exit_code = 1;
-                        stdout.clear();
-                    } else {
-                        let file_path = fs.resolve_path(&state.cwd, &target);
-                        if let Some(err) = check_output_redirect_target(state, fs, &file_path, &target, false, false) {
-                            stderr.push_str(&err);
-                            exit_code = 1;
-                            stdout.clear();
-                        } else {
-                            let _ = fs.append_file(&file_path, &stdout);
-                            stdout.clear();
-                        }
-                    }
-                } else if fd == 2 {
-                    if target == "/dev/stderr" {
-                        // No-op
-                    } else if target == "/dev/stdout" {
-                        stdout.push_str(&stderr);
-                        stderr.clear();
-                    } else if target == "/dev/full" {
-                        stderr.push_str("bash: echo: write error: No space left on device\n");
-                        exit_code = 1;
-                    } else {
-                        let file_path = fs.resolve_path(&state.cwd, &target);
-                        if let Some(err) = check_output_redirect_target(state, fs, &file_path, &target, false, false) {
-                            stderr.push_str(&err);
-                            exit_code = 1;
-                        } else {
-                            let _ = fs.append_file(&file_path, &stderr);
-                            stderr.clear();
-                        }
-                    }
-                }
+            RedirectionOperator::AndGreat | RedirectionOperator::AndDGreat => {
+                let truncate = redir.operator == RedirectionOperator::AndGreat;
+                let sink = file_sink(state, truncate, &out_sink, &err_sink);
+                out_sink = sink.clone();
+                err_sink = sink;
             }
 
-            RedirectionOperator::GreatAnd | RedirectionOperator::LessAnd => {
+            _ => {
+                // >&N / <&N duplicate fd N, >&- closes, N>&M- moves M to N
                 let fd = redir.fd.unwrap_or(1);
+                let (source, moved) = match target.strip_suffix('-') {
+                    Some(rest) if !rest.is_empty() => (rest, true),
+                    _ => (target.as_str(), false),
+                };
 
-                // Handle close operation
-                if target == "-" {
-                    continue;
-                }
-
-                // Handle FD move operation: N>&M-
-                if target.ends_with('-') {
-                    let source_fd_str = &target[..target.len() - 1];
-                    if let Ok(source_fd) = source_fd_str.parse::<i32>() {
-                        // Duplicate: copy content from source to target FD
-                        if source_fd == 1 && fd == 2 {
-                            stderr.push_str(&stdout);
-                        } else if source_fd == 2 && fd == 1 {
-                            stdout.push_str(&stderr);
+                let sink = if target == "-" {
+                    OutputSink::Discard
+                } else if let Ok(source_fd) = source.parse::<i32>() {
+                    match source_fd {
+                        1 => out_sink.clone(),
+                        2 => err_sink.clone(),
+                        _ => {
+                            let entry = state.file_descriptors.as_ref().and_then(|fds| fds.get(&source_fd));
+                            match entry {
+                                Some(entry) => entry_sink(entry),
+                                None => {
+                                    stderr.push_str(&format!("bash: {}: Bad file descriptor\n", source_fd));
+                                    exit_code = 1;
+                                    OutputSink::Discard
+                                }
+                            }
                         }
-                        // Close the source FD
-                        if source_fd == 1 {
-                            stdout.clear();
-                        } else if source_fd == 2 {
-                            stderr.clear();
+                    }
+                } else if redir.fd.is_none() {
+                    // >&word with a non-numeric word is the same as &>word
+                    let sink = file_sink(state, true, &out_sink, &err_sink);
+                    err_sink = sink.clone();
+                    sink
+                } else {
+                    file_sink(state, true, &out_sink, &err_sink)
+                };
+
+                if moved {
+                    if let Ok(source_fd) = source.parse::<i32>() {
+                        match source_fd {
+                            1 => out_sink = OutputSink::Discard,
+                            2 => err_sink = OutputSink::Discard,
+                            _ => {}
                         }
                         // Mark the move in persistent FDs
                         if let Some(ref mut fds) = state.file_descriptors {
-                            if let Some(content) = fds.get(&source_fd).cloned() {
+                            if let Some(content) = fds.remove(&source_fd) {
                                 fds.insert(fd, content);
                             }
-                            fds.remove(&source_fd);
                         }
-                        continue;
                     }
                 }
 
-                // >&2, 1>&2: redirect stdout to stderr
-                if target == "2" || target == "&2" {
-                    if fd == 1 {
-                        stderr.push_str(&stdout);
-                        stdout.clear();
-                    }
+                match fd {
+                    1 => out_sink = sink,
+                    2 => err_sink = sink,
+                    _ => {}
                 }
-                // 2>&1: redirect stderr to stdout
-                else if target == "1" || target == "&1" {
-                    if fd == 2 {
-                        stdout.push_str(&stderr);
-                        stderr.clear();
-                    }
+            }
+        }
+    }
+
+    // Route the command's output through the table
+    route_output(result, &out_sink, &err_sink, fs, &mut stdout, &mut stderr, &mut exit_code);
+    ExecResult::new(stdout, stderr, exit_code)
+}
+
+/// Where output to `fd` goes with only the shell's own descriptors in effect.
+fn fd_sink(state: &InterpreterState, fd: i32) -> OutputSink {
+    match state.file_descriptors.as_ref().and_then(|fds| fds.get(&fd)) {
+        Some(entry) => entry_sink(entry),
+        None if fd == 2 => OutputSink::Stderr,
+        None => OutputSink::Stdout,
+    }
+}
+
+/// The sink for writes to an fd table entry. Entries that can't be written
+/// to (closed, or open for reading) swallow the output.
+fn entry_sink(entry: &FileDescriptor) -> OutputSink {
+    match entry {
+        FileDescriptor::File(path) if path == "/dev/null" => OutputSink::Discard,
+        FileDescriptor::File(path) => OutputSink::File { path: path.clone(), truncate: false },
+        FileDescriptor::Dup(1) => OutputSink::Stdout,
+        FileDescriptor::Dup(2) => OutputSink::Stderr,
+        FileDescriptor::PipeWrite(pipe) => OutputSink::Pipe(pipe.clone()),
+        _ => OutputSink::Discard,
+    }
+}
+
+/// Send a command's stdout and stderr to their sinks. What reaches the
+/// shell's original streams is appended to `stdout`/`stderr`.
+fn route_output(
+    result: ExecResult,
+    out_sink: &OutputSink,
+    err_sink: &OutputSink,
+    fs: &dyn FileSystem,
+    stdout: &mut String,
+    stderr: &mut String,
+    exit_code: &mut i32,
+) {
+    let mut written: Vec<String> = Vec::new();
+    for (content, sink) in [(&result.stdout, out_sink), (&result.stderr, err_sink)] {
+        match sink {
+            OutputSink::Stdout => stdout.push_str(content),
+            OutputSink::Stderr => stderr.push_str(content),
+            OutputSink::Discard => {}
+            OutputSink::Full => {
+                stderr.push_str("bash: echo: write error: No space left on device\n");
+                *exit_code = 1;
+            }
+            OutputSink::File { path, truncate } => {
+                let first = !written.contains(path);
+                let write = || match *truncate && first {
+                    true => fs.write_file(path, content),
+                    false => fs.append_file(path, content),
+                };
+                // A truncated file was reported when `pre_open_output_redirects`
+                // opened it, and the second stream into a file continues the
+                // first one's write
+                let write = match *truncate || !first {
+                    true => unaudited(write),
+                    false => write(),
+                };
+                if let Err(e) = write {
+                    stderr.push_str(&format!("bash: write error: {}\n", e));
+                    *exit_code = 1;
                 }
-                // Handle writing to a user-allocated FD
-                else if let Ok(target_fd) = target.parse::<i32>() {
-                    if let Some(ref fds) = state.file_descriptors {
-                        if let Some(fd_info) = fds.get(&target_fd) {
-                            if fd_info.starts_with("__file__:") {
-                                let file_path = &fd_info[9..];
-                                if fd == 1 {
-                                    let _ = fs.append_file(file_path, &stdout);
-                                    stdout.clear();
-                                } else if fd == 2 {
-                                    let _ = fs.append_file(file_path, &stderr);
-                                    stderr.clear();
-                                }
-                            }
-                        } else if target_fd >= 3 {
-                            // Bad file descriptor
-                            stderr.push_str(&format!("bash: {}: Bad file descriptor\n", target_fd));
-                            exit_code = 1;
-                            stdout.clear();
-                        }
-                    }
+                written.push(path.clone());
+            }
+            OutputSink::Pipe(pipe) => pipe.write(content),
+        }
+    }
+}
+
+/// Route the output of a command without redirections through the
+/// descriptors `exec` left open on fd 1 and fd 2.
+pub fn apply_persistent_fds(state: &InterpreterState, result: ExecResult, fs: &dyn FileSystem) -> ExecResult {
+    match state.file_descriptors {
+        Some(ref fds) if fds.contains_key(&1) || fds.contains_key(&2) => {}
+        _ => return result,
+    }
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    let mut exit_code = result.exit_code;
+    let (out_sink, err_sink) = (fd_sink(state, 1), fd_sink(state, 2));
+    route_output(result, &out_sink, &err_sink, fs, &mut stdout, &mut stderr, &mut exit_code);
+    ExecResult::new(stdout, stderr, exit_code)
+}
+
+/// The fds among 1 and 2 that `redirections` send somewhere else.
+pub fn redirected_output_fds(redirections: &[RedirectionNode]) -> Vec<i32> {
+    let mut fds = Vec::new();
+    for redir in redirections {
+        if redir.fd_variable.is_some() || matches!(redir.target, RedirectionTarget::HereDoc(_)) {
+            continue;
+        }
+        let both = match redir.operator {
+            RedirectionOperator::AndGreat | RedirectionOperator::AndDGreat => true,
+            RedirectionOperator::GreatAnd => match (&redir.target, redir.fd) {
+                // >&word with a non-numeric word is the same as &>word
+                (RedirectionTarget::Word(word), None) => !word.parts.iter().all(|part| {
+                    matches!(part, WordPart::Literal(lit) if lit.value.chars().all(|c| c.is_ascii_digit() || c == '-'))
+                }),
+                _ => false,
+            },
+            RedirectionOperator::Great | RedirectionOperator::Clobber | RedirectionOperator::DGreat => false,
+            _ => continue,
+        };
+        if both {
+            fds.extend([1, 2]);
+        } else {
+            fds.push(redir.fd.unwrap_or(1));
+        }
+    }
+    fds.retain(|fd| *fd == 1 || *fd == 2);
+    fds
+}
+
+/// Run `body` with the shell's descriptors `fds` set aside, for output that
+/// is captured or redirected elsewhere: a pipe, `$(...)`, or `cmd > file`.
+/// The entries are put back afterwards, undoing any `exec` in between, and
+/// `fds` count as captured while `body` runs.
+pub fn with_fds_set_aside<T>(
+    state: &mut InterpreterState,
+    fds: &[i32],
+    body: impl FnOnce(&mut InterpreterState) -> T,
+) -> T {
+    let captured = state.captured_fds.len();
+    state.captured_fds.extend(fds.iter().filter(|fd| **fd == 1 || **fd == 2));
+    let body = |state: &mut InterpreterState| {
+        let result = body(state);
+        state.captured_fds.truncate(captured);
+        result
+    };
+    let saved: Vec<(i32, Option<FileDescriptor>)> = match state.file_descriptors.as_mut() {
+        Some(table) if fds.iter().any(|fd| table.contains_key(fd)) => {
+            fds.iter().map(|fd| (*fd, table.remove(fd))).collect()
+        }
+        _ => return body(state),
+    };
+    let result = body(state);
+    let table = state.file_descriptors.get_or_insert_with(HashMap::new);
+    for (fd, entry) in saved {
+        match entry {
+            Some(entry) => table.insert(fd, entry),
+            None => table.remove(&fd),
+        };
+    }
+    result
+}
+
+/// Apply the redirections of a bare `exec` (no command) to the shell itself.
+///
+/// Unlike a command's redirections, these stay in `state.file_descriptors`
+/// until closed: `exec 3>out.log` opens fd 3 for later `echo >&3`,
+/// `exec 4<input` opens fd 4 for `read -u 4`, `exec >log 2>&1` sends all
+/// further output to `log`, and `exec 3>&-` closes fd 3. `{var}>file`
+/// redirections are handled by `process_fd_variable_redirections`.
+pub fn apply_exec_redirections(
+    state: &mut InterpreterState,
+    redirections: &[RedirectionNode],
+    pre_expanded_targets: Option<&ExpandedRedirectTargets>,
+    fs: &dyn FileSystem,
+    expand_word_fn: impl Fn(&mut InterpreterState, &WordNode) -> String,
+) -> ExecResult {
+    for (i, redir) in redirections.iter().enumerate() {
+        if redir.fd_variable.is_some() {
+            continue;
+        }
+        let default_fd = match redir.operator {
+            RedirectionOperator::Less
+            | RedirectionOperator::LessGreat
+            | RedirectionOperator::LessAnd
+            | RedirectionOperator::DLess
+            | RedirectionOperator::DLessDash
+            | RedirectionOperator::TLess => 0,
+            _ => 1,
+        };
+        let fd = redir.fd.unwrap_or(default_fd);
+
+        let target = match &redir.target {
+            RedirectionTarget::HereDoc(heredoc) => {
+                let mut content = expand_word_fn(state, &heredoc.content);
+                if heredoc.strip_tabs {
+                    content = content
+                        .split('\n')
+                        .map(|line| line.trim_start_matches('\t'))
+                        .collect::<Vec<_>>()
+                        .join("\n");
                 }
+                set_fd(state, fd, Some(FileDescriptor::Content(content)));
+                continue;
             }
+            RedirectionTarget::Word(word) => match pre_expanded_targets.and_then(|targets| targets.get(&i)) {
+                Some(target) => target.clone(),
+                None => expand_word_fn(state, word),
+            },
+        };
 
-            RedirectionOperator::AndGreat => {
-                if target == "/dev/full" {
-                    stderr = "bash: echo: write error: No space left on device\n".to_string();
-                    exit_code = 1;
-                    stdout.clear();
+        match redir.operator {
+            RedirectionOperator::Great
+            | RedirectionOperator::Clobber
+            | RedirectionOperator::DGreat
+            | RedirectionOperator::AndGreat
+            | RedirectionOperator::AndDGreat => {
+                let append = matches!(redir.operator, RedirectionOperator::DGreat | RedirectionOperator::AndDGreat);
+                let entry = match output_fd_entry(state, fs, &target, append, redir.operator == RedirectionOperator::Clobber) {
+                    Ok(entry) => entry,
+                    Err(error) => return ExecResult::new(String::new(), error, 1),
+                };
+                if matches!(redir.operator, RedirectionOperator::AndGreat | RedirectionOperator::AndDGreat) {
+                    set_fd(state, 1, Some(entry.clone()));
+                    set_fd(state, 2, Some(entry));
                 } else {
-                    let file_path = fs.resolve_path(&state.cwd, &target);
-                    let combined = format!("{}{}", stdout, stderr);
-                    let _ = fs.write_file(&file_path, &combined);
-                    stdout.clear();
-                    stderr.clear();
+                    set_fd(state, fd, Some(entry));
                 }
             }
-
-            RedirectionOperator::AndDGreat => {
-                if target == "/dev/full" {
-                    stderr = "bash: echo: write error: No space left on device\n".to_string();
-                    exit_code = 1;
-                    stdout.clear();
+            RedirectionOperator::Less | RedirectionOperator::LessGreat => {
+                let file_path = fs.resolve_path(&state.cwd, &target);
+                let content = match fs.read_file(&file_path) {
+                    Ok(content) => content,
+                    Err(_) if redir.operator == RedirectionOperator::LessGreat => {
+                        let _ = fs.write_file(&file_path, "");
+                        String::new()
+                    }
+                    Err(_) => {
+                        return ExecResult::new(
+                            String::new(),
+                            format!("bash: {}: No such file or directory\n", target),
+                            1,
+                        );
+                    }
+                };
+                let entry = if redir.operator == RedirectionOperator::LessGreat {
+                    FileDescriptor::ReadWrite { path: file_path, position: 0, content }
                 } else {
-                    let file_path = fs.resolve_path(&state.cwd, &target);
-                    let combined = format!("{}{}", stdout, stderr);
-                    let _ = fs.append_file(&file_path, &combined);
-                    stdout.clear();
-                    stderr.clear();
+                    FileDescriptor::Content(content)
+                };
+                set_fd(state, fd, Some(entry));
+            }
+            RedirectionOperator::TLess => {
+                set_fd(state, fd, Some(FileDescriptor::Content(format!("{}\n", target))));
+            }
+            RedirectionOperator::GreatAnd | RedirectionOperator::LessAnd => {
+                // N>&M duplicates M, N>&- closes N, N>&M- moves M to N
+                if target == "-" {
+                    close_fd(state, fd);
+                    continue;
+                }
+                let (source, moved) = match target.strip_suffix('-') {
+                    Some(rest) => (rest, true),
+                    None => (target.as_str(), false),
+                };
+                let source_fd = match source.parse::<i32>() {
+                    Ok(source_fd) => source_fd,
+                    Err(_) if redir.fd.is_none() && redir.operator == RedirectionOperator::GreatAnd => {
+                        // >&word with a non-numeric word is the same as &>word
+                        let entry = match output_fd_entry(state, fs, &target, false, false) {
+                            Ok(entry) => entry,
+                            Err(error) => return ExecResult::new(String::new(), error, 1),
+                        };
+                        set_fd(state, 1, Some(entry.clone()));
+                        set_fd(state, 2, Some(entry));
+                        continue;
+                    }
+                    Err(_) => {
+                        return ExecResult::new(
+                            String::new(),
+                            format!("bash: {}: ambiguous redirect\n", target),
+                            1,
+                        );
+                    }
+                };
+                let entry = match current_fd(state, source_fd) {
+                    Some(entry) => entry,
+                    None => {
+                        return ExecResult::new(
+                            String::new(),
+                            format!("bash: {}: Bad file descriptor\n", source_fd),
+                            1,
+                        );
+                    }
+                };
+                set_fd(state, fd, Some(entry));
+                if moved && source_fd != fd {
+                    close_fd(state, source_fd);
                 }
             }
-
             _ => {}
         }
     }
 
-    // Apply persistent FD redirections (from exec)
-    if let Some(ref fds) = state.file_descriptors {
-        if let Some(fd1_info) = fds.get(&1) {
-            if fd1_info == "__dupout__:2" {
-                stderr.push_str(&stdout);
-                stdout.clear();
-            } else if fd1_info.starts_with("__file__:") {
-                let file_path = &fd1_info[9..];
-                let _ = fs.append_file(file_path, &stdout);
-                stdout.clear();
-            }
-        }
+    ExecResult::ok()
+}
 
-        if let Some(fd2_info) = fds.get(&2) {
-            if fd2_info == "__dupout__:1" {
-                stdout.push_str(&stderr);
-                stderr.clear();
-            } else if fd2_info.starts_with("__file__:") {
-                let file_path = &fd2_info[9..];
-                let _ = fs.append_file(file_path, &stderr);
-                stderr.clear();
-            }
+/// Open `target` for writing as an fd table entry, truncating it unless
+/// appending.
+fn output_fd_entry(
+    state: &InterpreterState,
+    fs: &dyn FileSystem,
+    target: &str,
+    append: bool,
+    is_clobber: bool,
+) -> Result<FileDescriptor, String> {
+    match target {
+        "/dev/stdout" => return current_fd(state, 1).ok_or_else(|| "bash: /dev/stdout: Bad file descriptor\n".to_string()),
+        "/dev/stderr" => return current_fd(state, 2).ok_or_else(|| "bash: /dev/stderr: Bad file descriptor\n".to_string()),
+        "/dev/null" => return Ok(FileDescriptor::File("/dev/null".to_string())),
+        _ => {}
+    }
+    let file_path = fs.resolve_path(&state.cwd, target);
+    if let Some(error) = check_output_redirect_target(state, fs, &file_path, target, !append, is_clobber) {
+        return Err(error);
+    }
+    if !append || !fs.exists(&file_path) {
+        let _ = fs.write_file(&file_path, "");
+    }
+    Ok(FileDescriptor::File(file_path))
+}
+
+/// What fd `fd` currently refers to, with fds 0-2 falling back to the
+/// shell's original streams. `None` means the fd is not open.
+fn current_fd(state: &InterpreterState, fd: i32) -> Option<FileDescriptor> {
+    match state.file_descriptors.as_ref().and_then(|fds| fds.get(&fd)) {
+        Some(entry) => Some(entry.clone()),
+        None if (0..=2).contains(&fd) => Some(FileDescriptor::Dup(fd)),
+        None => None,
+    }
+}
+
+/// Point `fd` at `entry`. Pointing fd 0-2 back at its original stream
+/// removes the entry, so `exec 1>&3` restores a saved stdout.
+fn set_fd(state: &mut InterpreterState, fd: i32, entry: Option<FileDescriptor>) {
+    let fds = state.file_descriptors.get_or_insert_with(HashMap::new);
+    match entry {
+        Some(FileDescriptor::Dup(original)) if original == fd => {
+            fds.remove(&fd);
+        }
+        Some(entry) => {
+            fds.insert(fd, entry);
+        }
+        None => {
+            fds.remove(&fd);
         }
     }
+}
 
-    ExecResult::new(stdout, stderr, exit_code)
+/// Close `fd`. Output to a closed stdout or stderr is dropped, which is
+/// recorded as an explicit `Content` entry so it doesn't fall back to the
+/// original stream.
+fn close_fd(state: &mut InterpreterState, fd: i32) {
+    if (0..=2).contains(&fd) {
+        set_fd(state, fd, Some(FileDescriptor::Content(String::new())));
+    } else {
+        set_fd(state, fd, None);
+    }
 }
 
 #[cfg(test)]
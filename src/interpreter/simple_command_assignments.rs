@@ -10,6 +10,10 @@ use crate::ast::types::{SimpleCommandNode, WordNode};
 use crate::interpreter::types::{ExecResult, InterpreterState};
 use crate::interpreter::helpers::nameref::{is_nameref, resolve_nameref, resolve_nameref_for_assignment, get_nameref_target, NamerefAssignmentResult};
 use crate::interpreter::helpers::readonly::is_readonly;
+use crate::interpreter::helpers::xtrace::quote_for_trace;
+use crate::interpreter::helpers::array::{parse_keyed_element_from_word, unquote_key};
+use crate::interpreter::arithmetic::evaluate_array_index;
+use crate::interpreter::builtins::declare_cmd::{apply_case_transform, is_integer};
 
 /// Result of processing assignments in a simple command
 #[derive(Debug, Clone)]
@@ -18,6 +22,8 @@ pub struct AssignmentResult {
     pub continue_to_next: bool,
     /// Accumulated xtrace output for assignments
     pub xtrace_output: String,
+    /// Assignments as `set -x` shows them, without the PS4 prefix
+    pub trace_lines: Vec<String>,
     /// Temporary assignments for prefix bindings (FOO=bar cmd)
     pub temp_assignments: HashMap<String, Option<String>>,
     /// Error result if assignment failed
@@ -29,6 +35,7 @@ impl Default for AssignmentResult {
         Self {
             continue_to_next: false,
             xtrace_output: String::new(),
+            trace_lines: Vec::new(),
             temp_assignments: HashMap::new(),
             error: None,
         }
@@ -40,6 +47,8 @@ impl Default for AssignmentResult {
 pub struct SingleAssignmentResult {
     pub continue_to_next: bool,
     pub xtrace_output: String,
+    /// The assignment as `set -x` shows it, once its values are expanded
+    pub trace_line: Option<String>,
     pub error: Option<ExecResult>,
 }
 
@@ -48,6 +57,7 @@ impl Default for SingleAssignmentResult {
         Self {
             continue_to_next: false,
             xtrace_output: String::new(),
+            trace_line: None,
             error: None,
         }
     }
@@ -55,10 +65,14 @@ impl Default for SingleAssignmentResult {
 
 /// Process all assignments in a simple command.
 /// Returns assignment results including temp bindings and any errors.
+///
+/// `expand_word_fn` expands a value without word splitting; array literal
+/// elements go through `expand_fields_fn`, which splits and globs.
 pub fn process_assignments(
     state: &mut InterpreterState,
     node: &SimpleCommandNode,
     expand_word_fn: impl Fn(&mut InterpreterState, &WordNode) -> String,
+    expand_fields_fn: impl Fn(&mut InterpreterState, &WordNode) -> Vec<String>,
 ) -> AssignmentResult {
     let mut result = AssignmentResult::default();
 
@@ -74,7 +88,10 @@ pub fn process_assignments(
                 array,
                 assignment.append,
                 &mut result.temp_assignments,
+                &expand_word_fn,
+                &expand_fields_fn,
             );
+            result.trace_lines.extend(array_result.trace_line);
             if let Some(error) = array_result.error {
                 result.error = Some(error);
                 return result;
@@ -90,6 +107,10 @@ pub fn process_assignments(
         } else {
             String::new()
         };
+        if state.options.xtrace {
+            let operator = if assignment.append { "+=" } else { "=" };
+            result.trace_lines.push(format!("{}{}{}", name, operator, quote_for_trace(&value)));
+        }
 
         // Check for empty subscript assignment: a[]=value is invalid
         let empty_subscript_re = regex_lite::Regex::new(r"^([a-zA-Z_][a-zA-Z0-9_]*)\[\]$").unwrap();
@@ -144,7 +165,26 @@ pub fn process_assignments(
     result
 }
 
+/// Undo the temporary prefix bindings made for a command, restoring each
+/// variable's previous value (or unsetting it if it had none).
+pub fn restore_temp_assignments(
+    state: &mut InterpreterState,
+    temp_assignments: HashMap<String, Option<String>>,
+) {
+    for (name, previous) in temp_assignments {
+        match previous {
+            Some(value) => {
+                state.env.insert(name, value);
+            }
+            None => {
+                state.env.remove(&name);
+            }
+        }
+    }
+}
+
 /// Process an array assignment: VAR=(a b c) or VAR+=(a b c)
+#[allow(clippy::too_many_arguments)]
 fn process_array_assignment(
     state: &mut InterpreterState,
     node: &SimpleCommandNode,
@@ -152,6 +192,8 @@ fn process_array_assignment(
     array: &[WordNode],
     append: bool,
     temp_assignments: &mut HashMap<String, Option<String>>,
+    expand_word_fn: &impl Fn(&mut InterpreterState, &WordNode) -> String,
+    expand_fields_fn: &impl Fn(&mut InterpreterState, &WordNode) -> Vec<String>,
 ) -> SingleAssignmentResult {
     let mut result = SingleAssignmentResult::default();
 
@@ -200,35 +242,132 @@ fn process_array_assignment(
         return result;
     }
 
-    // Clear existing array elements if not appending
-    if !append {
-        clear_array_elements(state, name);
+    // For prefix assignments with a command, bash binds the literal text
+    // of the list as a plain string for the duration of the command
+    if node.name.is_some() {
+        let mut elements = Vec::new();
+        for element in array {
+            elements.extend(expand_fields_fn(state, element));
+        }
+        result.trace_line = trace_array_assignment(state, name, append, elements.iter().map(|element| quote_for_trace(element)));
+        temp_assignments.insert(name.to_string(), state.env.get(name).cloned());
+        state.env.insert(name.to_string(), format!("({})", elements.join(" ")));
+        result.continue_to_next = true;
+        return result;
     }
 
-    // Process array elements
-    let start_index = if append {
-        get_array_max_index(state, name).map(|i| i + 1).unwrap_or(0)
-    } else {
-        0
-    };
+    let is_assoc = state.associative_arrays.as_ref().is_some_and(|a| a.contains(name));
 
-    for (i, _element) in array.iter().enumerate() {
-        // In a full implementation, we would expand each element
-        // For now, just set placeholder values
-        let env_key = format!("{}_{}", name, start_index + i);
-        state.env.insert(env_key, String::new());
+    // Expand every element before touching the array, so `a=(${a[@]} x)`
+    // still sees the old contents
+    let mut entries: Vec<(Option<(String, bool)>, String)> = Vec::new();
+    for element in array {
+        if let Some(parsed) = parse_keyed_element_from_word(element) {
+            let value_word = WordNode { parts: parsed.value_parts };
+            let value = expand_word_fn(state, &value_word);
+            entries.push((Some((parsed.key, parsed.append)), value));
+        } else {
+            for field in expand_fields_fn(state, element) {
+                entries.push((None, field));
+            }
+        }
     }
 
-    // For prefix assignments with a command, bash stringifies the array syntax
-    if node.name.is_some() {
-        temp_assignments.insert(name.to_string(), state.env.get(name).cloned());
-        // Would stringify array here
+    let traced = entries.iter().map(|(key, value)| match key {
+        Some((key, elem_append)) => {
+            format!("[{}]{}{}", key, if *elem_append { "+=" } else { "=" }, quote_for_trace(value))
+        }
+        None => quote_for_trace(value),
+    });
+    result.trace_line = trace_array_assignment(state, name, append, traced);
+
+    if !append {
+        clear_array_elements(state, name);
+    }
+
+    if is_assoc {
+        // Unkeyed elements of an associative array pair up as key, value
+        let mut pending_key: Option<String> = None;
+        for (key, value) in entries {
+            match key {
+                Some((key, elem_append)) => {
+                    let key = unquote_key(&key).to_string();
+                    let env_key = format!("{}_{}", name, key);
+                    let value = assigned_value(state, name, &env_key, &value, elem_append);
+                    state.env.insert(env_key, value);
+                }
+                None => match pending_key.take() {
+                    None => pending_key = Some(value),
+                    Some(key) => {
+                        let env_key = format!("{}_{}", name, key);
+                        let value = assigned_value(state, name, &env_key, &value, false);
+                        state.env.insert(env_key, value);
+                    }
+                },
+            }
+        }
+        if let Some(key) = pending_key {
+            state.env.insert(format!("{}_{}", name, key), String::new());
+        }
+    } else {
+        let mut next_index = if append {
+            get_array_max_index(state, name).map(|i| i as i64 + 1).unwrap_or(0)
+        } else {
+            0
+        };
+        for (key, value) in entries {
+            let (index, elem_append) = match key {
+                Some((key, elem_append)) => (evaluate_array_index(state, unquote_key(&key)), elem_append),
+                None => (next_index, false),
+            };
+            let env_key = format!("{}_{}", name, index);
+            let value = assigned_value(state, name, &env_key, &value, elem_append);
+            state.env.insert(env_key, value);
+            next_index = index + 1;
+        }
+        let length = get_array_max_index(state, name).map(|i| i + 1).unwrap_or(0);
+        state.env.insert(format!("{}__length", name), length.to_string());
     }
 
     result.continue_to_next = true;
     result
 }
 
+/// `name=(...)` as `set -x` shows it, given the traced elements.
+fn trace_array_assignment(
+    state: &InterpreterState,
+    name: &str,
+    append: bool,
+    elements: impl Iterator<Item = String>,
+) -> Option<String> {
+    if !state.options.xtrace {
+        return None;
+    }
+    let operator = if append { "+=" } else { "=" };
+    Some(format!("{}{}({})", name, operator, elements.collect::<Vec<_>>().join(" ")))
+}
+
+/// Compute the value stored at `env_key` by an assignment to `name`,
+/// honouring `+=` and the variable's integer and case attributes.
+fn assigned_value(
+    state: &mut InterpreterState,
+    name: &str,
+    env_key: &str,
+    value: &str,
+    append: bool,
+) -> String {
+    let existing = state.env.get(env_key).cloned().unwrap_or_default();
+    if is_integer(state, name) {
+        let mut number = evaluate_array_index(state, value);
+        if append {
+            number = number.wrapping_add(evaluate_array_index(state, &existing));
+        }
+        return number.to_string();
+    }
+    let value = if append { format!("{}{}", existing, value) } else { value.to_string() };
+    apply_case_transform(state, name, &value)
+}
+
 /// Clear existing array elements for a variable
 fn clear_array_elements(state: &mut InterpreterState, name: &str) {
     let prefix = format!("{}_", name);
@@ -303,44 +442,57 @@ fn process_subscript_assignment(
         return result;
     }
 
-    // Compute the index
-    let index = compute_array_index(state, subscript_expr);
-    let env_key = format!("{}_{}", resolved_array_name, index);
-
-    let final_value = if append {
-        let existing = state.env.get(&env_key).cloned().unwrap_or_default();
-        format!("{}{}", existing, value)
+    // Associative arrays take the subscript as a string key; indexed arrays
+    // evaluate it arithmetically
+    let is_assoc = state
+        .associative_arrays
+        .as_ref()
+        .is_some_and(|a| a.contains(&resolved_array_name));
+    let env_key = if is_assoc {
+        format!("{}_{}", resolved_array_name, unquote_key(subscript_expr))
     } else {
-        value.to_string()
+        let index = compute_array_index(state, subscript_expr);
+        if index < 0 {
+            let length = get_array_max_index(state, &resolved_array_name).map_or(0, |i| i as i64 + 1);
+            if index + length < 0 {
+                result.error = Some(ExecResult::new(
+                    String::new(),
+                    format!("bash: {}[{}]: bad array subscript\n", resolved_array_name, subscript_expr),
+                    1,
+                ));
+                return result;
+            }
+            format!("{}_{}", resolved_array_name, index + length)
+        } else {
+            format!("{}_{}", resolved_array_name, index)
+        }
     };
 
+    let final_value = assigned_value(state, &resolved_array_name, &env_key, value, append);
+
     if node.name.is_some() {
         temp_assignments.insert(env_key.clone(), state.env.get(&env_key).cloned());
         state.env.insert(env_key, final_value);
     } else {
         state.env.insert(env_key, final_value);
+        if !is_assoc {
+            // A plain scalar becomes element 0 of the new array
+            if let Some(scalar) = state.env.remove(&resolved_array_name) {
+                state.env.entry(format!("{}_0", resolved_array_name)).or_insert(scalar);
+            }
+            let length = get_array_max_index(state, &resolved_array_name).map_or(0, |i| i + 1);
+            state.env.insert(format!("{}__length", resolved_array_name), length.to_string());
+        }
     }
 
     result.continue_to_next = true;
     result
 }
 
-/// Compute the index for an array subscript
-fn compute_array_index(state: &InterpreterState, subscript_expr: &str) -> i64 {
-    // Try to parse as integer
-    if let Ok(index) = subscript_expr.parse::<i64>() {
-        return index;
-    }
-
-    // Try to look up as variable
-    if let Some(var_value) = state.env.get(subscript_expr) {
-        if let Ok(index) = var_value.parse::<i64>() {
-            return index;
-        }
-    }
-
-    // Default to 0
-    0
+/// Compute the index for an array subscript, which is an arithmetic
+/// expression (`a[i+1]=x`)
+fn compute_array_index(state: &mut InterpreterState, subscript_expr: &str) -> i64 {
+    evaluate_array_index(state, subscript_expr)
 }
 
 /// Process a scalar assignment
@@ -393,14 +545,6 @@ fn process_scalar_assignment(
         return result;
     }
 
-    // Handle append mode
-    let final_value = if append {
-        let existing = state.env.get(&target_name).cloned().unwrap_or_default();
-        format!("{}{}", existing, value)
-    } else {
-        value.to_string()
-    };
-
     // Compute actual env key (handle arrays)
     let actual_env_key = if is_array(state, &target_name) {
         format!("{}_0", target_name)
@@ -408,6 +552,8 @@ fn process_scalar_assignment(
         target_name.clone()
     };
 
+    let final_value = assigned_value(state, &target_name, &actual_env_key, value, append);
+
     if node.name.is_some() {
         temp_assignments.insert(actual_env_key.clone(), state.env.get(&actual_env_key).cloned());
         state.env.insert(actual_env_key, final_value);
@@ -428,10 +574,9 @@ fn process_scalar_assignment(
     result
 }
 
-/// Check if a variable is an array
+/// Check if a variable is an indexed array
 fn is_array(state: &InterpreterState, name: &str) -> bool {
-    let prefix = format!("{}_", name);
-    state.env.keys().any(|k| k.starts_with(&prefix) && !k.contains("__"))
+    get_array_max_index(state, name).is_some()
 }
 
 #[cfg(test)]
@@ -461,12 +606,13 @@ mod tests {
     #[test]
     fn test_compute_array_index() {
         let mut state = InterpreterState::default();
-        assert_eq!(compute_array_index(&state, "5"), 5);
-        assert_eq!(compute_array_index(&state, "-1"), -1);
-        assert_eq!(compute_array_index(&state, "invalid"), 0);
+        assert_eq!(compute_array_index(&mut state, "5"), 5);
+        assert_eq!(compute_array_index(&mut state, "-1"), -1);
+        assert_eq!(compute_array_index(&mut state, "invalid"), 0);
 
         state.env.insert("i".to_string(), "10".to_string());
-        assert_eq!(compute_array_index(&state, "i"), 10);
+        assert_eq!(compute_array_index(&mut state, "i"), 10);
+        assert_eq!(compute_array_index(&mut state, "i+1"), 11);
     }
 
     #[test]
@@ -3,7 +3,9 @@
 //! Handles execution of subshells (...), groups { ...; }, and user scripts
 
 use std::collections::HashMap;
-use crate::interpreter::types::{ExecResult, InterpreterState, LocalVarStackEntry, ShellOptions};
+use crate::interpreter::builtins::jobs_cmd::drain_jobs;
+use crate::interpreter::traps::{run_exit_trap, subshell_traps};
+use crate::interpreter::types::{ExecResult, InterpreterState, Job, LocalVarStackEntry, ShellOptions};
 use crate::interpreter::errors::{InterpreterError, ControlFlowError};
 
 /// Saved state for subshell execution.
@@ -24,6 +26,9 @@ pub struct SubshellSavedState {
     pub local_var_stack: Option<HashMap<String, Vec<LocalVarStackEntry>>>,
     pub local_var_depth: Option<HashMap<String, u32>>,
     pub fully_unset_locals: Option<HashMap<String, usize>>,
+    // The subshell starts with an empty job table and only inherited traps
+    pub jobs: Vec<Job>,
+    pub traps: HashMap<String, String>,
 }
 
 impl SubshellSavedState {
@@ -44,6 +49,8 @@ impl SubshellSavedState {
             local_var_stack: state.local_var_stack.clone(),
             local_var_depth: state.local_var_depth.clone(),
             fully_unset_locals: state.fully_unset_locals.clone(),
+            jobs: state.jobs.clone(),
+            traps: state.traps.clone(),
         }
     }
 
@@ -63,13 +70,19 @@ impl SubshellSavedState {
         state.local_var_stack = self.local_var_stack;
         state.local_var_depth = self.local_var_depth;
         state.fully_unset_locals = self.fully_unset_locals;
+        state.jobs = self.jobs;
+        state.traps = self.traps;
     }
 }
 
 /// Prepare state for subshell execution.
 /// Returns the saved state that should be restored after execution.
 pub fn prepare_subshell(state: &mut InterpreterState, stdin: Option<&str>) -> SubshellSavedState {
-    let saved = SubshellSavedState::save(state);
+    // The subshell starts with an empty job table, so the parent's is moved
+    // aside rather than copied along with the shells its jobs hold
+    let jobs = std::mem::take(&mut state.jobs);
+    let mut saved = SubshellSavedState::save(state);
+    saved.jobs = jobs;
 
     // Deep copy the local scoping structures for the subshell
     // Subshell gets a copy of these, but changes don't affect parent
@@ -87,6 +100,7 @@ pub fn prepare_subshell(state: &mut InterpreterState, stdin: Option<&str>) -> Su
     // Subshells get a new BASHPID (unlike $$ which stays the same)
     state.bash_pid = state.next_virtual_pid;
     state.next_virtual_pid += 1;
+    state.traps = subshell_traps(state);
 
     // Set stdin if provided
     if let Some(s) = stdin {
@@ -293,8 +307,9 @@ where
     for stmt in body {
         match execute_statement(state, stmt) {
             Ok(res) => result.append(&res),
-            Err(InterpreterError::ExecutionLimit(e)) => {
+            Err(InterpreterError::ExecutionLimit(mut e)) => {
                 // ExecutionLimitError must always propagate - these are safety limits
+                e.prepend_output(&result.stdout, &result.stderr);
                 saved.restore(state);
                 return Err(InterpreterError::ExecutionLimit(e));
             }
@@ -303,6 +318,7 @@ where
                 // This exits the subshell cleanly with exit code 0
                 result.stdout.push_str(&e.stdout);
                 result.stderr.push_str(&e.stderr);
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
@@ -311,6 +327,7 @@ where
                 // They only affect loops within the subshell
                 result.stdout.push_str(&e.stdout);
                 result.stderr.push_str(&e.stderr);
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
@@ -318,6 +335,7 @@ where
                 // ContinueError should NOT propagate out of subshell
                 result.stdout.push_str(&e.stdout);
                 result.stderr.push_str(&e.stderr);
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
@@ -327,6 +345,7 @@ where
                 result.stdout.push_str(&e.stdout);
                 result.stderr.push_str(&e.stderr);
                 result.exit_code = e.exit_code;
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
@@ -336,6 +355,7 @@ where
                 result.stdout.push_str(&e.stdout);
                 result.stderr.push_str(&e.stderr);
                 result.exit_code = e.exit_code;
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
@@ -344,6 +364,16 @@ where
                 result.stdout.push_str(&e.stdout);
                 result.stderr.push_str(&e.stderr);
                 result.exit_code = e.exit_code;
+                finish_subshell(state, &mut result, &mut execute_statement);
+                saved.restore(state);
+                return Ok(result.to_exec_result());
+            }
+            Err(InterpreterError::Nounset(e)) => {
+                // An unset variable under set -u ends only the subshell
+                result.stdout.push_str(&e.stdout);
+                result.stderr.push_str(&e.stderr);
+                result.exit_code = 1;
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
@@ -351,16 +381,34 @@ where
                 // Other errors - convert to result with error message
                 result.stderr.push_str(&format!("{}\n", e));
                 result.exit_code = 1;
+                finish_subshell(state, &mut result, &mut execute_statement);
                 saved.restore(state);
                 return Ok(result.to_exec_result());
             }
         }
     }
 
+    finish_subshell(state, &mut result, &mut execute_statement);
     saved.restore(state);
     Ok(result.to_exec_result())
 }
 
+/// A finishing subshell runs its EXIT trap. Background jobs started in it
+/// end with it too, so their held output becomes part of its output.
+fn finish_subshell<F>(state: &mut InterpreterState, result: &mut CompoundResult, execute_statement: &mut F)
+where
+    F: FnMut(&mut InterpreterState, &crate::StatementNode) -> Result<ExecResult, InterpreterError>,
+{
+    let trap = run_exit_trap(state, result.exit_code, &mut *execute_statement);
+    result.stdout.push_str(&trap.stdout);
+    result.stderr.push_str(&trap.stderr);
+    result.exit_code = trap.exit_code;
+
+    let (stdout, stderr) = drain_jobs(state, execute_statement);
+    result.stdout.push_str(&stdout);
+    result.stderr.push_str(&stderr);
+}
+
 /// Execute a group node { ...; }.
 /// Runs commands in the current execution environment.
 pub fn execute_group<F>(
@@ -379,8 +427,9 @@ where
     for stmt in body {
         match execute_statement(state, stmt) {
             Ok(res) => result.append(&res),
-            Err(InterpreterError::ExecutionLimit(e)) => {
+            Err(InterpreterError::ExecutionLimit(mut e)) => {
                 // ExecutionLimitError must always propagate - these are safety limits
+                e.prepend_output(&result.stdout, &result.stderr);
                 saved.restore(state);
                 return Err(InterpreterError::ExecutionLimit(e));
             }
@@ -405,6 +454,11 @@ where
                 saved.restore(state);
                 return Err(InterpreterError::Errexit(e));
             }
+            Err(InterpreterError::Nounset(mut e)) => {
+                e.prepend_output(&result.stdout, &result.stderr);
+                saved.restore(state);
+                return Err(InterpreterError::Nounset(e));
+            }
             Err(InterpreterError::Exit(mut e)) => {
                 e.prepend_output(&result.stdout, &result.stderr);
                 saved.restore(state);
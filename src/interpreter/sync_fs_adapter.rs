@@ -4,7 +4,7 @@
 //! Uses `tokio::task::block_in_place` + `block_on` to execute async operations synchronously.
 
 use std::sync::Arc;
-use crate::fs::FileSystem as AsyncFileSystem;
+use crate::fs::{FileSystem as AsyncFileSystem, FsStat, RmOptions};
 use crate::interpreter::interpreter::{FileSystem as SyncFileSystem, FileStat};
 
 /// Adapter that wraps an async FileSystem and provides a sync interface.
@@ -35,6 +35,21 @@ impl SyncFsAdapter {
     }
 }
 
+/// Convert async filesystem metadata to the interpreter's stat shape.
+fn to_file_stat(s: FsStat) -> FileStat {
+    FileStat {
+        is_file: s.is_file,
+        is_dir: s.is_directory,
+        is_symlink: s.is_symlink,
+        size: s.size,
+        mode: s.mode,
+        uid: 0,  // Not tracked in our virtual FS
+        gid: 0,  // Not tracked in our virtual FS
+        mtime: s.mtime.duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default().as_secs(),
+    }
+}
+
 impl SyncFileSystem for SyncFsAdapter {
     fn read_file(&self, path: &str) -> Result<String, std::io::Error> {
         self.block_on(self.inner.read_file(path))
@@ -51,6 +66,12 @@ impl SyncFileSystem for SyncFsAdapter {
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
     }
 
+    fn remove_file(&self, path: &str) -> Result<(), std::io::Error> {
+        let options = RmOptions { recursive: false, force: true };
+        self.block_on(self.inner.rm(path, &options))
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
     fn exists(&self, path: &str) -> bool {
         self.block_on(self.inner.exists(path))
     }
@@ -74,17 +95,18 @@ impl SyncFileSystem for SyncFsAdapter {
     fn stat(&self, path: &str) -> Result<FileStat, std::io::Error> {
         let s = self.block_on(self.inner.stat(path))
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
-        Ok(FileStat {
-            is_file: s.is_file,
-            is_dir: s.is_directory,
-            is_symlink: s.is_symlink,
-            size: s.size,
-            mode: s.mode,
-            uid: 0,  // Not tracked in our virtual FS
-            gid: 0,  // Not tracked in our virtual FS
-            mtime: s.mtime.duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default().as_secs(),
-        })
+        Ok(to_file_stat(s))
+    }
+
+    fn lstat(&self, path: &str) -> Result<FileStat, std::io::Error> {
+        let s = self.block_on(self.inner.lstat(path))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        Ok(to_file_stat(s))
+    }
+
+    fn realpath(&self, path: &str) -> Result<String, std::io::Error> {
+        self.block_on(self.inner.realpath(path))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
     }
 
     fn read_dir(&self, path: &str) -> Result<Vec<String>, std::io::Error> {
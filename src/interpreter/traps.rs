@@ -0,0 +1,199 @@
+//! Trap Execution
+//!
+//! The `trap` builtin only records handlers on the interpreter state; they
+//! are run from here at the points bash runs them:
+//! - EXIT when the shell or a subshell finishes, including through `exit`
+//! - ERR after a failing command, with the same exemptions as errexit
+//! - DEBUG before each simple command
+//! - RETURN when a function returns
+//! - signal handlers when a signal sent with `kill` is delivered
+//!
+//! Functions inherit ERR only under `set -E` (errtrace), and DEBUG and
+//! RETURN only under `set -T` (functrace). Subshells start with ignored
+//! signals and the traps those options carry over, and nothing else.
+
+use std::collections::HashMap;
+
+use crate::ast::types::{CommandNode, CompoundCommandNode, PipelineNode, StatementNode};
+use crate::interpreter::errors::InterpreterError;
+use crate::interpreter::interpreter::update_exit_code;
+use crate::interpreter::types::{ExecResult, InterpreterState};
+
+/// Check if a handler (other than an ignoring empty one) is set for `condition`.
+pub fn has_trap(state: &InterpreterState, condition: &str) -> bool {
+    state.traps.get(condition).is_some_and(|handler| !handler.is_empty())
+}
+
+/// Run the handler for `condition` in the current shell.
+///
+/// `$?` is restored afterwards, so a handler never changes the status seen by
+/// the next command. DEBUG and ERR do not fire while any handler runs, and
+/// no handler fires from inside itself.
+pub fn run_trap<F>(
+    state: &mut InterpreterState,
+    condition: &str,
+    mut execute_statement: F,
+) -> Result<ExecResult, InterpreterError>
+where
+    F: FnMut(&mut InterpreterState, &StatementNode) -> Result<ExecResult, InterpreterError>,
+{
+    let nested = match state.running_trap.as_deref() {
+        Some(running) => running == condition || matches!(condition, "DEBUG" | "ERR"),
+        None => false,
+    };
+    if nested || !has_trap(state, condition) {
+        return Ok(ExecResult::ok());
+    }
+    let handler = state.traps[condition].clone();
+    let ast = match crate::parser::parse(&handler) {
+        Ok(ast) => ast,
+        Err(e) => return Ok(ExecResult::failure(format!("bash: {}\n", e))),
+    };
+
+    let saved_running = state.running_trap.replace(condition.to_string());
+    let saved_status = state.last_exit_code;
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    for statement in &ast.statements {
+        match execute_statement(state, statement) {
+            Ok(result) => {
+                stdout.push_str(&result.stdout);
+                stderr.push_str(&result.stderr);
+                update_exit_code(state, result.exit_code);
+            }
+            Err(mut error) => {
+                state.running_trap = saved_running;
+                error.as_control_flow_mut().prepend_output(&stdout, &stderr);
+                return Err(error);
+            }
+        }
+    }
+    state.running_trap = saved_running;
+    update_exit_code(state, saved_status);
+    Ok(ExecResult::new(stdout, stderr, saved_status))
+}
+
+/// Run the EXIT trap of a shell finishing with `exit_code`.
+///
+/// The trap fires once. The returned result holds the handler's output and
+/// the final status, which `exit` in the handler may change.
+pub fn run_exit_trap<F>(state: &mut InterpreterState, exit_code: i32, execute_statement: F) -> ExecResult
+where
+    F: FnMut(&mut InterpreterState, &StatementNode) -> Result<ExecResult, InterpreterError>,
+{
+    if !has_trap(state, "EXIT") {
+        return ExecResult::new(String::new(), String::new(), exit_code);
+    }
+    update_exit_code(state, exit_code);
+    let result = run_trap(state, "EXIT", execute_statement);
+    state.traps.remove("EXIT");
+    match result {
+        Ok(result) => ExecResult::new(result.stdout, result.stderr, exit_code),
+        Err(InterpreterError::Exit(e)) => ExecResult::new(e.stdout, e.stderr, e.exit_code),
+        Err(mut error) => {
+            let output = error.as_control_flow_mut();
+            let stdout = std::mem::take(output.stdout_mut());
+            let stderr = std::mem::take(output.stderr_mut());
+            ExecResult::new(stdout, stderr, exit_code)
+        }
+    }
+}
+
+/// Whether a failing pipeline triggers ERR itself. Loops, conditionals and
+/// groups do not: the failing command inside them already did.
+pub fn pipeline_reports_failure(pipeline: &PipelineNode) -> bool {
+    match pipeline.commands.as_slice() {
+        [CommandNode::Compound(compound)] => matches!(
+            compound,
+            CompoundCommandNode::Subshell(_)
+                | CompoundCommandNode::ArithmeticCommand(_)
+                | CompoundCommandNode::ConditionalCommand(_)
+        ),
+        _ => true,
+    }
+}
+
+/// Traps a function does not inherit, set aside while it runs.
+#[derive(Debug, Default)]
+pub struct FunctionTraps {
+    hidden: Vec<(&'static str, String)>,
+}
+
+/// Hide the traps a function call does not inherit.
+pub fn enter_function_traps(state: &mut InterpreterState) -> FunctionTraps {
+    let mut saved = FunctionTraps::default();
+    let mut hidden: Vec<&'static str> = Vec::new();
+    if !state.options.errtrace {
+        hidden.push("ERR");
+    }
+    if !state.options.functrace {
+        hidden.extend(["DEBUG", "RETURN"]);
+    }
+    for condition in hidden {
+        if let Some(handler) = state.traps.remove(condition) {
+            saved.hidden.push((condition, handler));
+        }
+    }
+    saved
+}
+
+/// Bring back the hidden traps, unless the function set its own: traps are
+/// global, so those stay in effect after it returns.
+pub fn leave_function_traps(state: &mut InterpreterState, saved: FunctionTraps) {
+    for (condition, handler) in saved.hidden {
+        state.traps.entry(condition.to_string()).or_insert(handler);
+    }
+}
+
+/// The traps a subshell starts with.
+pub fn subshell_traps(state: &InterpreterState) -> HashMap<String, String> {
+    state
+        .traps
+        .iter()
+        .filter(|(condition, handler)| match condition.as_str() {
+            "ERR" => state.options.errtrace,
+            "DEBUG" | "RETURN" => state.options.functrace,
+            "EXIT" => false,
+            _ => handler.is_empty(),
+        })
+        .map(|(condition, handler)| (condition.clone(), handler.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_traps_are_hidden_and_restored() {
+        let mut state = InterpreterState::default();
+        state.traps.insert("ERR".to_string(), "echo err".to_string());
+        state.traps.insert("RETURN".to_string(), "echo old".to_string());
+        state.traps.insert("INT".to_string(), "echo int".to_string());
+
+        let saved = enter_function_traps(&mut state);
+        assert!(!state.traps.contains_key("ERR"));
+        assert!(state.traps.contains_key("INT"));
+        state.traps.insert("RETURN".to_string(), "echo new".to_string());
+        leave_function_traps(&mut state, saved);
+
+        assert_eq!(state.traps["ERR"], "echo err");
+        assert_eq!(state.traps["RETURN"], "echo new");
+    }
+
+    #[test]
+    fn test_subshell_traps() {
+        let mut state = InterpreterState::default();
+        state.traps.insert("EXIT".to_string(), "echo bye".to_string());
+        state.traps.insert("INT".to_string(), String::new());
+        state.traps.insert("TERM".to_string(), "echo term".to_string());
+        state.traps.insert("ERR".to_string(), "echo err".to_string());
+
+        let traps = subshell_traps(&state);
+        assert_eq!(traps.len(), 1);
+        assert!(traps.contains_key("INT"));
+
+        state.options.errtrace = true;
+        assert!(subshell_traps(&state).contains_key("ERR"));
+    }
+}
@@ -3,6 +3,11 @@
 //! Type definitions for the bash interpreter state and context.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use crate::clock::Clock;
+use crate::random::Random;
+use crate::ast::types::StatementNode;
 use crate::FunctionDefNode;
 
 /// Completion specification for a command, set by the `complete` builtin.
@@ -49,6 +54,10 @@ pub struct ShellOptions {
     pub vi: bool,
     /// set -o emacs: Use emacs-style line editing (mutually exclusive with vi)
     pub emacs: bool,
+    /// set -E: Shell functions, subshells and command substitutions inherit the ERR trap
+    pub errtrace: bool,
+    /// set -T: Shell functions inherit the DEBUG and RETURN traps
+    pub functrace: bool,
 }
 
 impl Default for ShellOptions {
@@ -66,6 +75,8 @@ impl Default for ShellOptions {
             noexec: false,
             vi: false,
             emacs: false,
+            errtrace: false,
+            functrace: false,
         }
     }
 }
@@ -91,6 +102,8 @@ pub struct ShoptOptions {
     pub nocasematch: bool,
     /// shopt -s expand_aliases: Enable alias expansion
     pub expand_aliases: bool,
+    /// shopt -s inherit_errexit: Command substitutions inherit set -e
+    pub inherit_errexit: bool,
     /// shopt -s lastpipe: Run last command of pipeline in current shell context
     pub lastpipe: bool,
     /// shopt -s xpg_echo: Make echo interpret backslash escapes by default (like echo -e)
@@ -109,6 +122,7 @@ impl Default for ShoptOptions {
             nocaseglob: false,
             nocasematch: false,
             expand_aliases: false,
+            inherit_errexit: false,
             lastpipe: false,
             xpg_echo: false,
         }
@@ -244,6 +258,12 @@ pub struct ProcessState {
     pub command_count: u64,
     /// Time when shell started (for $SECONDS)
     pub start_time: u64,
+    /// Time source for $SECONDS, $EPOCHSECONDS, prompts and commands
+    pub clock: Clock,
+    /// Randomness source for $RANDOM and commands
+    pub random: Random,
+    /// Values of $$, $PPID, $UID and $EUID
+    pub identity: ProcessIdentity,
     /// PID of last background job (for $!)
     pub last_background_pid: u32,
     /// Current BASHPID (changes in subshells, unlike $$)
@@ -257,6 +277,9 @@ impl Default for ProcessState {
         Self {
             command_count: 0,
             start_time: 0,
+            clock: Clock::real(),
+            random: Random::from_entropy(),
+            identity: ProcessIdentity::host(),
             last_background_pid: 0,
             bash_pid: std::process::id(),
             next_virtual_pid: 1000,
@@ -264,6 +287,166 @@ impl Default for ProcessState {
     }
 }
 
+/// Where a background job is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Started but not run yet
+    Running,
+    /// Ran to completion with this exit code
+    Done(i32),
+    /// Cancelled by `kill` with this signal number
+    Killed(i32),
+}
+
+impl JobStatus {
+    /// Exit status reported by `wait`.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            JobStatus::Running => 0,
+            JobStatus::Done(code) => code,
+            JobStatus::Killed(signal) => 128 + signal,
+        }
+    }
+}
+
+/// A background job started with `&`.
+///
+/// There is no concurrency in the sandbox, so a job doesn't run when it is
+/// launched. Until the shell waits for it (`wait`, or the end of the script
+/// or subshell that started it) or could observe what it did (`sleep`, a
+/// read from a file or fd, or the next check of a `while` or `until` loop)
+/// it is `Running`, and `kill` can cancel it before it does anything. Then it
+/// runs to completion in a subshell copy of the shell as it was at launch,
+/// and its output is held back until the shell collects it, so it always
+/// appears in the same place.
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// Job number (`%1`)
+    pub id: usize,
+    /// Virtual PID (`$!`)
+    pub pid: u32,
+    /// Source text of the job, as shown by `jobs`
+    pub command: String,
+    pub status: JobStatus,
+    pub stdout: String,
+    pub stderr: String,
+    /// Trap handlers the job has installed: the signals it inherited as
+    /// ignored while it is `Running`, then those it had when it finished
+    pub traps: HashMap<String, String>,
+    /// The command and shell a `Running` job will run in
+    pub pending: Option<Box<PendingJob>>,
+}
+
+/// A background job waiting to run.
+#[derive(Debug, Clone)]
+pub struct PendingJob {
+    pub statement: StatementNode,
+    /// Shell state at launch
+    pub shell: InterpreterState,
+}
+
+/// A coprocess started with `coproc`.
+///
+/// There is no concurrency in the sandbox, so a coprocess cannot sit waiting
+/// for input. It runs as late as it can instead: when the shell first reads
+/// from `NAME[0]`, closes `NAME[1]`, or waits for it. It sees everything
+/// written to `NAME[1]` by then followed by end of input, and whatever it
+/// writes becomes readable from `NAME[0]`. Until `NAME[1]` is closed, it runs
+/// again from the start each time the shell reads from it after writing more,
+/// on just the new input.
+#[derive(Debug)]
+pub struct Coprocess {
+    /// Name given to `coproc`, `COPROC` by default
+    pub name: String,
+    /// Virtual PID (`NAME_PID`)
+    pub pid: u32,
+    /// `$BASHPID` of the shell that started it, whose job table it is in
+    pub parent_pid: u32,
+    pub command: StatementNode,
+    /// Shell state the coprocess was started from
+    pub shell: InterpreterState,
+    /// Pipe behind `NAME[1]`, which the coprocess reads
+    pub input: Pipe,
+    /// Pipe behind `NAME[0]`, which the coprocess writes
+    pub output: Pipe,
+    /// Whether it has run yet
+    pub ran: bool,
+}
+
+/// A coprocess, shared by the shell and the subshells it starts.
+pub type SharedCoprocess = Arc<Mutex<Coprocess>>;
+
+/// A pipe buffer, shared by both of its ends.
+///
+/// Every fd referring to an end holds a handle, so a reader knows the write
+/// end is closed once its own handle is the only one left.
+#[derive(Debug, Clone, Default)]
+pub struct Pipe(Arc<Mutex<String>>);
+
+impl Pipe {
+    fn buffer(&self) -> MutexGuard<'_, String> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn write(&self, data: &str) {
+        self.buffer().push_str(data);
+    }
+
+    /// Everything buffered, without consuming it.
+    pub fn contents(&self) -> String {
+        self.buffer().clone()
+    }
+
+    /// Consume the first `bytes` bytes of the buffer.
+    pub fn consume(&self, bytes: usize) {
+        let mut buffer = self.buffer();
+        let bytes = bytes.min(buffer.len());
+        buffer.drain(..bytes);
+    }
+
+    /// Consume everything buffered.
+    pub fn take(&self) -> String {
+        std::mem::take(&mut *self.buffer())
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer().is_empty()
+    }
+
+    /// Whether both handles refer to the same pipe.
+    pub fn same_pipe(&self, other: &Pipe) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Whether a handle other than this one is still open.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+}
+
+/// An entry of the fd table: a descriptor opened by a redirection, or a
+/// persistent redirection of fd 0-2.
+#[derive(Debug, Clone)]
+pub enum FileDescriptor {
+    /// Data waiting to be read, such as a here-document or a file opened
+    /// for reading
+    Content(String),
+    /// A file opened for writing (resolved path)
+    File(String),
+    /// A file opened for reading and writing with `<>`, read from `position`
+    ReadWrite { path: String, position: usize, content: String },
+    /// A duplicate of another descriptor
+    Dup(i32),
+    /// The end of a pipe the shell reads from
+    PipeRead(Pipe),
+    /// The end of a pipe the shell writes to
+    PipeWrite(Pipe),
+}
+
 // ============================================================================
 // I/O State
 // ============================================================================
@@ -273,8 +456,8 @@ impl Default for ProcessState {
 pub struct IOState {
     /// Stdin available for commands in compound commands
     pub group_stdin: Option<String>,
-    /// File descriptors for process substitution and here-docs
-    pub file_descriptors: Option<HashMap<i32, String>>,
+    /// File descriptors opened by redirections and coprocesses
+    pub file_descriptors: Option<HashMap<i32, FileDescriptor>>,
     /// Next available file descriptor for {varname}>file allocation (starts at 10)
     pub next_fd: Option<i32>,
 }
@@ -314,6 +497,9 @@ pub struct InterpreterState {
     pub last_arg: String,
     /// Current line number being executed (for $LINENO)
     pub current_line: u32,
+    /// Added to the line numbers of code parsed out of a larger line, such as
+    /// a `$(...)` body or an `eval` string, whose own lines count from 1
+    pub line_offset: u32,
 
     // ---- Shell Options ----
     /// Shell options (set -e, etc.)
@@ -382,6 +568,11 @@ pub struct InterpreterState {
     pub call_depth: u32,
     /// Current source script nesting depth (for return in sourced scripts)
     pub source_depth: u32,
+    /// Nesting depth of `eval`
+    pub eval_depth: u32,
+    /// Nesting depth of script files run by path (`./build.sh`) and of
+    /// nested shells (`bash -c`)
+    pub script_depth: u32,
     /// Stack of call line numbers for BASH_LINENO
     pub call_line_stack: Option<Vec<u32>>,
     /// Stack of function names for FUNCNAME
@@ -406,18 +597,41 @@ pub struct InterpreterState {
     pub command_count: u64,
     /// Time when shell started (for $SECONDS)
     pub start_time: u64,
+    /// Time source for $SECONDS, $EPOCHSECONDS, prompts and commands
+    pub clock: Clock,
+    /// Randomness source for $RANDOM and commands
+    pub random: Random,
+    /// Values of $$, $PPID, $UID and $EUID
+    pub identity: ProcessIdentity,
     /// PID of last background job (for $!)
     pub last_background_pid: u32,
     /// Current BASHPID (changes in subshells, unlike $$)
     pub bash_pid: u32,
     /// Counter for generating unique virtual PIDs for subshells
     pub next_virtual_pid: u32,
+    /// Background jobs that have not been waited for yet
+    pub jobs: Vec<Job>,
+    /// Signals sent with `kill` as (target PID, signal), delivered once the
+    /// current command finishes
+    pub pending_signals: Vec<(u32, i32)>,
+    /// Coprocesses whose input has not been closed yet
+    pub coprocs: Vec<SharedCoprocess>,
+
+    // ---- Traps ----
+    /// Trap handlers keyed by EXIT, ERR, DEBUG, RETURN or a signal name
+    /// without the SIG prefix. An empty handler ignores the signal.
+    pub traps: HashMap<String, String>,
+    /// Trap whose handler is running; DEBUG and ERR do not fire inside it
+    pub running_trap: Option<String>,
 
     // ---- I/O ----
     /// Stdin available for commands in compound commands
     pub group_stdin: Option<String>,
-    /// File descriptors for process substitution and here-docs
-    pub file_descriptors: Option<HashMap<i32, String>>,
+    /// File descriptors opened by redirections and coprocesses
+    pub file_descriptors: Option<HashMap<i32, FileDescriptor>>,
+    /// Which of fd 1 and fd 2 the current command writes into a pipe,
+    /// `$(...)` or a redirection rather than the script's output
+    pub captured_fds: Vec<i32>,
     /// Next available file descriptor for {varname}>file allocation (starts at 10)
     pub next_fd: Option<i32>,
 
@@ -426,6 +640,12 @@ pub struct InterpreterState {
     pub expansion_exit_code: Option<i32>,
     /// Stderr from expansion errors
     pub expansion_stderr: Option<String>,
+    /// Command substitutions and evals the current command runs inside;
+    /// each adds a repetition of the first PS4 character to xtrace output
+    pub indirection_level: u32,
+    /// First unset variable an expansion read under `set -u`; the engine
+    /// turns it into a nounset error once the expansion finishes
+    pub unbound_variable: Option<String>,
 
     // ---- Aliases ----
     /// Alias definitions (name -> expansion)
@@ -441,6 +661,7 @@ impl Default for InterpreterState {
             last_exit_code: 0,
             last_arg: String::new(),
             current_line: 1,
+            line_offset: 0,
             options: ShellOptions::default(),
             shopt_options: ShoptOptions::default(),
             completion_specs: None,
@@ -469,6 +690,8 @@ impl Default for InterpreterState {
             functions: HashMap::new(),
             call_depth: 0,
             source_depth: 0,
+            eval_depth: 0,
+            script_depth: 0,
             call_line_stack: None,
             func_name_stack: None,
             source_stack: None,
@@ -479,14 +702,25 @@ impl Default for InterpreterState {
             errexit_safe: None,
             command_count: 0,
             start_time: 0,
+            clock: Clock::real(),
+            random: Random::from_entropy(),
+            identity: ProcessIdentity::host(),
             last_background_pid: 0,
             bash_pid: std::process::id(),
             next_virtual_pid: 1000,
+            jobs: Vec::new(),
+            pending_signals: Vec::new(),
+            coprocs: Vec::new(),
+            traps: HashMap::new(),
+            running_trap: None,
             group_stdin: None,
             file_descriptors: None,
+            captured_fds: Vec::new(),
             next_fd: None,
             expansion_exit_code: None,
             expansion_stderr: None,
+            indirection_level: 0,
+            unbound_variable: None,
             aliases: None,
         }
     }
@@ -542,6 +776,22 @@ pub struct ExecutionLimits {
     pub max_command_count: u64,
     /// Maximum number of loop iterations
     pub max_iterations: u64,
+    /// Maximum wall-clock time of one `Bash::exec`, in milliseconds
+    pub max_duration_ms: Option<u64>,
+    /// Maximum bytes of stdout plus stderr produced by one `Bash::exec`
+    pub max_output_size: usize,
+    /// Maximum length in bytes of a variable value or expanded word
+    pub max_string_length: usize,
+    /// Maximum number of elements in an array or expanded word list
+    pub max_array_elements: usize,
+    /// Maximum size of a single file in the default filesystem. This and the
+    /// two limits below are not applied to a filesystem passed in
+    /// `BashOptions::fs`, and the files `Bash::new` sets up don't count.
+    pub max_file_size: u64,
+    /// Maximum combined size of all files in the default filesystem
+    pub max_fs_size: u64,
+    /// Maximum number of entries in the default filesystem
+    pub max_fs_inodes: u64,
 }
 
 impl Default for ExecutionLimits {
@@ -550,10 +800,126 @@ impl Default for ExecutionLimits {
             max_recursion_depth: 1000,
             max_command_count: 100_000,
             max_iterations: 1_000_000,
+            max_duration_ms: None,
+            max_output_size: 10 * 1024 * 1024,
+            max_string_length: 10 * 1024 * 1024,
+            max_array_elements: 1_000_000,
+            max_file_size: 100 * 1024 * 1024,
+            max_fs_size: 256 * 1024 * 1024,
+            max_fs_inodes: 100_000,
         }
     }
 }
 
+/// Process and user IDs the shell reports about itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessIdentity {
+    /// `$$`
+    pub pid: u32,
+    /// `$PPID`
+    pub ppid: u32,
+    /// `$UID`
+    pub uid: u32,
+    /// `$EUID`
+    pub euid: u32,
+}
+
+impl ProcessIdentity {
+    /// PID the shell reports in deterministic mode.
+    pub const VIRTUAL_PID: u32 = 100;
+
+    /// The IDs of the host process.
+    pub fn host() -> Self {
+        #[cfg(unix)]
+        let (ppid, uid, euid) = (
+            std::os::unix::process::parent_id(),
+            unsafe { libc::getuid() },
+            unsafe { libc::geteuid() },
+        );
+        #[cfg(not(unix))]
+        let (ppid, uid, euid) = (0, 0, 0);
+        Self { pid: std::process::id(), ppid, uid, euid }
+    }
+
+    /// Fixed IDs that do not depend on the host.
+    pub fn virtual_identity() -> Self {
+        Self { pid: Self::VIRTUAL_PID, ppid: 1, uid: 1000, euid: 1000 }
+    }
+}
+
+/// The command count and nesting depth a shell shares with the nested
+/// shells its commands start through `ExecFn` (`bash -c`, `xargs`,
+/// `find -exec`), so those count against the same limits.
+///
+/// Clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct ShellNesting(Arc<Mutex<NestingCounters>>);
+
+#[derive(Debug, Default)]
+struct NestingCounters {
+    command_count: u64,
+    depth: u32,
+    captured_fds: Vec<i32>,
+}
+
+impl ShellNesting {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the shell's command count, depth and captured fds before
+    /// running a command that may start nested shells.
+    pub fn enter(&self, command_count: u64, depth: u32, captured_fds: &[i32]) {
+        let mut counters = self.0.lock().unwrap();
+        counters.command_count = command_count;
+        counters.depth = depth;
+        counters.captured_fds = captured_fds.to_vec();
+    }
+
+    /// The command count and depth a nested shell starts from.
+    pub fn current(&self) -> (u64, u32) {
+        let counters = self.0.lock().unwrap();
+        (counters.command_count, counters.depth)
+    }
+
+    /// The fds a nested shell's output is captured through.
+    pub fn captured_fds(&self) -> Vec<i32> {
+        self.0.lock().unwrap().captured_fds.clone()
+    }
+
+    /// Count `commands` run by a nested shell.
+    pub fn add_commands(&self, commands: u64) {
+        self.0.lock().unwrap().command_count += commands;
+    }
+
+    /// The shell's command count including what nested shells ran.
+    pub fn command_count(&self) -> u64 {
+        self.0.lock().unwrap().command_count
+    }
+}
+
+/// Lets the caller of `Bash::exec` stop a running script.
+///
+/// Clones share the same flag. The script is stopped before its next
+/// command once `cancel` has been called.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the script to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
 /// Trace callback type for performance profiling.
 pub type TraceCallback = Box<dyn Fn(&str, u64) + Send + Sync>;
 
@@ -14,7 +14,7 @@
 //! The actual expansion logic is implemented in the expansion/ submodules.
 //! Command substitution requires runtime dependencies (script execution).
 
-use crate::ast::types::{WordNode, WordPart, ScriptNode};
+use crate::ast::types::{ParameterExpansionPart, WordNode, WordPart, ScriptNode};
 use crate::interpreter::types::{ExecResult, InterpreterState};
 
 // Re-export commonly used expansion functions
@@ -87,8 +87,9 @@ pub type CommandSubstitutionFn = Box<dyn Fn(&str, &mut InterpreterState) -> Exec
 /// Callback type for command substitution (reference version).
 ///
 /// This is the signature used by the public API functions.
-/// The callback receives the command body and mutable state, returns (output, exit_code).
-pub type CommandSubstFn<'a> = &'a dyn Fn(&str, &mut InterpreterState) -> (String, i32);
+/// The callback receives the parsed command body and mutable state and returns
+/// the command's result; trailing newlines are stripped from its stdout.
+pub type CommandSubstFn<'a> = &'a dyn Fn(&ScriptNode, &mut InterpreterState) -> ExecResult;
 
 /// Expand a word without glob expansion.
 ///
@@ -280,18 +281,19 @@ pub fn expand_word_for_regex(
             }
             WordPart::SingleQuoted(sq) => {
                 // Single-quoted content is literal in regex
-                result.push_str(&sq.value);
+                result.push_str(&escape_regex_chars(&sq.value));
             }
             WordPart::DoubleQuoted(dq) => {
-                // Double-quoted: expand contents
+                // Double-quoted: expand contents, then match them literally
                 let inner_options = WordExpansionOptions {
                     in_double_quotes: true,
                     ..options.clone()
                 };
+                let mut inner_result = String::new();
                 for inner_part in &dq.parts {
                     let (expanded, part_stderr, exit_code) =
                         expand_part_with_cmd_subst(state, inner_part, &inner_options, cmd_subst);
-                    result.push_str(&expanded);
+                    inner_result.push_str(&expanded);
                     if !part_stderr.is_empty() {
                         stderr.push_str(&part_stderr);
                     }
@@ -299,6 +301,7 @@ pub fn expand_word_for_regex(
                         last_exit_code = exit_code;
                     }
                 }
+                result.push_str(&escape_regex_chars(&inner_result));
             }
             WordPart::TildeExpansion(_) => {
                 // Tilde expansion on RHS of =~ is treated as literal (regex chars escaped)
@@ -429,8 +432,11 @@ pub fn expand_word_with_glob(
     let extglob = state.shopt_options.extglob;
 
     if noglob || !has_glob_pattern(&pattern.value, extglob) {
-        // No glob expansion needed - return the expanded value
-        return pattern;
+        // No glob expansion needed - drop the escapes added for quoted parts
+        return WordExpansionResult {
+            value: unescape_glob_pattern(&pattern.value),
+            ..pattern
+        };
     }
 
     // Perform glob expansion
@@ -473,6 +479,48 @@ pub fn expand_word_with_glob(
     }
 }
 
+/// Expand a word and perform glob expansion against the interpreter's filesystem.
+///
+/// Same as `expand_word_with_glob`, but pathname expansion reads directories
+/// through `fs` instead of the host filesystem. Unquoted parameter, command
+/// and arithmetic expansions are split on IFS first, and each resulting field
+/// is globbed on its own.
+pub fn expand_word_with_fs_glob(
+    state: &mut InterpreterState,
+    word: &WordNode,
+    cmd_subst: Option<CommandSubstFn>,
+    fs: &dyn crate::interpreter::interpreter::FileSystem,
+) -> WordExpansionResult {
+    use crate::interpreter::expansion::word_glob_expansion::{
+        expand_glob_pattern_in_fs, WordExpansionOptions as GlobOptions,
+    };
+
+    let (fields, pattern) = expand_word_fields_for_globbing(state, word, cmd_subst);
+    let fields = fields.unwrap_or_else(|| vec![pattern.value.clone()]);
+    let options = GlobOptions::from_state(state);
+
+    let mut values = Vec::with_capacity(fields.len());
+    let mut stderr = pattern.stderr;
+    let mut exit_code = pattern.exit_code;
+    for field in &fields {
+        match expand_glob_pattern_in_fs(field, &state.cwd, fs, &options) {
+            Ok(glob_result) => values.extend(glob_result.values),
+            Err(e) => {
+                values.push(unescape_glob_pattern(field));
+                stderr.push_str(&format!("bash: {}\n", e));
+                exit_code = Some(1);
+            }
+        }
+    }
+
+    WordExpansionResult {
+        value: values.first().cloned().unwrap_or_default(),
+        split_words: if values.len() == 1 { None } else { Some(values) },
+        stderr,
+        exit_code,
+    }
+}
+
 /// Expand a word for glob matching.
 ///
 /// Unlike regular expansion, this escapes glob metacharacters in quoted parts
@@ -482,78 +530,108 @@ fn expand_word_for_globbing(
     word: &WordNode,
     cmd_subst: Option<CommandSubstFn>,
 ) -> WordExpansionResult {
+    let mut result = WordExpansionResult::simple(String::new());
+    for part in &word.parts {
+        let value = expand_part_for_globbing(state, part, cmd_subst, &mut result);
+        result.value.push_str(&value);
+    }
+    result
+}
+
+/// Expand one part of a word for glob matching, recording any command
+/// substitution stderr and exit code in `acc`.
+fn expand_part_for_globbing(
+    state: &mut InterpreterState,
+    part: &WordPart,
+    cmd_subst: Option<CommandSubstFn>,
+    acc: &mut WordExpansionResult,
+) -> String {
     use crate::interpreter::expansion::pattern_expansion::expand_variables_in_pattern;
 
-    let mut result = String::new();
-    let mut stderr = String::new();
-    let mut last_exit_code = None;
     let options = WordExpansionOptions::default();
+    let mut record = |part_stderr: String, exit_code: Option<i32>| {
+        acc.stderr.push_str(&part_stderr);
+        if exit_code.is_some() {
+            acc.exit_code = exit_code;
+        }
+    };
 
-    for part in &word.parts {
-        match part {
-            WordPart::SingleQuoted(sq) => {
-                // Single-quoted content: escape glob metacharacters for literal matching
-                result.push_str(&escape_glob_chars(&sq.value));
-            }
-            WordPart::Escaped(esc) => {
-                // Escaped character: escape if it's a glob metacharacter
-                let ch = &esc.value;
-                if "*?[]\\()|".contains(ch.as_str()) {
-                    result.push('\\');
-                    result.push_str(ch);
-                } else {
-                    result.push_str(ch);
-                }
-            }
-            WordPart::DoubleQuoted(dq) => {
-                // Double-quoted: expand contents and escape glob metacharacters
-                let inner_options = WordExpansionOptions {
-                    in_double_quotes: true,
-                    ..options.clone()
-                };
-                let mut inner_result = String::new();
-                for inner_part in &dq.parts {
-                    let (expanded, part_stderr, exit_code) =
-                        expand_part_with_cmd_subst(state, inner_part, &inner_options, cmd_subst);
-                    inner_result.push_str(&expanded);
-                    if !part_stderr.is_empty() {
-                        stderr.push_str(&part_stderr);
-                    }
-                    if exit_code.is_some() {
-                        last_exit_code = exit_code;
-                    }
-                }
-                result.push_str(&escape_glob_chars(&inner_result));
-            }
-            WordPart::Glob(g) => {
-                // Glob pattern: expand variables within extglob patterns
-                result.push_str(&expand_variables_in_pattern(state, &g.pattern));
-            }
-            WordPart::Literal(lit) => {
-                // Literal: keep as-is (may contain glob characters that should glob)
-                result.push_str(&lit.value);
+    match part {
+        WordPart::SingleQuoted(sq) => {
+            // Single-quoted content: escape glob metacharacters for literal matching
+            escape_glob_chars(&sq.value)
+        }
+        WordPart::Escaped(esc) => {
+            // Escaped character: escape if it's a glob metacharacter
+            let ch = &esc.value;
+            if "*?[]\\()|".contains(ch.as_str()) {
+                format!("\\{}", ch)
+            } else {
+                ch.clone()
             }
-            _ => {
-                // Other parts (ParameterExpansion, etc.): expand normally
+        }
+        WordPart::DoubleQuoted(dq) => {
+            // Double-quoted: expand contents and escape glob metacharacters
+            let inner_options = WordExpansionOptions {
+                in_double_quotes: true,
+                ..options
+            };
+            let mut inner_result = String::new();
+            for inner_part in &dq.parts {
                 let (expanded, part_stderr, exit_code) =
-                    expand_part_with_cmd_subst(state, part, &options, cmd_subst);
-                result.push_str(&expanded);
-                if !part_stderr.is_empty() {
-                    stderr.push_str(&part_stderr);
-                }
-                if exit_code.is_some() {
-                    last_exit_code = exit_code;
-                }
+                    expand_part_with_cmd_subst(state, inner_part, &inner_options, cmd_subst);
+                inner_result.push_str(&expanded);
+                record(part_stderr, exit_code);
             }
+            escape_glob_chars(&inner_result)
+        }
+        WordPart::Glob(g) => {
+            // Glob pattern: expand variables within extglob patterns
+            expand_variables_in_pattern(state, &g.pattern)
+        }
+        WordPart::Literal(lit) => {
+            // Literal: keep as-is (may contain glob characters that should glob)
+            lit.value.clone()
+        }
+        _ => {
+            // Other parts (ParameterExpansion, etc.): expand normally
+            let (expanded, part_stderr, exit_code) =
+                expand_part_with_cmd_subst(state, part, &options, cmd_subst);
+            record(part_stderr, exit_code);
+            expanded
         }
     }
+}
 
-    WordExpansionResult {
-        value: result,
-        split_words: None,
-        stderr,
-        exit_code: last_exit_code,
+/// Expand a word for glob matching and split the results of its unquoted
+/// expansions on IFS. Returns `None` when the word has nothing to split.
+fn expand_word_fields_for_globbing(
+    state: &mut InterpreterState,
+    word: &WordNode,
+    cmd_subst: Option<CommandSubstFn>,
+) -> (Option<Vec<String>>, WordExpansionResult) {
+    use crate::interpreter::expansion::word_split::{is_part_splittable, smart_word_split, WordSplitSegment};
+    use crate::interpreter::helpers::get_ifs;
+    use crate::interpreter::helpers::word_parts::is_quoted_part;
+
+    if !word.parts.iter().any(is_part_splittable) {
+        return (None, expand_word_for_globbing(state, word, cmd_subst));
+    }
+
+    let mut result = WordExpansionResult::simple(String::new());
+    let mut segments = Vec::with_capacity(word.parts.len());
+    for part in &word.parts {
+        let value = expand_part_for_globbing(state, part, cmd_subst, &mut result);
+        result.value.push_str(&value);
+        segments.push(WordSplitSegment {
+            value,
+            is_splittable: is_part_splittable(part),
+            is_quoted: is_quoted_part(part),
+        });
     }
+
+    let fields = smart_word_split(&segments, get_ifs(&state.env)).words;
+    (Some(fields), result)
 }
 
 /// Expand a single word part with command substitution support.
@@ -592,6 +670,7 @@ fn expand_part_with_cmd_subst(
         }
         WordPart::ParameterExpansion(param) => {
             // Simple variable expansion
+            check_nounset(state, param);
             (get_variable(state, &param.parameter), String::new(), None)
         }
         WordPart::DoubleQuoted(dq) => {
@@ -619,13 +698,10 @@ fn expand_part_with_cmd_subst(
         WordPart::CommandSubstitution(cmd_sub) => {
             // Command substitution requires the callback
             if let Some(callback) = cmd_subst {
-                // Convert the script body to a string representation
-                // For now, we use a simple approach - the body needs to be converted to a command string
-                let body = format_script_body(&cmd_sub.body);
-                let (output, exit_code) = callback(&body, state);
+                let result = callback(&cmd_sub.body, state);
                 // Remove trailing newlines (bash behavior)
-                let trimmed = output.trim_end_matches('\n').to_string();
-                (trimmed, String::new(), Some(exit_code))
+                let trimmed = result.stdout.trim_end_matches('\n').to_string();
+                (trimmed, result.stderr, Some(result.exit_code))
             } else {
                 // No callback provided - return empty
                 (String::new(), String::new(), None)
@@ -654,21 +730,39 @@ fn expand_part_with_cmd_subst(
     }
 }
 
-/// Format a script body for command substitution.
-///
-/// This converts a ScriptNode back to a string representation that can be executed.
-/// For simple cases, this works well; complex cases may need more sophisticated handling.
-fn format_script_body(script: &ScriptNode) -> String {
-    // For now, return a placeholder - in a full implementation this would
-    // serialize the AST back to a command string
-    // The actual implementation depends on how commands are represented
-    format!("{:?}", script)
-}
-
 // ============================================================================
 // Word Analysis Functions
 // ============================================================================
 
+/// Under `set -u`, remember the first unset parameter an expansion reads so
+/// the caller can end the shell once the word is done.
+fn check_nounset(state: &mut InterpreterState, param: &ParameterExpansionPart) {
+    use crate::ast::types::{InnerParameterOperation, ParameterOperation};
+    use crate::interpreter::expansion::variable::is_variable_set;
+
+    if !state.options.nounset || state.unbound_variable.is_some() {
+        return;
+    }
+    // Operators that supply a value for unset parameters are exempt
+    match &param.operation {
+        Some(ParameterOperation::Inner(
+            InnerParameterOperation::DefaultValue(_)
+            | InnerParameterOperation::AssignDefault(_)
+            | InnerParameterOperation::ErrorIfUnset(_)
+            | InnerParameterOperation::UseAlternative(_),
+        )) => return,
+        Some(ParameterOperation::Inner(_)) | None => {}
+        Some(_) => return,
+    }
+    let name = param.parameter.as_str();
+    if matches!(name, "@" | "*") || name.ends_with("[@]") || name.ends_with("[*]") {
+        return;
+    }
+    if !is_variable_set(state, name) {
+        state.unbound_variable = Some(name.to_string());
+    }
+}
+
 /// Check if a word is "fully quoted" - meaning glob characters should be treated literally.
 ///
 /// A word is fully quoted if all its parts are either:
@@ -864,8 +958,8 @@ mod tests {
         };
 
         // Callback that returns a fixed value
-        let callback: CommandSubstFn = &|_cmd: &str, _state: &mut InterpreterState| {
-            ("hello from callback\n".to_string(), 0)
+        let callback: CommandSubstFn = &|_body: &ScriptNode, _state: &mut InterpreterState| {
+            ExecResult::new("hello from callback\n".to_string(), String::new(), 0)
         };
 
         let result = expand_word(&mut state, &word, Some(callback));
@@ -898,7 +992,7 @@ mod tests {
         };
         let result = expand_word_for_regex(&mut state, &word, None);
         // Single-quoted content is literal
-        assert_eq!(result.value, "[abc]");
+        assert_eq!(result.value, "\\[abc\\]");
     }
 
     #[test]
@@ -3,18 +3,26 @@
 //! This library provides a complete parser and interpreter for bash scripts.
 
 pub mod ast;
+pub mod audit;
 pub mod bash;
+pub mod clock;
 pub mod commands;
 pub mod fs;
 pub mod interpreter;
 pub mod network;
 pub mod parser;
+pub mod policy;
+pub mod random;
 pub mod shell;
 pub mod sandbox;
 
 pub use ast::types::*;
 pub use parser::{parse, Parser, ParseException};
+pub use policy::{CommandPolicy, CommandRequest, PolicyDecision};
+pub use audit::{AuditEvent, AuditSink};
 pub use bash::Bash;
+pub use clock::Clock;
+pub use random::Random;
 pub use fs::{FileSystem, InMemoryFs};
 pub use commands::{Command, CommandContext, CommandResult};
 pub use sandbox::Sandbox;
@@ -1,6 +1,8 @@
 use clap::Parser;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
 use just_bash::bash::{Bash, BashOptions};
+use just_bash::{AuditEvent, AuditSink};
 
 #[derive(Parser)]
 #[command(name = "just-bash")]
@@ -23,6 +25,11 @@ struct Cli {
     #[arg(long = "json")]
     json: bool,
 
+    /// Write an audit record of every command, file access and network
+    /// request as JSON lines to FILE ("-" for stderr)
+    #[arg(long = "audit-log", value_name = "FILE")]
+    audit_log: Option<String>,
+
     /// Script file to execute
     #[arg()]
     script_file: Option<String>,
@@ -62,8 +69,30 @@ async fn main() {
         std::process::exit(0);
     }
 
+    let audit = match cli.audit_log.as_deref() {
+        None => None,
+        Some("-") => {
+            let sink: AuditSink = Arc::new(|event: AuditEvent| eprintln!("{}", event.to_json()));
+            Some(sink)
+        }
+        Some(path) => match std::fs::OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => {
+                let file = Mutex::new(file);
+                let sink: AuditSink = Arc::new(move |event: AuditEvent| {
+                    let _ = writeln!(file.lock().unwrap(), "{}", event.to_json());
+                });
+                Some(sink)
+            }
+            Err(e) => {
+                eprintln!("Error: Cannot open audit log: {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+    };
+
     let mut bash = Bash::new(BashOptions {
         cwd: cli.cwd,
+        audit,
         ..Default::default()
     }).await;
 
@@ -7,7 +7,7 @@ use crate::ast::types::{
     CaseModificationOp, DefaultValueOp, DoubleQuotedPart, ErrorIfUnsetOp,
     GlobPart, InnerParameterOperation, LengthOp, LengthSliceErrorOp,
     ParameterExpansionPart, ParameterOperation, PatternAnchor, PatternRemovalOp,
-    PatternRemovalSide, PatternReplacementOp, SubstringOp, TildeExpansionPart,
+    PatternRemovalSide, PatternReplacementOp, ProcessDirection, SubstringOp, TildeExpansionPart,
     TransformOp, TransformOperator, UseAlternativeOp, WordNode, WordPart, AST,
 };
 use crate::parser::arithmetic_parser::parse_arithmetic_expression;
@@ -1107,6 +1107,23 @@ pub fn parse_word_parts(
             }
         }
 
+        // Handle process substitution <(...) and >(...)
+        if (char == '<' || char == '>') && chars.get(i + 1) == Some(&'(') && !here_doc {
+            if let (Some(WordPart::CommandSubstitution(part)), end_index) =
+                (ctx.parse_command_substitution)(value, i)
+            {
+                flush_literal(&mut parts, &mut literal);
+                let direction = if char == '<' {
+                    ProcessDirection::Input
+                } else {
+                    ProcessDirection::Output
+                };
+                parts.push(AST::process_substitution(part.body, direction));
+                i = end_index;
+                continue;
+            }
+        }
+
         // Regular character
         literal.push(char);
         i += 1;
@@ -512,6 +512,15 @@ impl Lexer {
             )));
         }
 
+        // Process substitution <(...) / >(...) is read as (part of) a word
+        if matches!(c0, '<' | '>')
+            && c1 == Some('(')
+            && self.dparen_depth == 0
+            && self.scan_process_substitution(start_pos).is_some()
+        {
+            return self.read_word(start_pos, start_line, start_column);
+        }
+
         // Three-character operators
         // Special case: <<- (heredoc with tab stripping)
         if c0 == '<' && c1 == Some('<') && c2 == Some('-') {
@@ -1081,6 +1090,23 @@ impl Lexer {
                     continue;
                 }
 
+                // Handle process substitution
+                if matches!(c, '<' | '>') && self.peek(1) == Some('(') && bracket_depth == 0 {
+                    if let Some(end) = self.scan_process_substitution(self.pos) {
+                        for &ch in &self.input[self.pos..end] {
+                            value.push(ch);
+                            if ch == '\n' {
+                                ln += 1;
+                                col = 1;
+                            } else {
+                                col += 1;
+                            }
+                        }
+                        self.pos = end;
+                        continue;
+                    }
+                }
+
                 // Inside brackets, only break on newlines
                 if bracket_depth > 0 {
                     if c == '\n' {
@@ -1681,7 +1707,9 @@ impl Lexer {
     }
 
     fn read_heredoc_content(&mut self) -> Result<(), LexerError> {
-        while let Some(heredoc) = self.pending_heredocs.pop() {
+        // Bodies follow in the order their redirections appeared on the line
+        let pending = std::mem::take(&mut self.pending_heredocs);
+        for heredoc in pending {
             let start = self.pos;
             let start_line = self.line;
             let start_column = self.column;
@@ -2031,6 +2059,41 @@ impl Lexer {
         }
     }
 
+    /// Find the end (exclusive) of a `<(...)` or `>(...)` starting at
+    /// `start_pos`, skipping quoted text and nested parentheses.
+    fn scan_process_substitution(&self, start_pos: usize) -> Option<usize> {
+        let mut pos = start_pos + 2;
+        let mut depth = 1;
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+
+        while pos < self.input.len() {
+            let c = self.input[pos];
+            if in_single_quote {
+                in_single_quote = c != '\'';
+            } else if c == '\\' {
+                pos += 1;
+            } else if in_double_quote {
+                in_double_quote = c != '"';
+            } else {
+                match c {
+                    '\'' => in_single_quote = true,
+                    '"' => in_double_quote = true,
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(pos + 1);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            pos += 1;
+        }
+        None
+    }
+
     fn scan_fd_variable(&self, start_pos: usize) -> Option<FdVariableResult> {
         let mut pos = start_pos + 1;
 
@@ -2052,6 +2115,15 @@ impl Lexer {
             return None;
         }
 
+        // Optional array subscript: {arr[1]}
+        if pos < self.input.len() && self.input[pos] == '[' {
+            let close = self.input[pos..].iter().position(|&c| c == ']' || c == '}')? + pos;
+            if self.input[close] != ']' || close == pos + 1 {
+                return None;
+            }
+            pos = close + 1;
+        }
+
         let varname: String = self.input[name_start..pos].iter().collect();
 
         // Must be followed by closing brace
@@ -12,7 +12,7 @@
 //!   compound_cmd ::= if | for | while | until | case | subshell | group | (( | [[
 
 use crate::ast::types::{
-    AST, ArithmeticExpressionNode, CommandNode, CompoundCommandNode, DeferredError,
+    AST, ArithmeticExpressionNode, AssignmentNode, CommandNode, CompoundCommandNode, DeferredError,
     PipelineNode, RedirectionNode, ScriptNode, StatementNode, StatementOperator, WordNode,
     WordPart,
 };
@@ -21,7 +21,11 @@ use crate::parser::parser_substitution::{
     parse_backtick_substitution_from_string, parse_command_substitution_from_string,
     ErrorFn, ParserFactory,
 };
-use crate::parser::types::{ParseException, MAX_PARSER_DEPTH};
+use crate::parser::types::{
+    is_invalid_array_token, is_redirection_after_fd_variable, is_redirection_after_number,
+    is_redirection_token, ParseException, MAX_PARSER_DEPTH,
+};
+use crate::parser::word_parser::word_to_string;
 use crate::parser::word_parser::parse_arith_expr_from_string;
 use crate::parser::expansion_parser::{parse_word_parts, ExpansionContext};
 use crate::parser::conditional_parser::{parse_conditional_expression, CondParserContext, CondToken};
@@ -33,15 +37,6 @@ pub const MAX_TOKENS: usize = 100_000;
 pub const MAX_PARSE_ITERATIONS: usize = 1_000_000;
 // Note: MAX_PARSER_DEPTH is imported from types.rs
 
-/// Pending heredoc information
-#[derive(Debug, Clone)]
-struct PendingHeredoc {
-    redirect_idx: usize,
-    delimiter: String,
-    strip_tabs: bool,
-    quoted: bool,
-}
-
 // Independent helper functions for expansion parsing (don't require Parser self)
 
 /// Parse command substitution from a string - standalone version for ExpansionContext
@@ -152,8 +147,8 @@ impl Drop for DepthGuard {
 pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
-    pending_heredocs: Vec<PendingHeredoc>,
-    pending_redirections: Vec<RedirectionNode>,
+    /// Here-documents on the current line whose bodies have not been consumed yet
+    pending_heredocs: usize,
     parse_iterations: usize,
     parse_depth: usize,
     input: String,
@@ -165,8 +160,7 @@ impl Parser {
         Parser {
             tokens: Vec::new(),
             pos: 0,
-            pending_heredocs: Vec::new(),
-            pending_redirections: Vec::new(),
+            pending_heredocs: 0,
             parse_iterations: 0,
             parse_depth: 0,
             input: String::new(),
@@ -230,8 +224,7 @@ impl Parser {
         }
 
         self.pos = 0;
-        self.pending_heredocs = Vec::new();
-        self.pending_redirections = Vec::new();
+        self.pending_heredocs = 0;
         self.parse_iterations = 0;
         self.parse_depth = 0;
 
@@ -242,8 +235,7 @@ impl Parser {
     pub fn parse_tokens(&mut self, tokens: Vec<Token>) -> Result<ScriptNode, ParseException> {
         self.tokens = tokens;
         self.pos = 0;
-        self.pending_heredocs = Vec::new();
-        self.pending_redirections = Vec::new();
+        self.pending_heredocs = 0;
         self.parse_iterations = 0;
         self.parse_depth = 0;
 
@@ -358,43 +350,25 @@ impl Parser {
         }
     }
 
-    fn add_pending_heredoc(
-        &mut self,
-        redirect_idx: usize,
-        delimiter: String,
-        strip_tabs: bool,
-        quoted: bool,
-    ) {
-        self.pending_heredocs.push(PendingHeredoc {
-            redirect_idx,
-            delimiter,
-            strip_tabs,
-            quoted,
-        });
+    /// Find the body of the `index`-th pending here-document on this line.
+    ///
+    /// The lexer emits heredoc bodies as `HeredocContent` tokens after the
+    /// newline that ends the line, so they are located by lookahead while the
+    /// redirection itself is being parsed.
+    fn lookahead_heredoc_content(&self, index: usize) -> Option<String> {
+        self.tokens[self.pos..]
+            .iter()
+            .filter(|t| t.token_type == TokenType::HeredocContent)
+            .nth(index)
+            .map(|t| t.value.clone())
     }
 
     fn process_heredocs(&mut self) {
-        // Process pending here-documents
-        let heredocs = std::mem::take(&mut self.pending_heredocs);
-        for heredoc in heredocs {
+        // Bodies were already attached by do_parse_redirection; skip their tokens
+        let pending = std::mem::take(&mut self.pending_heredocs);
+        for _ in 0..pending {
             if self.check(&[TokenType::HeredocContent]) {
-                let content = self.advance();
-                let content_word = if heredoc.quoted {
-                    AST::word(vec![AST::literal(&content.value)])
-                } else {
-                    self.parse_word_from_string(&content.value, false, false, false, true)
-                };
-
-                if let Some(redirection) = self.pending_redirections.get_mut(heredoc.redirect_idx) {
-                    redirection.target = crate::ast::types::RedirectionTarget::HereDoc(
-                        AST::here_doc(
-                            &heredoc.delimiter,
-                            content_word,
-                            heredoc.strip_tabs,
-                            heredoc.quoted,
-                        ),
-                    );
-                }
+                self.advance();
             }
         }
     }
@@ -428,11 +402,13 @@ impl Parser {
                 | TokenType::While
                 | TokenType::Until
                 | TokenType::Case
+                | TokenType::Select
                 | TokenType::LParen
                 | TokenType::LBrace
                 | TokenType::DParenStart
                 | TokenType::DBrackStart
                 | TokenType::Function
+                | TokenType::Coproc
                 | TokenType::Bang
                 | TokenType::Time
                 | TokenType::In
@@ -560,6 +536,7 @@ impl Parser {
         }
 
         let start_offset = self.current().start;
+        let line = self.current().line;
 
         let mut pipelines = Vec::new();
         let mut operators = Vec::new();
@@ -596,13 +573,9 @@ impl Parser {
         };
         let source_text = self.input.get(start_offset..end_offset).map(|s| s.to_string());
 
-        Ok(Some(AST::statement(
-            pipelines,
-            operators,
-            background,
-            None,
-            source_text,
-        )))
+        let mut statement = AST::statement(pipelines, operators, background, None, source_text);
+        statement.line = Some(line);
+        Ok(Some(statement))
     }
 
     // ===========================================================================
@@ -677,6 +650,9 @@ impl Parser {
         if self.check(&[TokenType::Case]) {
             return Ok(self.parse_case()?);
         }
+        if self.check(&[TokenType::Select]) {
+            return self.parse_select();
+        }
         if self.check(&[TokenType::LParen]) {
             return Ok(self.parse_subshell()?);
         }
@@ -695,6 +671,9 @@ impl Parser {
         if self.check(&[TokenType::Function]) {
             return Ok(self.parse_function_def()?);
         }
+        if self.check(&[TokenType::Coproc]) {
+            return self.parse_coproc();
+        }
 
         // Check for function definition: name () { ... }
         if self.check(&[TokenType::Name, TokenType::Word])
@@ -1184,6 +1163,32 @@ impl Parser {
         ))
     }
 
+    fn parse_coproc(&mut self) -> Result<CommandNode, ParseException> {
+        self.expect(TokenType::Coproc, None)?;
+
+        // A name is only taken before a compound command: otherwise the
+        // first word is the command itself
+        let mut name = "COPROC".to_string();
+        if self.check(&[TokenType::Name, TokenType::Word]) && is_compound_command_start(self.peek(1).token_type) {
+            name = self.advance().value;
+        }
+
+        if !self.is_command_start() {
+            let token = match self.current().token_type {
+                TokenType::Eof | TokenType::Newline => "newline".to_string(),
+                _ => self.current().value.clone(),
+            };
+            return Err(ParseException::new(
+                format!("syntax error near unexpected token `{}'", token),
+                self.current().line,
+                self.current().column,
+            ));
+        }
+        let body = self.parse_command()?;
+
+        Ok(CommandNode::Coproc(AST::coproc(name, body)))
+    }
+
     fn parse_compound_command_body(&mut self, _for_function_body: bool) -> Result<CompoundCommandNode, ParseException> {
         let cmd = if self.check(&[TokenType::LBrace]) {
             self.parse_group()?
@@ -1199,6 +1204,8 @@ impl Parser {
             self.parse_until()?
         } else if self.check(&[TokenType::Case]) {
             self.parse_case()?
+        } else if self.check(&[TokenType::Select]) {
+            self.parse_select()?
         } else {
             return Err(ParseException::new("Expected compound command for function body", self.current().line, self.current().column));
         };
@@ -1364,8 +1371,47 @@ impl Parser {
         )))
     }
 
+    fn parse_select(&mut self) -> Result<CommandNode, ParseException> {
+        self.expect(TokenType::Select, None)?;
+
+        let variable = if self.is_word() {
+            self.advance().value
+        } else {
+            return Err(ParseException::new("Expected variable name in select", self.current().line, self.current().column));
+        };
+
+        self.skip_newlines();
+
+        let mut words = None;
+        if self.check(&[TokenType::In]) {
+            self.advance();
+            let mut word_list = Vec::new();
+            while !self.check(&[TokenType::Eof, TokenType::Newline, TokenType::Semicolon, TokenType::Do]) {
+                if self.is_word() {
+                    word_list.push(self.parse_word()?);
+                } else {
+                    break;
+                }
+            }
+            words = Some(word_list);
+        }
+
+        self.skip_separators(false);
+        self.expect(TokenType::Do, None)?;
+
+        let body = self.parse_compound_list()?;
+
+        self.expect(TokenType::Done, None)?;
+
+        let redirections = self.parse_optional_redirections()?;
+
+        Ok(CommandNode::Compound(CompoundCommandNode::Select(
+            AST::select_node(variable, words, body, redirections),
+        )))
+    }
+
     fn parse_c_style_for(&mut self, _variable: String) -> Result<CommandNode, ParseException> {
-        self.expect(TokenType::DParenStart, None)?;
+        let start_token = self.expect(TokenType::DParenStart, None)?;
 
         // Parse init; cond; step
         let mut init_str = String::new();
@@ -1396,13 +1442,14 @@ impl Parser {
                 self.advance();
             } else if self.check(&[TokenType::DParenEnd]) {
                 dparen_depth -= 1;
-                if dparen_depth > 0 && !current_str.is_empty() {
-                    current_str.push_str("))");
-                }
-                self.advance();
                 if dparen_depth == 0 {
+                    // The closing `))` is consumed by the expect below
                     break;
                 }
+                if !current_str.is_empty() {
+                    current_str.push_str("))");
+                }
+                self.advance();
             } else if self.check(&[TokenType::LParen]) {
                 paren_depth += 1;
                 current_str.push('(');
@@ -1468,7 +1515,7 @@ impl Parser {
                 update,
                 body,
                 redirections,
-                line: None,
+                line: Some(start_token.line),
             },
         )))
     }
@@ -1692,32 +1739,26 @@ impl Parser {
     }
 
     fn parse_simple_command(&mut self) -> Result<CommandNode, ParseException> {
+        // The line the command starts on, for $LINENO
+        let line = self.current().line;
+        let start_offset = self.current().start;
         let mut assignments = Vec::new();
         let mut name = None;
         let mut args = Vec::new();
         let mut redirections = Vec::new();
 
-        // Parse assignments before command name
-        while self.check(&[TokenType::AssignmentWord]) {
-            let token = self.advance();
-            let value = token.value.clone();
-            // Parse VAR=value or VAR+=value
-            if let Some(eq_pos) = value.find('=') {
-                let var_name = &value[..eq_pos];
-                let var_value = &value[eq_pos + 1..];
-                let append = var_value.starts_with('+');
-                let actual_value = if append {
-                    &var_value[1..]
-                } else {
-                    var_value
-                };
-
-                assignments.push(crate::ast::types::AssignmentNode {
-                    name: var_name.to_string(),
-                    value: Some(self.parse_word_from_string(actual_value, false, false, true, false)),
-                    append,
-                    array: None,
-                });
+        // Parse prefix assignments and redirections (they can be interleaved)
+        // e.g., FOO=foo >file BAR=bar cmd
+        loop {
+            if self.check(&[TokenType::AssignmentWord]) {
+                assignments.push(self.parse_assignment()?);
+            } else if self.is_redirection() {
+                match self.do_parse_redirection()? {
+                    Some(redir) => redirections.push(redir),
+                    None => break,
+                }
+            } else {
+                break;
             }
         }
 
@@ -1726,42 +1767,162 @@ impl Parser {
             name = Some(self.parse_word()?);
         }
 
-        // Parse arguments
-        while self.is_word() {
-            args.push(self.parse_word()?);
+        // Parse arguments and redirections (they can be interleaved).
+        // After the command name, NAME=value is an ordinary argument
+        // (e.g. `export FOO=bar`).
+        loop {
+            if self.is_redirection() {
+                match self.do_parse_redirection()? {
+                    Some(redir) => redirections.push(redir),
+                    None => break,
+                }
+            } else if self.check(&[TokenType::AssignmentWord]) {
+                args.push(self.parse_assignment_argument()?);
+            } else if self.is_word() {
+                args.push(self.parse_word()?);
+            } else {
+                break;
+            }
         }
 
-        // Parse redirections
-        while self.is_redirection() {
-            if let Some(redir) = self.do_parse_redirection()? {
-                redirections.push(redir);
+        let end_offset = self.tokens.get(self.pos.wrapping_sub(1)).map_or(start_offset, |token| token.end);
+        let mut node = AST::simple_command(name, args, assignments, redirections);
+        node.line = Some(line);
+        node.source_text = self.input.get(start_offset..end_offset).map(str::to_string);
+        Ok(CommandNode::Simple(node))
+    }
+
+    /// Parse a prefix assignment: `VAR=value`, `VAR+=value`, `VAR[sub]=value`
+    /// or an array literal `VAR=(a b c)`.
+    fn parse_assignment(&mut self) -> Result<AssignmentNode, ParseException> {
+        let token = self.advance();
+        let chars: Vec<char> = token.value.chars().collect();
+        let invalid = || ParseException::new(format!("Invalid assignment: {}", token.value), token.line, token.column);
+
+        let name_end = chars
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+            .count();
+        if name_end == 0 || chars[0].is_ascii_digit() {
+            return Err(invalid());
+        }
+        let mut name: String = chars[..name_end].iter().collect();
+        let mut pos = name_end;
+
+        // Subscript, with nested brackets: a[a[0]]=value
+        if chars.get(pos) == Some(&'[') {
+            let start = pos;
+            let mut depth = 0;
+            while pos < chars.len() {
+                match chars[pos] {
+                    '[' => depth += 1,
+                    ']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+            if depth != 0 {
+                return Err(invalid());
+            }
+            pos += 1;
+            // The subscript stays in the name so the runtime can tell a[i]=v from a=v
+            name.extend(&chars[start..pos]);
+        }
+
+        let append = chars.get(pos) == Some(&'+');
+        if append {
+            pos += 1;
+        }
+        if chars.get(pos) != Some(&'=') {
+            return Err(invalid());
+        }
+        let value: String = chars[pos + 1..].iter().collect();
+
+        // Array literal: the lexer splits `a=(` from its elements, or leaves
+        // the paren as a separate token immediately after `a=`
+        let adjacent_paren = value.is_empty()
+            && self.check(&[TokenType::LParen])
+            && self.current().start == token.end;
+        if value == "(" || adjacent_paren {
+            if adjacent_paren {
+                self.advance();
+            }
+            let elements = self.parse_array_elements()?;
+            self.expect(TokenType::RParen, None)?;
+            return Ok(AST::assignment(name, None, append, Some(elements)));
+        }
+
+        let word = if value.is_empty() {
+            None
+        } else {
+            Some(self.parse_word_from_string(&value, token.quoted, token.single_quoted, true, false))
+        };
+        Ok(AST::assignment(name, word, append, None))
+    }
+
+    /// Parse the elements of an array literal up to (not including) the `)`.
+    fn parse_array_elements(&mut self) -> Result<Vec<WordNode>, ParseException> {
+        let mut elements = Vec::new();
+        self.skip_newlines();
+        while !self.check(&[TokenType::RParen, TokenType::Eof]) {
+            if self.is_word() || self.check(&[TokenType::AssignmentWord]) {
+                elements.push(self.parse_word()?);
+            } else if is_invalid_array_token(self.current().token_type) {
+                let current = self.current();
+                return Err(ParseException::new(
+                    format!("syntax error near unexpected token `{}'", current.value),
+                    current.line,
+                    current.column,
+                ));
             } else {
-                break;
+                self.advance();
             }
+            self.skip_newlines();
         }
+        Ok(elements)
+    }
 
-        Ok(CommandNode::Simple(
-            AST::simple_command(name, args, assignments, redirections),
-        ))
+    /// Parse `NAME=value` after the command name. It is an ordinary argument
+    /// (for `export`, `local`, `declare`...), except that an array literal
+    /// `NAME=(a b)` is kept together as a single word.
+    fn parse_assignment_argument(&mut self) -> Result<WordNode, ParseException> {
+        let array_start = {
+            let value = &self.current().value;
+            value.ends_with("=(")
+                || (value.ends_with('=') && self.peek(1).token_type == TokenType::LParen)
+        };
+        if !array_start {
+            return self.parse_word();
+        }
+
+        let token = self.advance();
+        let base = token.value.trim_end_matches('(').trim_end_matches('=').to_string();
+        if !token.value.ends_with("=(") {
+            self.expect(TokenType::LParen, None)?;
+        }
+        let elements = self.parse_array_elements()?;
+        self.expect(TokenType::RParen, None)?;
+        let elements: Vec<String> = elements.iter().map(word_to_string).collect();
+        let array = format!("{}=({})", base, elements.join(" "));
+        Ok(self.parse_word_from_string(&array, false, false, false, false))
     }
 
     fn is_redirection(&self) -> bool {
-        let t = self.current().token_type;
-        matches!(
-            t,
-            TokenType::Less
-                | TokenType::Great
-                | TokenType::DLess
-                | TokenType::DGreat
-                | TokenType::LessAnd
-                | TokenType::GreatAnd
-                | TokenType::LessGreat
-                | TokenType::DLessDash
-                | TokenType::Clobber
-                | TokenType::TLess
-                | TokenType::AndGreat
-                | TokenType::AndDGreat
-        )
+        let current = self.current();
+        match current.token_type {
+            // `2>file` is a redirection only when the number touches the operator
+            TokenType::Number => {
+                let next = self.peek(1);
+                current.end == next.start && is_redirection_after_number(next.token_type)
+            }
+            TokenType::FdVariable => is_redirection_after_fd_variable(self.peek(1).token_type),
+            t => is_redirection_token(t),
+        }
     }
 
     fn do_parse_redirection(&mut self) -> Result<Option<RedirectionNode>, ParseException> {
@@ -1771,6 +1932,8 @@ impl Parser {
         // Check for fd or variable before redirection operator
         if self.check(&[TokenType::Number]) {
             fd = Some(self.advance().value.parse::<i32>().unwrap_or(0));
+        } else if self.check(&[TokenType::FdVariable]) {
+            fd_variable = Some(self.advance().value);
         }
 
         let operator = if self.check(&[TokenType::Less]) {
@@ -1819,32 +1982,33 @@ impl Parser {
                 | crate::ast::types::RedirectionOperator::DLessDash
         ) {
             // Here-document
-            let delimiter = if self.is_word() {
-                self.advance().value
+            let delimiter_token = if self.is_word() {
+                self.advance()
             } else {
                 return Err(ParseException::new("Expected here-document delimiter", self.current().line, self.current().column));
             };
 
-            let quoted = delimiter.starts_with('\'') || delimiter.starts_with('"');
+            let raw = delimiter_token.value;
+            let quoted = delimiter_token.quoted
+                || delimiter_token.single_quoted
+                || raw.contains(['\'', '"', '\\']);
+            let delimiter: String = raw.chars().filter(|c| !matches!(c, '\'' | '"' | '\\')).collect();
             let strip_tabs = operator == crate::ast::types::RedirectionOperator::DLessDash;
 
-            // Add to pending heredocs
-            let redirect_idx = self.pending_redirections.len();
-            self.add_pending_heredoc(redirect_idx, delimiter.clone(), strip_tabs, quoted);
+            let content = self
+                .lookahead_heredoc_content(self.pending_heredocs)
+                .unwrap_or_default();
+            self.pending_heredocs += 1;
 
-            let redirection = AST::redirection(
-                operator,
-                crate::ast::types::RedirectionTarget::HereDoc(
-                    AST::here_doc(&delimiter, AST::word(vec![]), strip_tabs, quoted),
-                ),
-                fd,
-                fd_variable.clone(),
-            );
-            self.pending_redirections.push(redirection.clone());
+            let content_word = if quoted {
+                AST::word(vec![AST::literal(&content)])
+            } else {
+                self.parse_word_from_string(&content, false, false, false, true)
+            };
 
             crate::ast::types::RedirectionTarget::HereDoc(AST::here_doc(
                 delimiter,
-                AST::word(vec![]),
+                content_word,
                 strip_tabs,
                 quoted,
             ))
@@ -1937,6 +2101,23 @@ impl Default for Parser {
     }
 }
 
+/// Check if a token begins a compound command.
+fn is_compound_command_start(token_type: TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::If
+            | TokenType::For
+            | TokenType::While
+            | TokenType::Until
+            | TokenType::Case
+            | TokenType::Select
+            | TokenType::LParen
+            | TokenType::LBrace
+            | TokenType::DParenStart
+            | TokenType::DBrackStart
+    )
+}
+
 /// Convenience function to parse a bash script
 pub fn parse(input: &str) -> Result<ScriptNode, ParseException> {
     let mut parser = Parser::new();
@@ -2001,6 +2182,27 @@ mod tests {
         assert_eq!(script.statements.len(), 1);
     }
 
+    #[test]
+    fn test_parse_select() {
+        let mut parser = Parser::new();
+        let script = parser.parse("select f in a b; do echo $f; break; done").unwrap();
+        assert_eq!(script.statements.len(), 1);
+        assert!(parser.parse("select f; do echo $f; done").is_ok());
+    }
+
+    #[test]
+    fn test_parse_coproc() {
+        let coproc = |input: &str| match &parse(input).unwrap().statements[0].pipelines[0].commands[0] {
+            CommandNode::Coproc(coproc) => coproc.clone(),
+            other => panic!("expected coproc, got {:?}", other),
+        };
+        assert_eq!(coproc("coproc cat -n").name, "COPROC");
+        assert!(matches!(*coproc("coproc cat -n").body, CommandNode::Simple(_)));
+        assert_eq!(coproc("coproc BC { bc; }").name, "BC");
+        assert_eq!(coproc("coproc BC").name, "COPROC");
+        assert!(parse("coproc").is_err());
+    }
+
     #[test]
     fn test_parse_while_loop() {
         let mut parser = Parser::new();
@@ -2045,4 +2247,66 @@ mod tests {
         let script = result.unwrap();
         assert_eq!(script.statements.len(), 1);
     }
+
+    #[test]
+    fn test_parse_fd_redirections_and_heredoc_body() {
+        let mut parser = Parser::new();
+        let script = parser.parse("cmd 2>&1 <<'A' 3 > f\none\nA\n").unwrap();
+        let cmd = match &script.statements[0].pipelines[0].commands[0] {
+            CommandNode::Simple(cmd) => cmd,
+            other => panic!("expected simple command, got {:?}", other),
+        };
+        assert_eq!(cmd.args.len(), 1);
+        assert_eq!(cmd.redirections.len(), 3);
+        assert_eq!(cmd.redirections[0].fd, Some(2));
+        match &cmd.redirections[1].target {
+            crate::ast::types::RedirectionTarget::HereDoc(doc) => {
+                assert_eq!(doc.delimiter, "A");
+                assert!(doc.quoted);
+                assert_eq!(doc.content, AST::word(vec![AST::literal("one\n")]));
+            }
+            other => panic!("expected heredoc, got {:?}", other),
+        }
+        assert_eq!(cmd.redirections[2].fd, None);
+    }
+
+    #[test]
+    fn test_parse_prefix_assignments() {
+        let mut parser = Parser::new();
+        let script = parser.parse("a=(1 \"b c\") n[i+1]+=x FOO=bar env").unwrap();
+        let cmd = match &script.statements[0].pipelines[0].commands[0] {
+            CommandNode::Simple(cmd) => cmd,
+            other => panic!("expected simple command, got {:?}", other),
+        };
+        assert_eq!(cmd.assignments.len(), 3);
+        assert_eq!(cmd.assignments[0].name, "a");
+        assert_eq!(cmd.assignments[0].array.as_ref().map(|a| a.len()), Some(2));
+        assert_eq!(cmd.assignments[1].name, "n[i+1]");
+        assert!(cmd.assignments[1].append);
+        assert_eq!(cmd.assignments[2].value, Some(AST::word(vec![AST::literal("bar")])));
+        assert!(cmd.name.is_some());
+    }
+
+    #[test]
+    fn test_parse_process_substitution() {
+        let mut parser = Parser::new();
+        let script = parser.parse("diff <(sort a) >(cat) < <(echo x)").unwrap();
+        let cmd = match &script.statements[0].pipelines[0].commands[0] {
+            CommandNode::Simple(cmd) => cmd,
+            other => panic!("expected simple command, got {:?}", other),
+        };
+        let directions: Vec<_> = cmd
+            .args
+            .iter()
+            .filter_map(|arg| match &arg.parts[..] {
+                [WordPart::ProcessSubstitution(part)] => Some(part.direction),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            directions,
+            vec![crate::ast::types::ProcessDirection::Input, crate::ast::types::ProcessDirection::Output]
+        );
+        assert_eq!(cmd.redirections.len(), 1);
+    }
 }
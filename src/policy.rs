@@ -0,0 +1,65 @@
+//! Policy
+//!
+//! A hook the embedder uses to veto or rewrite commands before they run.
+//! It sees every simple command, whether a builtin, function, registry
+//! command or script, including those started through `bash -c`, `xargs`
+//! and `find -exec`.
+//!
+//! Redirections that don't belong to a simple command are checked too. A
+//! compound command is named by its opening keyword (`{`, `(`, `while`,
+//! ...), a function definition by the function's name, and a redirection
+//! with no command at all (`> file`) by the empty string. These have no
+//! arguments, so a rewrite is treated as allowing them. A bare `exec`
+//! rewritten with arguments runs as `exec ARGS` with its redirections.
+
+/// A command about to run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandRequest<'a> {
+    /// The command name with any directory stripped (`/bin/rm` becomes `rm`)
+    pub name: &'a str,
+    /// The name as the script wrote it
+    pub invoked_as: &'a str,
+    /// Expanded arguments, without the command name
+    pub args: &'a [String],
+    pub cwd: &'a str,
+    /// Files the command's redirections open
+    pub redirections: &'a [RedirectionRequest],
+}
+
+/// A file a command's redirection opens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectionRequest {
+    /// The redirection operator, e.g. `>`, `>>` or `<`
+    pub operator: String,
+    /// The target resolved against the cwd
+    pub path: String,
+}
+
+impl RedirectionRequest {
+    /// Whether the redirection writes to its target.
+    pub fn is_write(&self) -> bool {
+        self.operator != "<"
+    }
+}
+
+/// What to do with a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    /// Don't run the command; it fails with `stderr` and `exit_code`
+    Deny { stderr: String, exit_code: i32 },
+    /// Run the command with these arguments instead
+    Rewrite { args: Vec<String> },
+}
+
+impl PolicyDecision {
+    /// Deny with `bash: NAME: MESSAGE` on stderr and exit code 126.
+    pub fn deny(name: &str, message: &str) -> Self {
+        Self::Deny { stderr: format!("bash: {}: {}\n", name, message), exit_code: 126 }
+    }
+}
+
+/// Consulted before every command runs.
+pub trait CommandPolicy: Send + Sync {
+    fn check(&self, request: &CommandRequest) -> PolicyDecision;
+}
@@ -18,12 +18,19 @@ impl Sandbox {
             max_recursion_depth: opts.max_call_depth.unwrap_or(1000),
             max_command_count: opts.max_command_count.unwrap_or(100_000),
             max_iterations: opts.max_loop_iterations.unwrap_or(1_000_000),
+            max_duration_ms: opts.timeout_ms,
+            ..Default::default()
         };
         let bash = Bash::new(BashOptions {
             env: opts.env,
             cwd: opts.cwd,
             fs: None,
             limits: Some(limits),
+            fetch: None,
+            clock: None,
+            seed: None,
+            audit: None,
+            policy: None,
         })
         .await;
         Self { bash }
@@ -44,14 +51,11 @@ impl Sandbox {
         // Build a wrapper script that applies cwd/env before the command
         let mut preamble_parts: Vec<String> = Vec::new();
 
-        // Apply per-command environment variables using for-loop trick
-        // (plain assignments are not yet processed by the execution engine)
+        // Apply per-command environment variables
         if let Some(env) = &opts.env {
             for (key, value) in env {
-                let escaped = value.replace('\\', "\\\\").replace(' ', "\\ ");
-                preamble_parts.push(
-                    format!("for {} in {}; do true; done", key, escaped),
-                );
+                let quoted = value.replace('\'', "'\\''");
+                preamble_parts.push(format!("export {}='{}'", key, quoted));
             }
         }
 